@@ -0,0 +1,639 @@
+//! 访问控制列表（ACL），管理用户、密码以及用户可以执行的命令、访问的键和频道
+//! Access control lists (ACL), managing users, their passwords, and the commands, keys and channels they can access.
+//!
+//! 用户规则与 Redis 的 `ACL SETUSER` 相同，`requirepass` 只是为默认用户设置密码。
+//! User rules are the same as Redis `ACL SETUSER`, `requirepass` just sets the password of the default user.
+
+use std::collections::{HashMap, VecDeque};
+use std::fs;
+use std::sync::{Mutex, RwLock};
+use std::time::Instant;
+use lazy_static::lazy_static;
+use sha2::{Digest, Sha256};
+use crate::{client, glob};
+use crate::db::now_millis;
+use crate::config::get_security_config;
+use crate::dict::Command;
+use crate::error::Error;
+
+/// 默认用户，新连接使用该用户
+/// The default user, used by new connections.
+pub const DEFAULT_USER: &str = "default";
+
+/// ACL 命令类别
+/// ACL command categories.
+pub const CATEGORIES: &[&str] = &[
+    "keyspace", "read", "write", "set", "sortedset", "list", "hash", "string", "bitmap", "hyperloglog",
+    "geo", "stream", "pubsub", "admin", "fast", "slow", "blocking", "dangerous", "connection",
+    "transaction", "scripting",
+];
+
+lazy_static! {
+    /// 所有用户，key 是用户名
+    /// All users, keyed by user name.
+    static ref USERS: RwLock<HashMap<String, User>> = RwLock::new(HashMap::from([
+        (DEFAULT_USER.to_string(), User::default_user()),
+    ]));
+    /// 被拒绝的命令和认证失败的记录，最新的在最前面
+    /// Records of denied commands and failed authentications, the newest first.
+    static ref ACL_LOG: Mutex<AclLog> = Mutex::new(AclLog::default());
+}
+
+/// ACL 用户
+/// An ACL user.
+#[derive(Debug, Clone)]
+pub struct User {
+    /// 用户名
+    /// User name
+    pub name: String,
+    /// 是否启用，禁用的用户不能认证
+    /// Whether the user is enabled, disabled users cannot authenticate
+    pub enabled: bool,
+    /// 是否不需要密码
+    /// Whether no password is needed
+    pub nopass: bool,
+    /// SHA-256 哈希后的密码
+    /// Passwords hashed with SHA-256
+    pub passwords: Vec<String>,
+    /// 命令规则，按顺序生效，后面的规则覆盖前面的
+    /// Command rules, applied in order, later rules override earlier ones
+    pub commands: Vec<CommandRule>,
+    /// 可以访问的键模式
+    /// Key patterns that can be accessed
+    pub keys: Vec<KeyPattern>,
+    /// 可以访问的频道模式
+    /// Channel patterns that can be accessed
+    pub channels: Vec<String>,
+}
+
+/// 命令规则，例如 `+get`、`-@admin`
+/// A command rule, such as `+get` or `-@admin`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommandRule {
+    /// 允许还是禁止
+    /// Allow or deny
+    pub allow: bool,
+    /// 规则作用的命令
+    /// Commands the rule applies to
+    pub target: RuleTarget,
+}
+
+/// 命令规则作用的命令
+/// Commands a command rule applies to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RuleTarget {
+    /// 所有命令
+    /// All commands
+    All,
+    /// 某个类别的命令
+    /// Commands of a category
+    Category(String),
+    /// 单个命令或子命令，例如 `get`、`client|kill`
+    /// A single command or subcommand, such as `get` or `client|kill`
+    Command(String),
+}
+
+/// 键模式，例如 `~cache:*`、`%R~cache:*`
+/// A key pattern, such as `~cache:*` or `%R~cache:*`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyPattern {
+    /// 键的模式
+    /// Pattern of the keys
+    pub pattern: String,
+    /// 是否可以读
+    /// Whether the keys can be read
+    pub read: bool,
+    /// 是否可以写
+    /// Whether the keys can be written
+    pub write: bool,
+}
+
+/// 命令被拒绝的原因
+/// Reason a command is denied.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Denied {
+    /// 不能执行该命令
+    /// The command cannot be executed
+    Command(String),
+    /// 不能访问该键
+    /// The key cannot be accessed
+    Key(String),
+    /// 不能访问该频道
+    /// The channel cannot be accessed
+    Channel(String),
+}
+
+/// ACL LOG 的一条记录
+/// An entry of ACL LOG.
+#[derive(Debug, Clone)]
+pub struct LogEntry {
+    /// 相同记录在 60 秒内重复的次数
+    /// Number of times the same entry repeated within 60 seconds
+    pub count: u64,
+    /// 原因：command、key、channel 或 auth
+    /// Reason: command, key, channel or auth
+    pub reason: String,
+    /// 上下文，目前总是 toplevel
+    /// Context, currently always toplevel
+    pub context: String,
+    /// 被拒绝的命令、键或频道
+    /// The denied command, key or channel
+    pub object: String,
+    /// 用户名
+    /// User name
+    pub username: String,
+    /// 客户端信息，与 `CLIENT INFO` 相同
+    /// Client information, the same as `CLIENT INFO`
+    pub client_info: String,
+    /// 记录的唯一 ID
+    /// Unique ID of the entry
+    pub entry_id: u64,
+    /// 创建的时间
+    /// Time the entry was created
+    pub created: Instant,
+    /// 创建的时间戳，以毫秒为单位
+    /// Creation timestamp in milliseconds
+    pub timestamp_created: u64,
+    /// 最后更新的时间戳，以毫秒为单位
+    /// Last update timestamp in milliseconds
+    pub timestamp_updated: u64,
+}
+
+/// ACL LOG 的记录
+/// Entries of ACL LOG.
+#[derive(Debug, Default)]
+struct AclLog {
+    entries: VecDeque<LogEntry>,
+    next_id: u64,
+}
+
+impl User {
+    /// 新建的用户：禁用，没有密码，不能执行任何命令，不能访问任何键和频道
+    /// A new user: disabled, no passwords, cannot run any command or access any key or channel.
+    pub fn new(name: &str) -> User {
+        User {
+            name: name.to_string(),
+            enabled: false,
+            nopass: false,
+            passwords: Vec::new(),
+            commands: Vec::new(),
+            keys: Vec::new(),
+            channels: Vec::new(),
+        }
+    }
+
+    /// 默认用户：启用，无需密码，可以执行所有命令、访问所有键和频道
+    /// The default user: enabled, no password needed, can run every command and access every key and channel.
+    fn default_user() -> User {
+        User {
+            name: DEFAULT_USER.to_string(),
+            enabled: true,
+            nopass: true,
+            passwords: Vec::new(),
+            commands: vec![CommandRule { allow: true, target: RuleTarget::All }],
+            keys: vec![KeyPattern { pattern: "*".to_string(), read: true, write: true }],
+            channels: vec!["*".to_string()],
+        }
+    }
+
+    /// 应用一条 `ACL SETUSER` 规则
+    /// Apply an `ACL SETUSER` rule.
+    pub fn apply_rule(&mut self, rule: &str) -> Result<(), String> {
+        let lower = rule.to_lowercase();
+        match lower.as_str() {
+            "on" => self.enabled = true,
+            "off" => self.enabled = false,
+            "nopass" => {
+                self.nopass = true;
+                self.passwords.clear();
+            }
+            "resetpass" => {
+                self.nopass = false;
+                self.passwords.clear();
+            }
+            "allkeys" => self.apply_rule("~*")?,
+            "resetkeys" => self.keys.clear(),
+            "allchannels" => self.apply_rule("&*")?,
+            "resetchannels" => self.channels.clear(),
+            "allcommands" => self.apply_rule("+@all")?,
+            "nocommands" => self.apply_rule("-@all")?,
+            "reset" => {
+                for rule in ["resetpass", "resetkeys", "resetchannels", "nocommands", "off"] {
+                    self.apply_rule(rule)?;
+                }
+            }
+            _ => return self.apply_pattern_rule(rule),
+        }
+        Ok(())
+    }
+
+    /// 应用带参数的规则：密码、键、频道和命令
+    /// Apply a rule with an argument: passwords, keys, channels and commands.
+    fn apply_pattern_rule(&mut self, rule: &str) -> Result<(), String> {
+        if let Some(password) = rule.strip_prefix('>') {
+            self.add_password(hash_password(password));
+        } else if let Some(password) = rule.strip_prefix('<') {
+            self.passwords.retain(|hash| *hash != hash_password(password));
+        } else if let Some(hash) = rule.strip_prefix('#') {
+            if hash.len() != 64 || !hash.chars().all(|c| c.is_ascii_digit() || ('a'..='f').contains(&c)) {
+                return Err("The password hash must be exactly 64 characters and contain only lowercase hexadecimal characters".to_string());
+            }
+            self.add_password(hash.to_string());
+        } else if let Some(hash) = rule.strip_prefix('!') {
+            self.passwords.retain(|h| h != hash);
+        } else if let Some(pattern) = rule.strip_prefix('~') {
+            self.add_key_pattern(pattern, true, true);
+        } else if let Some(rest) = rule.strip_prefix('%') {
+            let Some((permissions, pattern)) = rest.split_once('~') else {
+                return Err("Syntax error".to_string());
+            };
+            let permissions = permissions.to_uppercase();
+            if permissions.is_empty() || permissions.chars().any(|c| c != 'R' && c != 'W') {
+                return Err("Syntax error".to_string());
+            }
+            self.add_key_pattern(pattern, permissions.contains('R'), permissions.contains('W'));
+        } else if let Some(pattern) = rule.strip_prefix('&') {
+            if pattern == "*" {
+                self.channels.clear();
+            }
+            if !self.channels.iter().any(|p| p == pattern || p == "*") {
+                self.channels.push(pattern.to_string());
+            }
+        } else if let Some(name) = rule.strip_prefix('+') {
+            self.add_command_rule(true, name)?;
+        } else if let Some(name) = rule.strip_prefix('-') {
+            self.add_command_rule(false, name)?;
+        } else {
+            return Err("Syntax error".to_string());
+        }
+        Ok(())
+    }
+
+    /// 添加一个哈希后的密码，之后需要密码才能认证
+    /// Add a hashed password, a password is needed to authenticate afterwards.
+    fn add_password(&mut self, hash: String) {
+        self.nopass = false;
+        if !self.passwords.contains(&hash) {
+            self.passwords.push(hash);
+        }
+    }
+
+    /// 添加键模式，`*` 会覆盖之前的所有模式
+    /// Add a key pattern, `*` overrides all the previous patterns.
+    fn add_key_pattern(&mut self, pattern: &str, read: bool, write: bool) {
+        if pattern == "*" && read && write {
+            self.keys.clear();
+        }
+        match self.keys.iter_mut().find(|p| p.pattern == pattern) {
+            Some(existing) => {
+                existing.read |= read;
+                existing.write |= write;
+            }
+            None => self.keys.push(KeyPattern { pattern: pattern.to_string(), read, write }),
+        }
+    }
+
+    /// 添加命令规则，`+@all` 和 `-@all` 会覆盖之前的所有规则
+    /// Add a command rule, `+@all` and `-@all` override all the previous rules.
+    fn add_command_rule(&mut self, allow: bool, name: &str) -> Result<(), String> {
+        let name = name.to_lowercase();
+        let target = match name.strip_prefix('@') {
+            Some("all") => RuleTarget::All,
+            Some(category) if CATEGORIES.contains(&category) => RuleTarget::Category(category.to_string()),
+            None if command_exists(&name) => RuleTarget::Command(name),
+            _ => return Err("Unknown command or category name in ACL".to_string()),
+        };
+        if target == RuleTarget::All {
+            self.commands.clear();
+        }
+        let rule = CommandRule { allow, target };
+        // 相同的命令只保留最后一条规则
+        // Only the last rule is kept for the same command
+        self.commands.retain(|r| r.target != rule.target);
+        self.commands.push(rule);
+        Ok(())
+    }
+
+    /// 是否可以执行该命令，`subcommand` 为小写的子命令
+    /// Whether the command can be executed, `subcommand` is the lowercase subcommand.
+    pub fn can_run(&self, command: &str, subcommand: Option<&str>) -> bool {
+        let full_name = subcommand.map(|sub| format!("{}|{}", command, sub));
+        let categories = categories(command, subcommand);
+        let mut allowed = false;
+        for rule in &self.commands {
+            let matched = match &rule.target {
+                RuleTarget::All => true,
+                RuleTarget::Category(category) => categories.contains(&category.as_str()),
+                RuleTarget::Command(name) => name == command || Some(name) == full_name.as_ref(),
+            };
+            if matched {
+                allowed = rule.allow;
+            }
+        }
+        allowed
+    }
+
+    /// 是否可以按需要的权限访问该键，读取并修改值的命令需要同一个模式同时允许读和写
+    /// Whether the key can be accessed with the needed permissions, commands reading and modifying the value need a
+    /// single pattern allowing both.
+    pub fn can_access_key(&self, key: &str, read: bool, write: bool) -> bool {
+        self.keys
+            .iter()
+            .any(|p| (p.read || !read) && (p.write || !write) && glob::matches(&p.pattern, key))
+    }
+
+    /// 是否可以访问该频道，订阅模式时模式必须与用户的某个频道模式相同
+    /// Whether the channel can be accessed, a subscribed pattern must be identical to one of the user's channel patterns.
+    pub fn can_access_channel(&self, channel: &str, is_pattern: bool) -> bool {
+        self.channels.iter().any(|p| {
+            p == "*" || if is_pattern { p == channel } else { glob::matches(p, channel) }
+        })
+    }
+
+    /// 用户的标志，用于 `ACL GETUSER`
+    /// Flags of the user, used by `ACL GETUSER`.
+    pub fn flags(&self) -> Vec<&'static str> {
+        let mut flags = vec![if self.enabled { "on" } else { "off" }];
+        if self.nopass {
+            flags.push("nopass");
+        }
+        flags
+    }
+
+    /// 命令规则的描述，例如 `+@all -@admin +client|id`
+    /// Description of the command rules, such as `+@all -@admin +client|id`.
+    pub fn command_rules(&self) -> String {
+        if self.commands.is_empty() {
+            return "-@all".to_string();
+        }
+        let rules: Vec<String> = self.commands.iter().map(|rule| {
+            let sign = if rule.allow { '+' } else { '-' };
+            match &rule.target {
+                RuleTarget::All => format!("{}@all", sign),
+                RuleTarget::Category(category) => format!("{}@{}", sign, category),
+                RuleTarget::Command(name) => format!("{}{}", sign, name),
+            }
+        }).collect();
+        rules.join(" ")
+    }
+
+    /// 键模式的描述，例如 `~cache:* %R~logs:*`
+    /// Description of the key patterns, such as `~cache:* %R~logs:*`.
+    pub fn key_rules(&self) -> String {
+        let rules: Vec<String> = self.keys.iter().map(|p| match (p.read, p.write) {
+            (true, true) => format!("~{}", p.pattern),
+            (true, false) => format!("%R~{}", p.pattern),
+            _ => format!("%W~{}", p.pattern),
+        }).collect();
+        rules.join(" ")
+    }
+
+    /// 频道模式的描述，例如 `&news:*`
+    /// Description of the channel patterns, such as `&news:*`.
+    pub fn channel_rules(&self) -> String {
+        let rules: Vec<String> = self.channels.iter().map(|p| format!("&{}", p)).collect();
+        rules.join(" ")
+    }
+
+    /// 用户的完整描述，用于 `ACL LIST` 和 ACL 文件
+    /// Full description of the user, used by `ACL LIST` and the ACL file.
+    pub fn describe(&self) -> String {
+        let mut parts = vec![format!("user {}", self.name)];
+        parts.extend(self.flags().iter().map(|flag| flag.to_string()));
+        parts.extend(self.passwords.iter().map(|hash| format!("#{}", hash)));
+        let keys = self.key_rules();
+        if !keys.is_empty() {
+            parts.push(keys);
+        }
+        parts.push(if self.channels.is_empty() { "resetchannels".to_string() } else { self.channel_rules() });
+        parts.push(self.command_rules());
+        parts.join(" ")
+    }
+}
+
+/// 按配置初始化用户：`requirepass` 设置默认用户的密码，再从 `aclfile` 加载用户
+/// Initialize the users from the configuration: `requirepass` sets the password of the default user, then users are loaded from `aclfile`.
+pub fn load() -> crate::Result<()> {
+    let config = get_security_config();
+    let mut users = HashMap::from([(DEFAULT_USER.to_string(), User::default_user())]);
+    if let Some(requirepass) = &config.requirepass {
+        let default = users.get_mut(DEFAULT_USER).unwrap();
+        default.apply_rule("resetpass")?;
+        default.apply_rule(&format!(">{}", requirepass))?;
+    }
+    if let Some(aclfile) = &config.aclfile {
+        for (number, line) in fs::read_to_string(aclfile)?.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let mut words = line.split_whitespace();
+            let (Some("user"), Some(name)) = (words.next(), words.next()) else {
+                return Err(format!("{}:{}: 每行必须以 user <用户名> 开头", aclfile, number + 1).into());  // Every line must start with user <name>
+            };
+            let mut user = User::new(name);
+            for rule in words {
+                user.apply_rule(rule)
+                    .map_err(|err| format!("{}:{}: '{}': {}", aclfile, number + 1, rule, err))?;
+            }
+            users.insert(name.to_string(), user);
+        }
+    }
+    *USERS.write().unwrap() = users;
+    Ok(())
+}
+
+/// 获取用户
+/// Get a user.
+pub fn get_user(name: &str) -> Option<User> {
+    USERS.read().unwrap().get(name).cloned()
+}
+
+/// 所有用户，按用户名排序
+/// All users, sorted by name.
+pub fn users() -> Vec<User> {
+    let mut users: Vec<User> = USERS.read().unwrap().values().cloned().collect();
+    users.sort_by(|a, b| a.name.cmp(&b.name));
+    users
+}
+
+/// 创建或修改用户，任一规则出错时不做任何修改
+/// Create or modify a user, nothing is changed if any rule fails.
+pub fn set_user(name: &str, rules: &[String]) -> Result<(), Error> {
+    let mut users = USERS.write().unwrap();
+    let mut user = users.get(name).cloned().unwrap_or_else(|| User::new(name));
+    for rule in rules {
+        user.apply_rule(rule)
+            .map_err(|err| Error::Err(format!("Error in ACL SETUSER modifier '{}': {}", rule, err)))?;
+    }
+    users.insert(name.to_string(), user);
+    Ok(())
+}
+
+/// 删除用户并断开使用该用户的连接，返回删除的用户数量
+/// Delete users and disconnect the connections using them, returning the number of users deleted.
+pub fn delete_users(names: &[String]) -> Result<usize, Error> {
+    if names.iter().any(|name| name == DEFAULT_USER) {
+        return Err(Error::Err("The 'default' user cannot be removed".to_string()));
+    }
+    let mut deleted = Vec::new();
+    {
+        let mut users = USERS.write().unwrap();
+        for name in names {
+            if users.remove(name).is_some() {
+                deleted.push(name.clone());
+            }
+        }
+    }
+    for handle in client::all() {
+        if deleted.contains(&handle.state().user) {
+            handle.kill();
+        }
+    }
+    Ok(deleted.len())
+}
+
+/// 使用用户名和密码认证，用户不存在、被禁用或密码错误时返回 false
+/// Authenticate with a user name and password, false if the user does not exist, is disabled or the password is wrong.
+pub fn authenticate(name: &str, password: &str) -> bool {
+    match USERS.read().unwrap().get(name) {
+        Some(user) if user.enabled => user.nopass || user.passwords.contains(&hash_password(password)),
+        _ => false,
+    }
+}
+
+/// 新连接是否不需要认证，即默认用户启用且无需密码
+/// Whether new connections need no authentication, i.e. the default user is enabled and needs no password.
+pub fn default_user_needs_no_auth() -> bool {
+    USERS.read().unwrap().get(DEFAULT_USER).is_some_and(|user| user.enabled && user.nopass)
+}
+
+/// 检查用户是否可以执行命令，`args` 是命令名之后的参数
+/// Check whether a user can execute a command, `args` are the arguments after the command name.
+pub fn check(username: &str, command: &str, args: &[String]) -> Result<(), Denied> {
+    let users = USERS.read().unwrap();
+    // 用户已被删除时拒绝所有命令
+    // Every command is denied if the user has been deleted
+    let Some(user) = users.get(username) else {
+        return Err(Denied::Command(command.to_string()));
+    };
+    let subcommand = args.first().map(|arg| arg.to_lowercase()).filter(|_| has_subcommands(command));
+    if !user.can_run(command, subcommand.as_deref()) {
+        let name = match &subcommand {
+            Some(sub) => format!("{}|{}", command, sub),
+            None => command.to_string(),
+        };
+        return Err(Denied::Command(name));
+    }
+
+    if let Some(command) = Command::lookup(command) {
+        let (read, write) = command.key_access();
+        for key in command.keys(args) {
+            if !user.can_access_key(key, read, write) {
+                return Err(Denied::Key(key.clone()));
+            }
+        }
+    }
+
+    let channels: &[String] = match command {
+        "publish" | "spublish" => &args[..args.len().min(1)],
+        "subscribe" | "psubscribe" | "ssubscribe" => args,
+        _ => &[],
+    };
+    for channel in channels {
+        if !user.can_access_channel(channel, command == "psubscribe") {
+            return Err(Denied::Channel(channel.clone()));
+        }
+    }
+    Ok(())
+}
+
+/// 命令的类别，子命令没有单独定义类别时使用命令的类别
+/// Categories of a command, the command's categories are used if the subcommand has none of its own.
+pub fn categories(command: &str, subcommand: Option<&str>) -> &'static [&'static str] {
+    let Some(command) = Command::lookup(command) else {
+        return &[];
+    };
+    subcommand.and_then(|sub| command.subcommand(sub)).unwrap_or(&command).categories
+}
+
+/// 属于某个类别的命令
+/// Commands belonging to a category.
+pub fn commands_in_category(category: &str) -> Vec<String> {
+    Command::all()
+        .into_iter()
+        .flat_map(|command| {
+            let subcommands = command.subcommands.clone();
+            std::iter::once(command).chain(subcommands)
+        })
+        .filter(|command| command.categories.contains(&category))
+        .map(|command| command.name)
+        .collect()
+}
+
+/// 命令是否有子命令
+/// Whether a command has subcommands.
+fn has_subcommands(command: &str) -> bool {
+    Command::lookup(command).is_some_and(|command| !command.subcommands.is_empty())
+}
+
+/// 命令或子命令是否存在
+/// Whether a command or subcommand exists.
+fn command_exists(name: &str) -> bool {
+    match name.split_once('|') {
+        Some((command, _)) => Command::exists(command),
+        None => Command::exists(name),
+    }
+}
+
+/// 记录被拒绝的命令或认证失败，60 秒内相同的记录只增加次数
+/// Record a denied command or a failed authentication, the same entry within 60 seconds only increases its count.
+pub fn log(reason: &str, object: &str, username: &str, client_info: String) {
+    let max_len = get_security_config().acllog_max_len;
+    let now = now_millis();
+    let mut log = ACL_LOG.lock().unwrap();
+    if let Some(entry) = log.entries.iter_mut().find(|entry| {
+        entry.reason == reason
+            && entry.object == object
+            && entry.username == username
+            && now - entry.timestamp_updated < 60_000
+    }) {
+        entry.count += 1;
+        entry.timestamp_updated = now;
+        entry.client_info = client_info;
+        return;
+    }
+    let entry_id = log.next_id;
+    log.next_id += 1;
+    log.entries.push_front(LogEntry {
+        count: 1,
+        reason: reason.to_string(),
+        context: "toplevel".to_string(),
+        object: object.to_string(),
+        username: username.to_string(),
+        client_info,
+        entry_id,
+        created: Instant::now(),
+        timestamp_created: now,
+        timestamp_updated: now,
+    });
+    log.entries.truncate(max_len);
+}
+
+/// 最新的若干条 ACL LOG 记录
+/// The newest ACL LOG entries.
+pub fn log_entries(count: usize) -> Vec<LogEntry> {
+    ACL_LOG.lock().unwrap().entries.iter().take(count).cloned().collect()
+}
+
+/// 清空 ACL LOG
+/// Clear ACL LOG.
+pub fn reset_log() {
+    ACL_LOG.lock().unwrap().entries.clear();
+}
+
+/// 使用 SHA-256 哈希密码，返回小写的十六进制字符串
+/// Hash a password with SHA-256, returning a lowercase hexadecimal string.
+pub fn hash_password(password: &str) -> String {
+    Sha256::digest(password.as_bytes()).iter().map(|b| format!("{:02x}", b)).collect()
+}
@@ -0,0 +1,516 @@
+//! 客户端注册表，记录每个连接在服务端共享的状态
+//! Client registry, records the server-side shared state of every connection.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+use lazy_static::lazy_static;
+use log::warn;
+use tokio::sync::{mpsc, Notify};
+use crate::acl;
+use crate::config::get_server_config;
+use crate::db::Lag;
+use crate::dict::Command;
+use crate::frame::Frame;
+use crate::tracking;
+
+lazy_static! {
+    /// 所有在线客户端，key 是客户端 ID
+    /// All connected clients, keyed by client ID.
+    static ref CLIENT_REGISTRY: RwLock<HashMap<u64, Arc<ClientHandle>>> = RwLock::new(HashMap::new());
+    /// `CLIENT PAUSE` 的状态
+    /// State of `CLIENT PAUSE`.
+    static ref PAUSE: Mutex<Option<Pause>> = Mutex::new(None);
+    /// 解除暂停时唤醒等待的客户端
+    /// Wakes up the waiting clients when the pause is lifted.
+    static ref UNPAUSED: Notify = Notify::new();
+}
+
+/// 客户端 ID 生成器，从 1 开始递增
+/// Client ID generator, increases from 1.
+static NEXT_CLIENT_ID: AtomicU64 = AtomicU64::new(1);
+
+/// 其他连接可见的客户端句柄
+/// A client handle that is visible to other connections.
+#[derive(Debug)]
+pub struct ClientHandle {
+    /// 客户端唯一 ID
+    /// Unique client ID
+    pub id: u64,
+    /// 客户端地址
+    /// Client address
+    pub addr: String,
+    /// 客户端连接的本地地址
+    /// Local address of the client connection
+    pub laddr: String,
+    /// 连接建立的时间
+    /// Time the connection was established
+    pub created: Instant,
+    /// 推送消息的发送端，例如失效消息
+    /// Sender for pushed messages, such as invalidation messages
+    push: mpsc::UnboundedSender<Frame>,
+    /// 断开该连接的信号，由连接的 `Shutdown` 接收
+    /// Signal to disconnect this connection, received by the connection's `Shutdown`
+    kill: Arc<Notify>,
+    /// 客户端的可变状态
+    /// Mutable state of the client
+    state: Mutex<ClientState>,
+}
+
+/// 客户端的可变状态
+/// Mutable state of a client.
+#[derive(Debug, Clone)]
+pub struct ClientState {
+    /// RESP 协议版本，2 或 3
+    /// RESP protocol version, 2 or 3
+    pub resp: u8,
+    /// 是否处于发布/订阅模式
+    /// Whether the client is in pub/sub mode
+    pub pubsub: bool,
+    /// 客户端名称，由 `CLIENT SETNAME` 设置
+    /// Client name, set by `CLIENT SETNAME`
+    pub name: Option<String>,
+    /// 当前使用的数据库
+    /// Database currently in use
+    pub db: usize,
+    /// 最后执行的命令
+    /// Last command executed
+    pub cmd: String,
+    /// 最后一次交互的时间
+    /// Time of the last interaction
+    pub last_interaction: Instant,
+    /// 订阅的频道数量
+    /// Number of subscribed channels
+    pub sub: usize,
+    /// 订阅的模式数量
+    /// Number of subscribed patterns
+    pub psub: usize,
+    /// 订阅的分片频道数量
+    /// Number of subscribed shard channels
+    pub ssub: usize,
+    /// 是否订阅了失效消息的频道，RESP2 的重定向目标只有订阅了它才会收到失效消息
+    /// Whether the invalidation channel is subscribed, RESP2 redirection targets only get invalidations if it is
+    pub invalidate_subscribed: bool,
+    /// 输入缓冲区中未处理的字节数
+    /// Unprocessed bytes in the input buffer
+    pub qbuf: usize,
+    /// 输入缓冲区的剩余容量
+    /// Free capacity of the input buffer
+    pub qbuf_free: usize,
+    /// 等待发送的推送消息数量
+    /// Number of pushed messages waiting to be sent
+    pub oll: usize,
+    /// 等待发送的推送消息占用的字节数
+    /// Bytes used by pushed messages waiting to be sent
+    pub omem: usize,
+    /// 输出缓冲区开始超过软限制的时间
+    /// Time the output buffer started exceeding the soft limit
+    pub soft_limit_since: Option<Instant>,
+    /// 是否不参与内存淘汰，由 `CLIENT NO-EVICT` 设置
+    /// Whether the client is excluded from eviction, set by `CLIENT NO-EVICT`
+    pub no_evict: bool,
+    /// 认证的用户
+    /// Authenticated user
+    pub user: String,
+    /// 是否已经认证，默认用户需要密码时新连接未认证
+    /// Whether the client is authenticated, new connections are not when the default user needs a password
+    pub authenticated: bool,
+    /// 订阅的频道和模式的积压
+    /// Lags of the subscribed channels and patterns
+    pub pubsub_lags: Vec<Lag>,
+    /// 因为订阅跟不上发布速度而丢弃的消息总数
+    /// Total number of messages dropped because the subscriptions could not keep up with publishers
+    pub pubsub_dropped: u64,
+}
+
+/// 连接自身持有的客户端，包含推送消息的接收端
+/// The client owned by the connection itself, including the receiver of pushed messages.
+#[derive(Debug)]
+pub struct Client {
+    /// 共享的客户端句柄
+    /// Shared client handle
+    pub handle: Arc<ClientHandle>,
+    /// 推送消息的接收端
+    /// Receiver of pushed messages
+    pub pushes: mpsc::UnboundedReceiver<Frame>,
+    /// 回复模式，由 `CLIENT REPLY` 设置
+    /// Reply mode, set by `CLIENT REPLY`
+    pub reply: ReplyMode,
+}
+
+/// 回复模式
+/// Reply mode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReplyMode {
+    /// 正常回复
+    /// Reply normally
+    On,
+    /// 不回复任何命令
+    /// Do not reply to any command
+    Off,
+    /// 不回复下一条命令
+    /// Do not reply to the next command
+    SkipNext,
+    /// 不回复当前命令，之后恢复正常回复
+    /// Do not reply to the current command, then reply normally again
+    Skip,
+}
+
+/// 客户端类型，用于 `CLIENT LIST TYPE` 和 `CLIENT KILL TYPE`
+/// Client type, used by `CLIENT LIST TYPE` and `CLIENT KILL TYPE`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClientType {
+    Normal,
+    Replica,
+    Master,
+    PubSub,
+}
+
+/// 暂停的范围
+/// Scope of a pause.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PauseMode {
+    /// 只暂停写命令
+    /// Only pause write commands
+    Write,
+    /// 暂停所有命令
+    /// Pause all commands
+    All,
+}
+
+/// `CLIENT PAUSE` 的状态
+/// State of `CLIENT PAUSE`.
+#[derive(Debug, Clone, Copy)]
+struct Pause {
+    /// 暂停结束的时间
+    /// Time the pause ends
+    until: Instant,
+    /// 暂停的范围
+    /// Scope of the pause
+    mode: PauseMode,
+}
+
+impl ClientType {
+    /// 解析客户端类型的名称
+    /// Parse the name of a client type.
+    pub fn parse(name: &str) -> Option<ClientType> {
+        match name.to_lowercase().as_str() {
+            "normal" => Some(ClientType::Normal),
+            "replica" | "slave" => Some(ClientType::Replica),
+            "master" => Some(ClientType::Master),
+            "pubsub" => Some(ClientType::PubSub),
+            _ => None,
+        }
+    }
+}
+
+impl ClientHandle {
+    /// 向客户端推送一条消息，客户端已断开时忽略
+    /// Push a message to the client, ignored if the client has disconnected.
+    pub fn push(&self, frame: Frame) {
+        let size = frame.to_bytes().map_or(0, |bytes| bytes.len());
+        if self.push.send(frame).is_ok() {
+            self.queued(size);
+            // 消费太慢的客户端会被断开
+            // Clients consuming too slowly are disconnected
+            if self.exceeds_output_limit(0) {
+                warn!("客户端 {} 超过输出缓冲区限制，断开连接", self.id);  // Output buffer limit reached, disconnecting
+                self.kill();
+            }
+        }
+    }
+
+    /// 加上即将写出的字节数后，输出缓冲区是否超过客户端类型的限制
+    /// Whether the output buffer, plus the bytes about to be written, exceeds the limits of the client type.
+    ///
+    /// 超过硬限制立即返回 true；超过软限制时开始计时，持续超过 `soft_seconds` 秒后返回 true。
+    /// Returns true immediately above the hard limit; above the soft limit a timer starts, and true is
+    /// returned once it has been exceeded for `soft_seconds` seconds.
+    pub fn exceeds_output_limit(&self, pending: usize) -> bool {
+        let limits = get_server_config().client_output_buffer_limit;
+        let limit = match self.client_type() {
+            ClientType::Normal => limits.normal,
+            ClientType::PubSub => limits.pubsub,
+            ClientType::Replica | ClientType::Master => limits.replica,
+        };
+        let mut state = self.state.lock().unwrap();
+        let used = state.omem + pending;
+        if limit.hard_limit > 0 && used > limit.hard_limit {
+            return true;
+        }
+        if limit.soft_limit > 0 && used > limit.soft_limit {
+            let since = *state.soft_limit_since.get_or_insert_with(Instant::now);
+            return since.elapsed() >= Duration::from_secs(limit.soft_seconds);
+        }
+        state.soft_limit_since = None;
+        false
+    }
+
+    /// 一条等待发送的消息计入输出缓冲区
+    /// Account a message waiting to be sent in the output buffer.
+    pub fn queued(&self, size: usize) {
+        self.update(|state| {
+            state.oll += 1;
+            state.omem += size;
+        });
+    }
+
+    /// 推送的消息已发送给客户端，从输出缓冲区中扣除
+    /// A pushed message has been sent to the client, and is removed from the output buffer.
+    pub fn pushed(&self, frame: &Frame) {
+        self.sent(frame.to_bytes().map_or(0, |bytes| bytes.len()));
+    }
+
+    /// 计入输出缓冲区的消息已发送给客户端
+    /// A message accounted in the output buffer has been sent to the client.
+    pub fn sent(&self, size: usize) {
+        self.update(|state| {
+            state.oll = state.oll.saturating_sub(1);
+            state.omem = state.omem.saturating_sub(size);
+        });
+    }
+
+    /// 断开该客户端的连接
+    /// Disconnect this client.
+    pub fn kill(&self) {
+        self.kill.notify_one();
+    }
+
+    /// 断开信号，交给连接的 `Shutdown` 监听
+    /// The disconnect signal, listened to by the connection's `Shutdown`.
+    pub fn kill_signal(&self) -> Arc<Notify> {
+        self.kill.clone()
+    }
+
+    /// 获取客户端状态的快照
+    /// Get a snapshot of the client state.
+    pub fn state(&self) -> ClientState {
+        self.state.lock().unwrap().clone()
+    }
+
+    /// 修改客户端状态
+    /// Modify the client state.
+    pub fn update<F: FnOnce(&mut ClientState)>(&self, f: F) {
+        f(&mut self.state.lock().unwrap());
+    }
+
+    /// 客户端类型
+    /// The client type.
+    pub fn client_type(&self) -> ClientType {
+        if self.state().pubsub {
+            ClientType::PubSub
+        } else {
+            ClientType::Normal
+        }
+    }
+
+    /// 订阅的频道和模式中尚未处理的消息总数
+    /// Total number of messages not handled yet across the subscribed channels and patterns.
+    pub fn pubsub_lag(&self) -> u64 {
+        self.state.lock().unwrap().pubsub_lags.iter().map(Lag::pending).sum()
+    }
+
+    /// 生成 `CLIENT LIST` 和 `CLIENT INFO` 中的一行客户端信息
+    /// Generate a line of client information for `CLIENT LIST` and `CLIENT INFO`.
+    pub fn info(&self) -> String {
+        let state = self.state();
+        let tracking = tracking::options(self.id);
+
+        // 客户端标志，与 Redis 相同
+        // Client flags, the same as Redis
+        let mut flags = String::new();
+        if state.pubsub {
+            flags.push('P');
+        }
+        if let Some(options) = &tracking {
+            flags.push('t');
+            if options.bcast {
+                flags.push('B');
+            }
+            if options.redirect.is_some_and(|id| lookup(id).is_none()) {
+                flags.push('R');
+            }
+        }
+        if state.no_evict {
+            flags.push('e');
+        }
+        if flags.is_empty() {
+            flags.push('N');
+        }
+        let redir = match &tracking {
+            Some(options) => options.redirect.map_or(0, |id| id as i64),
+            None => -1,
+        };
+
+        format!(
+            "id={} addr={} laddr={} name={} age={} idle={} flags={} db={} sub={} psub={} ssub={} qbuf={} qbuf-free={} obl=0 oll={} omem={} cmd={} user={} redir={} resp={}",
+            self.id,
+            self.addr,
+            self.laddr,
+            state.name.as_deref().unwrap_or(""),
+            self.created.elapsed().as_secs(),
+            state.last_interaction.elapsed().as_secs(),
+            flags,
+            state.db,
+            state.sub,
+            state.psub,
+            state.ssub,
+            state.qbuf,
+            state.qbuf_free,
+            state.oll,
+            state.omem,
+            if state.cmd.is_empty() { "NULL" } else { state.cmd.as_str() },
+            state.user,
+            redir,
+            state.resp,
+        )
+    }
+}
+
+impl Client {
+    /// 创建客户端并注册到注册表中
+    /// Create a client and register it in the registry.
+    pub fn register(addr: String, laddr: String) -> Client {
+        let (push, pushes) = mpsc::unbounded_channel();
+        let now = Instant::now();
+        let handle = Arc::new(ClientHandle {
+            id: NEXT_CLIENT_ID.fetch_add(1, Ordering::Relaxed),
+            addr,
+            laddr,
+            created: now,
+            push,
+            kill: Arc::new(Notify::new()),
+            state: Mutex::new(ClientState {
+                resp: 2,
+                pubsub: false,
+                name: None,
+                db: 0,
+                cmd: String::new(),
+                last_interaction: now,
+                sub: 0,
+                psub: 0,
+                ssub: 0,
+                invalidate_subscribed: false,
+                qbuf: 0,
+                qbuf_free: 0,
+                oll: 0,
+                omem: 0,
+                soft_limit_since: None,
+                no_evict: false,
+                user: acl::DEFAULT_USER.to_string(),
+                authenticated: acl::default_user_needs_no_auth(),
+                pubsub_lags: Vec::new(),
+                pubsub_dropped: 0,
+            }),
+        });
+        CLIENT_REGISTRY.write().unwrap().insert(handle.id, handle.clone());
+        Client { handle, pushes, reply: ReplyMode::On }
+    }
+
+    /// 客户端 ID
+    /// Client ID.
+    pub fn id(&self) -> u64 {
+        self.handle.id
+    }
+
+    /// 开始处理一条命令，`SkipNext` 在这里变为 `Skip`
+    /// Start processing a command, `SkipNext` becomes `Skip` here.
+    pub fn begin_command(&mut self) {
+        self.reply = match self.reply {
+            ReplyMode::SkipNext => ReplyMode::Skip,
+            ReplyMode::Skip => ReplyMode::On,
+            mode => mode,
+        };
+    }
+
+    /// 当前命令是否需要回复
+    /// Whether the current command should be replied to.
+    pub fn should_reply(&self) -> bool {
+        self.reply == ReplyMode::On
+    }
+}
+
+impl Drop for Client {
+    /// 连接结束时从注册表中移除
+    /// Remove from the registry when the connection ends.
+    fn drop(&mut self) {
+        CLIENT_REGISTRY.write().unwrap().remove(&self.handle.id);
+        tracking::disable(self.handle.id);
+    }
+}
+
+/// 根据 ID 查找客户端
+/// Look up a client by ID.
+pub fn lookup(id: u64) -> Option<Arc<ClientHandle>> {
+    CLIENT_REGISTRY.read().unwrap().get(&id).cloned()
+}
+
+/// 在线客户端的数量
+/// Number of connected clients.
+pub fn count() -> usize {
+    CLIENT_REGISTRY.read().unwrap().len()
+}
+
+/// 所有在线客户端，按 ID 排序
+/// All connected clients, sorted by ID.
+pub fn all() -> Vec<Arc<ClientHandle>> {
+    let mut clients: Vec<Arc<ClientHandle>> = CLIENT_REGISTRY.read().unwrap().values().cloned().collect();
+    clients.sort_by_key(|client| client.id);
+    clients
+}
+
+/// 暂停客户端，直到超时或 `CLIENT UNPAUSE`
+/// Pause clients until the timeout expires or `CLIENT UNPAUSE` is called.
+///
+/// 已经处于暂停状态时，取更晚的结束时间和更大的暂停范围。
+/// If clients are already paused, the later end time and the wider scope are kept.
+pub fn pause(timeout: Duration, mode: PauseMode) {
+    let mut pause = PAUSE.lock().unwrap();
+    let until = Instant::now() + timeout;
+    *pause = Some(match *pause {
+        Some(current) if current.until > Instant::now() => Pause {
+            until: current.until.max(until),
+            mode: if current.mode == PauseMode::All { PauseMode::All } else { mode },
+        },
+        _ => Pause { until, mode },
+    });
+}
+
+/// 解除暂停
+/// Lift the pause.
+pub fn unpause() {
+    *PAUSE.lock().unwrap() = None;
+    UNPAUSED.notify_waiters();
+}
+
+/// 如果命令被暂停，等待暂停结束
+/// Wait for the pause to end if the command is paused.
+///
+/// `CLIENT` 命令不会被暂停，以便可以执行 `CLIENT UNPAUSE`。
+/// `CLIENT` commands are never paused, so that `CLIENT UNPAUSE` can be executed.
+pub async fn wait_if_paused(command: &Command) {
+    if command.name == "client" {
+        return;
+    }
+    loop {
+        // 先注册唤醒，避免错过检查之后的解除暂停
+        // Register for wake-up first, so an unpause right after the check is not missed
+        let unpaused = UNPAUSED.notified();
+        let until = match *PAUSE.lock().unwrap() {
+            Some(pause) if pause.until > Instant::now()
+                && (pause.mode == PauseMode::All || is_write_command(command)) => pause.until,
+            _ => return,
+        };
+        tokio::select! {
+            _ = tokio::time::sleep_until(until.into()) => {}
+            _ = unpaused => {}
+        }
+    }
+}
+
+/// 会修改数据的命令，`CLIENT PAUSE WRITE` 时被暂停，发布消息的命令也会被暂停
+/// Commands that may modify data, paused by `CLIENT PAUSE WRITE` along with the commands publishing messages.
+fn is_write_command(command: &Command) -> bool {
+    command.has_flag("write") || matches!(command.name.as_str(), "publish" | "spublish")
+}
@@ -0,0 +1,33 @@
+use crate::acl;
+use crate::client::Client;
+use crate::error::Error;
+use crate::frame::Frame;
+use crate::parse::Parse;
+
+/// `ACL CAT` lists the command categories, or the commands in a category.
+/// `ACL CAT` 列出命令类别，或某个类别中的命令。
+///
+/// # Example
+/// ```text
+/// ACL CAT [category]
+/// ```
+pub struct Cat;
+
+impl Cat {
+    /// Executes the `ACL CAT` command.
+    /// 执行 `ACL CAT` 命令。
+    pub fn cat_command(_client: &mut Client, parse: &mut Parse) -> crate::Result<Frame> {
+        let bulk = |s: &str| Frame::Bulk(s.as_bytes().to_vec());
+        match parse.args_number()? {
+            0 => Ok(Frame::Array(acl::CATEGORIES.iter().map(|c| bulk(c)).collect())),
+            1 => {
+                let category = parse.next_string()?.to_lowercase();
+                if !acl::CATEGORIES.contains(&category.as_str()) {
+                    return Err(Error::Err(format!("Unknown category '{}'", category)).into());
+                }
+                Ok(Frame::Array(acl::commands_in_category(&category).iter().map(|name| bulk(name)).collect()))
+            }
+            _ => Err(Error::WrongArity("acl|cat".to_string()).into()),
+        }
+    }
+}
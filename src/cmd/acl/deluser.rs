@@ -0,0 +1,38 @@
+use crate::acl;
+use crate::client::Client;
+use crate::frame::Frame;
+use crate::parse::Parse;
+
+/// `ACL DELUSER` deletes users and disconnects the connections authenticated as them.
+/// `ACL DELUSER` 删除用户，并断开以这些用户认证的连接。
+///
+/// Returns the number of users deleted, the `default` user cannot be deleted.
+/// 返回删除的用户数量，`default` 用户不能被删除。
+///
+/// # Example
+/// ```text
+/// ACL DELUSER username [username ...]
+/// ```
+pub struct DelUser {
+    usernames: Vec<String>,  // The users to delete / 要删除的用户
+}
+
+impl DelUser {
+    /// Executes the `ACL DELUSER` command.
+    /// 执行 `ACL DELUSER` 命令。
+    pub fn deluser_command(_client: &mut Client, parse: &mut Parse) -> crate::Result<Frame> {
+        let deluser = DelUser::parse_command(parse)?;
+        let deleted = acl::delete_users(&deluser.usernames)?;
+        Ok(Frame::Integer(deleted as i64))
+    }
+
+    /// Parses the usernames.
+    /// 解析用户名。
+    fn parse_command(parse: &mut Parse) -> crate::Result<Self> {
+        let mut usernames = Vec::new();
+        while let Ok(username) = parse.next_string() {
+            usernames.push(username);
+        }
+        Ok(DelUser { usernames })
+    }
+}
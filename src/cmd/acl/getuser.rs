@@ -0,0 +1,33 @@
+use crate::acl;
+use crate::client::Client;
+use crate::frame::Frame;
+use crate::parse::Parse;
+
+/// `ACL GETUSER` returns the rules of a user, or nil if the user does not exist.
+/// `ACL GETUSER` 返回用户的规则，用户不存在时返回 nil。
+///
+/// # Example
+/// ```text
+/// ACL GETUSER username
+/// ```
+pub struct GetUser;
+
+impl GetUser {
+    /// Executes the `ACL GETUSER` command.
+    /// 执行 `ACL GETUSER` 命令。
+    pub fn getuser_command(client: &mut Client, parse: &mut Parse) -> crate::Result<Frame> {
+        let Some(user) = acl::get_user(&parse.next_string()?) else {
+            return Ok(Frame::Null);
+        };
+        let bulk = |s: &str| Frame::Bulk(s.as_bytes().to_vec());
+        let fields = vec![
+            (bulk("flags"), Frame::Array(user.flags().into_iter().map(bulk).collect())),
+            (bulk("passwords"), Frame::Array(user.passwords.iter().map(|hash| bulk(hash)).collect())),
+            (bulk("commands"), bulk(&user.command_rules())),
+            (bulk("keys"), bulk(&user.key_rules())),
+            (bulk("channels"), bulk(&user.channel_rules())),
+            (bulk("selectors"), Frame::Array(vec![])),
+        ];
+        Ok(Frame::map(fields, client.handle.state().resp))
+    }
+}
@@ -0,0 +1,20 @@
+use crate::acl;
+use crate::client::Client;
+use crate::frame::Frame;
+use crate::parse::Parse;
+
+/// `ACL LIST` returns every user and its rules, in the same format as the ACL file.
+/// `ACL LIST` 返回所有用户及其规则，格式与 ACL 文件相同。
+pub struct List;
+
+impl List {
+    /// Executes the `ACL LIST` command.
+    /// 执行 `ACL LIST` 命令。
+    pub fn list_command(_client: &mut Client, _parse: &mut Parse) -> crate::Result<Frame> {
+        let users = acl::users()
+            .iter()
+            .map(|user| Frame::Bulk(user.describe().into_bytes()))
+            .collect();
+        Ok(Frame::Array(users))
+    }
+}
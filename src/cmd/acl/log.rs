@@ -0,0 +1,73 @@
+use crate::acl;
+use crate::client::Client;
+use crate::error::Error;
+use crate::frame::Frame;
+use crate::parse::Parse;
+
+/// `ACL LOG` shows the recent denied commands and failed authentications, newest first.
+/// `ACL LOG` 显示最近被拒绝的命令和认证失败，最新的在最前面。
+///
+/// # Example
+/// ```text
+/// ACL LOG [count | RESET]
+/// ```
+pub struct Log {
+    count: usize,  // Number of entries to return / 返回的记录数量
+    reset: bool,   // Whether to clear the log / 是否清空记录
+}
+
+impl Log {
+    /// Executes the `ACL LOG` command.
+    /// 执行 `ACL LOG` 命令。
+    pub fn log_command(client: &mut Client, parse: &mut Parse) -> crate::Result<Frame> {
+        let log = Log::parse_command(parse)?;
+        if log.reset {
+            acl::reset_log();
+            return Ok(Frame::Simple("OK".to_string()));
+        }
+
+        let resp = client.handle.state().resp;
+        let bulk = |s: &str| Frame::Bulk(s.as_bytes().to_vec());
+        let entries = acl::log_entries(log.count)
+            .into_iter()
+            .map(|entry| {
+                let fields = vec![
+                    (bulk("count"), Frame::Integer(entry.count as i64)),
+                    (bulk("reason"), bulk(&entry.reason)),
+                    (bulk("context"), bulk(&entry.context)),
+                    (bulk("object"), bulk(&entry.object)),
+                    (bulk("username"), bulk(&entry.username)),
+                    (bulk("age-seconds"), bulk(&format!("{:.3}", entry.created.elapsed().as_secs_f64()))),
+                    (bulk("client-info"), bulk(&entry.client_info)),
+                    (bulk("entry-id"), Frame::Integer(entry.entry_id as i64)),
+                    (bulk("timestamp-created"), Frame::Integer(entry.timestamp_created as i64)),
+                    (bulk("timestamp-last-updated"), Frame::Integer(entry.timestamp_updated as i64)),
+                ];
+                Frame::map(fields, resp)
+            })
+            .collect();
+        Ok(Frame::Array(entries))
+    }
+
+    /// Parses the optional count or `RESET`.
+    /// 解析可选的数量或 `RESET`。
+    fn parse_command(parse: &mut Parse) -> crate::Result<Self> {
+        let mut log = Log { count: 10, reset: false };
+        match parse.args_number()? {
+            0 => {}
+            1 => {
+                let arg = parse.next_string()?;
+                if arg.eq_ignore_ascii_case("reset") {
+                    log.reset = true;
+                } else {
+                    match arg.parse::<usize>() {
+                        Ok(count) => log.count = count,
+                        Err(_) => return Err(Error::Err("value is out of range, must be positive".to_string()).into()),
+                    }
+                }
+            }
+            _ => return Err(Error::WrongArity("acl|log".to_string()).into()),
+        }
+        Ok(log)
+    }
+}
@@ -0,0 +1,42 @@
+pub mod setuser;
+pub mod getuser;
+pub mod deluser;
+pub mod list;
+pub mod whoami;
+pub mod cat;
+pub mod log;
+
+use crate::client::Client;
+use crate::error::Error;
+use crate::frame::Frame;
+use crate::parse::Parse;
+
+/// `ACL` command, dispatches to its subcommands.
+/// `ACL` 命令，分发到各个子命令。
+///
+/// # Example
+/// ```text
+/// ACL <subcommand> [arguments ...]
+/// ```
+pub struct AclCommand;
+
+impl AclCommand {
+    /// Executes the `ACL` command.
+    /// 执行 `ACL` 命令。
+    pub fn acl_command(client: &mut Client, parse: &mut Parse) -> crate::Result<Frame> {
+        let subcommand = parse.next_string()?.to_lowercase();
+        // Shown as the last command in `CLIENT LIST`
+        // 在 `CLIENT LIST` 中显示为最后执行的命令
+        client.handle.update(|state| state.cmd = format!("acl|{}", subcommand));
+        match subcommand.as_str() {
+            "setuser" => setuser::SetUser::setuser_command(client, parse),
+            "getuser" => getuser::GetUser::getuser_command(client, parse),
+            "deluser" => deluser::DelUser::deluser_command(client, parse),
+            "list" => list::List::list_command(client, parse),
+            "whoami" => whoami::WhoAmI::whoami_command(client, parse),
+            "cat" => cat::Cat::cat_command(client, parse),
+            "log" => log::Log::log_command(client, parse),
+            _ => Err(Error::Err(format!("unknown subcommand '{}'. Try ACL HELP.", subcommand)).into()),
+        }
+    }
+}
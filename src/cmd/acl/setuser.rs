@@ -0,0 +1,41 @@
+use crate::acl;
+use crate::client::Client;
+use crate::frame::Frame;
+use crate::parse::Parse;
+
+/// `ACL SETUSER` creates a user, or modifies an existing one, by applying rules in order.
+/// `ACL SETUSER` 按顺序应用规则，创建用户或修改已有的用户。
+///
+/// If any rule is invalid the user is left unchanged.
+/// 任一规则无效时不修改用户。
+///
+/// # Example
+/// ```text
+/// ACL SETUSER username [rule [rule ...]]
+/// ACL SETUSER alice on >secret ~cache:* %R~logs:* &news:* +@read -@dangerous
+/// ```
+pub struct SetUser {
+    username: String,    // The user to create or modify / 要创建或修改的用户
+    rules: Vec<String>,  // The rules to apply / 要应用的规则
+}
+
+impl SetUser {
+    /// Executes the `ACL SETUSER` command.
+    /// 执行 `ACL SETUSER` 命令。
+    pub fn setuser_command(_client: &mut Client, parse: &mut Parse) -> crate::Result<Frame> {
+        let setuser = SetUser::parse_command(parse)?;
+        acl::set_user(&setuser.username, &setuser.rules)?;
+        Ok(Frame::Simple("OK".to_string()))
+    }
+
+    /// Parses the username and the rules.
+    /// 解析用户名和规则。
+    fn parse_command(parse: &mut Parse) -> crate::Result<Self> {
+        let username = parse.next_string()?;
+        let mut rules = Vec::new();
+        while let Ok(rule) = parse.next_string() {
+            rules.push(rule);
+        }
+        Ok(SetUser { username, rules })
+    }
+}
@@ -0,0 +1,15 @@
+use crate::client::Client;
+use crate::frame::Frame;
+use crate::parse::Parse;
+
+/// `ACL WHOAMI` returns the user the current connection is authenticated as.
+/// `ACL WHOAMI` 返回当前连接认证的用户。
+pub struct WhoAmI;
+
+impl WhoAmI {
+    /// Executes the `ACL WHOAMI` command.
+    /// 执行 `ACL WHOAMI` 命令。
+    pub fn whoami_command(client: &mut Client, _parse: &mut Parse) -> crate::Result<Frame> {
+        Ok(Frame::Bulk(client.handle.state().user.into_bytes()))
+    }
+}
@@ -0,0 +1,64 @@
+use crate::acl;
+use crate::client::Client;
+use crate::error::Error;
+use crate::frame::Frame;
+use crate::parse::Parse;
+
+/// `AUTH` command, authenticates the current connection.
+/// `AUTH` 命令，认证当前连接。
+///
+/// # Example
+/// ```text
+/// AUTH [username] password
+/// ```
+///
+/// Without a username the `default` user is used, which is how `requirepass` works.
+/// 不指定用户名时使用 `default` 用户，即 `requirepass` 的认证方式。
+pub struct Auth {
+    username: Option<String>,  // The user to authenticate as / 认证的用户
+    password: String,          // The password / 密码
+}
+
+impl Auth {
+    /// Executes the `AUTH` command.
+    /// 执行 `AUTH` 命令。
+    pub fn auth_command(client: &mut Client, parse: &mut Parse) -> crate::Result<Frame> {
+        let auth = Auth::parse_command(parse)?;
+        // AUTH <password> makes no sense if the default user needs no password
+        // 默认用户无需密码时 AUTH <password> 没有意义
+        if auth.username.is_none()
+            && acl::get_user(acl::DEFAULT_USER).is_some_and(|user| user.nopass)
+        {
+            return Err(Error::Err(
+                "AUTH <password> called without any password configured for the default user. Are you sure your configuration is correct?".to_string(),
+            ).into());
+        }
+        let username = auth.username.unwrap_or_else(|| acl::DEFAULT_USER.to_string());
+        authenticate(client, &username, &auth.password)?;
+        Ok(Frame::Simple("OK".to_string()))
+    }
+
+    /// Parses the optional username and the password.
+    /// 解析可选的用户名和密码。
+    fn parse_command(parse: &mut Parse) -> crate::Result<Self> {
+        match parse.args_number()? {
+            1 => Ok(Auth { username: None, password: parse.next_string()? }),
+            2 => Ok(Auth { username: Some(parse.next_string()?), password: parse.next_string()? }),
+            _ => Err(Error::WrongArity("auth".to_string()).into()),
+        }
+    }
+}
+
+/// Authenticates the client as the user, shared with `HELLO AUTH`; failures are recorded in ACL LOG.
+/// 将客户端认证为该用户，与 `HELLO AUTH` 共用；认证失败会记录到 ACL LOG。
+pub fn authenticate(client: &mut Client, username: &str, password: &str) -> Result<(), Error> {
+    if acl::authenticate(username, password) {
+        client.handle.update(|state| {
+            state.user = username.to_string();
+            state.authenticated = true;
+        });
+        return Ok(());
+    }
+    acl::log("auth", "AUTH", username, client.handle.info());
+    Err(Error::Other("WRONGPASS invalid username-password pair or user is disabled.".to_string()))
+}
@@ -0,0 +1,51 @@
+use crate::client::Client;
+use crate::frame::Frame;
+use crate::parse::Parse;
+use crate::tracking;
+use crate::error::Error;
+
+/// `CLIENT CACHING` controls whether the keys read by the next command are tracked.
+/// `CLIENT CACHING` 控制下一条命令读取的键是否被追踪。
+///
+/// Only valid when tracking is enabled in `OPTIN` (`yes`) or `OPTOUT` (`no`) mode.
+/// 只在 `OPTIN`（`yes`）或 `OPTOUT`（`no`）模式下开启追踪时有效。
+///
+/// # Example
+/// ```text
+/// CLIENT CACHING yes|no
+/// ```
+pub struct Caching {
+    yes: bool,  // Whether to cache the keys read by the next command / 是否缓存下一条命令读取的键
+}
+
+impl Caching {
+    /// Executes the `CLIENT CACHING` command.
+    /// 执行 `CLIENT CACHING` 命令。
+    pub fn caching_command(client: &mut Client, parse: &mut Parse) -> crate::Result<Frame> {
+        let caching = Caching::parse_command(parse)?;
+        let options = match tracking::options(client.id()) {
+            Some(options) if options.optin || options.optout => options,
+            _ => {
+                return Err(Error::Err("CLIENT CACHING can be called only when the client is in tracking mode with OPTIN or OPTOUT mode enabled".to_string()).into());
+            }
+        };
+        if caching.yes && !options.optin {
+            return Err(Error::Err("CLIENT CACHING YES is only valid when tracking is enabled in OPTIN mode.".to_string()).into());
+        }
+        if !caching.yes && !options.optout {
+            return Err(Error::Err("CLIENT CACHING NO is only valid when tracking is enabled in OPTOUT mode.".to_string()).into());
+        }
+        tracking::set_caching(client.id(), caching.yes);
+        Ok(Frame::Simple("OK".to_string()))
+    }
+
+    /// Parses the `yes` or `no` argument.
+    /// 解析 `yes` 或 `no` 参数。
+    fn parse_command(parse: &mut Parse) -> crate::Result<Self> {
+        match parse.next_string()?.to_lowercase().as_str() {
+            "yes" => Ok(Caching { yes: true }),
+            "no" => Ok(Caching { yes: false }),
+            _ => Err(Error::Syntax.into()),
+        }
+    }
+}
@@ -0,0 +1,18 @@
+use crate::client::Client;
+use crate::frame::Frame;
+use crate::parse::Parse;
+
+/// `CLIENT GETNAME` returns the name of the current connection, or nil if no name is set.
+/// `CLIENT GETNAME` 返回当前连接的名称，未设置名称时返回 nil。
+pub struct GetName;
+
+impl GetName {
+    /// Executes the `CLIENT GETNAME` command.
+    /// 执行 `CLIENT GETNAME` 命令。
+    pub fn getname_command(client: &mut Client, _parse: &mut Parse) -> crate::Result<Frame> {
+        match client.handle.state().name {
+            Some(name) => Ok(Frame::Bulk(name.into_bytes())),
+            None => Ok(Frame::Null),
+        }
+    }
+}
@@ -0,0 +1,23 @@
+use crate::client::Client;
+use crate::frame::Frame;
+use crate::parse::Parse;
+use crate::tracking;
+
+/// `CLIENT GETREDIR` returns the client ID that invalidation messages are redirected to.
+/// `CLIENT GETREDIR` 返回失效消息重定向到的客户端 ID。
+///
+/// Returns `-1` if tracking is disabled and `0` if tracking is enabled without redirection.
+/// 未开启追踪时返回 `-1`，开启追踪但没有重定向时返回 `0`。
+pub struct GetRedir;
+
+impl GetRedir {
+    /// Executes the `CLIENT GETREDIR` command.
+    /// 执行 `CLIENT GETREDIR` 命令。
+    pub fn getredir_command(client: &mut Client, _parse: &mut Parse) -> crate::Result<Frame> {
+        let redirect = match tracking::options(client.id()) {
+            Some(options) => options.redirect.map_or(0, |id| id as i64),
+            None => -1,
+        };
+        Ok(Frame::Integer(redirect))
+    }
+}
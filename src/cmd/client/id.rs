@@ -0,0 +1,15 @@
+use crate::client::Client;
+use crate::frame::Frame;
+use crate::parse::Parse;
+
+/// `CLIENT ID` returns the ID of the current connection.
+/// `CLIENT ID` 返回当前连接的 ID。
+pub struct Id;
+
+impl Id {
+    /// Executes the `CLIENT ID` command.
+    /// 执行 `CLIENT ID` 命令。
+    pub fn id_command(client: &mut Client, _parse: &mut Parse) -> crate::Result<Frame> {
+        Ok(Frame::Integer(client.id() as i64))
+    }
+}
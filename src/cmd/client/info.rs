@@ -0,0 +1,17 @@
+use crate::client::Client;
+use crate::frame::Frame;
+use crate::parse::Parse;
+
+/// `CLIENT INFO` returns information about the current connection, in the same format as `CLIENT LIST`.
+/// `CLIENT INFO` 返回当前连接的信息，格式与 `CLIENT LIST` 相同。
+pub struct Info;
+
+impl Info {
+    /// Executes the `CLIENT INFO` command.
+    /// 执行 `CLIENT INFO` 命令。
+    pub fn info_command(client: &mut Client, _parse: &mut Parse) -> crate::Result<Frame> {
+        let mut info = client.handle.info();
+        info.push('\n');
+        Ok(Frame::Bulk(info.into_bytes()))
+    }
+}
@@ -0,0 +1,134 @@
+use crate::client::{self, Client, ClientHandle, ClientType};
+use crate::frame::Frame;
+use crate::parse::Parse;
+use crate::error::Error;
+
+/// `CLIENT KILL` closes client connections through their shutdown signal.
+/// `CLIENT KILL` 通过关闭信号断开客户端连接。
+///
+/// # Example
+/// ```text
+/// CLIENT KILL ip:port
+/// CLIENT KILL [ID client-id] [TYPE <NORMAL | MASTER | REPLICA | PUBSUB>] [USER username]
+///             [ADDR ip:port] [LADDR ip:port] [SKIPME <YES | NO>] [MAXAGE maxage]
+/// ```
+///
+/// The old form replies `OK`, the filter form replies the number of clients killed.
+/// 旧格式回复 `OK`，过滤条件格式回复断开的客户端数量。
+pub struct Kill {
+    old_form: bool,                   // Whether the old `CLIENT KILL ip:port` form is used / 是否使用旧格式
+    id: Option<u64>,                  // Filter by client ID / 按客户端 ID 过滤
+    client_type: Option<ClientType>,  // Filter by client type / 按客户端类型过滤
+    user: Option<String>,             // Filter by authenticated user / 按认证用户过滤
+    addr: Option<String>,             // Filter by client address / 按客户端地址过滤
+    laddr: Option<String>,            // Filter by local address / 按本地地址过滤
+    skipme: bool,                     // Whether to skip the calling client / 是否跳过当前客户端
+    maxage: Option<u64>,              // Only kill clients older than this, in seconds / 只断开存在时间超过该值的客户端，单位秒
+}
+
+impl Kill {
+    /// Executes the `CLIENT KILL` command.
+    /// 执行 `CLIENT KILL` 命令。
+    pub fn kill_command(client: &mut Client, parse: &mut Parse) -> crate::Result<Frame> {
+        let kill = Kill::parse_command(parse)?;
+
+        let mut killed = 0;
+        for handle in client::all() {
+            if kill.matches(client, &handle) {
+                handle.kill();
+                killed += 1;
+            }
+        }
+
+        if kill.old_form {
+            if killed == 0 {
+                return Err(Error::Err("No such client".to_string()).into());
+            }
+            return Ok(Frame::Simple("OK".to_string()));
+        }
+        Ok(Frame::Integer(killed))
+    }
+
+    /// Whether a client matches all the filters.
+    /// 客户端是否满足所有过滤条件。
+    fn matches(&self, client: &Client, handle: &ClientHandle) -> bool {
+        if self.skipme && handle.id == client.id() {
+            return false;
+        }
+        if self.id.is_some_and(|id| id != handle.id) {
+            return false;
+        }
+        if self.client_type.is_some_and(|t| t != handle.client_type()) {
+            return false;
+        }
+        if self.addr.as_ref().is_some_and(|addr| *addr != handle.addr) {
+            return false;
+        }
+        if self.laddr.as_ref().is_some_and(|laddr| *laddr != handle.laddr) {
+            return false;
+        }
+        if self.maxage.is_some_and(|maxage| handle.created.elapsed().as_secs() < maxage) {
+            return false;
+        }
+        if let Some(user) = &self.user {
+            return *user == handle.state().user;
+        }
+        true
+    }
+
+    /// Parses either the old form or the filters.
+    /// 解析旧格式或过滤条件。
+    fn parse_command(parse: &mut Parse) -> crate::Result<Self> {
+        let mut kill = Kill {
+            old_form: false,
+            id: None,
+            client_type: None,
+            user: None,
+            addr: None,
+            laddr: None,
+            skipme: true,
+            maxage: None,
+        };
+
+        match parse.args_number()? {
+            // The old form only takes the address and does not skip the calling client
+            // 旧格式只有地址参数，并且不跳过当前客户端
+            1 => {
+                kill.old_form = true;
+                kill.skipme = false;
+                kill.addr = Some(parse.next_string()?);
+                return Ok(kill);
+            }
+            n if n % 2 != 0 => return Err(Error::Syntax.into()),
+            _ => {}
+        }
+
+        while let Ok(option) = parse.next_string() {
+            let value = parse.next_string()?;
+            match option.to_lowercase().as_str() {
+                "id" => match value.parse::<u64>() {
+                    Ok(id) if id > 0 => kill.id = Some(id),
+                    _ => return Err(Error::Err("client-id should be greater than 0".to_string()).into()),
+                },
+                "type" => match ClientType::parse(&value) {
+                    Some(client_type) => kill.client_type = Some(client_type),
+                    None => return Err(Error::Err(format!("Unknown client type '{}'", value)).into()),
+                },
+                "user" => kill.user = Some(value),
+                "addr" => kill.addr = Some(value),
+                "laddr" => kill.laddr = Some(value),
+                "skipme" => match value.to_lowercase().as_str() {
+                    "yes" => kill.skipme = true,
+                    "no" => kill.skipme = false,
+                    _ => return Err(Error::Syntax.into()),
+                },
+                "maxage" => match value.parse::<u64>() {
+                    Ok(maxage) => kill.maxage = Some(maxage),
+                    Err(_) => return Err(Error::NotInteger.into()),
+                },
+                _ => return Err(Error::Syntax.into()),
+            }
+        }
+        Ok(kill)
+    }
+}
@@ -0,0 +1,67 @@
+use crate::client::{self, Client, ClientType};
+use crate::frame::Frame;
+use crate::parse::Parse;
+use crate::error::Error;
+
+/// `CLIENT LIST` returns information about the connected clients, one line per client.
+/// `CLIENT LIST` 返回在线客户端的信息，每个客户端一行。
+///
+/// # Example
+/// ```text
+/// CLIENT LIST [TYPE <NORMAL | MASTER | REPLICA | PUBSUB>] [ID client-id [client-id ...]]
+/// ```
+pub struct List {
+    client_type: Option<ClientType>,  // Only list clients of this type / 只列出该类型的客户端
+    ids: Vec<u64>,                    // Only list clients with these IDs / 只列出这些 ID 的客户端
+}
+
+impl List {
+    /// Executes the `CLIENT LIST` command.
+    /// 执行 `CLIENT LIST` 命令。
+    pub fn list_command(_client: &mut Client, parse: &mut Parse) -> crate::Result<Frame> {
+        let list = List::parse_command(parse)?;
+
+        let mut lines = String::new();
+        for handle in client::all() {
+            if list.client_type.is_some_and(|t| t != handle.client_type()) {
+                continue;
+            }
+            if !list.ids.is_empty() && !list.ids.contains(&handle.id) {
+                continue;
+            }
+            lines.push_str(&handle.info());
+            lines.push('\n');
+        }
+        Ok(Frame::Bulk(lines.into_bytes()))
+    }
+
+    /// Parses the optional `TYPE` and `ID` filters.
+    /// 解析可选的 `TYPE` 和 `ID` 过滤条件。
+    fn parse_command(parse: &mut Parse) -> crate::Result<Self> {
+        let mut list = List { client_type: None, ids: Vec::new() };
+        while let Ok(option) = parse.next_string() {
+            match option.to_lowercase().as_str() {
+                "type" => {
+                    let name = parse.next_string().map_err(|_| Error::Syntax)?;
+                    match ClientType::parse(&name) {
+                        Some(client_type) => list.client_type = Some(client_type),
+                        None => return Err(Error::Err(format!("Unknown client type '{}'", name)).into()),
+                    }
+                }
+                "id" => {
+                    while let Ok(id) = parse.next_string() {
+                        match id.parse::<u64>() {
+                            Ok(id) if id > 0 => list.ids.push(id),
+                            _ => return Err(Error::Err("Invalid client ID".to_string()).into()),
+                        }
+                    }
+                    if list.ids.is_empty() {
+                        return Err(Error::Syntax.into());
+                    }
+                }
+                _ => return Err(Error::Syntax.into()),
+            }
+        }
+        Ok(list)
+    }
+}
@@ -0,0 +1,54 @@
+pub mod id;
+pub mod list;
+pub mod info;
+pub mod setname;
+pub mod getname;
+pub mod kill;
+pub mod pause;
+pub mod unpause;
+pub mod no_evict;
+pub mod reply;
+pub mod tracking;
+pub mod caching;
+pub mod getredir;
+
+use crate::client::Client;
+use crate::frame::Frame;
+use crate::parse::Parse;
+use crate::error::Error;
+
+/// `CLIENT` command, dispatches to its subcommands.
+/// `CLIENT` 命令，分发到各个子命令。
+///
+/// # Example
+/// ```text
+/// CLIENT <subcommand> [arguments ...]
+/// ```
+pub struct ClientCommand;
+
+impl ClientCommand {
+    /// Executes the `CLIENT` command.
+    /// 执行 `CLIENT` 命令。
+    pub fn client_command(client: &mut Client, parse: &mut Parse) -> crate::Result<Frame> {
+        let subcommand = parse.next_string()?.to_lowercase();
+        // Shown as the last command in `CLIENT LIST`
+        // 在 `CLIENT LIST` 中显示为最后执行的命令
+        client.handle.update(|state| state.cmd = format!("client|{}", subcommand));
+        match subcommand.as_str() {
+            "id" => id::Id::id_command(client, parse),
+            "list" => list::List::list_command(client, parse),
+            "info" => info::Info::info_command(client, parse),
+            "setname" => setname::SetName::setname_command(client, parse),
+            "getname" => getname::GetName::getname_command(client, parse),
+            "kill" => kill::Kill::kill_command(client, parse),
+            "pause" => pause::Pause::pause_command(client, parse),
+            "unpause" => unpause::Unpause::unpause_command(client, parse),
+            "no-evict" => no_evict::NoEvict::no_evict_command(client, parse),
+            "reply" => reply::Reply::reply_command(client, parse),
+            "tracking" => tracking::Tracking::tracking_command(client, parse),
+            "caching" => caching::Caching::caching_command(client, parse),
+            "getredir" => getredir::GetRedir::getredir_command(client, parse),
+            _ => Err(Error::Err(format!("unknown subcommand '{}'. Try CLIENT HELP.", subcommand)).into()),
+        }
+    }
+}
@@ -0,0 +1,35 @@
+use crate::client::Client;
+use crate::frame::Frame;
+use crate::parse::Parse;
+use crate::error::Error;
+
+/// `CLIENT NO-EVICT` sets whether the current connection is excluded from client eviction.
+/// `CLIENT NO-EVICT` 设置当前连接是否不参与客户端淘汰。
+///
+/// # Example
+/// ```text
+/// CLIENT NO-EVICT <ON | OFF>
+/// ```
+pub struct NoEvict {
+    on: bool,  // Whether the connection is excluded from eviction / 连接是否不参与淘汰
+}
+
+impl NoEvict {
+    /// Executes the `CLIENT NO-EVICT` command.
+    /// 执行 `CLIENT NO-EVICT` 命令。
+    pub fn no_evict_command(client: &mut Client, parse: &mut Parse) -> crate::Result<Frame> {
+        let no_evict = NoEvict::parse_command(parse)?;
+        client.handle.update(|state| state.no_evict = no_evict.on);
+        Ok(Frame::Simple("OK".to_string()))
+    }
+
+    /// Parses the `ON` or `OFF` argument.
+    /// 解析 `ON` 或 `OFF` 参数。
+    fn parse_command(parse: &mut Parse) -> crate::Result<Self> {
+        match parse.next_string()?.to_lowercase().as_str() {
+            "on" => Ok(NoEvict { on: true }),
+            "off" => Ok(NoEvict { on: false }),
+            _ => Err(Error::Syntax.into()),
+        }
+    }
+}
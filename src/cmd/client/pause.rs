@@ -0,0 +1,52 @@
+use std::time::Duration;
+use crate::client::{self, Client, PauseMode};
+use crate::frame::Frame;
+use crate::parse::Parse;
+use crate::error::Error;
+
+/// `CLIENT PAUSE` suspends the processing of client commands for a number of milliseconds.
+/// `CLIENT PAUSE` 在指定的毫秒数内暂停处理客户端的命令。
+///
+/// `WRITE` only pauses commands that may modify data, `ALL` (the default) pauses every command.
+/// `WRITE` 只暂停可能修改数据的命令，`ALL`（默认）暂停所有命令。
+///
+/// # Example
+/// ```text
+/// CLIENT PAUSE timeout [WRITE | ALL]
+/// ```
+pub struct Pause {
+    timeout: u64,     // Pause duration in milliseconds / 暂停时长，单位毫秒
+    mode: PauseMode,  // Which commands are paused / 暂停哪些命令
+}
+
+impl Pause {
+    /// Executes the `CLIENT PAUSE` command.
+    /// 执行 `CLIENT PAUSE` 命令。
+    pub fn pause_command(_client: &mut Client, parse: &mut Parse) -> crate::Result<Frame> {
+        let pause = Pause::parse_command(parse)?;
+        client::pause(Duration::from_millis(pause.timeout), pause.mode);
+        Ok(Frame::Simple("OK".to_string()))
+    }
+
+    /// Parses the timeout and the optional mode.
+    /// 解析超时时间和可选的暂停范围。
+    fn parse_command(parse: &mut Parse) -> crate::Result<Self> {
+        if parse.args_number()? > 2 {
+            return Err(Error::WrongArity("client|pause".to_string()).into());
+        }
+        let timeout = match parse.next_string()?.parse::<i64>() {
+            Ok(timeout) if timeout < 0 => return Err(Error::Err("timeout is negative".to_string()).into()),
+            Ok(timeout) => timeout as u64,
+            Err(_) => return Err(Error::Err("timeout is not an integer or out of range".to_string()).into()),
+        };
+        let mode = match parse.next_string() {
+            Ok(mode) => match mode.to_lowercase().as_str() {
+                "write" => PauseMode::Write,
+                "all" => PauseMode::All,
+                _ => return Err(Error::Syntax.into()),
+            },
+            Err(_) => PauseMode::All,
+        };
+        Ok(Pause { timeout, mode })
+    }
+}
@@ -0,0 +1,51 @@
+use crate::client::{Client, ReplyMode};
+use crate::frame::Frame;
+use crate::parse::Parse;
+use crate::error::Error;
+
+/// `CLIENT REPLY` controls whether the server replies to the commands of the current connection.
+/// `CLIENT REPLY` 控制服务端是否回复当前连接的命令。
+///
+/// `OFF` and `SKIP` are not replied to themselves, `SKIP` only skips the reply of the next command.
+/// `OFF` 和 `SKIP` 本身不会得到回复，`SKIP` 只跳过下一条命令的回复。
+///
+/// # Example
+/// ```text
+/// CLIENT REPLY <ON | OFF | SKIP>
+/// ```
+pub struct Reply {
+    mode: ReplyMode,  // The new reply mode / 新的回复模式
+}
+
+impl Reply {
+    /// Executes the `CLIENT REPLY` command.
+    /// 执行 `CLIENT REPLY` 命令。
+    pub fn reply_command(client: &mut Client, parse: &mut Parse) -> crate::Result<Frame> {
+        let reply = Reply::parse_command(parse)?;
+        match reply.mode {
+            ReplyMode::On => {
+                client.reply = ReplyMode::On;
+                Ok(Frame::Simple("OK".to_string()))
+            }
+            // SKIP has no effect while replies are switched off
+            // 回复已关闭时 SKIP 不生效
+            ReplyMode::SkipNext if client.reply == ReplyMode::Off => Ok(Frame::NoResponse),
+            mode => {
+                client.reply = mode;
+                Ok(Frame::NoResponse)
+            }
+        }
+    }
+
+    /// Parses the reply mode.
+    /// 解析回复模式。
+    fn parse_command(parse: &mut Parse) -> crate::Result<Self> {
+        let mode = match parse.next_string()?.to_lowercase().as_str() {
+            "on" => ReplyMode::On,
+            "off" => ReplyMode::Off,
+            "skip" => ReplyMode::SkipNext,
+            _ => return Err(Error::Syntax.into()),
+        };
+        Ok(Reply { mode })
+    }
+}
@@ -0,0 +1,49 @@
+use crate::client::Client;
+use crate::frame::Frame;
+use crate::parse::Parse;
+use crate::error::Error;
+
+/// `CLIENT SETNAME` assigns a name to the current connection.
+/// `CLIENT SETNAME` 为当前连接设置名称。
+///
+/// An empty name removes the name of the connection.
+/// 名称为空时移除连接的名称。
+///
+/// # Example
+/// ```text
+/// CLIENT SETNAME connection-name
+/// ```
+pub struct SetName {
+    name: String,  // The new connection name / 新的连接名称
+}
+
+impl SetName {
+    /// Executes the `CLIENT SETNAME` command.
+    /// 执行 `CLIENT SETNAME` 命令。
+    pub fn setname_command(client: &mut Client, parse: &mut Parse) -> crate::Result<Frame> {
+        let setname = SetName::parse_command(parse)?;
+        set_name(client, setname.name)?;
+        Ok(Frame::Simple("OK".to_string()))
+    }
+
+    /// Parses the connection name.
+    /// 解析连接名称。
+    fn parse_command(parse: &mut Parse) -> crate::Result<Self> {
+        let name = parse.next_string()?;
+        Ok(SetName { name })
+    }
+}
+
+/// Validates and sets the connection name, shared with `HELLO SETNAME`.
+/// 校验并设置连接名称，与 `HELLO SETNAME` 共用。
+pub fn set_name(client: &mut Client, name: String) -> crate::Result<()> {
+    // Names cannot contain spaces, newlines or special characters, as they are shown in `CLIENT LIST`
+    // 名称会显示在 `CLIENT LIST` 中，所以不能包含空格、换行或特殊字符
+    if name.chars().any(|c| !('!'..='~').contains(&c)) {
+        return Err(Error::Err("Client names cannot contain spaces, newlines or special characters.".to_string()).into());
+    }
+    client.handle.update(|state| {
+        state.name = if name.is_empty() { None } else { Some(name) };
+    });
+    Ok(())
+}
@@ -0,0 +1,90 @@
+use crate::client::{self, Client};
+use crate::frame::Frame;
+use crate::parse::Parse;
+use crate::tracking::{self, TrackingOptions};
+use crate::error::Error;
+
+/// `CLIENT TRACKING` enables or disables server assisted client-side caching.
+/// `CLIENT TRACKING` 开启或关闭服务端辅助的客户端缓存。
+///
+/// # Example
+/// ```text
+/// CLIENT TRACKING <ON | OFF> [REDIRECT client-id] [PREFIX prefix [PREFIX prefix ...]] [BCAST] [OPTIN] [OPTOUT] [NOLOOP]
+/// ```
+pub struct Tracking {
+    on: bool,                  // Whether to enable tracking / 是否开启追踪
+    options: TrackingOptions,  // The tracking options / 追踪选项
+}
+
+impl Tracking {
+    /// Executes the `CLIENT TRACKING` command.
+    /// 执行 `CLIENT TRACKING` 命令。
+    pub fn tracking_command(client: &mut Client, parse: &mut Parse) -> crate::Result<Frame> {
+        let t = Tracking::parse_command(parse)?;
+
+        if !t.on {
+            tracking::disable(client.id());
+            return Ok(Frame::Simple("OK".to_string()));
+        }
+
+        // The client that invalidation messages are redirected to must exist
+        // 失效消息重定向到的客户端必须存在
+        if let Some(redirect) = t.options.redirect
+            && client::lookup(redirect).is_none()
+        {
+            return Err(Error::Err("The client ID you want redirect to does not exist".to_string()).into());
+        }
+        tracking::enable(client.id(), t.options)?;
+        Ok(Frame::Simple("OK".to_string()))
+    }
+
+    /// Parses the switch and the tracking options.
+    /// 解析开关和追踪选项。
+    fn parse_command(parse: &mut Parse) -> crate::Result<Self> {
+        let on = match parse.next_string()?.to_lowercase().as_str() {
+            "on" => true,
+            "off" => false,
+            _ => return Err(Error::Syntax.into()),
+        };
+
+        let mut options = TrackingOptions::default();
+        while let Ok(option) = parse.next_string() {
+            match option.to_lowercase().as_str() {
+                "redirect" => {
+                    if options.redirect.is_some() {
+                        return Err(Error::Err("A client can only redirect to a single other client".to_string()).into());
+                    }
+                    let id = parse
+                        .next_string()
+                        .map_err(|_| Error::Syntax)?
+                        .parse::<u64>()
+                        .map_err(|_| Error::NotInteger)?;
+                    options.redirect = Some(id);
+                }
+                "prefix" => {
+                    let prefix = parse.next_string().map_err(|_| Error::Syntax)?;
+                    options.prefixes.push(prefix);
+                }
+                "bcast" => options.bcast = true,
+                "optin" => options.optin = true,
+                "optout" => options.optout = true,
+                "noloop" => options.noloop = true,
+                _ => return Err(Error::Syntax.into()),
+            }
+        }
+
+        if on {
+            if !options.bcast && !options.prefixes.is_empty() {
+                return Err(Error::Err("PREFIX option requires BCAST mode to be enabled".to_string()).into());
+            }
+            if options.optin && options.optout {
+                return Err(Error::Err("You can't use both OPTIN and OPTOUT".to_string()).into());
+            }
+            if options.bcast && (options.optin || options.optout) {
+                return Err(Error::Err("OPTIN and OPTOUT are not compatible with BCAST".to_string()).into());
+            }
+        }
+
+        Ok(Tracking { on, options })
+    }
+}
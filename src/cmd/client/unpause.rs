@@ -0,0 +1,16 @@
+use crate::client::{self, Client};
+use crate::frame::Frame;
+use crate::parse::Parse;
+
+/// `CLIENT UNPAUSE` resumes the processing of clients paused by `CLIENT PAUSE`.
+/// `CLIENT UNPAUSE` 恢复处理被 `CLIENT PAUSE` 暂停的客户端。
+pub struct Unpause;
+
+impl Unpause {
+    /// Executes the `CLIENT UNPAUSE` command.
+    /// 执行 `CLIENT UNPAUSE` 命令。
+    pub fn unpause_command(_client: &mut Client, _parse: &mut Parse) -> crate::Result<Frame> {
+        client::unpause();
+        Ok(Frame::Simple("OK".to_string()))
+    }
+}
@@ -0,0 +1,16 @@
+use crate::client::Client;
+use crate::dict::Command;
+use crate::frame::Frame;
+use crate::parse::Parse;
+
+/// `COMMAND COUNT` returns the number of commands, subcommands are not counted.
+/// `COMMAND COUNT` 返回命令的数量，不计算子命令。
+pub struct Count;
+
+impl Count {
+    /// Executes the `COMMAND COUNT` command.
+    /// 执行 `COMMAND COUNT` 命令。
+    pub fn count_command(_client: &mut Client, _parse: &mut Parse) -> crate::Result<Frame> {
+        Ok(Frame::Integer(Command::all().len() as i64))
+    }
+}
@@ -0,0 +1,83 @@
+use crate::client::Client;
+use crate::dict::Command;
+use crate::frame::Frame;
+use crate::parse::Parse;
+
+/// `COMMAND DOCS` returns the documentation of the given commands, or of all commands without any name.
+/// `COMMAND DOCS` 返回指定命令的文档，不指定命令时返回所有命令的文档。
+///
+/// The documentation is a map from command name to its summary, group, time complexity and subcommands.
+/// Unknown commands are skipped.
+/// 文档是命令名到其描述、分组、时间复杂度和子命令的映射，不存在的命令被忽略。
+///
+/// # Example
+/// ```text
+/// COMMAND DOCS [command-name [command-name ...]]
+/// ```
+pub struct Docs {
+    names: Vec<String>,  // Names of the commands / 命令名
+}
+
+impl Docs {
+    /// Executes the `COMMAND DOCS` command.
+    /// 执行 `COMMAND DOCS` 命令。
+    pub fn docs_command(client: &mut Client, parse: &mut Parse) -> crate::Result<Frame> {
+        let docs = Docs::parse_command(parse);
+        let commands = if docs.names.is_empty() {
+            Command::all()
+        } else {
+            docs.names.iter().filter_map(|name| Command::get_command_detail(name)).collect()
+        };
+        let resp = client.handle.state().resp;
+        let entries = commands.iter().map(|command| command_docs(command, resp)).collect();
+        Ok(Frame::map(entries, resp))
+    }
+
+    /// Parses the command names.
+    /// 解析命令名。
+    fn parse_command(parse: &mut Parse) -> Self {
+        let mut names = Vec::new();
+        while let Ok(name) = parse.next_string() {
+            names.push(name);
+        }
+        Docs { names }
+    }
+}
+
+/// 一个命令的名称和文档
+/// Name and documentation of one command.
+fn command_docs(command: &Command, resp: u8) -> (Frame, Frame) {
+    let bulk = |s: &str| Frame::Bulk(s.as_bytes().to_vec());
+    let mut fields = vec![
+        (bulk("summary"), bulk(&command.description)),
+        (bulk("group"), bulk(group(command))),
+        (bulk("complexity"), bulk(&command.time_complexity)),
+    ];
+    if !command.subcommands.is_empty() {
+        let subcommands = command.subcommands.iter().map(|subcommand| command_docs(subcommand, resp)).collect();
+        fields.push((bulk("subcommands"), Frame::map(subcommands, resp)));
+    }
+    (bulk(&command.name), Frame::map(fields, resp))
+}
+
+/// 命令所属的分组，由 ACL 类别得出
+/// Group of a command, derived from its ACL categories.
+fn group(command: &Command) -> &'static str {
+    let name = command.name.split('|').next().unwrap_or_default();
+    let has = |category: &str| command.categories.contains(&category);
+    if has("string") {
+        "string"
+    } else if has("hash") {
+        "hash"
+    } else if has("list") {
+        "list"
+    } else if has("pubsub") || name == "pubsub" {
+        "pubsub"
+    } else if has("keyspace") {
+        "generic"
+    } else if has("connection") && name != "command" {
+        "connection"
+    } else {
+        "server"
+    }
+}
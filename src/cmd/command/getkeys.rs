@@ -0,0 +1,57 @@
+use crate::client::Client;
+use crate::dict::Command;
+use crate::error::Error;
+use crate::frame::Frame;
+use crate::parse::Parse;
+
+/// `COMMAND GETKEYS` returns the keys in a full command, using the key positions of the command.
+/// `COMMAND GETKEYS` 根据命令中键的位置，返回一条完整命令中的键。
+///
+/// # Example
+/// ```text
+/// COMMAND GETKEYS command [arg [arg ...]]
+/// ```
+pub struct GetKeys {
+    name: String,       // Name of the command / 命令名
+    args: Vec<String>,  // Arguments after the command name / 命令名之后的参数
+}
+
+impl GetKeys {
+    /// Executes the `COMMAND GETKEYS` command.
+    /// 执行 `COMMAND GETKEYS` 命令。
+    pub fn getkeys_command(_client: &mut Client, parse: &mut Parse) -> crate::Result<Frame> {
+        let getkeys = GetKeys::parse_command(parse)?;
+        let Some(command) = Command::get_command_detail(&getkeys.name) else {
+            return Err(Error::Err("Invalid command specified".to_string()).into());
+        };
+        // 有子命令时使用子命令的规格
+        // The subcommand's specification is used for commands with subcommands
+        let command = match getkeys.args.first() {
+            Some(sub) if !command.subcommands.is_empty() => match command.subcommand(sub) {
+                Some(subcommand) => subcommand,
+                None => return Err(Error::Err("Invalid subcommand specified".to_string()).into()),
+            },
+            _ => &command,
+        };
+        let argc = getkeys.args.len() as i64 + 1;
+        if (command.arity > 0 && argc != command.arity) || argc < -command.arity {
+            return Err(Error::Err("Invalid number of arguments specified for command".to_string()).into());
+        }
+        let keys = command.keys(&getkeys.args);
+        if keys.is_empty() {
+            return Err(Error::Err("The command has no key arguments".to_string()).into());
+        }
+        Ok(Frame::Array(keys.into_iter().map(|key| Frame::Bulk(key.as_bytes().to_vec())).collect()))
+    }
+
+    /// Parses the command name and its arguments, the name is required.
+    /// 解析命令名及其参数，命令名是必需的。
+    fn parse_command(parse: &mut Parse) -> crate::Result<Self> {
+        let name = parse.next_string()?.to_lowercase();
+        let mut args = Vec::new();
+        while let Ok(arg) = parse.next_string() {
+            args.push(arg);
+        }
+        Ok(GetKeys { name, args })
+    }
+}
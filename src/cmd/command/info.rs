@@ -0,0 +1,110 @@
+use crate::client::Client;
+use crate::dict::Command;
+use crate::frame::Frame;
+use crate::parse::Parse;
+
+/// `COMMAND INFO` returns the metadata of the given commands, or of all commands without any name.
+/// `COMMAND INFO` 返回指定命令的元数据，不指定命令时返回所有命令的元数据。
+///
+/// Each command is described by its name, arity, flags, first key, last key, key step, ACL categories, tips,
+/// key specifications and subcommands. Unknown commands are replied as null.
+/// 每个命令包含命令名、参数个数、标志、第一个键、最后一个键、键的步长、ACL 类别、提示、键规格和子命令。
+/// 不存在的命令回复为空值。
+///
+/// # Example
+/// ```text
+/// COMMAND INFO [command-name [command-name ...]]
+/// ```
+pub struct Info {
+    names: Vec<String>,  // Names of the commands / 命令名
+}
+
+impl Info {
+    /// Executes the `COMMAND INFO` command.
+    /// 执行 `COMMAND INFO` 命令。
+    pub fn info_command(_client: &mut Client, parse: &mut Parse) -> crate::Result<Frame> {
+        let info = Info::parse_command(parse);
+        if info.names.is_empty() {
+            return Ok(all_commands_info());
+        }
+        let commands = info
+            .names
+            .iter()
+            .map(|name| Command::get_command_detail(name).map_or(Frame::Null, |command| command_info(&command)))
+            .collect();
+        Ok(Frame::Array(commands))
+    }
+
+    /// Parses the command names.
+    /// 解析命令名。
+    fn parse_command(parse: &mut Parse) -> Self {
+        let mut names = Vec::new();
+        while let Ok(name) = parse.next_string() {
+            names.push(name);
+        }
+        Info { names }
+    }
+}
+
+/// 所有命令的元数据，`COMMAND` 和不带参数的 `COMMAND INFO` 使用
+/// Metadata of all commands, used by `COMMAND` and `COMMAND INFO` without arguments.
+pub fn all_commands_info() -> Frame {
+    Frame::Array(Command::all().iter().map(command_info).collect())
+}
+
+/// 一个命令的元数据
+/// Metadata of one command.
+fn command_info(command: &Command) -> Frame {
+    let simple = |s: String| Frame::Simple(s);
+    Frame::Array(vec![
+        Frame::Bulk(command.name.as_bytes().to_vec()),
+        Frame::Integer(command.arity),
+        Frame::Array(command.flags.iter().map(|flag| simple(flag.to_string())).collect()),
+        Frame::Integer(command.first_key as i64),
+        Frame::Integer(command.last_key),
+        Frame::Integer(command.key_step as i64),
+        Frame::Array(command.categories.iter().map(|category| simple(format!("@{}", category))).collect()),
+        // 提示
+        // Tips
+        Frame::Array(vec![]),
+        Frame::Array(key_specs(command)),
+        Frame::Array(command.subcommands.iter().map(command_info).collect()),
+    ])
+}
+
+/// 命令的键规格：从第一个键开始按步长查找键，带有键的访问方式
+/// Key specifications of a command: keys are found from the first key by the key step, along with the key access.
+fn key_specs(command: &Command) -> Vec<Frame> {
+    if command.first_key == 0 {
+        return Vec::new();
+    }
+    let bulk = |s: &str| Frame::Bulk(s.as_bytes().to_vec());
+    // 范围的最后一个键相对于第一个键，负数仍然从末尾倒数
+    // The last key of the range is relative to the first key, a negative one still counts from the end
+    let last_key = if command.last_key < 0 { command.last_key } else { command.last_key - command.first_key as i64 };
+    vec![Frame::Array(vec![
+        bulk("flags"),
+        Frame::Array(vec![Frame::Simple(command.key_access.to_string())]),
+        bulk("begin_search"),
+        Frame::Array(vec![
+            bulk("type"),
+            bulk("index"),
+            bulk("spec"),
+            Frame::Array(vec![bulk("index"), Frame::Integer(command.first_key as i64)]),
+        ]),
+        bulk("find_keys"),
+        Frame::Array(vec![
+            bulk("type"),
+            bulk("range"),
+            bulk("spec"),
+            Frame::Array(vec![
+                bulk("lastkey"),
+                Frame::Integer(last_key),
+                bulk("keystep"),
+                Frame::Integer(command.key_step as i64),
+                bulk("limit"),
+                Frame::Integer(0),
+            ]),
+        ]),
+    ])]
+}
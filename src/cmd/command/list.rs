@@ -0,0 +1,73 @@
+use crate::client::Client;
+use crate::dict::Command;
+use crate::frame::Frame;
+use crate::glob;
+use crate::parse::Parse;
+use crate::error::Error;
+
+/// `COMMAND LIST` returns the names of all commands and subcommands, optionally filtered.
+/// `COMMAND LIST` 返回所有命令和子命令的名称，可以过滤。
+///
+/// Modules are not supported, so filtering by module always returns an empty list.
+/// 不支持模块，按模块过滤时总是返回空列表。
+///
+/// # Example
+/// ```text
+/// COMMAND LIST [FILTERBY <MODULE module-name | ACLCAT category | PATTERN pattern>]
+/// ```
+pub struct List {
+    filter: Option<Filter>,  // Filter of the names / 名称的过滤条件
+}
+
+/// 过滤条件
+/// Filter of `COMMAND LIST`.
+enum Filter {
+    Module,
+    AclCat(String),
+    Pattern(String),
+}
+
+impl List {
+    /// Executes the `COMMAND LIST` command.
+    /// 执行 `COMMAND LIST` 命令。
+    pub fn list_command(_client: &mut Client, parse: &mut Parse) -> crate::Result<Frame> {
+        let list = List::parse_command(parse)?;
+        let names = Command::all()
+            .into_iter()
+            .flat_map(|command| {
+                let subcommands = command.subcommands.clone();
+                std::iter::once(command).chain(subcommands)
+            })
+            .filter(|command| match &list.filter {
+                None => true,
+                Some(Filter::Module) => false,
+                Some(Filter::AclCat(category)) => command.categories.contains(&category.as_str()),
+                Some(Filter::Pattern(pattern)) => glob::matches(pattern, &command.name),
+            })
+            .map(|command| Frame::Bulk(command.name.into_bytes()))
+            .collect();
+        Ok(Frame::Array(names))
+    }
+
+    /// Parses the optional filter.
+    /// 解析可选的过滤条件。
+    fn parse_command(parse: &mut Parse) -> crate::Result<Self> {
+        match parse.args_number()? {
+            0 => return Ok(List { filter: None }),
+            3 => {}
+            _ => return Err(Error::Syntax.into()),
+        }
+        if parse.next_string()?.to_lowercase() != "filterby" {
+            return Err(Error::Syntax.into());
+        }
+        let kind = parse.next_string()?.to_lowercase();
+        let value = parse.next_string()?;
+        let filter = match kind.as_str() {
+            "module" => Filter::Module,
+            "aclcat" => Filter::AclCat(value.to_lowercase()),
+            "pattern" => Filter::Pattern(value.to_lowercase()),
+            _ => return Err(Error::Syntax.into()),
+        };
+        Ok(List { filter: Some(filter) })
+    }
+}
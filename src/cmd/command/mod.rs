@@ -0,0 +1,39 @@
+pub mod count;
+pub mod info;
+pub mod docs;
+pub mod list;
+pub mod getkeys;
+
+use crate::client::Client;
+use crate::frame::Frame;
+use crate::parse::Parse;
+use crate::error::Error;
+
+/// `COMMAND` command, returns the metadata of all commands, or dispatches to its subcommands.
+/// `COMMAND` 命令，返回所有命令的元数据，或分发到各个子命令。
+///
+/// # Example
+/// ```text
+/// COMMAND
+/// COMMAND <subcommand> [arguments ...]
+/// ```
+pub struct CommandCommand;
+
+impl CommandCommand {
+    /// Executes the `COMMAND` command.
+    /// 执行 `COMMAND` 命令。
+    pub fn command_command(client: &mut Client, parse: &mut Parse) -> crate::Result<Frame> {
+        let subcommand = match parse.next_string() {
+            Ok(subcommand) => subcommand.to_lowercase(),
+            Err(_) => return Ok(info::all_commands_info()),
+        };
+        match subcommand.as_str() {
+            "count" => count::Count::count_command(client, parse),
+            "info" => info::Info::info_command(client, parse),
+            "docs" => docs::Docs::docs_command(client, parse),
+            "list" => list::List::list_command(client, parse),
+            "getkeys" => getkeys::GetKeys::getkeys_command(client, parse),
+            _ => Err(Error::Err(format!("unknown subcommand '{}'. Try COMMAND HELP.", subcommand)).into()),
+        }
+    }
+}
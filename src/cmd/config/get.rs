@@ -0,0 +1,47 @@
+use crate::config::{get_parameter, PARAMETERS};
+use crate::frame::Frame;
+use crate::glob;
+use crate::parse::Parse;
+
+/// `CONFIG GET` returns the parameters matching any of the glob patterns, as name and value pairs.
+/// `CONFIG GET` 返回与任意一个通配符模式匹配的参数，按名称和值成对返回。
+///
+/// # Example
+/// ```text
+/// CONFIG GET parameter [parameter ...]
+/// ```
+pub struct Get {
+    patterns: Vec<String>,  // Patterns of the parameter names / 参数名的模式
+}
+
+impl Get {
+    /// Executes the `CONFIG GET` command.
+    /// 执行 `CONFIG GET` 命令。
+    pub fn get_command(parse: &mut Parse) -> crate::Result<Frame> {
+        let get = match Get::parse_command(parse) {
+            Ok(get) => get,
+            Err(err) => return Ok(Frame::Error(err.to_string())),
+        };
+        let mut pairs = Vec::new();
+        for name in PARAMETERS {
+            if !get.patterns.iter().any(|pattern| glob::matches(&pattern.to_lowercase(), name)) {
+                continue;
+            }
+            if let Some(value) = get_parameter(name) {
+                pairs.push(Frame::Bulk(name.as_bytes().to_vec()));
+                pairs.push(Frame::Bulk(value.into_bytes()));
+            }
+        }
+        Ok(Frame::Array(pairs))
+    }
+
+    /// Parses the parameter patterns, at least one is required.
+    /// 解析参数模式，至少需要一个。
+    fn parse_command(parse: &mut Parse) -> crate::Result<Self> {
+        let mut patterns = Vec::new();
+        while let Ok(pattern) = parse.next_string() {
+            patterns.push(pattern);
+        }
+        Ok(Get { patterns })
+    }
+}
@@ -0,0 +1,29 @@
+pub mod get;
+pub mod set;
+
+use std::sync::{Arc, Mutex};
+use crate::db::Db;
+use crate::frame::Frame;
+use crate::parse::Parse;
+
+/// `CONFIG` command, dispatches to its subcommands.
+/// `CONFIG` 命令，分发到各个子命令。
+///
+/// # Example
+/// ```text
+/// CONFIG <subcommand> [arguments ...]
+/// ```
+pub struct ConfigCommand;
+
+impl ConfigCommand {
+    /// Executes the `CONFIG` command.
+    /// 执行 `CONFIG` 命令。
+    pub fn config_command(_db: &mut Arc<Mutex<Db>>, parse: &mut Parse) -> crate::Result<Frame> {
+        let subcommand = parse.next_string()?.to_lowercase();
+        match subcommand.as_str() {
+            "get" => get::Get::get_command(parse),
+            "set" => set::Set::set_command(parse),
+            _ => Ok(Frame::Error(format!("ERR unknown subcommand '{}'. Try CONFIG HELP.", subcommand))),
+        }
+    }
+}
@@ -0,0 +1,45 @@
+use crate::config::set_parameters;
+use crate::error::Error;
+use crate::frame::Frame;
+use crate::parse::Parse;
+
+/// `CONFIG SET` changes parameters at runtime, either all of them are changed or none.
+/// `CONFIG SET` 在运行时修改参数，要么全部修改，要么都不修改。
+///
+/// # Example
+/// ```text
+/// CONFIG SET parameter value [parameter value ...]
+/// ```
+pub struct Set {
+    parameters: Vec<(String, String)>,  // Parameter names and values / 参数名和值
+}
+
+impl Set {
+    /// Executes the `CONFIG SET` command.
+    /// 执行 `CONFIG SET` 命令。
+    pub fn set_command(parse: &mut Parse) -> crate::Result<Frame> {
+        let set = match Set::parse_command(parse) {
+            Ok(set) => set,
+            Err(err) => return Ok(Frame::Error(err.to_string())),
+        };
+        match set_parameters(&set.parameters) {
+            Ok(()) => Ok(Frame::Simple("OK".to_string())),
+            Err(err) => Ok(Frame::Error(err)),
+        }
+    }
+
+    /// Parses the parameter name and value pairs.
+    /// 解析参数名和值。
+    fn parse_command(parse: &mut Parse) -> crate::Result<Self> {
+        let mut parameters = Vec::new();
+        while let Ok(name) = parse.next_string() {
+            // 参数名和值必须成对出现
+            // Parameter names and values have to come in pairs
+            let Ok(value) = parse.next_string() else {
+                return Err(Error::WrongArity("config|set".to_string()).into());
+            };
+            parameters.push((name.to_lowercase(), value));
+        }
+        Ok(Set { parameters })
+    }
+}
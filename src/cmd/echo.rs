@@ -1,4 +1,3 @@
-use std::io::Error;
 use std::sync::{Arc, Mutex};
 use crate::db::Db;
 use crate::frame::Frame;
@@ -12,20 +11,11 @@ pub struct Echo{
 
 impl Echo{
     pub fn echo_command(_db: &mut Arc<Mutex<Db>>, parse: &mut Parse) -> crate::Result<Frame> {
-        match Echo::parse_command(parse) {
-            Ok(echo) => {
-                Ok(Frame::Bulk(echo.message.into_bytes()))
-            }
-            Err(_) => {
-                Ok(Frame::Error("ERR wrong number of arguments for 'echo' command".to_string()))
-            }
-        }
+        let echo = Echo::parse_command(parse)?;
+        Ok(Frame::Bulk(echo.message.into_bytes()))
     }
 
     fn parse_command(parse: &mut Parse) -> crate::Result<Self>{
-        if parse.args_number()?!=1{
-            return Err(Box::new(Error::new(std::io::ErrorKind::Other,"ERR wrong number of arguments for 'echo' command".to_string())));
-        }
         let message = parse.next_string()?;
         Ok(Echo{message})
     }
@@ -3,6 +3,7 @@ use std::sync::Mutex;
 use crate::db::{Db, DbType};
 use crate::frame::Frame;
 use crate::parse::Parse;
+use crate::error::Error;
 
 /// Represents the `HEXISTS` command in a Redis-like system.
 ///
@@ -44,31 +45,24 @@ impl Hexists {
         db: &mut Arc<Mutex<Db>>,
         parse: &mut Parse
     ) -> crate::Result<Frame> {
-        match Hexists::parse_command(parse) {
-            Ok(hexists) => {
-                let mut db = db.lock().unwrap();
-                match db.get(&hexists.key) {
-                    Some(DbType::Hash(hash)) => {
-                        // Check if the field exists in the hash. / 检查字段是否存在于哈希表中。
-                        if hash.contains_key(&hexists.field) {
-                            Ok(Frame::Integer(1)) // Field exists, return 1. / 字段存在，返回 1。
-                        } else {
-                            Ok(Frame::Integer(0)) // Field does not exist, return 0. / 字段不存在，返回 0。
-                        }
-                    },
-                    Some(_) => {
-                        // Key exists but type mismatch, return WRONGTYPE error. / 键存在，但类型不匹配，返回 WRONGTYPE 错误。
-                        Ok(Frame::Error("WRONGTYPE Operation against a key holding the wrong kind of value".to_string()))
-                    },
-                    None => {
-                        // Key does not exist, return 0. / 键不存在，返回 0。
-                        Ok(Frame::Integer(0))
-                    }
+        let hexists = Hexists::parse_command(parse)?;
+        let mut db = db.lock().unwrap();
+        match db.lookup_read(&hexists.key) {
+            Some(DbType::Hash(hash)) => {
+                // Check if the field exists in the hash. / 检查字段是否存在于哈希表中。
+                if hash.contains_key(&hexists.field) {
+                    Ok(Frame::Integer(1)) // Field exists, return 1. / 字段存在，返回 1。
+                } else {
+                    Ok(Frame::Integer(0)) // Field does not exist, return 0. / 字段不存在，返回 0。
                 }
-            }
-            Err(_) => {
-                // Incorrect number of arguments, return error. / 参数数量错误，返回错误。
-                Ok(Frame::Error("ERR wrong number of arguments for 'hexists' command".to_string()))
+            },
+            Some(_) => {
+                // Key exists but type mismatch, return WRONGTYPE error. / 键存在，但类型不匹配，返回 WRONGTYPE 错误。
+                Ok(Error::WrongType.into())
+            },
+            None => {
+                // Key does not exist, return 0. / 键不存在，返回 0。
+                Ok(Frame::Integer(0))
             }
         }
     }
@@ -86,10 +80,6 @@ impl Hexists {
     fn parse_command(parse: &mut Parse) -> crate::Result<Self> {
         // The command requires exactly two arguments: key and field.
         // 命令需要恰好两个参数：键和字段。
-        if parse.args_number()? != 2 {
-            return Err(Box::new(std::io::Error::new(std::io::ErrorKind::Other, "ERR wrong number of arguments for 'hexists' command")));
-        }
-
         let key = parse.next_string()?;
         let field = parse.next_string()?;
 
@@ -3,6 +3,7 @@ use std::sync::Mutex;
 use crate::db::{Db, DbType};
 use crate::frame::Frame;
 use crate::parse::Parse;
+use crate::error::Error;
 
 /// Represents the `HGET` command in a Redis-like system.
 ///
@@ -47,32 +48,25 @@ impl Hget {
         db: &mut Arc<Mutex<Db>>,
         parse: &mut Parse
     ) -> crate::Result<Frame> {
-        match Hget::parse_command(parse) {
-            Ok(hget) => {
-                let mut db = db.lock().unwrap();
-                match db.get(&hget.key) {
-                    Some(DbType::Hash(hash)) => {
-                        // If the field exists, return its value. / 如果字段存在，则返回其值
-                        if let Some(value) = hash.get(&hget.field) {
-                            Ok(Frame::Bulk(value.clone().into_bytes())) // Return the value of the field. / 返回字段的值
-                        } else {
-                            // If the field does not exist, return nil. / 如果字段不存在，返回 nil。
-                            Ok(Frame::Null)
-                        }
-                    },
-                    Some(_) => {
-                        // Key exists but type mismatch, return WRONGTYPE error. / 键存在，但类型不匹配，返回 WRONGTYPE 错误。
-                        Ok(Frame::Error("WRONGTYPE Operation against a key holding the wrong kind of value".to_string()))
-                    },
-                    None => {
-                        // Key does not exist, return nil. / 键不存在，返回 nil。
-                        Ok(Frame::Null)
-                    }
+        let hget = Hget::parse_command(parse)?;
+        let mut db = db.lock().unwrap();
+        match db.lookup_read(&hget.key) {
+            Some(DbType::Hash(hash)) => {
+                // If the field exists, return its value. / 如果字段存在，则返回其值
+                if let Some(value) = hash.get(&hget.field) {
+                    Ok(Frame::Bulk(value.clone().into_bytes())) // Return the value of the field. / 返回字段的值
+                } else {
+                    // If the field does not exist, return nil. / 如果字段不存在，返回 nil。
+                    Ok(Frame::Null)
                 }
-            }
-            Err(_) => {
-                // Incorrect number of arguments, return error. / 参数数量错误，返回错误。
-                Ok(Frame::Error("ERR wrong number of arguments for 'hget' command".to_string()))
+            },
+            Some(_) => {
+                // Key exists but type mismatch, return WRONGTYPE error. / 键存在，但类型不匹配，返回 WRONGTYPE 错误。
+                Ok(Error::WrongType.into())
+            },
+            None => {
+                // Key does not exist, return nil. / 键不存在，返回 nil。
+                Ok(Frame::Null)
             }
         }
     }
@@ -90,10 +84,6 @@ impl Hget {
     fn parse_command(parse: &mut Parse) -> crate::Result<Self> {
         // The command requires exactly two arguments: key and field.
         // 命令需要恰好两个参数：键和字段。
-        if parse.args_number()? != 2 {
-            return Err(Box::new(std::io::Error::new(std::io::ErrorKind::Other, "ERR wrong number of arguments for 'hget' command")));
-        }
-
         let key = parse.next_string()?;
         let field = parse.next_string()?;
 
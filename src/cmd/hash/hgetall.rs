@@ -3,6 +3,7 @@ use std::sync::Mutex;
 use crate::db::{Db, DbType};
 use crate::frame::Frame;
 use crate::parse::Parse;
+use crate::error::Error;
 
 /// Represents the `HGETALL` command in a Redis-like system.
 ///
@@ -44,36 +45,29 @@ impl Hgetall {
         db: &mut Arc<Mutex<Db>>,
         parse: &mut Parse
     ) -> crate::Result<Frame> {
-        match Hgetall::parse_command(parse) {
-            Ok(hgetall) => {
-                let mut db = db.lock().unwrap();
-                match db.get(&hgetall.key) {
-                    Some(DbType::Hash(hash)) => {
-                        let mut result = Vec::new();
+        let hgetall = Hgetall::parse_command(parse)?;
+        let mut db = db.lock().unwrap();
+        match db.lookup_read(&hgetall.key) {
+            Some(DbType::Hash(hash)) => {
+                let mut result = Vec::new();
 
-                        // Iterate over each field and value in the hash. / 遍历哈希表中的每个字段和值
-                        for (field, value) in hash.iter() {
-                            // Add field and value to the result. / 将字段和值添加到结果中
-                            result.push(Frame::Bulk(field.clone().into_bytes()));
-                            result.push(Frame::Bulk(value.clone().into_bytes()));
-                        }
-
-                        // Return the result, multiple Bulk data types. / 返回结果，多个 Bulk 数据类型
-                        Ok(Frame::Array(result))
-                    },
-                    Some(_) => {
-                        // Key exists, but type mismatch, return WRONGTYPE error. / 键存在，但类型不匹配，返回 WRONGTYPE 错误。
-                        Ok(Frame::Error("WRONGTYPE Operation against a key holding the wrong kind of value".to_string()))
-                    },
-                    None => {
-                        // Key does not exist, return an empty list message. / 键不存在，返回空列表信息
-                        Ok(Frame::Simple("(empty list or set)".to_string()))
-                    }
+                // Iterate over each field and value in the hash. / 遍历哈希表中的每个字段和值
+                for (field, value) in hash.iter() {
+                    // Add field and value to the result. / 将字段和值添加到结果中
+                    result.push(Frame::Bulk(field.clone().into_bytes()));
+                    result.push(Frame::Bulk(value.clone().into_bytes()));
                 }
-            }
-            Err(_) => {
-                // Incorrect number of arguments, return error. / 参数数量错误，返回错误。
-                Ok(Frame::Error("ERR wrong number of arguments for 'hgetall' command".to_string()))
+
+                // Return the result, multiple Bulk data types. / 返回结果，多个 Bulk 数据类型
+                Ok(Frame::Array(result))
+            },
+            Some(_) => {
+                // Key exists, but type mismatch, return WRONGTYPE error. / 键存在，但类型不匹配，返回 WRONGTYPE 错误。
+                Ok(Error::WrongType.into())
+            },
+            None => {
+                // Key does not exist, return an empty list message. / 键不存在，返回空列表信息
+                Ok(Frame::Simple("(empty list or set)".to_string()))
             }
         }
     }
@@ -91,9 +85,6 @@ impl Hgetall {
     fn parse_command(parse: &mut Parse) -> crate::Result<Self> {
         // The command requires exactly one argument: the key.
         // 命令需要恰好一个参数：键。
-        if parse.args_number()? != 1 {
-            return Err(Box::new(std::io::Error::new(std::io::ErrorKind::Other, "ERR wrong number of arguments for 'hgetall' command")));
-        }
         let key = parse.next_string()?;
 
         Ok(Hgetall { key })
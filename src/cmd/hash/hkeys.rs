@@ -3,6 +3,7 @@ use std::sync::Mutex;
 use crate::db::{Db, DbType};
 use crate::frame::Frame;
 use crate::parse::Parse;
+use crate::error::Error;
 
 /// Represents the `HKEYS` command in a Redis-like system.
 ///
@@ -44,32 +45,25 @@ impl Hkeys {
         db: &mut Arc<Mutex<Db>>,
         parse: &mut Parse
     ) -> crate::Result<Frame> {
-        match Hkeys::parse_command(parse) {
-            Ok(hkeys) => {
-                let mut db = db.lock().unwrap();
-                match db.get(&hkeys.key) {
-                    Some(DbType::Hash(hash)) => {
-                        // Get all field names. / 获取所有的字段名
-                        let fields: Vec<Frame> = hash.keys()
-                            .map(|field| Frame::Bulk(field.clone().into_bytes())) // Convert field names to Frame::Bulk / 将字段名转化为 Frame::Bulk
-                            .collect();
+        let hkeys = Hkeys::parse_command(parse)?;
+        let mut db = db.lock().unwrap();
+        match db.lookup_read(&hkeys.key) {
+            Some(DbType::Hash(hash)) => {
+                // Get all field names. / 获取所有的字段名
+                let fields: Vec<Frame> = hash.keys()
+                    .map(|field| Frame::Bulk(field.clone().into_bytes())) // Convert field names to Frame::Bulk / 将字段名转化为 Frame::Bulk
+                    .collect();
 
-                        // Return the array of field names. / 返回字段名的数组
-                        Ok(Frame::Array(fields))
-                    },
-                    Some(_) => {
-                        // Key exists but type mismatch, return WRONGTYPE error. / 键存在，但类型不匹配，返回 WRONGTYPE 错误。
-                        Ok(Frame::Error("WRONGTYPE Operation against a key holding the wrong kind of value".to_string()))
-                    },
-                    None => {
-                        // Key does not exist, return nil. / 键不存在，返回 nil。
-                        Ok(Frame::Null)
-                    }
-                }
-            }
-            Err(_) => {
-                // Incorrect number of arguments, return error. / 参数数量错误，返回错误。
-                Ok(Frame::Error("ERR wrong number of arguments for 'hkeys' command".to_string()))
+                // Return the array of field names. / 返回字段名的数组
+                Ok(Frame::Array(fields))
+            },
+            Some(_) => {
+                // Key exists but type mismatch, return WRONGTYPE error. / 键存在，但类型不匹配，返回 WRONGTYPE 错误。
+                Ok(Error::WrongType.into())
+            },
+            None => {
+                // Key does not exist, return nil. / 键不存在，返回 nil。
+                Ok(Frame::Null)
             }
         }
     }
@@ -87,10 +81,6 @@ impl Hkeys {
     fn parse_command(parse: &mut Parse) -> crate::Result<Self> {
         // The command requires exactly one argument: the key.
         // 命令需要恰好一个参数：键。
-        if parse.args_number()? != 1 {
-            return Err(Box::new(std::io::Error::new(std::io::ErrorKind::Other, "ERR wrong number of arguments for 'hkeys' command")));
-        }
-
         let key = parse.next_string()?;
 
         Ok(Hkeys { key })
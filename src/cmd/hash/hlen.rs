@@ -3,6 +3,7 @@ use std::sync::Mutex;
 use crate::db::{Db, DbType};
 use crate::frame::Frame;
 use crate::parse::Parse;
+use crate::error::Error;
 
 /// Represents the `HLEN` command in a Redis-like system.
 ///
@@ -43,29 +44,22 @@ impl Hlen {
         db: &mut Arc<Mutex<Db>>,
         parse: &mut Parse
     ) -> crate::Result<Frame> {
-        match Hlen::parse_command(parse) {
-            Ok(hlen) => {
-                let mut db = db.lock().unwrap();
-                match db.get(&hlen.key) {
-                    Some(DbType::Hash(hash)) => {
-                        // Get the number of fields in the hash. / 获取哈希表中的字段数量
-                        let field_count = hash.len();
-                        // Return the field count. / 返回字段数量
-                        Ok(Frame::Integer(field_count as i64))
-                    },
-                    Some(_) => {
-                        // Key exists but type mismatch, return WRONGTYPE error. / 键存在，但类型不匹配，返回 WRONGTYPE 错误。
-                        Ok(Frame::Error("WRONGTYPE Operation against a key holding the wrong kind of value".to_string()))
-                    },
-                    None => {
-                        // Key does not exist, return 0. / 键不存在，返回 0。
-                        Ok(Frame::Integer(0))
-                    }
-                }
-            }
-            Err(_) => {
-                // Incorrect number of arguments, return error. / 参数数量错误，返回错误。
-                Ok(Frame::Error("ERR wrong number of arguments for 'hlen' command".to_string()))
+        let hlen = Hlen::parse_command(parse)?;
+        let mut db = db.lock().unwrap();
+        match db.lookup_read(&hlen.key) {
+            Some(DbType::Hash(hash)) => {
+                // Get the number of fields in the hash. / 获取哈希表中的字段数量
+                let field_count = hash.len();
+                // Return the field count. / 返回字段数量
+                Ok(Frame::Integer(field_count as i64))
+            },
+            Some(_) => {
+                // Key exists but type mismatch, return WRONGTYPE error. / 键存在，但类型不匹配，返回 WRONGTYPE 错误。
+                Ok(Error::WrongType.into())
+            },
+            None => {
+                // Key does not exist, return 0. / 键不存在，返回 0。
+                Ok(Frame::Integer(0))
             }
         }
     }
@@ -83,10 +77,6 @@ impl Hlen {
     fn parse_command(parse: &mut Parse) -> crate::Result<Self> {
         // The command requires exactly one argument: the key.
         // 命令需要恰好一个参数：键。
-        if parse.args_number()? != 1 {
-            return Err(Box::new(std::io::Error::new(std::io::ErrorKind::Other, "ERR wrong number of arguments for 'hlen' command")));
-        }
-
         let key = parse.next_string()?;
 
         Ok(Hlen { key })
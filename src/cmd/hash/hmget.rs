@@ -3,6 +3,7 @@ use std::sync::Mutex;
 use crate::db::{Db, DbType};
 use crate::frame::Frame;
 use crate::parse::Parse;
+use crate::error::Error;
 
 /// Represents the `HMGET` command in a Redis-like system.
 ///
@@ -46,40 +47,33 @@ impl Hmget {
         db: &mut Arc<Mutex<Db>>,
         parse: &mut Parse
     ) -> crate::Result<Frame> {
-        match Hmget::parse_command(parse) {
-            Ok(hmget) => {
-                let mut db = db.lock().unwrap();
-                match db.get(&hmget.key) {
-                    Some(DbType::Hash(hash)) => {
-                        // Get the values for each field. / 为每个字段获取值
-                        let mut result = Vec::new();
-                        for field in hmget.fields {
-                            if let Some(value) = hash.get(&field) {
-                                // If the field exists, return its value. / 如果字段存在，返回字段的值
-                                result.push(Frame::Bulk(value.clone().into_bytes()));
-                            } else {
-                                // If the field does not exist, return nil. / 如果字段不存在，返回 nil
-                                result.push(Frame::Null);
-                            }
-                        }
-
-                        // Return the list of field values. / 返回字段值的列表
-                        Ok(Frame::Array(result))
-                    },
-                    Some(_) => {
-                        // Key exists but type mismatch, return WRONGTYPE error. / 键存在，但类型不匹配，返回 WRONGTYPE 错误。
-                        Ok(Frame::Error("WRONGTYPE Operation against a key holding the wrong kind of value".to_string()))
-                    },
-                    None => {
-                        // Key does not exist, return nil for each requested field. / 键不存在，为每个请求的字段返回 nil
-                        let result = vec![Frame::Null; hmget.fields.len()];
-                        Ok(Frame::Array(result))
+        let hmget = Hmget::parse_command(parse)?;
+        let mut db = db.lock().unwrap();
+        match db.lookup_read(&hmget.key) {
+            Some(DbType::Hash(hash)) => {
+                // Get the values for each field. / 为每个字段获取值
+                let mut result = Vec::new();
+                for field in hmget.fields {
+                    if let Some(value) = hash.get(&field) {
+                        // If the field exists, return its value. / 如果字段存在，返回字段的值
+                        result.push(Frame::Bulk(value.clone().into_bytes()));
+                    } else {
+                        // If the field does not exist, return nil. / 如果字段不存在，返回 nil
+                        result.push(Frame::Null);
                     }
                 }
-            }
-            Err(_) => {
-                // Incorrect number of arguments, return error. / 参数数量错误，返回错误。
-                Ok(Frame::Error("ERR wrong number of arguments for 'hmget' command".to_string()))
+
+                // Return the list of field values. / 返回字段值的列表
+                Ok(Frame::Array(result))
+            },
+            Some(_) => {
+                // Key exists but type mismatch, return WRONGTYPE error. / 键存在，但类型不匹配，返回 WRONGTYPE 错误。
+                Ok(Error::WrongType.into())
+            },
+            None => {
+                // Key does not exist, return nil for each requested field. / 键不存在，为每个请求的字段返回 nil
+                let result = vec![Frame::Null; hmget.fields.len()];
+                Ok(Frame::Array(result))
             }
         }
     }
@@ -97,10 +91,6 @@ impl Hmget {
     fn parse_command(parse: &mut Parse) -> crate::Result<Self> {
         // The command requires at least two arguments: the key and at least one field.
         // 命令需要至少两个参数：键和至少一个字段。
-        if parse.args_number()? < 2 {
-            return Err(Box::new(std::io::Error::new(std::io::ErrorKind::Other, "ERR wrong number of arguments for 'hmget' command")));
-        }
-
         let key = parse.next_string()?;
         let mut fields = Vec::new();
 
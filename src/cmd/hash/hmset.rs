@@ -1,9 +1,10 @@
 use std::sync::{Arc, Mutex};
 use crate::db::{Db, DbType};
 use crate::frame::Frame;
+use crate::notify;
 use crate::parse::Parse;
 use std::collections::HashMap;
-use crate::persistence::aof::propagate_aof;
+use crate::error::Error;
 
 /// Represents the `HMSET` command in a Redis-like system.
 /// `HMSET` 命令用于设置多个哈希表字段。
@@ -26,45 +27,34 @@ impl Hmset {
         db: &mut Arc<Mutex<Db>>,
         parse: &mut Parse
     ) -> crate::Result<Frame> {
-        match Hmset::parse_command(parse) {
-            Ok(hmset) => {
-                let mut db = db.lock().unwrap();
-                match db.get_dbtype_mut(&hmset.key) {
-                    Some(DbType::Hash(hash)) => {
-                        // Iterate over the fields and values, and update or insert them in the hash.
-                        // 遍历字段和值，并插入或更新哈希表中的字段。
-                        for (field, value) in hmset.fields_values {
-                            hash.insert(field.clone(), value.clone());
-                            // Propagate each field-value pair to AOF after insertion.
-                            // 插入后将每个字段-值对传播到 AOF。
-                            Hmset::propagate_aof("hset", &hmset.key, &field, &value);
-                        }
-                        Ok(Frame::Simple("OK".to_string()))  // Return "OK" indicating success.
-                    },
-                    Some(_) => {
-                        // If the key exists but it's not a hash, return WRONGTYPE error.
-                        // 如果键存在但类型不匹配，返回 WRONGTYPE 错误。
-                        Ok(Frame::Error("WRONGTYPE Operation against a key holding the wrong kind of value".to_string()))
-                    },
-                    None => {
-                        // If the key does not exist, create a new hash and set the fields.
-                        // 如果键不存在，创建新的哈希表，并设置字段。
-                        let mut new_hash = HashMap::new();
-                        for (field, value) in hmset.fields_values {
-                            new_hash.insert(field.clone(), value.clone());
-                            // Propagate each field-value pair to AOF after insertion.
-                            // 插入后将每个字段-值对传播到 AOF。
-                            Hmset::propagate_aof("hset", &hmset.key, &field, &value);
-                        }
-                        db.set(&hmset.key, DbType::Hash(new_hash), None); // Set the new hash in the database.
-                        Ok(Frame::Simple("OK".to_string()))  // Return "OK" indicating success.
-                    }
+        let hmset = Hmset::parse_command(parse)?;
+        let mut db = db.lock().unwrap();
+        match db.get_dbtype_mut(&hmset.key) {
+            Some(DbType::Hash(hash)) => {
+                // Iterate over the fields and values, and update or insert them in the hash.
+                // 遍历字段和值，并插入或更新哈希表中的字段。
+                for (field, value) in hmset.fields_values {
+                    hash.insert(field.clone(), value.clone());
                 }
-            }
-            Err(_) => {
-                // If parsing fails, return an error indicating wrong number of arguments.
-                // 如果解析失败，返回错误信息。
-                Ok(Frame::Error("ERR wrong number of arguments for 'hmset' command".to_string()))
+                db.signal_modified_key(&hmset.key);
+                db.notify_keyspace_event(notify::HASH, "hset", &hmset.key);
+                Ok(Frame::Simple("OK".to_string()))  // Return "OK" indicating success.
+            },
+            Some(_) => {
+                // If the key exists but it's not a hash, return WRONGTYPE error.
+                // 如果键存在但类型不匹配，返回 WRONGTYPE 错误。
+                Ok(Error::WrongType.into())
+            },
+            None => {
+                // If the key does not exist, create a new hash and set the fields.
+                // 如果键不存在，创建新的哈希表，并设置字段。
+                let mut new_hash = HashMap::new();
+                for (field, value) in hmset.fields_values {
+                    new_hash.insert(field.clone(), value.clone());
+                }
+                db.set(&hmset.key, DbType::Hash(new_hash), None); // Set the new hash in the database.
+                db.notify_keyspace_event(notify::HASH, "hset", &hmset.key);
+                Ok(Frame::Simple("OK".to_string()))  // Return "OK" indicating success.
             }
         }
     }
@@ -76,10 +66,10 @@ impl Hmset {
     /// Returns a `Result` containing the `Hmset` struct with the parsed key and fields/values.
     /// 如果成功，返回包含解析后的键和字段/值的 `Hmset` 结构体。
     fn parse_command(parse: &mut Parse) -> crate::Result<Self> {
-        // The command requires at least three arguments: the key and at least one field-value pair.
-        // 命令需要至少三个参数：键和至少一个字段-值对。
-        if parse.args_number()? < 3 || parse.args_number()? % 2 != 1 {
-            return Err(Box::new(std::io::Error::new(std::io::ErrorKind::Other, "ERR wrong number of arguments for 'hmset' command")));
+        // The fields and values have to come in pairs, the minimum is checked from the command table.
+        // 字段和值必须成对出现，最少的参数个数由命令表检查。
+        if parse.args_number()? % 2 != 1 {
+            return Err(Error::WrongArity("hmset".to_string()).into());
         }
 
         let key = parse.next_string()?;
@@ -96,13 +86,4 @@ impl Hmset {
             fields_values,
         })
     }
-
-    /// Propagates the `HMSET` command to AOF.
-    /// 将 `HMSET` 命令传播到 AOF。
-    fn propagate_aof(command: &str, key: &str, field: &str, value: &str) {
-        // Propagate the field-value pair for each field in the hmset operation.
-        // 对于 `hmset` 操作中的每个字段-值对，传播到 AOF。
-        let args = vec![key.to_string(), field.to_string(), value.to_string()];
-        propagate_aof(command.to_string(), args);
-    }
 }
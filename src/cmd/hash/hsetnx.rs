@@ -2,8 +2,8 @@ use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
 use crate::db::{Db, DbType};
 use crate::frame::Frame;
+use crate::notify;
 use crate::parse::Parse;
-use crate::persistence::aof::propagate_aof;
 
 /// Represents the `HSETNX` command in a Redis-like system.
 /// `HSETNX` 命令仅在哈希表中指定字段不存在时才设置字段的值。
@@ -30,41 +30,31 @@ impl Hsetnx {
         db: &mut Arc<Mutex<Db>>,
         parse: &mut Parse,
     ) -> crate::Result<Frame> {
-        match Hsetnx::parse_command(parse) {
-            Ok(hsetnx) => {
-                let mut db = db.lock().unwrap();
-                match db.get_dbtype_mut(&hsetnx.key) {
-                    Some(DbType::Hash(hash)) => {
-                        // If the field exists, do nothing and return 0.
-                        // 如果字段已存在，不做任何操作，返回 0。
-                        if hash.contains_key(&hsetnx.field) {
-                            Ok(Frame::Integer(0))
-                        } else {
-                            // If the field does not exist, insert it and return 1.
-                            // 如果字段不存在，插入并返回 1。
-                            hash.insert(hsetnx.field.clone(), hsetnx.value.clone());
-                            // Propagate the command to AOF after successfully adding the field.
-                            // 成功添加字段后将命令传播到 AOF。
-                            Hsetnx::propagate_aof("hset", &hsetnx.key, &hsetnx.field, &hsetnx.value);
-                            Ok(Frame::Integer(1))
-                        }
-                    },
-                    // If the key does not exist, create a new hash and insert the field.
-                    // 如果哈希表不存在，创建新的哈希表并插入字段，返回 1。
-                    _ => {
-                        let mut new_hash = HashMap::new();
-                        new_hash.insert(hsetnx.field.clone(), hsetnx.value.clone());
-                        db.set(&hsetnx.key, DbType::Hash(new_hash), None);
-                        // Propagate the command to AOF after creating a new hash and adding the field.
-                        // 创建新的哈希表并插入字段后将命令传播到 AOF。
-                        Hsetnx::propagate_aof("hset", &hsetnx.key, &hsetnx.field, &hsetnx.value);
-                        Ok(Frame::Integer(1)) // Return 1 as the new field is added.
-                    }
+        let hsetnx = Hsetnx::parse_command(parse)?;
+        let mut db = db.lock().unwrap();
+        match db.get_dbtype_mut(&hsetnx.key) {
+            Some(DbType::Hash(hash)) => {
+                // If the field exists, do nothing and return 0.
+                // 如果字段已存在，不做任何操作，返回 0。
+                if hash.contains_key(&hsetnx.field) {
+                    Ok(Frame::Integer(0))
+                } else {
+                    // If the field does not exist, insert it and return 1.
+                    // 如果字段不存在，插入并返回 1。
+                    hash.insert(hsetnx.field.clone(), hsetnx.value.clone());
+                    db.signal_modified_key(&hsetnx.key);
+                    db.notify_keyspace_event(notify::HASH, "hset", &hsetnx.key);
+                    Ok(Frame::Integer(1))
                 }
-            }
-            Err(_) => {
-                // Incorrect number of arguments, return error. / 参数数量错误，返回错误。
-                Ok(Frame::Error("ERR wrong number of arguments for 'hsetnx' command".to_string()))
+            },
+            // If the key does not exist, create a new hash and insert the field.
+            // 如果哈希表不存在，创建新的哈希表并插入字段，返回 1。
+            _ => {
+                let mut new_hash = HashMap::new();
+                new_hash.insert(hsetnx.field.clone(), hsetnx.value.clone());
+                db.set(&hsetnx.key, DbType::Hash(new_hash), None);
+                db.notify_keyspace_event(notify::HASH, "hset", &hsetnx.key);
+                Ok(Frame::Integer(1)) // Return 1 as the new field is added.
             }
         }
     }
@@ -77,9 +67,6 @@ impl Hsetnx {
     /// Otherwise, returns an error frame indicating the problem.
     /// 如果成功，返回包含解析后的键、字段和值的 `Hsetnx` 结构体。如果失败，返回错误帧以指示问题。
     fn parse_command(parse: &mut Parse) -> crate::Result<Self> {
-        if parse.args_number()? != 3 {
-            return Err(Box::new(std::io::Error::new(std::io::ErrorKind::Other, "ERR wrong number of arguments for 'hsetnx' command")));
-        }
         let key = parse.next_string()?;
         let field = parse.next_string()?;
         let value = parse.next_string()?;
@@ -90,13 +77,4 @@ impl Hsetnx {
             value,
         })
     }
-
-    /// Propagates the `HSETNX` command to AOF.
-    /// 将 `HSETNX` 命令传播到 AOF。
-    fn propagate_aof(command: &str, key: &str, field: &str, value: &str) {
-        // Propagate the HSETNX command with key, field, and value to AOF.
-        // 将 `HSETNX` 命令与键、字段和值传播到 AOF。
-        let args = vec![key.to_string(), field.to_string(), value.to_string()];
-        propagate_aof(command.to_string(), args);
-    }
 }
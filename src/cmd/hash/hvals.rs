@@ -3,6 +3,7 @@ use std::sync::Mutex;
 use crate::db::{Db, DbType};
 use crate::frame::Frame;
 use crate::parse::Parse;
+use crate::error::Error;
 
 /// Represents the `HVALS` command in a Redis-like system.
 ///
@@ -41,37 +42,29 @@ impl Hvals {
         db: &mut Arc<Mutex<Db>>,
         parse: &mut Parse
     ) -> crate::Result<Frame> {
-        match Hvals::parse_command(parse) {
-            Ok(hvals) => {
-                let mut db = db.lock().unwrap();
-                match db.get(&hvals.key) {
-                    Some(DbType::Hash(hash)) => {
-                        // Get all field values from the hash
-                        // 获取哈希表中所有字段的值
-                        let values: Vec<Frame> = hash.values()
-                            .map(|value| Frame::Bulk(value.clone().into_bytes())) // Convert field values to Frame::Bulk
-                            .collect();
+        let hvals = Hvals::parse_command(parse)?;
+        let mut db = db.lock().unwrap();
+        match db.lookup_read(&hvals.key) {
+            Some(DbType::Hash(hash)) => {
+                // Get all field values from the hash
+                // 获取哈希表中所有字段的值
+                let values: Vec<Frame> = hash.values()
+                    .map(|value| Frame::Bulk(value.clone().into_bytes())) // Convert field values to Frame::Bulk
+                    .collect();
 
-                        // Return the array of field values
-                        // 返回包含字段值的数组
-                        Ok(Frame::Array(values))
-                    },
-                    Some(_) => {
-                        // If the key exists but is not a hash, return WRONGTYPE error
-                        // 如果键存在但不是哈希表，返回 WRONGTYPE 错误
-                        Ok(Frame::Error("WRONGTYPE Operation against a key holding the wrong kind of value".to_string()))
-                    },
-                    None => {
-                        // If the key does not exist, return nil
-                        // 如果键不存在，返回 nil
-                        Ok(Frame::Null)
-                    }
-                }
-            }
-            Err(_) => {
-                // If the number of arguments is wrong, return an error
-                // 如果参数数量错误，返回错误
-                Ok(Frame::Error("ERR wrong number of arguments for 'hvals' command".to_string()))
+                // Return the array of field values
+                // 返回包含字段值的数组
+                Ok(Frame::Array(values))
+            },
+            Some(_) => {
+                // If the key exists but is not a hash, return WRONGTYPE error
+                // 如果键存在但不是哈希表，返回 WRONGTYPE 错误
+                Ok(Error::WrongType.into())
+            },
+            None => {
+                // If the key does not exist, return nil
+                // 如果键不存在，返回 nil
+                Ok(Frame::Null)
             }
         }
     }
@@ -87,10 +80,6 @@ impl Hvals {
     ///
     /// 如果成功，返回包含解析后的键的 `Hvals` 结构体。如果失败，返回错误帧以指示问题。
     fn parse_command(parse: &mut Parse) -> crate::Result<Self> {
-        if parse.args_number()? != 1 {
-            return Err(Box::new(std::io::Error::new(std::io::ErrorKind::Other, "ERR wrong number of arguments for 'hvals' command")));
-        }
-
         let key = parse.next_string()?;
 
         Ok(Hvals { key })
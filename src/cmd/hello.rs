@@ -0,0 +1,96 @@
+use crate::client::Client;
+use crate::cmd::auth::authenticate;
+use crate::cmd::client::setname::set_name;
+use crate::error::Error;
+use crate::frame::Frame;
+use crate::parse::Parse;
+
+/// `HELLO` command, switches the protocol version of the connection.
+/// `HELLO` 命令，切换连接使用的协议版本。
+///
+/// # Example
+/// ```text
+/// HELLO [protover [AUTH username password] [SETNAME clientname]]
+/// ```
+///
+/// Returns the server and connection information, as a map under RESP3 and a flat array under RESP2.
+/// 返回服务端和连接的信息，RESP3 下为映射类型，RESP2 下为扁平数组。
+pub struct Hello {
+    protover: Option<u8>,            // The requested protocol version / 请求的协议版本
+    auth: Option<(String, String)>,  // The username and password to authenticate with / 用于认证的用户名和密码
+    setname: Option<String>,         // The connection name to set / 要设置的连接名称
+}
+
+impl Hello {
+    /// Executes the `HELLO` command.
+    /// 执行 `HELLO` 命令。
+    pub fn hello_command(client: &mut Client, parse: &mut Parse) -> crate::Result<Frame> {
+        let hello = Hello::parse_command(parse)?;
+
+        if let Some((username, password)) = hello.auth {
+            authenticate(client, &username, &password)?;
+        }
+        if !client.handle.state().authenticated {
+            return Err(Error::Other(
+                "NOAUTH HELLO must be called with the client already authenticated, otherwise the HELLO <proto> AUTH <user> <pass> option can be used to authenticate the client and select the RESP protocol version at the same time".to_string(),
+            ).into());
+        }
+        if let Some(name) = hello.setname {
+            set_name(client, name)?;
+        }
+        if let Some(protover) = hello.protover {
+            client.handle.update(|state| state.resp = protover);
+        }
+        let resp = client.handle.state().resp;
+
+        let fields = vec![
+            ("server", Frame::Bulk(b"p-rudis".to_vec())),
+            ("version", Frame::Bulk(env!("CARGO_PKG_VERSION").as_bytes().to_vec())),
+            ("proto", Frame::Integer(resp as i64)),
+            ("id", Frame::Integer(client.id() as i64)),
+            ("mode", Frame::Bulk(b"standalone".to_vec())),
+            ("role", Frame::Bulk(b"master".to_vec())),
+            ("modules", Frame::Array(vec![])),
+        ];
+        let fields = fields
+            .into_iter()
+            .map(|(name, value)| (Frame::Bulk(name.as_bytes().to_vec()), value))
+            .collect();
+
+        // RESP3 returns a map, RESP2 returns a flat array of fields and values
+        // RESP3 返回映射，RESP2 返回字段和值交替的扁平数组
+        Ok(Frame::map(fields, resp))
+    }
+
+    /// Parses the optional protocol version.
+    /// 解析可选的协议版本。
+    fn parse_command(parse: &mut Parse) -> crate::Result<Self> {
+        let protover = match parse.next_string() {
+            Ok(protover) => protover,
+            Err(_) => return Ok(Hello { protover: None, auth: None, setname: None }),
+        };
+        let protover = match protover.parse::<i64>() {
+            Ok(protover) => protover,
+            Err(_) => return Err(Error::Err("Protocol version is not an integer or out of range".to_string()).into()),
+        };
+        if protover != 2 && protover != 3 {
+            return Err(Error::Other("NOPROTO unsupported protocol version".to_string()).into());
+        }
+        let mut auth = None;
+        let mut setname = None;
+        while let Ok(option) = parse.next_string() {
+            match option.to_lowercase().as_str() {
+                "auth" => match (parse.next_string(), parse.next_string()) {
+                    (Ok(username), Ok(password)) => auth = Some((username, password)),
+                    _ => return Err(Error::Err(format!("Syntax error in HELLO option '{}'", option)).into()),
+                },
+                "setname" => match parse.next_string() {
+                    Ok(name) => setname = Some(name),
+                    Err(_) => return Err(Error::Err(format!("Syntax error in HELLO option '{}'", option)).into()),
+                },
+                _ => return Err(Error::Err(format!("Syntax error in HELLO option '{}'", option)).into()),
+            }
+        }
+        Ok(Hello { protover: Some(protover as u8), auth, setname })
+    }
+}
@@ -0,0 +1,245 @@
+use std::fmt::Write;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::Ordering;
+use crate::client;
+use crate::config::{get_aof_config, get_server_config};
+use crate::db::Db;
+use crate::frame::Frame;
+use crate::parse::Parse;
+use crate::persistence::aof;
+use crate::stats;
+
+/// 不指定段或指定 `default` 时返回的段
+/// Sections returned when no section or `default` is given
+const DEFAULT_SECTIONS: [&str; 6] = ["server", "clients", "memory", "persistence", "stats", "keyspace"];
+
+/// 指定 `all` 或 `everything` 时返回的段
+/// Sections returned for `all` or `everything`
+const ALL_SECTIONS: [&str; 7] = ["server", "clients", "memory", "persistence", "stats", "commandstats", "keyspace"];
+
+/// `INFO` command, returns information and statistics about the server.
+/// `INFO` 命令，返回服务端的运行信息和统计数据。
+///
+/// # Example
+/// ```text
+/// INFO [section [section ...]]
+/// ```
+///
+/// Without a section, or with `default`, every section except `commandstats` is returned; `all` and `everything`
+/// also include `commandstats`. Section names are case-insensitive and unknown sections are ignored.
+/// 不指定段或指定 `default` 时返回除 `commandstats` 之外的所有段，`all` 和 `everything` 还包含 `commandstats`。
+/// 段名不区分大小写，未知的段被忽略。
+pub struct Info {
+    sections: Vec<String>,  // Requested sections, in lower case / 请求的段，小写
+}
+
+impl Info {
+    /// Executes the `INFO` command.
+    /// 执行 `INFO` 命令。
+    pub fn info_command(db: &mut Arc<Mutex<Db>>, parse: &mut Parse) -> crate::Result<Frame> {
+        let info = Info::parse_command(parse);
+        let mut blocks = Vec::new();
+        for section in ALL_SECTIONS {
+            if !info.includes(section) {
+                continue;
+            }
+            let block = match section {
+                "server" => server(),
+                "clients" => clients(),
+                "memory" => memory(db),
+                "persistence" => persistence(),
+                "stats" => stats(db),
+                "commandstats" => commandstats(),
+                _ => keyspace(db),
+            };
+            blocks.push(block);
+        }
+        Ok(Frame::Bulk(blocks.join("\r\n").into_bytes()))
+    }
+
+    /// 解析请求的段
+    /// Parse the requested sections.
+    fn parse_command(parse: &mut Parse) -> Self {
+        let mut sections = Vec::new();
+        while let Ok(section) = parse.next_string() {
+            sections.push(section.to_lowercase());
+        }
+        Info { sections }
+    }
+
+    /// 是否返回该段
+    /// Whether the section is returned.
+    fn includes(&self, section: &str) -> bool {
+        if self.sections.is_empty() {
+            return DEFAULT_SECTIONS.contains(&section);
+        }
+        self.sections.iter().any(|requested| match requested.as_str() {
+            "all" | "everything" => true,
+            "default" => DEFAULT_SECTIONS.contains(&section),
+            requested => requested == section,
+        })
+    }
+}
+
+/// 生成一个段，每个字段一行
+/// Build a section, one line per field.
+fn section(title: &str, fields: Vec<(&str, String)>) -> String {
+    let mut block = format!("# {}\r\n", title);
+    for (name, value) in fields {
+        let _ = write!(block, "{}:{}\r\n", name, value);
+    }
+    block
+}
+
+fn server() -> String {
+    let address = get_server_config().address;
+    let port = address.rsplit(':').next().unwrap_or_default().to_string();
+    let uptime = stats::uptime().as_secs();
+    section("Server", vec![
+        ("redis_version", env!("CARGO_PKG_VERSION").to_string()),
+        ("redis_mode", "standalone".to_string()),
+        ("os", format!("{} {}", std::env::consts::OS, std::env::consts::ARCH)),
+        ("arch_bits", (usize::BITS).to_string()),
+        ("process_id", std::process::id().to_string()),
+        ("run_id", stats::run_id().to_string()),
+        ("tcp_port", port),
+        ("uptime_in_seconds", uptime.to_string()),
+        ("uptime_in_days", (uptime / 86400).to_string()),
+    ])
+}
+
+fn clients() -> String {
+    let clients = client::all();
+    let states: Vec<_> = clients.iter().map(|client| client.state()).collect();
+    section("Clients", vec![
+        ("connected_clients", client::count().to_string()),
+        ("blocked_clients", stats::BLOCKED_CLIENTS.load(Ordering::Relaxed).to_string()),
+        ("pubsub_clients", states.iter().filter(|state| state.pubsub).count().to_string()),
+        ("maxclients", get_server_config().maxclients.to_string()),
+        ("client_recent_max_input_buffer", states.iter().map(|state| state.qbuf).max().unwrap_or(0).to_string()),
+        ("client_recent_max_output_buffer", states.iter().map(|state| state.omem).max().unwrap_or(0).to_string()),
+    ])
+}
+
+fn memory(db: &Arc<Mutex<Db>>) -> String {
+    let used = stats::used_memory(db);
+    let peak = stats::USED_MEMORY_PEAK.load(Ordering::Relaxed);
+    let dataset = db.lock().unwrap().dataset_memory();
+    let maxmemory = get_server_config().maxmemory;
+    section("Memory", vec![
+        ("used_memory", used.to_string()),
+        ("used_memory_human", human_bytes(used)),
+        ("used_memory_peak", peak.to_string()),
+        ("used_memory_peak_human", human_bytes(peak)),
+        ("used_memory_rss", rss().to_string()),
+        ("used_memory_dataset", dataset.to_string()),
+        ("maxmemory", maxmemory.to_string()),
+        ("maxmemory_human", human_bytes(maxmemory)),
+        ("maxmemory_policy", "noeviction".to_string()),
+    ])
+}
+
+fn persistence() -> String {
+    let rdb = stats::rdb_status();
+    let aof = stats::aof_status();
+    let rewrite = stats::aof_rewrite_status();
+    let aof_config = get_aof_config();
+    let aof_size = if aof_config.enabled {
+        aof::current_size()
+    } else {
+        0
+    };
+    let status = |status: &stats::WriteStatus| if status.last_error.is_none() { "ok" } else { "err" }.to_string();
+    section("Persistence", vec![
+        ("loading", (stats::LOADING.load(Ordering::Relaxed) as u8).to_string()),
+        ("rdb_changes_since_last_save", stats::get(&stats::DIRTY).to_string()),
+        ("rdb_bgsave_in_progress", (stats::RDB_SAVE_IN_PROGRESS.load(Ordering::Relaxed) as u8).to_string()),
+        ("rdb_last_save_time", rdb.last_success.to_string()),
+        ("rdb_last_bgsave_status", status(&rdb)),
+        ("rdb_last_bgsave_time_sec", rdb.last_duration.to_string()),
+        ("rdb_last_error", rdb.last_error.clone().unwrap_or_default()),
+        ("aof_enabled", (aof_config.enabled as u8).to_string()),
+        ("aof_rewrite_in_progress", (stats::AOF_REWRITE_IN_PROGRESS.load(Ordering::Relaxed) as u8).to_string()),
+        ("aof_rewrite_scheduled", (aof::rewrite_scheduled() as u8).to_string()),
+        ("aof_last_rewrite_time_sec", rewrite.last_duration.to_string()),
+        ("aof_last_bgrewrite_status", status(&rewrite)),
+        ("aof_last_write_status", status(&aof)),
+        ("aof_last_error", aof.last_error.clone().unwrap_or_default()),
+        ("aof_current_size", aof_size.to_string()),
+        ("aof_base_size", aof::rewrite_base_size().to_string()),
+        ("aof_buffer_length", aof::buffer_len().to_string()),
+        ("aof_delayed_fsync", stats::get(&stats::AOF_DELAYED_FSYNC).to_string()),
+    ])
+}
+
+fn stats(db: &Arc<Mutex<Db>>) -> String {
+    let (channels, patterns, shard_channels) = {
+        let db = db.lock().unwrap();
+        (db.channels(None).len(), db.numpat(), db.shard_channel_count())
+    };
+    section("Stats", vec![
+        ("total_connections_received", stats::get(&stats::TOTAL_CONNECTIONS_RECEIVED).to_string()),
+        ("total_commands_processed", stats::get(&stats::TOTAL_COMMANDS_PROCESSED).to_string()),
+        ("instantaneous_ops_per_sec", stats::instantaneous_ops_per_sec().to_string()),
+        ("rejected_connections", stats::get(&stats::REJECTED_CONNECTIONS).to_string()),
+        ("expired_keys", stats::get(&stats::EXPIRED_KEYS).to_string()),
+        ("evicted_keys", stats::get(&stats::EVICTED_KEYS).to_string()),
+        ("keyspace_hits", stats::get(&stats::KEYSPACE_HITS).to_string()),
+        ("keyspace_misses", stats::get(&stats::KEYSPACE_MISSES).to_string()),
+        ("pubsub_channels", channels.to_string()),
+        ("pubsub_patterns", patterns.to_string()),
+        ("pubsub_shardchannels", shard_channels.to_string()),
+    ])
+}
+
+fn commandstats() -> String {
+    let mut block = "# Commandstats\r\n".to_string();
+    for (name, stat) in stats::command_stats() {
+        let calls = stat.calls.load(Ordering::Relaxed);
+        let usec = stat.usec.load(Ordering::Relaxed);
+        let per_call = if calls == 0 { 0.0 } else { usec as f64 / calls as f64 };
+        let _ = write!(
+            block,
+            "cmdstat_{}:calls={},usec={},usec_per_call={:.2},rejected_calls={}\r\n",
+            name, calls, usec, per_call, stat.rejected_calls.load(Ordering::Relaxed),
+        );
+    }
+    block
+}
+
+fn keyspace(db: &Arc<Mutex<Db>>) -> String {
+    let (keys, expires, avg_ttl) = db.lock().unwrap().keyspace_info();
+    let mut block = "# Keyspace\r\n".to_string();
+    if keys > 0 {
+        let _ = write!(block, "db0:keys={},expires={},avg_ttl={}\r\n", keys, expires, avg_ttl);
+    }
+    block
+}
+
+/// 以易读的单位表示字节数，例如 `1.50M`
+/// Bytes in a human-readable unit, such as `1.50M`
+fn human_bytes(bytes: usize) -> String {
+    const UNITS: [&str; 5] = ["B", "K", "M", "G", "T"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{}B", bytes)
+    } else {
+        format!("{:.2}{}", value, UNITS[unit])
+    }
+}
+
+/// 进程的常驻内存，从 `/proc/self/statm` 读取，无法读取时为 0
+/// Resident memory of the process, read from `/proc/self/statm`, 0 if it can't be read
+fn rss() -> usize {
+    let pages = std::fs::read_to_string("/proc/self/statm")
+        .ok()
+        .and_then(|statm| statm.split_whitespace().nth(1).and_then(|pages| pages.parse::<usize>().ok()))
+        .unwrap_or(0);
+    let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) };
+    pages * page_size.max(0) as usize
+}
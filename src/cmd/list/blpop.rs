@@ -1,10 +1,10 @@
-use std::collections::VecDeque;
 use std::sync::{Arc, Mutex};
 use crate::db::{Db, DbType};
 use crate::frame::Frame;
+use crate::notify;
 use crate::parse::Parse;
 use std::time::{Duration, Instant};
-use crate::persistence::aof::propagate_aof;
+use crate::error::Error;
 
 /// Represents the `BLPOP` command in a Redis-like system.
 ///
@@ -46,44 +46,36 @@ impl Blpop {
         db: &mut Arc<Mutex<Db>>,
         parse: &mut Parse
     ) -> crate::Result<Frame> {
-        match Blpop::parse_command(parse) {
-            Ok(blpop) => {
-                let mut db = db.lock().unwrap();
-                db.set(&blpop.key, DbType::List(VecDeque::new()),None); //TODO 模拟
-                match db.get_dbtype_mut(&blpop.key) {
-                    Some(DbType::List(list)) => {
-                        let start_time = Instant::now();
-                        // Block until an element is available or the timeout is reached.
-                        // 如果列表为空，阻塞直到有元素或者超时
-                        while list.is_empty() {
-                            if start_time.elapsed() >= Duration::new(blpop.timeout, 0) {
-                                return Ok(Frame::Null); // Timeout reached.
-                            }
-                            // Here we simulate a wait (this could be an actual sleep in a real system)
-                            std::thread::sleep(Duration::from_millis(100)); // Check periodically.
-                        }
-
-                        // Pop the first element from the list.
-                        let value = list.pop_front().unwrap();
-                        propagate_aof("lpop".to_string(), vec![blpop.key.clone()]);
-                        Ok(Frame::Bulk(value.into_bytes())) // Return the popped value.
-                    },
-                    // If the key exists but is not a list, return an error.
-                    // 如果键存在但不是列表类型，返回错误。
-                    Some(_) => {
-                        Ok(Frame::Error("WRONGTYPE Operation against a key holding the wrong kind of value".to_string()))
-                    },
-                    // If the key does not exist, return nil.
-                    // 如果键不存在，返回 nil。
-                    None => {
-                        Ok(Frame::Null)
+        let blpop = Blpop::parse_command(parse)?;
+        let mut db = db.lock().unwrap();
+        match db.get_dbtype_mut(&blpop.key) {
+            Some(DbType::List(list)) => {
+                let start_time = Instant::now();
+                // Block until an element is available or the timeout is reached.
+                // 如果列表为空，阻塞直到有元素或者超时
+                while list.is_empty() {
+                    if start_time.elapsed() >= Duration::new(blpop.timeout, 0) {
+                        return Ok(Frame::Null); // Timeout reached.
                     }
+                    // Here we simulate a wait (this could be an actual sleep in a real system)
+                    std::thread::sleep(Duration::from_millis(100)); // Check periodically.
                 }
+
+                // Pop the first element from the list.
+                let value = list.pop_front().unwrap();
+                db.signal_modified_key(&blpop.key);
+                db.notify_keyspace_event(notify::LIST, "lpop", &blpop.key);
+                Ok(Frame::Bulk(value.into_bytes())) // Return the popped value.
             },
-            Err(_) => {
-                // Incorrect number of arguments, return error.
-                // 参数数量错误，返回错误。
-                Ok(Frame::Error("ERR wrong number of arguments for 'blpop' command".to_string()))
+            // If the key exists but is not a list, return an error.
+            // 如果键存在但不是列表类型，返回错误。
+            Some(_) => {
+                Ok(Error::WrongType.into())
+            },
+            // If the key does not exist, return nil.
+            // 如果键不存在，返回 nil。
+            None => {
+                Ok(Frame::Null)
             }
         }
     }
@@ -102,17 +94,13 @@ impl Blpop {
     fn parse_command(parse: &mut Parse) -> crate::Result<Self> {
         // The command requires exactly two arguments: the key and the timeout.
         // 命令需要正好两个参数：键和超时时间。
-        if parse.args_number()? != 2 {
-            return Err(Box::new(std::io::Error::new(std::io::ErrorKind::Other, "ERR wrong number of arguments for 'blpop' command")));
-        }
-
         let key = parse.next_string()?; // Parse the key. / 解析键。
         let timeout = parse.next_string()?; // Parse the timeout. / 解析超时时间。
 
         let timeout = match timeout.parse::<u64>() {
             Ok(timeout) => timeout,
             Err(_) => {
-                return Err(Box::new(std::io::Error::new(std::io::ErrorKind::Other, "ERR timeout is not a valid integer")));
+                return Err(Error::Err("timeout is not a valid integer".to_string()).into());
             }
         };
 
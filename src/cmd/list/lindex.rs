@@ -2,6 +2,7 @@ use std::sync::{Arc, Mutex};
 use crate::db::{Db, DbType};
 use crate::frame::Frame;
 use crate::parse::Parse;
+use crate::error::Error;
 
 /// Represents the `LINDEX` command in a Redis-like system.
 ///
@@ -47,40 +48,32 @@ impl Lindex {
         db: &mut Arc<Mutex<Db>>,
         parse: &mut Parse
     ) -> crate::Result<Frame> {
-        match Lindex::parse_command(parse) {
-            Ok(lindex) => {
-                let mut db = db.lock().unwrap();
-                match db.get_dbtype_mut(&lindex.key) {
-                    // If the key exists and is a list, return the element at the specified index.
-                    // 如果键存在并且是列表类型，返回指定索引位置的元素。
-                    Some(DbType::List(list)) => {
-                        let len = list.len() as isize;
-                        let index = if lindex.index < 0 {
-                            len.saturating_add(lindex.index)
-                        } else {
-                            lindex.index
-                        }.clamp(0, len.saturating_sub(1));
+        let lindex = Lindex::parse_command(parse)?;
+        let mut db = db.lock().unwrap();
+        match db.lookup_read(&lindex.key) {
+            // If the key exists and is a list, return the element at the specified index.
+            // 如果键存在并且是列表类型，返回指定索引位置的元素。
+            Some(DbType::List(list)) => {
+                let len = list.len() as isize;
+                let index = if lindex.index < 0 {
+                    len.saturating_add(lindex.index)
+                } else {
+                    lindex.index
+                }.clamp(0, len.saturating_sub(1));
 
-                        // Check if the index is within bounds.
-                        // 检查索引是否在有效范围内。
-                        if index >= 0 && index < len {
-                            Ok(Frame::Bulk(list[index as usize].clone().into_bytes()))
-                        } else {
-                            // If the index is out of range, return `nil`.
-                            // 如果索引超出范围，返回 `nil`。
-                            Ok(Frame::Null)
-                        }
-                    }
-                    // If the key does not exist or is not a list, return `nil`.
-                    // 如果键不存在或不是列表类型，返回 `nil`。
-                    _ => Ok(Frame::Null),
+                // Check if the index is within bounds.
+                // 检查索引是否在有效范围内。
+                if index >= 0 && index < len {
+                    Ok(Frame::Bulk(list[index as usize].clone().into_bytes()))
+                } else {
+                    // If the index is out of range, return `nil`.
+                    // 如果索引超出范围，返回 `nil`。
+                    Ok(Frame::Null)
                 }
             }
-            // If the command has an incorrect number of arguments, return an error.
-            // 如果命令参数数量不正确，返回错误。
-            Err(_) => {
-                Ok(Frame::Error("ERR wrong number of arguments for 'lindex' command".to_string()))
-            }
+            // If the key does not exist or is not a list, return `nil`.
+            // 如果键不存在或不是列表类型，返回 `nil`。
+            _ => Ok(Frame::Null),
         }
     }
 
@@ -102,17 +95,13 @@ impl Lindex {
     fn parse_command(parse: &mut Parse) -> crate::Result<Self> {
         // Check that there are exactly two arguments: the key and the index.
         // 检查命令有且仅有两个参数：键和索引。
-        if parse.args_number()? != 2 {
-            return Err(Box::new(std::io::Error::new(std::io::ErrorKind::Other, "ERR wrong number of arguments for 'lindex' command")));
-        }
-
         let key = parse.next_string()?;    // Parse the key. / 解析键。
         let index = parse.next_string()?;   // Parse the index. / 解析索引。
 
         let index = match index.parse::<isize>() {
             Ok(index) => index,
             Err(_) => {
-                return Err(Box::new(std::io::Error::new(std::io::ErrorKind::Other, "ERR value is not an integer or out of range")));
+                return Err(Error::NotInteger.into());
             }
         };
 
@@ -37,25 +37,17 @@ impl Llen {
         db: &mut Arc<Mutex<Db>>,
         parse: &mut Parse
     ) -> crate::Result<Frame> {
-        match Llen::parse_command(parse) {
-            Ok(llen) => {
-                let mut db = db.lock().unwrap();
-                match db.get_dbtype_mut(&llen.key) {
-                    // If the key exists and is a list, return the length of the list.
-                    // 如果键存在并且是列表类型，返回列表的长度。
-                    Some(DbType::List(list)) => {
-                        Ok(Frame::Integer(list.len() as i64))
-                    }
-                    // If the key does not exist or is not a list, return 0.
-                    // 如果键不存在或不是列表类型，返回 0。
-                    _ => Ok(Frame::Integer(0)),
-                }
-            }
-            // If the command has an incorrect number of arguments, return an error.
-            // 如果命令参数数量不正确，返回错误。
-            Err(_) => {
-                Ok(Frame::Error("ERR wrong number of arguments for 'llen' command".to_string()))
+        let llen = Llen::parse_command(parse)?;
+        let mut db = db.lock().unwrap();
+        match db.lookup_read(&llen.key) {
+            // If the key exists and is a list, return the length of the list.
+            // 如果键存在并且是列表类型，返回列表的长度。
+            Some(DbType::List(list)) => {
+                Ok(Frame::Integer(list.len() as i64))
             }
+            // If the key does not exist or is not a list, return 0.
+            // 如果键不存在或不是列表类型，返回 0。
+            _ => Ok(Frame::Integer(0)),
         }
     }
 
@@ -77,10 +69,6 @@ impl Llen {
     fn parse_command(parse: &mut Parse) -> crate::Result<Self> {
         // Check that there is exactly one argument: the key.
         // 检查命令有且仅有一个参数：键。
-        if parse.args_number()? != 1 {
-            return Err(Box::new(std::io::Error::new(std::io::ErrorKind::Other, "ERR wrong number of arguments for 'llen' command")));
-        }
-
         let key = parse.next_string()?; // Parse the key. / 解析键。
 
         Ok(Llen {
@@ -3,8 +3,9 @@ use std::sync::{Arc, Mutex};
 use libc::atexit;
 use crate::db::{Db, DbType};
 use crate::frame::Frame;
+use crate::notify;
 use crate::parse::Parse;
-use crate::persistence::aof::propagate_aof;
+use crate::error::Error;
 
 /// Represents the `LPOP` command in a Redis-like system.
 ///
@@ -46,39 +47,31 @@ impl Lpop {
         db: &mut Arc<Mutex<Db>>,
         parse: &mut Parse
     ) -> crate::Result<Frame> {
-        match Lpop::parse_command(parse) {
-            Ok(lpop) => {
-                let mut db = db.lock().unwrap();
-                match db.get_dbtype_mut(&lpop.key) {
-                    // If the key exists and is a list, remove and return the first element.
-                    // 如果键存在并且是列表类型，删除并返回第一个元素。
-                    Some(DbType::List(list)) => {
-                        if let Some(value) = list.pop_front() {
-                            let ars = vec![lpop.key.clone()];
-                            propagate_aof("lpop".to_string(), ars);
-                            Ok(Frame::Bulk(value.into_bytes()))
-                        } else {
-                            // If the list is empty, return nil.
-                            // 如果列表为空，返回 nil。
-                            Ok(Frame::Null)
-                        }
-                    }
-                    // If the key exists but is not a list, return an error.
-                    // 如果键存在，但不是列表类型，返回错误。
-                    Some(_) => {
-                        Ok(Frame::Error("WRONGTYPE Operation against a key holding the wrong kind of value".to_string()))
-                    }
-                    // If the key does not exist, return nil.
-                    // 如果键不存在，返回 nil。
-                    None => {
-                        Ok(Frame::Null)
-                    }
+        let lpop = Lpop::parse_command(parse)?;
+        let mut db = db.lock().unwrap();
+        match db.get_dbtype_mut(&lpop.key) {
+            // If the key exists and is a list, remove and return the first element.
+            // 如果键存在并且是列表类型，删除并返回第一个元素。
+            Some(DbType::List(list)) => {
+                if let Some(value) = list.pop_front() {
+                    db.signal_modified_key(&lpop.key);
+                    db.notify_keyspace_event(notify::LIST, "lpop", &lpop.key);
+                    Ok(Frame::Bulk(value.into_bytes()))
+                } else {
+                    // If the list is empty, return nil.
+                    // 如果列表为空，返回 nil。
+                    Ok(Frame::Null)
                 }
             }
-            // If the command has an incorrect number of arguments, return an error.
-            // 如果命令参数数量不正确，返回错误。
-            Err(_) => {
-                Ok(Frame::Error("ERR wrong number of arguments for 'lpop' command".to_string()))
+            // If the key exists but is not a list, return an error.
+            // 如果键存在，但不是列表类型，返回错误。
+            Some(_) => {
+                Ok(Error::WrongType.into())
+            }
+            // If the key does not exist, return nil.
+            // 如果键不存在，返回 nil。
+            None => {
+                Ok(Frame::Null)
             }
         }
     }
@@ -97,10 +90,6 @@ impl Lpop {
     fn parse_command(parse: &mut Parse) -> crate::Result<Self> {
         // The command requires exactly one argument: the key.
         // 命令需要正好一个参数：键。
-        if parse.args_number()? != 1 {
-            return Err(Box::new(std::io::Error::new(std::io::ErrorKind::Other, "ERR wrong number of arguments for 'lpop' command")));
-        }
-
         let key = parse.next_string()?; // Parse the key. / 解析键。
 
         Ok(Lpop {
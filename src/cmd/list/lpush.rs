@@ -2,8 +2,9 @@ use std::collections::VecDeque;
 use std::sync::{Arc, Mutex};
 use crate::db::{Db, DbType};
 use crate::frame::Frame;
+use crate::notify;
 use crate::parse::Parse;
-use crate::persistence::aof::propagate_aof;
+use crate::error::Error;
 
 /// Represents the `LPUSH` command in a Redis-like system.
 ///
@@ -45,46 +46,36 @@ impl Lpush {
         db: &mut Arc<Mutex<Db>>,
         parse: &mut Parse
     ) -> crate::Result<Frame> {
-        match Lpush::parse_command(parse) {
-            Ok(lpush) => {
-                let mut db = db.lock().unwrap();
-                match db.get_dbtype_mut(&lpush.key) {
-                    // If the key exists and is a list, insert the values at the head of the list.
-                    // 如果键存在并且是列表类型，将值插入列表头部。
-                    Some(DbType::List(list)) => {
-                        let mut args= vec![lpush.key.to_string()];
-                        for value in lpush.values.iter() {
-                            args.push(value.to_string());
-                            list.push_front(value.to_string());
-                        }
-                        propagate_aof("lpush".to_string(), args);
-                        Ok(Frame::Integer(list.len() as i64))
-                    }
-                    // If the key exists but is not a list, return an error.
-                    // 如果键存在，但不是列表类型，返回错误。
-                    Some(_) => {
-                        Ok(Frame::Error("WRONGTYPE Operation against a key holding the wrong kind of value".to_string()))
-                    }
-                    // If the key does not exist, create a new list and insert the values.
-                    // 如果键不存在，创建一个新的列表并插入值。
-                    None => {
-                        let mut list = VecDeque::new();
-                        let mut args = vec![lpush.key.to_string()];
-                        for value in lpush.values.iter() {
-                            args.push(value.to_string());
-                            list.push_front(value.to_string());
-                        }
-                        propagate_aof("lpush".to_string(), args);
-                        let len = list.len();
-                        db.set(lpush.key.as_str(), DbType::List(list), None);
-                        Ok(Frame::Integer(len as i64))
-                    }
+        let lpush = Lpush::parse_command(parse)?;
+        let mut db = db.lock().unwrap();
+        match db.get_dbtype_mut(&lpush.key) {
+            // If the key exists and is a list, insert the values at the head of the list.
+            // 如果键存在并且是列表类型，将值插入列表头部。
+            Some(DbType::List(list)) => {
+                for value in lpush.values.iter() {
+                    list.push_front(value.to_string());
                 }
+                let len = list.len();
+                db.signal_modified_key(&lpush.key);
+                db.notify_keyspace_event(notify::LIST, "lpush", &lpush.key);
+                Ok(Frame::Integer(len as i64))
             }
-            // If the command has an incorrect number of arguments, return an error.
-            // 如果命令参数数量不正确，返回错误。
-            Err(_) => {
-                Ok(Frame::Error("ERR wrong number of arguments for 'lpush' command".to_string()))
+            // If the key exists but is not a list, return an error.
+            // 如果键存在，但不是列表类型，返回错误。
+            Some(_) => {
+                Ok(Error::WrongType.into())
+            }
+            // If the key does not exist, create a new list and insert the values.
+            // 如果键不存在，创建一个新的列表并插入值。
+            None => {
+                let mut list = VecDeque::new();
+                for value in lpush.values.iter() {
+                    list.push_front(value.to_string());
+                }
+                let len = list.len();
+                db.set(lpush.key.as_str(), DbType::List(list), None);
+                db.notify_keyspace_event(notify::LIST, "lpush", &lpush.key);
+                Ok(Frame::Integer(len as i64))
             }
         }
     }
@@ -103,10 +94,6 @@ impl Lpush {
     fn parse_command(parse: &mut Parse) -> crate::Result<Self> {
         // Check that there are at least two arguments: the key and at least one value.
         // 检查命令至少有两个参数：键和至少一个值。
-        if parse.args_number()? < 2 {
-            return Err(Box::new(std::io::Error::new(std::io::ErrorKind::Other, "ERR wrong number of arguments for 'lpush' command")));
-        }
-
         let key = parse.next_string()?; // Parse the key. / 解析键。
         let mut values = Vec::new();
 
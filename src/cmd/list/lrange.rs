@@ -2,6 +2,7 @@ use std::sync::{Arc, Mutex};
 use crate::db::{Db, DbType};
 use crate::frame::Frame;
 use crate::parse::Parse;
+use crate::error::Error;
 
 /// Represents the `LRANGE` command in a Redis-like system.
 ///
@@ -55,49 +56,41 @@ impl Lrange {
         db: &mut Arc<Mutex<Db>>,
         parse: &mut Parse
     ) -> crate::Result<Frame> {
-        match Lrange::parse_command(parse) {
-            Ok(lrange) => {
-                let mut db = db.lock().unwrap();
-                match db.get_dbtype_mut(&lrange.key) {
-                    // If the key exists and is a list, return the elements in the specified range.
-                    // 如果键存在并且是列表类型，返回指定范围内的元素。
-                    Some(DbType::List(list)) => {
-                        let len = list.len() as isize;
-                        // Adjust negative indices if necessary.
-                        let start = if lrange.start < 0 {
-                            len.saturating_add(lrange.start) // 防止负数下溢
-                        } else {
-                            lrange.start
-                        }.clamp(0, len.saturating_sub(1)); // 确保在 0..len 范围内
+        let lrange = Lrange::parse_command(parse)?;
+        let mut db = db.lock().unwrap();
+        match db.lookup_read(&lrange.key) {
+            // If the key exists and is a list, return the elements in the specified range.
+            // 如果键存在并且是列表类型，返回指定范围内的元素。
+            Some(DbType::List(list)) => {
+                let len = list.len() as isize;
+                // Adjust negative indices if necessary.
+                let start = if lrange.start < 0 {
+                    len.saturating_add(lrange.start) // 防止负数下溢
+                } else {
+                    lrange.start
+                }.clamp(0, len.saturating_sub(1)); // 确保在 0..len 范围内
 
-                        let end = if lrange.end < 0 {
-                            len.saturating_add(lrange.end)
-                        } else {
-                            lrange.end
-                        }.clamp(0, len.saturating_sub(1)); // 确保在 0..len 范围内
+                let end = if lrange.end < 0 {
+                    len.saturating_add(lrange.end)
+                } else {
+                    lrange.end
+                }.clamp(0, len.saturating_sub(1)); // 确保在 0..len 范围内
 
-                        let range = list
-                            .iter()
-                            .skip(start as usize)
-                            .take((end - start + 1) as usize)
-                            .map(|x| Frame::Bulk(x.clone().into_bytes()))
-                            .collect::<Vec<Frame>>();
+                let range = list
+                    .iter()
+                    .skip(start as usize)
+                    .take((end - start + 1) as usize)
+                    .map(|x| Frame::Bulk(x.clone().into_bytes()))
+                    .collect::<Vec<Frame>>();
 
-                        Ok(Frame::Array(range))
-                    }
-                    Some(_)=>{
-                        Ok(Frame::Error("WRONGTYPE Operation against a key holding the wrong kind of value".to_string()))
-                    }
-                    // If the key does not exist, return an empty array.
-                    // 如果键不存在，返回空数组。
-                    _ => Ok(Frame::Array(Vec::new())),
-                }
+                Ok(Frame::Array(range))
             }
-            // If the command has an incorrect number of arguments, return an error.
-            // 如果命令参数数量不正确，返回错误。
-            Err(_) => {
-                Ok(Frame::Error("ERR wrong number of arguments for 'lrange' command".to_string()))
+            Some(_)=>{
+                Ok(Error::WrongType.into())
             }
+            // If the key does not exist, return an empty array.
+            // 如果键不存在，返回空数组。
+            _ => Ok(Frame::Array(Vec::new())),
         }
     }
 
@@ -119,10 +112,6 @@ impl Lrange {
     fn parse_command(parse: &mut Parse) -> crate::Result<Self> {
         // Check that there are exactly three arguments: the key, start index, and end index.
         // 检查命令有且仅有三个参数：键、起始索引和结束索引。
-        if parse.args_number()? != 3 {
-            return Err(Box::new(std::io::Error::new(std::io::ErrorKind::Other, "ERR wrong number of arguments for 'lrange' command")));
-        }
-
         let key = parse.next_string()?;  // Parse the key. / 解析键。
         let start = parse.next_string()?; // Parse the start index. / 解析起始索引。
         let end = parse.next_string()?;   // Parse the end index. / 解析结束索引。
@@ -136,7 +125,7 @@ impl Lrange {
                 end,
             })
         }else{
-            Err(Box::new(std::io::Error::new(std::io::ErrorKind::Other, "ERR invalid range indices for 'lrange' command")))
+            Err(Error::NotInteger.into())
         }
     }
 }
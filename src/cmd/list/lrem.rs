@@ -2,8 +2,9 @@ use std::collections::VecDeque;
 use std::sync::{Arc, Mutex};
 use crate::db::{Db, DbType};
 use crate::frame::Frame;
+use crate::notify;
 use crate::parse::Parse;
-use crate::persistence::aof::propagate_aof;
+use crate::error::Error;
 
 /// Represents the `LREM` command in a Redis-like system.
 ///
@@ -43,59 +44,53 @@ impl Lrem {
         db: &mut Arc<Mutex<Db>>,
         parse: &mut Parse
     ) -> crate::Result<Frame> {
-        match Lrem::parse_command(parse) {
-            Ok(lrem) => {
-                let mut db = db.lock().unwrap();
-                match db.get_dbtype_mut(&lrem.key) {
-                    // If the key exists and is a list, remove the specified number of matching elements.
-                    // 如果键存在且是列表类型，从列表中移除指定数量的匹配元素。
-                    Some(DbType::List(list)) => {
-                        let mut removed_count = 0;
-                        if lrem.count == 0 {
-                            // Remove all matching elements if count is 0.
-                            // 如果 count 为 0，移除所有匹配的元素。
-                            let len= list.len() as i64;
-                            list.retain(|x| x != &lrem.value);
-                            removed_count =len - list.len() as i64;
-                        } else if lrem.count > 0 {
-                            // Remove elements from the head of the list.
-                            // 如果 count 大于 0，从列表的头部开始移除。
-                            let mut i = 0;
-                            while i < list.len() && removed_count < lrem.count {
-                                if list[i] == lrem.value {
-                                    list.remove(i);  // Directly remove the element.
-                                    removed_count += 1;
-                                } else {
-                                    i += 1;
-                                }
-                            }
+        let lrem = Lrem::parse_command(parse)?;
+        let mut db = db.lock().unwrap();
+        match db.get_dbtype_mut(&lrem.key) {
+            // If the key exists and is a list, remove the specified number of matching elements.
+            // 如果键存在且是列表类型，从列表中移除指定数量的匹配元素。
+            Some(DbType::List(list)) => {
+                let mut removed_count = 0;
+                if lrem.count == 0 {
+                    // Remove all matching elements if count is 0.
+                    // 如果 count 为 0，移除所有匹配的元素。
+                    let len= list.len() as i64;
+                    list.retain(|x| x != &lrem.value);
+                    removed_count =len - list.len() as i64;
+                } else if lrem.count > 0 {
+                    // Remove elements from the head of the list.
+                    // 如果 count 大于 0，从列表的头部开始移除。
+                    let mut i = 0;
+                    while i < list.len() && removed_count < lrem.count {
+                        if list[i] == lrem.value {
+                            list.remove(i);  // Directly remove the element.
+                            removed_count += 1;
                         } else {
-                            // Remove elements from the tail of the list.
-                            // 如果 count 小于 0，从列表的尾部开始移除。
-                            let mut i = list.len() as i64 - 1;
-                            while i >= 0 && removed_count < -lrem.count {
-                                if list[i as usize] == lrem.value {
-                                    list.remove(i as usize);  // Directly remove the element.
-                                    removed_count += 1;
-                                }
-                                i -= 1;
-                            }
+                            i += 1;
                         }
-                        let args=vec![lrem.key,removed_count.to_string(),lrem.value];
-                        propagate_aof("lrem".to_string(),args);
-                        Ok(Frame::Integer(removed_count))
                     }
-                    // If the key does not exist or is not a list, return an error.
-                    // 如果键不存在或不是列表类型，返回错误。
-                    _ => {
-                        Ok(Frame::Error("WRONGTYPE Operation against a key holding the wrong kind of value".to_string()))
+                } else {
+                    // Remove elements from the tail of the list.
+                    // 如果 count 小于 0，从列表的尾部开始移除。
+                    let mut i = list.len() as i64 - 1;
+                    while i >= 0 && removed_count < -lrem.count {
+                        if list[i as usize] == lrem.value {
+                            list.remove(i as usize);  // Directly remove the element.
+                            removed_count += 1;
+                        }
+                        i -= 1;
                     }
                 }
+                if removed_count > 0 {
+                    db.signal_modified_key(&lrem.key);
+                    db.notify_keyspace_event(notify::LIST, "lrem", &lrem.key);
+                }
+                Ok(Frame::Integer(removed_count))
             }
-            // If the command has an incorrect number of arguments, return an error.
-            // 如果命令参数数量不正确，返回错误。
-            Err(_) => {
-                Ok(Frame::Error("ERR wrong number of arguments for 'lrem' command".to_string()))
+            // If the key does not exist or is not a list, return an error.
+            // 如果键不存在或不是列表类型，返回错误。
+            _ => {
+                Ok(Error::WrongType.into())
             }
         }
     }
@@ -118,10 +113,6 @@ impl Lrem {
     fn parse_command(parse: &mut Parse) -> crate::Result<Self> {
         // Check that there are exactly three arguments: the key, count, and value.
         // 检查命令有且仅有三个参数：键、计数和值。
-        if parse.args_number()? != 3 {
-            return Err(Box::new(std::io::Error::new(std::io::ErrorKind::Other, "ERR wrong number of arguments for 'lrem' command")));
-        }
-
         let key = parse.next_string()?; // Parse the key. / 解析键。
         let count = parse.next_string()?;  // Parse the count. / 解析计数。
         let value = parse.next_string()?; // Parse the value. / 解析值。
@@ -129,7 +120,7 @@ impl Lrem {
         let count = match count.parse::<i64>() {
             Ok(count) => count,
             Err(_) => {
-                return Err(Box::new(std::io::Error::new(std::io::ErrorKind::Other, "ERR value is not an integer or out of range")));
+                return Err(Error::NotInteger.into());
             }
         };
 
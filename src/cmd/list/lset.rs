@@ -2,8 +2,9 @@ use std::collections::VecDeque;
 use std::sync::{Arc, Mutex};
 use crate::db::{Db, DbType};
 use crate::frame::Frame;
+use crate::notify;
 use crate::parse::Parse;
-use crate::persistence::aof::propagate_aof;
+use crate::error::Error;
 
 /// Represents the `LSET` command in a Redis-like system.
 ///
@@ -45,35 +46,27 @@ impl Lset {
         db: &mut Arc<Mutex<Db>>,
         parse: &mut Parse
     ) -> crate::Result<Frame> {
-        match Lset::parse_command(parse) {
-            Ok(lset) => {
-                let mut db = db.lock().unwrap();
-                match db.get_dbtype_mut(&lset.key) {
-                    // If the key exists and is a list, set the value at the specified index.
-                    // 如果键存在并且是列表类型，在指定的索引位置设置值。
-                    Some(DbType::List(list)) => {
-                        if lset.index < 0 || lset.index >= list.len() as i64 {
-                            // If the index is out of range, return an error.
-                            // 如果索引超出范围，返回错误。
-                            Ok(Frame::Error("ERR index out of range".to_string()))
-                        } else {
-                            let args = vec![lset.key.clone(), lset.index.to_string(), lset.value.clone()];
-                            propagate_aof("lset".to_string(),args);
-                            list[lset.index as usize] = lset.value;
-                            Ok(Frame::Simple("OK".to_string()))
-                        }
-                    }
-                    // If the key does not exist or is not a list, return an error.
-                    // 如果键不存在或不是列表类型，返回错误。
-                    _ => {
-                        Ok(Frame::Error("WRONGTYPE Operation against a key holding the wrong kind of value".to_string()))
-                    }
+        let lset = Lset::parse_command(parse)?;
+        let mut db = db.lock().unwrap();
+        match db.get_dbtype_mut(&lset.key) {
+            // If the key exists and is a list, set the value at the specified index.
+            // 如果键存在并且是列表类型，在指定的索引位置设置值。
+            Some(DbType::List(list)) => {
+                if lset.index < 0 || lset.index >= list.len() as i64 {
+                    // If the index is out of range, return an error.
+                    // 如果索引超出范围，返回错误。
+                    Ok(Frame::Error("ERR index out of range".to_string()))
+                } else {
+                    list[lset.index as usize] = lset.value;
+                    db.signal_modified_key(&lset.key);
+                    db.notify_keyspace_event(notify::LIST, "lset", &lset.key);
+                    Ok(Frame::Simple("OK".to_string()))
                 }
             }
-            // If the command has an incorrect number of arguments, return an error.
-            // 如果命令参数数量不正确，返回错误。
-            Err(_) => {
-                Ok(Frame::Error("ERR wrong number of arguments for 'lset' command".to_string()))
+            // If the key does not exist or is not a list, return an error.
+            // 如果键不存在或不是列表类型，返回错误。
+            _ => {
+                Ok(Error::WrongType.into())
             }
         }
     }
@@ -96,10 +89,6 @@ impl Lset {
     fn parse_command(parse: &mut Parse) -> crate::Result<Self> {
         // Check that there are exactly three arguments: the key, index, and value.
         // 检查命令有且仅有三个参数：键、索引和值。
-        if parse.args_number()? != 3 {
-            return Err(Box::new(std::io::Error::new(std::io::ErrorKind::Other, "ERR wrong number of arguments for 'lset' command")));
-        }
-
         let key = parse.next_string()?; // Parse the key. / 解析键。
         let index = parse.next_string()?;  // Parse the index. / 解析索引。
         let value = parse.next_string()?; // Parse the value. / 解析值。
@@ -107,7 +96,7 @@ impl Lset {
         let index = match index.parse::<i64>() {
             Ok(index) => index,
             Err(_) => {
-                return Err(Box::new(std::io::Error::new(std::io::ErrorKind::Other, "ERR value is not an integer or out of range")));
+                return Err(Error::NotInteger.into());
             }
         };
 
@@ -2,7 +2,9 @@ use std::collections::VecDeque;
 use std::sync::{Arc, Mutex};
 use crate::db::{Db, DbType};
 use crate::frame::Frame;
+use crate::notify;
 use crate::parse::Parse;
+use crate::error::Error;
 
 /// Represents the `LTRIM` command in a Redis-like system.
 ///
@@ -42,49 +44,44 @@ impl Ltrim {
         db: &mut Arc<Mutex<Db>>,
         parse: &mut Parse
     ) -> crate::Result<Frame> {
-        match Ltrim::parse_command(parse) {
-            Ok(ltrim) => {
-                let mut db = db.lock().unwrap();
-                match db.get_dbtype_mut(&ltrim.key) {
-                    // If the key exists and is a list, trim the list to the specified range.
-                    // 如果键存在并且是列表类型，修剪列表为指定范围。
-                    Some(DbType::List(list)) => {
-                        // Ensure the range is within bounds.
-                        let len = list.len() as i64;
-                        let start = if ltrim.start < 0 {
-                            len.saturating_add(ltrim.start) // 防止负数下溢
-                        } else {
-                            ltrim.start
-                        }.clamp(0, len.saturating_sub(1)); // 确保 start ∈ [0, len-1]
+        let ltrim = Ltrim::parse_command(parse)?;
+        let mut db = db.lock().unwrap();
+        match db.get_dbtype_mut(&ltrim.key) {
+            // If the key exists and is a list, trim the list to the specified range.
+            // 如果键存在并且是列表类型，修剪列表为指定范围。
+            Some(DbType::List(list)) => {
+                // Ensure the range is within bounds.
+                let len = list.len() as i64;
+                let start = if ltrim.start < 0 {
+                    len.saturating_add(ltrim.start) // 防止负数下溢
+                } else {
+                    ltrim.start
+                }.clamp(0, len.saturating_sub(1)); // 确保 start ∈ [0, len-1]
 
-                        let stop = if ltrim.stop < 0 {
-                            len.saturating_add(ltrim.stop) // 防止负数下溢
-                        } else {
-                            ltrim.stop
-                        }.clamp(0, len.saturating_sub(1)); // 确保 stop ∈ [0, len-1]
+                let stop = if ltrim.stop < 0 {
+                    len.saturating_add(ltrim.stop) // 防止负数下溢
+                } else {
+                    ltrim.stop
+                }.clamp(0, len.saturating_sub(1)); // 确保 stop ∈ [0, len-1]
 
-                        if start <= stop {
-                            let trimmed: VecDeque<String> = list.iter().skip(start as usize).take((stop - start + 1) as usize).cloned().collect();
-                            *list = trimmed;
-                        } else {
-                            // If the start index is greater than the stop index, the list will be empty.
-                            // 如果起始索引大于结束索引，列表将为空。
-                            list.clear();
-                        }
-
-                        Ok(Frame::Integer(list.len() as i64))
-                    }
-                    // If the key does not exist or is not a list, return an error.
-                    // 如果键不存在或不是列表类型，返回错误。
-                    _ => {
-                        Ok(Frame::Error("WRONGTYPE Operation against a key holding the wrong kind of value".to_string()))
-                    }
+                if start <= stop {
+                    let trimmed: VecDeque<String> = list.iter().skip(start as usize).take((stop - start + 1) as usize).cloned().collect();
+                    *list = trimmed;
+                } else {
+                    // If the start index is greater than the stop index, the list will be empty.
+                    // 如果起始索引大于结束索引，列表将为空。
+                    list.clear();
                 }
+
+                let len = list.len();
+                db.signal_modified_key(&ltrim.key);
+                db.notify_keyspace_event(notify::LIST, "ltrim", &ltrim.key);
+                Ok(Frame::Integer(len as i64))
             }
-            // If the command has an incorrect number of arguments, return an error.
-            // 如果命令参数数量不正确，返回错误。
-            Err(_) => {
-                Ok(Frame::Error("ERR wrong number of arguments for 'ltrim' command".to_string()))
+            // If the key does not exist or is not a list, return an error.
+            // 如果键不存在或不是列表类型，返回错误。
+            _ => {
+                Ok(Error::WrongType.into())
             }
         }
     }
@@ -107,21 +104,17 @@ impl Ltrim {
     fn parse_command(parse: &mut Parse) -> crate::Result<Self> {
         // Check that there are exactly three arguments: the key, start, and stop indices.
         // 检查命令有且仅有三个参数：键、起始索引和结束索引。
-        if parse.args_number()? != 3 {
-            return Err(Box::new(std::io::Error::new(std::io::ErrorKind::Other, "ERR wrong number of arguments for 'ltrim' command")));
-        }
-
         let key = parse.next_string()?;  // Parse the key. / 解析键。
         let start = parse.next_string()?;   // Parse the start index. / 解析起始索引。
         let stop = parse.next_string()?;    // Parse the stop index. / 解析结束索引。
 
         let start = match start.parse::<i64>() {
             Ok(num) => num,
-            Err(_) => return Err(Box::new(std::io::Error::new(std::io::ErrorKind::Other, "ERR value is not an integer or out of range"))),
+            Err(_) => return Err(Error::NotInteger.into()),
         };
         let stop = match stop.parse::<i64>() {
             Ok(num) => num,
-            Err(_) => return Err(Box::new(std::io::Error::new(std::io::ErrorKind::Other, "ERR value is not an integer or out of range"))),
+            Err(_) => return Err(Error::NotInteger.into()),
         };
 
         Ok(Ltrim {
@@ -1,8 +1,8 @@
 use std::sync::{Arc, Mutex};
 use crate::db::{Db, DbType};
 use crate::frame::Frame;
+use crate::notify;
 use crate::parse::Parse;
-use crate::persistence::aof::propagate_aof;
 
 /// Represents the `RPOP` command in a Redis-like system.
 ///
@@ -45,31 +45,23 @@ impl Rpop {
         db: &mut Arc<Mutex<Db>>,
         parse: &mut Parse
     ) -> crate::Result<Frame> {
-        match Rpop::parse_command(parse) {
-            Ok(rpop) => {
-                let mut db = db.lock().unwrap();
-                match db.get_dbtype_mut(&rpop.key) {
-                    // If the key exists and is a list, remove the last element and return it.
-                    // 如果键存在并且是列表类型，移除列表的最后一个元素并返回它。
-                    Some(DbType::List(list)) => {
-                        if let Some(value) = list.pop_back() {
-                            let args= vec![rpop.key.clone()];
-                            propagate_aof("rpop".to_string(),args);
-                            Ok(Frame::Bulk(value.into_bytes())) // Return the last element.
-                        } else {
-                            Ok(Frame::Null) // Return nil if the list is empty.
-                        }
-                    }
-                    // If the key does not exist or is not a list, return nil.
-                    // 如果键不存在或不是列表类型，返回 nil。
-                    _ => Ok(Frame::Null),
+        let rpop = Rpop::parse_command(parse)?;
+        let mut db = db.lock().unwrap();
+        match db.get_dbtype_mut(&rpop.key) {
+            // If the key exists and is a list, remove the last element and return it.
+            // 如果键存在并且是列表类型，移除列表的最后一个元素并返回它。
+            Some(DbType::List(list)) => {
+                if let Some(value) = list.pop_back() {
+                    db.signal_modified_key(&rpop.key);
+                    db.notify_keyspace_event(notify::LIST, "rpop", &rpop.key);
+                    Ok(Frame::Bulk(value.into_bytes())) // Return the last element.
+                } else {
+                    Ok(Frame::Null) // Return nil if the list is empty.
                 }
             }
-            // If the command has an incorrect number of arguments, return an error.
-            // 如果命令参数数量不正确，返回错误。
-            Err(_) => {
-                Ok(Frame::Error("ERR wrong number of arguments for 'rpop' command".to_string()))
-            }
+            // If the key does not exist or is not a list, return nil.
+            // 如果键不存在或不是列表类型，返回 nil。
+            _ => Ok(Frame::Null),
         }
     }
 
@@ -91,10 +83,6 @@ impl Rpop {
     fn parse_command(parse: &mut Parse) -> crate::Result<Self> {
         // Check that there is exactly one argument: the key.
         // 检查命令恰好有一个参数：键。
-        if parse.args_number()? != 1 {
-            return Err(Box::new(std::io::Error::new(std::io::ErrorKind::Other, "ERR wrong number of arguments for 'rpop' command")));
-        }
-
         let key = parse.next_string()?; // Parse the key. / 解析键。
 
         Ok(Rpop {
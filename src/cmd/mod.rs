@@ -4,3 +4,13 @@ pub mod string;
 pub mod hash;
 pub mod pubsub;
 pub mod list;
+pub mod hello;
+pub mod client;
+pub mod auth;
+pub mod acl;
+pub mod quit;
+pub mod reset;
+pub mod config;
+pub mod info;
+pub mod command;
+pub mod persistence;
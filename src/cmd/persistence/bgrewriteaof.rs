@@ -0,0 +1,38 @@
+use std::sync::{Arc, Mutex};
+use crate::db::Db;
+use crate::error::Error;
+use crate::frame::Frame;
+use crate::parse::Parse;
+use crate::persistence::aof::{self, Rewrite};
+
+/// `BGREWRITEAOF` command, rewrites the append only file in the background.
+/// `BGREWRITEAOF` 命令，在后台重写 AOF 文件。
+///
+/// # Example
+/// ```text
+/// BGREWRITEAOF
+/// ```
+///
+/// The new base file holds the current keyspace, later writes go to a new incremental file, and the manifest is
+/// atomically switched to them once the base file is written. If a background save is in progress, the rewrite is
+/// scheduled to run after it. AOF must be enabled.
+/// 新的基础文件保存当前的数据，之后的写入进入新的增量文件，基础文件写完后原子地切换清单。
+/// 后台保存正在进行时，重写被安排在它结束之后进行。需要启用 AOF。
+pub struct Bgrewriteaof;
+
+impl Bgrewriteaof {
+    /// Executes the `BGREWRITEAOF` command.
+    /// 执行 `BGREWRITEAOF` 命令。
+    pub fn bgrewriteaof_command(db: &mut Arc<Mutex<Db>>, _parse: &mut Parse) -> crate::Result<Frame> {
+        match aof::background_rewrite(db) {
+            Rewrite::Started => Ok(Frame::Simple("Background append only file rewriting started".to_string())),
+            Rewrite::Scheduled => Ok(Frame::Simple("Background append only file rewriting scheduled".to_string())),
+            Rewrite::InProgress => {
+                Err(Error::Err("Background append only file rewriting already in progress".to_string()).into())
+            }
+            Rewrite::NotRunning => {
+                Err(Error::Err("Background append only file rewriting needs appendonly enabled and loaded".to_string()).into())
+            }
+        }
+    }
+}
@@ -0,0 +1,51 @@
+use std::sync::{Arc, Mutex};
+use crate::db::Db;
+use crate::error::Error;
+use crate::frame::Frame;
+use crate::parse::Parse;
+use crate::persistence::rdb::{self, Bgsave as Outcome};
+
+/// `BGSAVE` command, saves the dataset to the RDB file in the background.
+/// `BGSAVE` 命令，在后台把数据集保存到 RDB 文件。
+///
+/// # Example
+/// ```text
+/// BGSAVE [SCHEDULE]
+/// ```
+///
+/// Keys are saved in batches and the database is only locked while saving each batch; keys modified meanwhile are
+/// saved as they were when the save started. If a background save is already in progress, an error is returned,
+/// unless `SCHEDULE` is given, in which case another save runs once the current one ends.
+/// 键分批保存，只有保存每一批时才锁住数据库，期间被修改的键按保存开始时的值保存。已经有后台保存在进行时返回错误，
+/// 指定 `SCHEDULE` 时则在当前的保存结束之后再保存一次。
+pub struct Bgsave {
+    schedule: bool,  // Whether to schedule a save if one is in progress / 已有保存在进行时是否安排再保存一次
+}
+
+impl Bgsave {
+    /// Executes the `BGSAVE` command.
+    /// 执行 `BGSAVE` 命令。
+    pub fn bgsave_command(db: &mut Arc<Mutex<Db>>, parse: &mut Parse) -> crate::Result<Frame> {
+        let bgsave = Bgsave::parse_command(parse)?;
+        match rdb::background_save(db, bgsave.schedule) {
+            Outcome::Started => Ok(Frame::Simple("Background saving started".to_string())),
+            Outcome::Scheduled => Ok(Frame::Simple("Background saving scheduled".to_string())),
+            Outcome::InProgress => Err(Error::Err("Background save already in progress".to_string()).into()),
+            Outcome::RewriteInProgress => Err(Error::Err(
+                "An AOF log rewriting in progress: can't BGSAVE right now. Use BGSAVE SCHEDULE in order to schedule a BGSAVE whenever possible.".to_string(),
+            ).into()),
+        }
+    }
+
+    fn parse_command(parse: &mut Parse) -> crate::Result<Self> {
+        let schedule = match parse.next_string() {
+            Ok(option) if option.eq_ignore_ascii_case("schedule") => true,
+            Ok(_) => return Err(Error::Syntax.into()),
+            Err(_) => false,
+        };
+        if parse.next_string().is_ok() {
+            return Err(Error::Syntax.into());
+        }
+        Ok(Bgsave { schedule })
+    }
+}
@@ -0,0 +1,25 @@
+use std::sync::{Arc, Mutex};
+use crate::db::Db;
+use crate::frame::Frame;
+use crate::parse::Parse;
+use crate::stats;
+
+/// `LASTSAVE` command, returns the UNIX time of the latest successful save to the RDB file.
+/// `LASTSAVE` 命令，返回最近一次成功保存 RDB 文件的 UNIX 时间戳。
+///
+/// # Example
+/// ```text
+/// LASTSAVE
+/// ```
+///
+/// Before the first save, the time the server started is returned.
+/// 第一次保存之前返回服务端启动的时间。
+pub struct Lastsave;
+
+impl Lastsave {
+    /// Executes the `LASTSAVE` command.
+    /// 执行 `LASTSAVE` 命令。
+    pub fn lastsave_command(_db: &mut Arc<Mutex<Db>>, _parse: &mut Parse) -> crate::Result<Frame> {
+        Ok(Frame::Integer(stats::rdb_status().last_success as i64))
+    }
+}
@@ -0,0 +1,4 @@
+pub mod save;
+pub mod bgsave;
+pub mod lastsave;
+pub mod bgrewriteaof;
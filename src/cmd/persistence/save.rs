@@ -0,0 +1,28 @@
+use std::sync::{Arc, Mutex};
+use crate::db::Db;
+use crate::error::Error;
+use crate::frame::Frame;
+use crate::parse::Parse;
+use crate::persistence::rdb;
+
+/// `SAVE` command, synchronously saves the dataset to the RDB file.
+/// `SAVE` 命令，同步地把数据集保存到 RDB 文件。
+///
+/// # Example
+/// ```text
+/// SAVE
+/// ```
+///
+/// The database is locked for the whole dump, so clients are blocked meanwhile; `BGSAVE` should be preferred.
+/// Fails while a background save is in progress.
+/// 保存期间数据库被锁住，客户端都会被阻塞，应该优先使用 `BGSAVE`。后台保存正在进行时失败。
+pub struct Save;
+
+impl Save {
+    /// Executes the `SAVE` command.
+    /// 执行 `SAVE` 命令。
+    pub fn save_command(db: &mut Arc<Mutex<Db>>, _parse: &mut Parse) -> crate::Result<Frame> {
+        rdb::save(db).map_err(Error::Err)?;
+        Ok(Frame::Simple("OK".to_string()))
+    }
+}
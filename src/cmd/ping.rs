@@ -1,5 +1,6 @@
 use std::sync::{Arc, Mutex};
 use crate::db::Db;
+use crate::error::Error;
 use crate::frame::Frame;
 use crate::parse::Parse;
 
@@ -11,4 +12,14 @@ impl Ping {
     pub fn ping_command(_db: &mut Arc<Mutex<Db>>, _parse: &mut Parse) -> crate::Result<Frame> {
         Ok(Frame::Simple("PONG".to_string()))
     }
-}
\ No newline at end of file
+
+    /// 订阅模式下的 RESP2 连接收到的 PING 回复 `pong` 和可选的消息，格式与订阅消息相同
+    /// PING from a RESP2 connection in subscribed mode replies `pong` and the optional message, shaped like a subscribed message
+    pub fn subscribed_ping_command(parse: &mut Parse) -> crate::Result<Frame> {
+        let message = parse.next_string().unwrap_or_default();
+        if parse.next_string().is_ok() {
+            return Err(Error::WrongArity("ping".to_string()).into());
+        }
+        Ok(Frame::Array(vec![Frame::Bulk("pong".into()), Frame::Bulk(message.into())]))
+    }
+}
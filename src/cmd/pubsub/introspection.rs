@@ -0,0 +1,94 @@
+use std::sync::{Arc, Mutex};
+use crate::client;
+use crate::db::Db;
+use crate::frame::Frame;
+use crate::parse::Parse;
+use crate::error::Error;
+
+/// `PUBSUB` command, inspects the state of the pub/sub system.
+/// `PUBSUB` 命令，查看发布/订阅系统的状态。
+///
+/// # Example
+/// ```text
+/// PUBSUB CHANNELS [pattern]
+/// PUBSUB NUMSUB [channel [channel ...]]
+/// PUBSUB NUMPAT
+/// PUBSUB SHARDCHANNELS [pattern]
+/// PUBSUB SHARDNUMSUB [shardchannel [shardchannel ...]]
+/// PUBSUB LAG [client-id [client-id ...]]
+/// ```
+///
+/// Only channels with at least one subscriber are reported, pattern subscriptions are not counted in `NUMSUB`.
+/// 只返回至少有一个订阅者的频道，`NUMSUB` 不计算模式订阅。
+///
+/// `SHARDCHANNELS` and `SHARDNUMSUB` report the shard channels subscribed with `SSUBSCRIBE`, which are kept apart
+/// from regular channels.
+/// `SHARDCHANNELS` 和 `SHARDNUMSUB` 返回通过 `SSUBSCRIBE` 订阅的分片频道，它们与普通频道分开保存。
+///
+/// `LAG` reports, for the given clients or every client in subscribed mode, the messages not handled yet (`lag`) and
+/// the messages dropped so far (`dropped`). Unknown client IDs are skipped.
+/// `LAG` 返回指定的客户端（或所有处于订阅模式的客户端）尚未处理的消息数（`lag`）和已经丢弃的消息数（`dropped`），
+/// 不存在的客户端 ID 被忽略。
+pub struct PubSub;
+
+impl PubSub {
+    /// Executes the `PUBSUB` command.
+    /// 执行 `PUBSUB` 命令。
+    pub fn pubsub_command(db: &mut Arc<Mutex<Db>>, parse: &mut Parse) -> crate::Result<Frame> {
+        let subcommand = parse.next_string()?.to_lowercase();
+        let mut args = Vec::new();
+        while let Ok(arg) = parse.next_string() {
+            args.push(arg);
+        }
+        if subcommand == "lag" {
+            return Ok(Self::lag(&args));
+        }
+        let db = db.lock().unwrap();
+        match subcommand.as_str() {
+            "channels" | "shardchannels" if args.len() <= 1 => {
+                let pattern = args.first().map(String::as_str);
+                let channels = if subcommand == "channels" { db.channels(pattern) } else { db.shard_channel_names(pattern) };
+                Ok(Frame::Array(channels.into_iter().map(|channel| Frame::Bulk(channel.into_bytes())).collect()))
+            }
+            "numsub" | "shardnumsub" => {
+                let mut counts = Vec::with_capacity(args.len() * 2);
+                for channel in args {
+                    let count = if subcommand == "numsub" { db.numsub(&channel) } else { db.shardnumsub(&channel) };
+                    counts.push(Frame::Bulk(channel.into_bytes()));
+                    counts.push(Frame::Integer(count as i64));
+                }
+                Ok(Frame::Array(counts))
+            }
+            "numpat" if args.is_empty() => Ok(Frame::Integer(db.numpat() as i64)),
+            "channels" | "shardchannels" => {
+                Err(Error::WrongArity(format!("pubsub|{}", subcommand)).into())
+            }
+            _ => Err(Error::Err(format!("unknown subcommand '{}'. Try PUBSUB HELP.", subcommand)).into()),
+        }
+    }
+
+    /// 每个客户端一项：`id`、`lag` 和 `dropped`
+    /// One entry per client: `id`, `lag` and `dropped`
+    fn lag(ids: &[String]) -> Frame {
+        let clients = if ids.is_empty() {
+            client::all().into_iter().filter(|handle| handle.state().pubsub).collect()
+        } else {
+            let mut clients = Vec::with_capacity(ids.len());
+            for id in ids {
+                match id.parse::<u64>() {
+                    Ok(id) => clients.extend(client::lookup(id)),
+                    Err(_) => return Error::NotInteger.into(),
+                }
+            }
+            clients
+        };
+        Frame::Array(clients.into_iter().map(|handle| Frame::Array(vec![
+            Frame::Bulk("id".into()),
+            Frame::Integer(handle.id as i64),
+            Frame::Bulk("lag".into()),
+            Frame::Integer(handle.pubsub_lag() as i64),
+            Frame::Bulk("dropped".into()),
+            Frame::Integer(handle.state().pubsub_dropped as i64),
+        ])).collect())
+    }
+}
@@ -1,3 +1,10 @@
 pub mod publish;
 pub mod subscribe;
-pub mod psubscribe;
\ No newline at end of file
+pub mod psubscribe;
+pub mod unsubscribe;
+pub mod punsubscribe;
+pub mod spublish;
+pub mod ssubscribe;
+pub mod sunsubscribe;
+pub mod subscriber;
+pub mod introspection;
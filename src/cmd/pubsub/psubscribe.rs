@@ -1,12 +1,10 @@
 use std::sync::{Arc, Mutex};
-use crate::connection::ConnectionHandler;
-use crate::db::Db;
+use crate::client::{Client, ClientHandle};
+use crate::cmd::pubsub::subscriber::{receive_messages, Subscriber};
+use crate::db::{Db, Lag, Messages};
 use crate::frame::Frame;
 use crate::parse::Parse;
-use crate::shutdown::Shutdown;
-use tokio::select;
-use tokio::sync::broadcast;
-use tokio_stream::{StreamExt, StreamMap};
+use tokio_stream::StreamMap;
 
 /// `PSubscribe` struct represents the pattern subscription operation.
 /// `PSubscribe` 结构体用于表示模式订阅操作。
@@ -26,97 +24,24 @@ impl PSubscribe {
     /// Executes the `psubscribe` command.
     /// 执行 `psubscribe` 命令。
     ///
-    /// This method will parse the incoming command and perform the pattern subscription accordingly.
-    /// 该方法将解析传入的命令并进行相应的模式订阅操作。
-    /// It will send a confirmation message for each pattern and subscribe to channels matching these patterns in the background.
-    /// 它将为每个模式发送确认消息，并且在后台订阅匹配该模式的频道以接收消息。
+    /// The patterns are added to the connection's subscriptions, which puts the connection in subscribed mode.
+    /// 将模式加入连接的订阅中，使连接进入订阅模式。
+    /// A confirmation is replied for each pattern, carrying the total number of subscriptions.
+    /// 每个模式回复一条确认消息，其中包含订阅总数。
     ///
     /// # Arguments
     /// # 参数
-    /// - `db`: Shared reference to the database for access. / 用于访问数据库的共享引用。
     /// - `parse`: For parsing the command from the client. / 用于解析客户端传来的命令。
-    /// - `connection`: The connection handler for communication with the client. / 用于与客户端进行数据通信的连接句柄。
-    /// - `shutdown`: The shutdown channel for receiving shutdown signals. / 用于接收关闭信号的通道。
+    /// - `subscriber`: The subscriptions of the connection. / 连接的订阅。
+    /// - `client`: The client state, used to record the subscription counts. / 客户端状态，用于记录订阅数量。
     ///
     /// # Return
     /// # 返回
-    /// If the command is parsed successfully, returns the result of the subscription operation.
-    /// 如果命令解析成功，则返回订阅操作的结果。
-    pub async fn psubscribe_command(
-        db: &mut Arc<Mutex<Db>>,
-        parse: &mut Parse,
-        connection: &mut ConnectionHandler,
-        shutdown: &mut Shutdown,
-    ) -> crate::Result<()> {
-        match PSubscribe::parse_command(parse) {
-            Ok(p) => {
-                // Send subscription confirmation message for each pattern
-                // 发送每个模式的订阅确认消息
-                for (index, pattern) in p.patterns.iter().enumerate() {
-                    let confirm_frames = vec![
-                        Frame::Bulk("psubscribe".into()),  // Representing the psubscribe command / 表示 psubscribe 命令
-                        Frame::Bulk(pattern.clone().into()),  // Current pattern / 当前模式
-                        Frame::Integer((index + 1) as i64),  // Sequence number starts from 1 / 序号从 1 开始递增
-                    ];
-                    connection.write_data(Frame::Array(confirm_frames)).await?; // Send confirmation message / 发送确认消息
-                }
-
-                // `StreamMap` is designed for managing multiple subscriptions in asynchronous streams
-                // streamMap 是专门为异步流设计的哈希Map，用于管理多个订阅
-                let mut subscriptions = StreamMap::new();
-
-                // Subscribe to channels matching each pattern
-                // 为每个模式匹配的频道建立订阅
-                for pattern in &p.patterns {
-                    psubscribe_to_pattern(db, pattern, &mut subscriptions).await?; // Subscribe to channels matching the pattern / 为匹配模式的频道订阅
-                }
-
-                // Listen to the subscribed channels and connection messages
-                // 监听订阅的频道和连接的消息
-                loop {
-                    select! {
-                        // Handle received message from a matching channel
-                        // 收到匹配频道的消息并处理
-                        Some((channel, msg)) = subscriptions.next() => {
-                            let msg = vec![
-                               Frame::Bulk("message".into()),  // Message type / 消息类型
-                               Frame::Bulk(channel.into()),  // The channel that sent the message / 发送消息的频道
-                               Frame::Bulk(msg.to_vec()),    // The content of the subscribed message / 订阅的消息内容
-                            ];
-                            // Send the message back to the client
-                            // 将消息发送回客户端
-                            connection.write_data(Frame::Array(msg)).await?; // Send message to the client / 发送消息给客户端
-                        }
-                        // Receive request from the client
-                        // 接收到客户端发来的请求
-                        res = connection.read_data() => {
-                            let frame = match res? {
-                                Some(frame) => frame,
-                                // Receive the subscription mode close signal
-                                // 接收订阅模式关闭信号
-                                None => return Ok(()),
-                            };
-                            return Ok(());  // Exit if the client ends the connection / 如果客户端关闭连接，退出
-                        }
-                        // Handle shutdown signal
-                        // 处理关闭信号
-                        _ = shutdown.recv() => {
-                            return Ok(()); // Close the connection / 关闭连接
-                        }
-                    }
-                }
-            }
-            Err(_) => {
-                // If parsing fails, send error message
-                // 如果解析失败，发送错误信息
-                connection
-                    .write_data(Frame::Error(
-                        "ERR wrong number of arguments for 'psubscribe' command".to_string(),
-                    ))
-                    .await?; // Send error message / 发送错误消息
-                Ok(())
-            }
-        }
+    /// Returns the confirmation messages, or an error if no pattern is given.
+    /// 返回确认消息，没有传入模式时返回错误。
+    pub fn psubscribe_command(parse: &mut Parse, subscriber: &mut Subscriber, client: &Client) -> crate::Result<Vec<Frame>> {
+        let s = PSubscribe::parse_command(parse)?;
+        Ok(subscriber.psubscribe(s.patterns, client))
     }
 
     /// Parse the client's `PSUBSCRIBE` command and return a `PSubscribe` instance.
@@ -145,15 +70,6 @@ impl PSubscribe {
             patterns.push(arg);
         }
 
-        // If no patterns are provided, return an error
-        // 如果没有传入任何模式，则返回错误
-        if patterns.is_empty() {
-            return Err(Box::new(std::io::Error::new(
-                std::io::ErrorKind::Other,
-                "ERR wrong number of arguments for 'psubscribe' command".to_string(),
-            )));
-        }
-
         Ok(PSubscribe { patterns })
     }
 }
@@ -169,41 +85,25 @@ impl PSubscribe {
 /// # 参数
 /// - `db`: Shared reference to the database for access. / 用于访问数据库的共享引用。
 /// - `pattern`: The pattern to match channels. / 用于匹配频道的模式。
+/// - `client`: The subscribing client, which records dropped messages. / 订阅的客户端，记录丢弃的消息。
 /// - `subscriptions`: The `StreamMap` that manages multiple subscriptions. / 用于管理多个订阅的 `StreamMap`。
 ///
 /// # Return
 /// # 返回
-/// Returns the result of the subscription operation.
-/// 返回订阅操作的结果。
-pub async fn psubscribe_to_pattern(
-    db: &mut Arc<Mutex<Db>>,
+/// Returns the lag of the subscription. / 返回该订阅的积压。
+pub fn psubscribe_to_pattern(
+    db: &Arc<Mutex<Db>>,
     pattern: &str,
-    subscriptions: &mut StreamMap<String, crate::db::Messages>,
-) -> crate::Result<()> {
-    // Get the mutable reference to the specified pattern and subscribe to matching channels
-    // 获取指定模式的可变引用并订阅匹配的频道
-    let mut guard = db.lock().unwrap();
-    let sender = guard.psubscribe(pattern);
-
-    let mut receiver = sender.subscribe();
-
-    // Create an asynchronous stream using async_stream to receive messages from the channels
-    // 使用 async_stream 创建一个异步流，用于接收频道的消息
-    let receiver = Box::pin(async_stream::stream! {
-        loop {
-            match receiver.recv().await {
-                Ok(msg) => yield msg,  // Successfully received a message, forward it / 成功接收到消息，进行转发
-                Err(broadcast::error::RecvError::Lagged(_)) => {},  // Skip if the message is slightly delayed / 如果接收稍有延迟，跳过
-                Err(_) => break,  // Exit loop if other errors occur / 如果发生其他错误，退出循环
-            }
-        }
-    });
+    client: &Arc<ClientHandle>,
+    subscriptions: &mut StreamMap<String, Messages>,
+) -> Lag {
+    // Subscribe to the pattern in the database
+    // 在数据库中订阅该模式
+    let subscription = db.lock().unwrap().psubscribe(pattern);
+    let lag = subscription.lag.clone();
 
     // Insert the subscribed pattern and the message receiving stream into the subscriptions map
     // 将订阅的模式和对应的消息接收流插入到 subscriptions 中进行管理
-    subscriptions.insert(pattern.to_string(), receiver);
-
-    // Return success
-    // 返回成功
-    Ok(())
+    subscriptions.insert(pattern.to_string(), receive_messages(subscription, client.clone()));
+    lag
 }
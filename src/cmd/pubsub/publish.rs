@@ -1,5 +1,6 @@
-use std::io::Error;
 use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use crate::config::{get_pubsub_config, LagPolicy};
 use crate::db::Db;
 use crate::frame::Frame;
 use crate::parse::Parse;
@@ -46,18 +47,33 @@ impl Publish {
         db: &mut Arc<Mutex<Db>>,
         parse: &mut Parse,
     ) -> crate::Result<Frame> {
-        match Publish::parse_command(parse) {
-            Ok(p) => {
-                let mut db = db.lock().unwrap();
-                // Publish the message to the specified channel
-                // 向指定频道发布消息
-                let received_count = db.publish(&p.channel, p.message);
-                // Return the number of subscribers who received the message
-                // 返回接收到消息的订阅者数量
-                Ok(Frame::Integer(received_count as i64))
-            }
-            Err(_) => Ok(Frame::Error("ERR wrong number of arguments for 'publish' command".to_string())),
+        let p = Publish::parse_command(parse)?;
+        let mut db = db.lock().unwrap();
+        // Publish the message to the specified channel
+        // 向指定频道发布消息
+        let received_count = db.publish(&p.channel, p.message);
+        // Return the number of subscribers who received the message
+        // 返回接收到消息的订阅者数量
+        Ok(Frame::Integer(received_count as i64))
+    }
+
+    /// Executes the `PUBLISH` command, applying the `block` lag policy.
+    ///
+    /// Under `pubsub.lag_policy = "block"`, while a subscriber of the channel or of a matching pattern has a full
+    /// buffer, the publisher waits for it to catch up for at most `pubsub.block_timeout` milliseconds, then publishes
+    /// anyway and the subscriber drops its oldest message. Other policies publish right away.
+    ///
+    /// 执行 `PUBLISH` 命令，并应用 `block` 策略。
+    /// `pubsub.lag_policy = "block"` 时，如果频道或匹配模式的订阅者缓冲区已满，发布者最多等待 `pubsub.block_timeout`
+    /// 毫秒让它赶上，超时后照常发布，由订阅者丢弃最早的消息。其他策略直接发布。
+    pub async fn publish_blocking_command(
+        db: &mut Arc<Mutex<Db>>,
+        parse: &mut Parse,
+    ) -> crate::Result<Frame> {
+        if let Some(channel) = parse.peek_strings().first() {
+            wait_for_subscribers(db, |db| db.is_publish_blocked(channel)).await;
         }
+        Publish::publish_command(db, parse)
     }
 
     /// Parses the `PUBLISH` command, extracting the channel name and message content.
@@ -68,9 +84,6 @@ impl Publish {
     ///
     /// 返回一个包含解析后的频道名称和消息内容的 `Publish` 结构体实例。
     fn parse_command(parse: &mut Parse) -> crate::Result<Self> {
-        if parse.args_number()? != 2 {
-            return Err(Box::new(Error::new(std::io::ErrorKind::Other, "ERR wrong number of arguments for 'publish' command".to_string())));
-        }
         // Parse the channel name
         // 解析频道名称
         let channel = parse.next_string()?;
@@ -81,3 +94,19 @@ impl Publish {
         Ok(Publish { channel, message })
     }
 }
+
+/// Under the `block` lag policy, waits while `is_blocked` reports a subscriber with a full buffer, for at most
+/// `pubsub.block_timeout` milliseconds. Returns right away under other policies.
+///
+/// `block` 策略下，在 `is_blocked` 报告有订阅者缓冲区已满时等待，最多等待 `pubsub.block_timeout` 毫秒。
+/// 其他策略直接返回。
+pub async fn wait_for_subscribers(db: &Arc<Mutex<Db>>, is_blocked: impl Fn(&Db) -> bool) {
+    let config = get_pubsub_config();
+    if config.lag_policy != LagPolicy::Block {
+        return;
+    }
+    let deadline = Instant::now() + Duration::from_millis(config.block_timeout);
+    while Instant::now() < deadline && is_blocked(&db.lock().unwrap()) {
+        tokio::time::sleep(Duration::from_millis(1)).await;
+    }
+}
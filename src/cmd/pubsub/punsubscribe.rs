@@ -0,0 +1,42 @@
+use crate::client::Client;
+use crate::cmd::pubsub::subscriber::Subscriber;
+use crate::frame::Frame;
+use crate::parse::Parse;
+
+/// `PUnsubscribe` struct represents the pattern unsubscription operation.
+/// `PUnsubscribe` 结构体用于表示模式退订操作。
+///
+/// Without any pattern, the client is unsubscribed from all the patterns it subscribed to.
+/// 没有指定模式时，退订客户端订阅的所有模式。
+///
+/// # Example
+/// ```text
+/// PUNSUBSCRIBE [pattern1 pattern2 ...]
+/// ```
+pub struct PUnsubscribe {
+    patterns: Vec<String>,  // The list of patterns to unsubscribe from. / 退订的模式列表
+}
+
+impl PUnsubscribe {
+    /// Executes the `punsubscribe` command.
+    /// 执行 `punsubscribe` 命令。
+    ///
+    /// A confirmation is replied for each pattern, carrying the number of subscriptions left.
+    /// 每个模式回复一条确认消息，其中包含剩余的订阅数量。
+    /// The connection returns to normal mode once no subscription is left.
+    /// 没有剩余订阅时连接恢复正常模式。
+    pub fn punsubscribe_command(parse: &mut Parse, subscriber: &mut Subscriber, client: &Client) -> crate::Result<Vec<Frame>> {
+        let u = PUnsubscribe::parse_command(parse);
+        Ok(subscriber.punsubscribe(u.patterns, client))
+    }
+
+    /// Parse the client's `PUNSUBSCRIBE` command and return a `PUnsubscribe` instance.
+    /// 解析客户端的 `PUNSUBSCRIBE` 命令并返回一个 `PUnsubscribe` 实例
+    fn parse_command(parse: &mut Parse) -> PUnsubscribe {
+        let mut patterns = Vec::new();
+        while let Ok(arg) = parse.next_string() {
+            patterns.push(arg);
+        }
+        PUnsubscribe { patterns }
+    }
+}
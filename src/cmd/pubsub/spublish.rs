@@ -0,0 +1,60 @@
+use std::sync::{Arc, Mutex};
+use crate::cmd::pubsub::publish::wait_for_subscribers;
+use crate::db::Db;
+use crate::frame::Frame;
+use crate::parse::Parse;
+
+/// Represents the `SPUBLISH` command, which publishes a message to a shard channel.
+///
+/// Only clients subscribed to the shard channel with `SSUBSCRIBE` receive the message, regular channels and
+/// patterns with the same name are not involved. The command returns the number of receivers.
+///
+/// `SPUBLISH` 命令向分片频道发布消息。只有通过 `SSUBSCRIBE` 订阅了该分片频道的客户端会收到消息，
+/// 同名的普通频道和模式不受影响。返回接收到消息的客户端数量。
+///
+/// # Example
+///
+/// ```text
+/// SPUBLISH shardchannel message
+/// ```
+pub struct SPublish {
+    channel: String,  // The shard channel to publish to. / 发布消息的分片频道
+    message: String,  // The message content to be sent. / 要发送的消息内容
+}
+
+impl SPublish {
+    /// Executes the `SPUBLISH` command.
+    ///
+    /// 执行 `SPUBLISH` 命令。
+    pub fn spublish_command(
+        db: &mut Arc<Mutex<Db>>,
+        parse: &mut Parse,
+    ) -> crate::Result<Frame> {
+        let p = SPublish::parse_command(parse)?;
+        let received_count = db.lock().unwrap().spublish(&p.channel, p.message);
+        Ok(Frame::Integer(received_count as i64))
+    }
+
+    /// Executes the `SPUBLISH` command, waiting for slow subscribers under the `block` lag policy like `PUBLISH`.
+    ///
+    /// 执行 `SPUBLISH` 命令，与 `PUBLISH` 相同，`block` 策略下等待落后的订阅者。
+    pub async fn spublish_blocking_command(
+        db: &mut Arc<Mutex<Db>>,
+        parse: &mut Parse,
+    ) -> crate::Result<Frame> {
+        if let Some(channel) = parse.peek_strings().first() {
+            wait_for_subscribers(db, |db| db.is_spublish_blocked(channel)).await;
+        }
+        SPublish::spublish_command(db, parse)
+    }
+
+    /// Parses the `SPUBLISH` command, extracting the shard channel and message content.
+    ///
+    /// 解析 `SPUBLISH` 命令，获取分片频道和消息内容。
+    fn parse_command(parse: &mut Parse) -> crate::Result<Self> {
+        let channel = parse.next_string()?;
+        let message = parse.next_string()?;
+
+        Ok(SPublish { channel, message })
+    }
+}
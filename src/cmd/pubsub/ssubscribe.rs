@@ -0,0 +1,69 @@
+use std::sync::{Arc, Mutex};
+use crate::client::{Client, ClientHandle};
+use crate::cmd::pubsub::subscriber::{receive_messages, Subscriber};
+use crate::db::{Db, Lag, Messages};
+use crate::error::Error;
+use crate::frame::Frame;
+use crate::parse::Parse;
+use crate::slot;
+use tokio_stream::StreamMap;
+
+/// `SSubscribe` struct represents the shard channel subscription operation.
+/// `SSubscribe` 结构体用于表示分片频道的订阅操作。
+///
+/// Shard channels live in their own namespace: they only receive messages sent with `SPUBLISH`, and never
+/// match `PSUBSCRIBE` patterns. All the channels of one command must hash to the same slot, whether or not
+/// cluster mode is enabled.
+/// 分片频道有独立的命名空间：只接收 `SPUBLISH` 发布的消息，也不会匹配 `PSUBSCRIBE` 的模式。
+/// 无论是否启用集群模式，同一条命令中的所有频道都必须属于同一个哈希槽。
+///
+/// # Example
+/// ```text
+/// SSUBSCRIBE shardchannel1 shardchannel2 ...
+/// ```
+pub struct SSubscribe {
+    channels: Vec<String>,  // The list of shard channels to subscribe to. / 订阅的分片频道列表
+}
+
+impl SSubscribe {
+    /// Executes the `ssubscribe` command.
+    /// 执行 `ssubscribe` 命令。
+    ///
+    /// A confirmation is replied for each shard channel, carrying the number of subscribed shard channels.
+    /// 每个分片频道回复一条确认消息，其中包含订阅的分片频道数量。
+    pub fn ssubscribe_command(parse: &mut Parse, subscriber: &mut Subscriber, client: &Client) -> crate::Result<Vec<Frame>> {
+        let s = SSubscribe::parse_command(parse);
+        if !slot::is_single_slot(&s.channels) {
+            return Err(Error::CrossSlot.into());
+        }
+        Ok(subscriber.ssubscribe(s.channels, client))
+    }
+
+    /// Parse the client's `SSUBSCRIBE` command and return a `SSubscribe` instance.
+    /// 解析客户端的 `SSUBSCRIBE` 命令并返回一个 `SSubscribe` 实例
+    fn parse_command(parse: &mut Parse) -> SSubscribe {
+        let mut channels = Vec::new();
+        while let Ok(arg) = parse.next_string() {
+            channels.push(arg);
+        }
+        SSubscribe { channels }
+    }
+}
+
+/// Subscribe to a shard channel and insert the receiving stream into `StreamMap` for management
+/// 订阅分片频道并将接收流插入 `StreamMap` 中进行管理
+///
+/// # Return
+/// # 返回
+/// Returns the lag of the subscription. / 返回该订阅的积压。
+pub fn ssubscribe_to_channel(
+    db: &Arc<Mutex<Db>>,
+    channel: &str,
+    client: &Arc<ClientHandle>,
+    subscriptions: &mut StreamMap<String, Messages>,
+) -> Lag {
+    let subscription = db.lock().unwrap().ssubscribe(channel);
+    let lag = subscription.lag.clone();
+    subscriptions.insert(channel.to_string(), receive_messages(subscription, client.clone()));
+    lag
+}
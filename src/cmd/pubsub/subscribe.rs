@@ -1,12 +1,10 @@
 use std::sync::{Arc, Mutex};
-use crate::connection::ConnectionHandler;
-use crate::db::Db;
+use crate::client::{Client, ClientHandle};
+use crate::cmd::pubsub::subscriber::{receive_messages, Subscriber};
+use crate::db::{Db, Lag, Messages};
 use crate::frame::Frame;
 use crate::parse::Parse;
-use crate::shutdown::Shutdown;
-use tokio::select;
-use tokio::sync::broadcast;
-use tokio_stream::{StreamExt, StreamMap};
+use tokio_stream::StreamMap;
 
 /// `Subscribe` struct represents the subscription operation.
 /// `Subscribe` 结构体用于表示订阅操作。
@@ -27,97 +25,24 @@ impl Subscribe {
     /// Executes the `subscribe` command.
     /// 执行 `subscribe` 命令。
     ///
-    /// This method will parse the incoming command and perform the subscription accordingly.
-    /// 该方法将解析传入的命令并进行相应的订阅操作。
-    /// It will send a confirmation message for each channel and subscribe to these channels in the background.
-    /// 它将为每个频道发送确认消息，并且在后台订阅这些频道以接收消息。
+    /// The channels are added to the connection's subscriptions, which puts the connection in subscribed mode.
+    /// 将频道加入连接的订阅中，使连接进入订阅模式。
+    /// A confirmation is replied for each channel, carrying the total number of subscriptions.
+    /// 每个频道回复一条确认消息，其中包含订阅总数。
     ///
     /// # Arguments
     /// # 参数
-    /// - `db`: Shared reference to the database for access. / 用于访问数据库的共享引用。
     /// - `parse`: For parsing the command from the client. / 用于解析客户端传来的命令。
-    /// - `connection`: The connection handler for communication with the client. / 用于与客户端进行数据通信的连接句柄。
-    /// - `shutdown`: The shutdown channel for receiving shutdown signals. / 用于接收关闭信号的通道。
+    /// - `subscriber`: The subscriptions of the connection. / 连接的订阅。
+    /// - `client`: The client state, used to record the subscription counts. / 客户端状态，用于记录订阅数量。
     ///
     /// # Return
     /// # 返回
-    /// If the command is parsed successfully, returns the result of the subscription operation.
-    /// 如果命令解析成功，则返回订阅操作的结果。
-    pub async fn subscribe_command(
-        db: &mut Arc<Mutex<Db>>,
-        parse: &mut Parse,
-        connection: &mut ConnectionHandler,
-        shutdown: &mut Shutdown,
-    ) -> crate::Result<()> {
-        match Subscribe::parse_command(parse) {
-            Ok(s) => {
-                // Send subscription confirmation message, similar to Redis behavior
-                // 发送订阅确认消息，类似于 Redis 的行为
-                for (index, channel) in s.channels.iter().enumerate() {
-                    let confirm_frames = vec![
-                        Frame::Bulk("subscribe".into()),  // Representing the subscribe command / 表示 subscribe 命令
-                        Frame::Bulk(channel.clone().into()),  // Current channel's name / 当前频道的名称
-                        Frame::Integer((index + 1) as i64),  // Sequence number starts from 1 / 序号从 1 开始递增
-                    ];
-                    connection.write_data(Frame::Array(confirm_frames)).await?; // Send confirmation message / 发送确认消息
-                }
-
-                // `StreamMap` is designed for managing multiple subscriptions in asynchronous streams
-                // streamMap 是专门为异步流设计的哈希Map，用于管理多个订阅
-                let mut subscriptions = StreamMap::new();
-
-                // Subscribe to each channel
-                // 为每个频道建立订阅
-                for channel in &s.channels {
-                    subscribe_to_channel(db, channel, &mut subscriptions).await?; // Subscribe to the channel / 为频道订阅消息
-                }
-
-                // Listen to the subscribed channels and connection messages
-                // 监听订阅的频道和连接的消息
-                loop {
-                    select! {
-                        // Handle received message from a subscribed channel
-                        // 收到某个频道的消息并处理
-                        Some((channel, msg)) = subscriptions.next() => {
-                            let msg = vec![
-                               Frame::Bulk("message".into()),  // Message type / 消息类型
-                               Frame::Bulk(channel.into()),  // The channel that sent the message / 发送消息的频道
-                               Frame::Bulk(msg.to_vec()),    // The content of the subscribed message / 订阅的消息内容
-                            ];
-                            // Send the message back to the client
-                            // 将消息发送回客户端
-                            connection.write_data(Frame::Array(msg)).await?; // Send message to the client / 发送消息给客户端
-                        }
-                        // Receive request from the client
-                        // 接收到客户端发来的请求
-                        res = connection.read_data() => {
-                            let frame = match res? {
-                                Some(frame) => frame,
-                                // Receive the subscription mode close signal
-                                // 接收订阅模式关闭信号
-                                None => return Ok(()),
-                            };
-                            return Ok(());
-                        }
-                        // Handle shutdown signal
-                        // 处理关闭信号
-                        _ = shutdown.recv() => {
-                            return Ok(()); // Close the connection / 关闭连接
-                        }
-                    }
-                }
-            }
-            Err(_) => {
-                // If parsing fails, send error message
-                // 如果解析失败，发送错误信息
-                connection
-                    .write_data(Frame::Error(
-                        "ERR wrong number of arguments for 'subscribe' command".to_string(),
-                    ))
-                    .await?; // Send error message / 发送错误消息
-                Ok(())
-            }
-        }
+    /// Returns the confirmation messages, or an error if no channel is given.
+    /// 返回确认消息，没有传入频道时返回错误。
+    pub fn subscribe_command(parse: &mut Parse, subscriber: &mut Subscriber, client: &Client) -> crate::Result<Vec<Frame>> {
+        let s = Subscribe::parse_command(parse)?;
+        Ok(subscriber.subscribe(s.channels, client))
     }
 
     /// Parse the client's `SUBSCRIBE` command and return a `Subscribe` instance.
@@ -146,15 +71,6 @@ impl Subscribe {
             channels.push(arg);
         }
 
-        // If no channels are provided, return an error
-        // 如果没有传入任何频道，则返回错误
-        if channels.is_empty() {
-            return Err(Box::new(std::io::Error::new(
-                std::io::ErrorKind::Other,
-                "ERR wrong number of arguments for 'subscribe' command".to_string(),
-            )));
-        }
-
         Ok(Subscribe { channels })
     }
 }
@@ -170,41 +86,25 @@ impl Subscribe {
 /// # 参数
 /// - `db`: Shared reference to the database for access. / 用于访问数据库的共享引用。
 /// - `channel`: The channel name. / 频道名称。
+/// - `client`: The subscribing client, which records dropped messages. / 订阅的客户端，记录丢弃的消息。
 /// - `subscriptions`: The `StreamMap` that manages multiple subscriptions. / 用于管理多个订阅的 `StreamMap`。
 ///
 /// # Return
 /// # 返回
-/// Returns the result of the subscription operation.
-/// 返回订阅操作的结果。
-pub async fn subscribe_to_channel(
-    db: &mut Arc<Mutex<Db>>,
+/// Returns the lag of the subscription. / 返回该订阅的积压。
+pub fn subscribe_to_channel(
+    db: &Arc<Mutex<Db>>,
     channel: &str,
-    subscriptions: &mut StreamMap<String, crate::db::Messages>,
-) -> crate::Result<()> {
-    // Get the mutable reference to the specified channel and subscribe to it
-    // 获取指定频道的可变引用并订阅该频道
-    let mut guard = db.lock().unwrap();
-    let sender = guard.subscribe(channel);
-
-    let mut receiver = sender.subscribe();
-
-    // Create an asynchronous stream using async_stream to receive messages from the channel
-    // 使用 async_stream 创建一个异步流，用于接收频道的消息
-    let receiver = Box::pin(async_stream::stream! {
-        loop {
-            match receiver.recv().await {
-                Ok(msg) => yield msg,  // Successfully received a message, forward it / 成功接收到消息，进行转发
-                Err(broadcast::error::RecvError::Lagged(_)) => {},  // Skip if the message is slightly delayed / 如果接收稍有延迟，跳过
-                Err(_) => break,  // Exit loop if other errors occur / 如果发生其他错误，退出循环
-            }
-        }
-    });
+    client: &Arc<ClientHandle>,
+    subscriptions: &mut StreamMap<String, Messages>,
+) -> Lag {
+    // Subscribe to the channel in the database
+    // 在数据库中订阅该频道
+    let subscription = db.lock().unwrap().subscribe(channel);
+    let lag = subscription.lag.clone();
 
     // Insert the subscribed channel and the message receiving stream into the subscriptions map
     // 将订阅的频道和对应的消息接收流插入到 subscriptions 中进行管理
-    subscriptions.insert(channel.to_string(), receiver);
-
-    // Return success
-    // 返回成功
-    Ok(())
+    subscriptions.insert(channel.to_string(), receive_messages(subscription, client.clone()));
+    lag
 }
@@ -0,0 +1,394 @@
+//! 订阅连接的状态：已订阅的频道、模式和分片频道
+//! State of a subscribed connection: the subscribed channels, patterns and shard channels.
+//!
+//! 订阅数量大于 0 时连接处于订阅模式，RESP2 连接在该模式下只能执行订阅相关的命令，
+//! 订阅数量回到 0 时恢复正常模式。
+//! The connection is in subscribed mode while the subscription count is above 0, in which a RESP2 connection
+//! can only run subscription related commands. It returns to normal mode once the count drops back to 0.
+//!
+//! 订阅者跟不上发布速度时按 `pubsub.lag_policy` 处理，丢弃的消息数和当前的积压记录在客户端状态中，
+//! 可以通过 `PUBSUB LAG` 查询。
+//! Subscribers that cannot keep up with publishers are handled according to `pubsub.lag_policy`, the number of
+//! dropped messages and the current lag are recorded in the client state and can be queried with `PUBSUB LAG`.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use log::warn;
+use tokio::select;
+use tokio::sync::broadcast::error::RecvError;
+use tokio_stream::{StreamExt, StreamMap};
+use crate::client::{Client, ClientHandle};
+use crate::config::{get_pubsub_config, LagPolicy};
+use crate::db::{Db, Lag, Messages, Subscription};
+use crate::error::Error;
+use crate::frame::Frame;
+use crate::tracking;
+use crate::cmd::pubsub::psubscribe::psubscribe_to_pattern;
+use crate::cmd::pubsub::ssubscribe::ssubscribe_to_channel;
+use crate::cmd::pubsub::subscribe::subscribe_to_channel;
+
+/// 订阅模式下 RESP2 连接可以执行的命令
+/// Commands a RESP2 connection can run in subscribed mode
+const ALLOWED_COMMANDS: [&str; 9] = [
+    "subscribe", "psubscribe", "ssubscribe", "unsubscribe", "punsubscribe", "sunsubscribe", "ping", "quit", "reset",
+];
+
+/// 订阅的种类
+/// Kind of subscription
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    /// 普通频道
+    /// Regular channels
+    Channel,
+    /// 通配符模式
+    /// Glob patterns
+    Pattern,
+    /// 分片频道
+    /// Shard channels
+    Shard,
+}
+
+impl Kind {
+    /// 订阅确认的类型
+    /// Kind of a subscription confirmation
+    fn subscribe_reply(self) -> &'static str {
+        match self {
+            Kind::Channel => "subscribe",
+            Kind::Pattern => "psubscribe",
+            Kind::Shard => "ssubscribe",
+        }
+    }
+
+    /// 退订确认的类型
+    /// Kind of an unsubscription confirmation
+    fn unsubscribe_reply(self) -> &'static str {
+        match self {
+            Kind::Channel => "unsubscribe",
+            Kind::Pattern => "punsubscribe",
+            Kind::Shard => "sunsubscribe",
+        }
+    }
+}
+
+/// 同一种类的订阅：消息流和积压
+/// Subscriptions of one kind: their message streams and lags
+#[derive(Default)]
+struct Subscriptions {
+    streams: StreamMap<String, Messages>,
+    lags: HashMap<String, Lag>,
+}
+
+impl Subscriptions {
+    fn len(&self) -> usize {
+        self.streams.len()
+    }
+
+    fn names(&self) -> Vec<String> {
+        self.streams.keys().cloned().collect()
+    }
+
+    fn remove(&mut self, name: &str) {
+        self.streams.remove(name);
+        self.lags.remove(name);
+    }
+
+    fn clear(&mut self) {
+        self.streams.clear();
+        self.lags.clear();
+    }
+}
+
+/// 连接订阅的频道、模式和分片频道，以及它们的消息流
+/// The channels, patterns and shard channels subscribed by a connection, with their message streams.
+///
+/// 退订或连接关闭时释放数据库中不再有订阅者的频道和模式。
+/// Channels and patterns left without subscribers are released in the database on unsubscribe or when the connection closes.
+///
+/// 与 Redis 一致，分片频道的确认中的数量只计算分片频道，其他确认中的数量只计算频道和模式。
+/// Like Redis, the count in shard channel confirmations only covers shard channels, and the count in other
+/// confirmations only covers channels and patterns.
+pub struct Subscriber {
+    /// 保存频道和模式的数据库
+    /// The database holding the channels and patterns
+    db: Arc<Mutex<Db>>,
+    /// 订阅的频道
+    /// Subscribed channels
+    channels: Subscriptions,
+    /// 订阅的模式
+    /// Subscribed patterns
+    patterns: Subscriptions,
+    /// 订阅的分片频道
+    /// Subscribed shard channels
+    shard_channels: Subscriptions,
+}
+
+impl Subscriber {
+    pub fn new(db: Arc<Mutex<Db>>) -> Self {
+        Subscriber {
+            db,
+            channels: Subscriptions::default(),
+            patterns: Subscriptions::default(),
+            shard_channels: Subscriptions::default(),
+        }
+    }
+
+    /// 连接是否处于订阅模式
+    /// Whether the connection is in subscribed mode
+    pub fn is_active(&self) -> bool {
+        self.channels.len() + self.patterns.len() + self.shard_channels.len() > 0
+    }
+
+    /// 订阅模式下 RESP2 连接是否可以执行该命令
+    /// Whether a RESP2 connection can run the command in subscribed mode
+    pub fn allows(command: &str) -> bool {
+        ALLOWED_COMMANDS.contains(&command)
+    }
+
+    /// 订阅模式下拒绝其他命令的错误
+    /// The error rejecting other commands in subscribed mode
+    pub fn rejected(command: &str) -> Error {
+        Error::Err(format!(
+            "Can't execute '{}': only (P|S)SUBSCRIBE / (P|S)UNSUBSCRIBE / PING / QUIT / RESET are allowed in this context",
+            command
+        ))
+    }
+
+    /// 订阅频道，每个频道回复一条确认，已订阅的频道不会重复订阅
+    /// Subscribe to channels, one confirmation is replied per channel, already subscribed channels are not subscribed twice
+    pub fn subscribe(&mut self, channels: Vec<String>, client: &Client) -> Vec<Frame> {
+        self.add(Kind::Channel, channels, client)
+    }
+
+    /// 订阅模式，每个模式回复一条确认，已订阅的模式不会重复订阅
+    /// Subscribe to patterns, one confirmation is replied per pattern, already subscribed patterns are not subscribed twice
+    pub fn psubscribe(&mut self, patterns: Vec<String>, client: &Client) -> Vec<Frame> {
+        self.add(Kind::Pattern, patterns, client)
+    }
+
+    /// 订阅分片频道，每个频道回复一条确认，已订阅的频道不会重复订阅
+    /// Subscribe to shard channels, one confirmation is replied per channel, already subscribed channels are not subscribed twice
+    pub fn ssubscribe(&mut self, channels: Vec<String>, client: &Client) -> Vec<Frame> {
+        self.add(Kind::Shard, channels, client)
+    }
+
+    /// 退订频道，没有指定频道时退订所有频道
+    /// Unsubscribe from channels, or from all channels if none is given
+    pub fn unsubscribe(&mut self, channels: Vec<String>, client: &Client) -> Vec<Frame> {
+        self.remove(Kind::Channel, channels, client)
+    }
+
+    /// 退订模式，没有指定模式时退订所有模式
+    /// Unsubscribe from patterns, or from all patterns if none is given
+    pub fn punsubscribe(&mut self, patterns: Vec<String>, client: &Client) -> Vec<Frame> {
+        self.remove(Kind::Pattern, patterns, client)
+    }
+
+    /// 退订分片频道，没有指定频道时退订所有分片频道
+    /// Unsubscribe from shard channels, or from all shard channels if none is given
+    pub fn sunsubscribe(&mut self, channels: Vec<String>, client: &Client) -> Vec<Frame> {
+        self.remove(Kind::Shard, channels, client)
+    }
+
+    /// 不回复地退订所有频道和模式，用于 `RESET`
+    /// Unsubscribe from all channels and patterns without replying, used by `RESET`
+    pub fn clear(&mut self, client: &Client) {
+        self.release_all();
+        self.sync(client);
+    }
+
+    /// 等待下一条订阅消息，按连接的协议版本 `resp` 编码，没有任何订阅时返回 None
+    /// Wait for the next subscribed message, shaped for the connection's protocol version `resp`,
+    /// None is returned if nothing is subscribed
+    pub async fn next_message(&mut self, resp: u8) -> Option<Frame> {
+        let items = select! {
+            Some((_, msg)) = self.channels.streams.next() => vec![
+                Frame::Bulk("message".into()),  // Message type / 消息类型
+                Frame::Bulk(msg.channel.into()),  // The channel that sent the message / 发送消息的频道
+                Frame::Bulk(msg.payload.to_vec()),  // The content of the subscribed message / 订阅的消息内容
+            ],
+            Some((pattern, msg)) = self.patterns.streams.next() => vec![
+                Frame::Bulk("pmessage".into()),  // Message type / 消息类型
+                Frame::Bulk(pattern.into()),  // The pattern matching the channel / 匹配频道的模式
+                Frame::Bulk(msg.channel.into()),  // The channel that sent the message / 发送消息的频道
+                Frame::Bulk(msg.payload.to_vec()),  // The content of the subscribed message / 订阅的消息内容
+            ],
+            Some((_, msg)) = self.shard_channels.streams.next() => vec![
+                Frame::Bulk("smessage".into()),  // Message type / 消息类型
+                Frame::Bulk(msg.channel.into()),  // The shard channel that sent the message / 发送消息的分片频道
+                Frame::Bulk(msg.payload.to_vec()),  // The content of the subscribed message / 订阅的消息内容
+            ],
+            else => return None,
+        };
+        Some(Self::frame(resp, items))
+    }
+
+    /// 某一种类的订阅
+    /// Subscriptions of one kind
+    fn subscriptions(&mut self, kind: Kind) -> &mut Subscriptions {
+        match kind {
+            Kind::Channel => &mut self.channels,
+            Kind::Pattern => &mut self.patterns,
+            Kind::Shard => &mut self.shard_channels,
+        }
+    }
+
+    /// 订阅频道、模式或分片频道，每项回复一条确认
+    /// Subscribe to channels, patterns or shard channels, one confirmation is replied per entry
+    fn add(&mut self, kind: Kind, names: Vec<String>, client: &Client) -> Vec<Frame> {
+        let mut replies = Vec::with_capacity(names.len());
+        for name in names {
+            let db = self.db.clone();
+            let subscriptions = self.subscriptions(kind);
+            if !subscriptions.streams.contains_key(&name) {
+                let lag = match kind {
+                    Kind::Channel => subscribe_to_channel(&db, &name, &client.handle, &mut subscriptions.streams),
+                    Kind::Pattern => psubscribe_to_pattern(&db, &name, &client.handle, &mut subscriptions.streams),
+                    Kind::Shard => ssubscribe_to_channel(&db, &name, &client.handle, &mut subscriptions.streams),
+                };
+                subscriptions.lags.insert(name.clone(), lag);
+            }
+            replies.push(self.reply(client, kind, kind.subscribe_reply(), Some(name)));
+        }
+        self.sync(client);
+        replies
+    }
+
+    /// 退订频道、模式或分片频道，每项回复一条确认，其中的数量是移除该项之后的订阅总数，
+    /// 没有任何可以退订的项时回复一条名称为空的确认
+    /// Unsubscribe from channels, patterns or shard channels, one confirmation is replied per entry with the total count
+    /// after removing it, or a single confirmation with an empty name if there is nothing to unsubscribe from
+    fn remove(&mut self, kind: Kind, names: Vec<String>, client: &Client) -> Vec<Frame> {
+        let names: Vec<String> = if names.is_empty() { self.subscriptions(kind).names() } else { names };
+        if names.is_empty() {
+            return vec![self.reply(client, kind, kind.unsubscribe_reply(), None)];
+        }
+        let mut replies = Vec::with_capacity(names.len());
+        for name in names {
+            // 先移除消息流，使订阅者数量减少，再释放
+            // Remove the message stream first so the subscriber count drops, then release
+            self.subscriptions(kind).remove(&name);
+            Self::release(&mut self.db.lock().unwrap(), kind, &name);
+            replies.push(self.reply(client, kind, kind.unsubscribe_reply(), Some(name)));
+        }
+        self.sync(client);
+        replies
+    }
+
+    /// 退订所有频道、模式和分片频道，并释放不再有订阅者的频道和模式
+    /// Unsubscribe from all channels, patterns and shard channels, releasing those left without subscribers
+    fn release_all(&mut self) {
+        let mut released = Vec::new();
+        for kind in [Kind::Channel, Kind::Pattern, Kind::Shard] {
+            let subscriptions = self.subscriptions(kind);
+            released.extend(subscriptions.names().into_iter().map(|name| (kind, name)));
+            subscriptions.clear();
+        }
+        let mut db = self.db.lock().unwrap();
+        released.iter().for_each(|(kind, name)| Self::release(&mut db, *kind, name));
+    }
+
+    /// 在数据库中释放没有订阅者的频道、模式或分片频道
+    /// Release a channel, pattern or shard channel without subscribers in the database
+    fn release(db: &mut Db, kind: Kind, name: &str) {
+        match kind {
+            Kind::Channel => db.release_channel(name),
+            Kind::Pattern => db.release_pattern(name),
+            Kind::Shard => db.release_shard_channel(name),
+        }
+    }
+
+    /// 订阅和退订的回复：类型、名称和当前的订阅数量
+    /// Reply to a subscription change: the kind, the name and the current count of subscriptions
+    fn reply(&self, client: &Client, kind: Kind, reply: &str, name: Option<String>) -> Frame {
+        let count = match kind {
+            Kind::Shard => self.shard_channels.len(),
+            Kind::Channel | Kind::Pattern => self.channels.len() + self.patterns.len(),
+        };
+        Self::frame(client.handle.state().resp, vec![
+            Frame::Bulk(reply.into()),
+            name.map_or(Frame::Null, |name| Frame::Bulk(name.into())),
+            Frame::Integer(count as i64),
+        ])
+    }
+
+    /// RESP3 连接使用推送消息，RESP2 使用数组
+    /// Push messages are used for RESP3 connections and arrays for RESP2
+    fn frame(resp: u8, items: Vec<Frame>) -> Frame {
+        if resp > 2 {
+            Frame::Push(items)
+        } else {
+            Frame::Array(items)
+        }
+    }
+
+    /// 同步客户端状态中的订阅数量和积压
+    /// Sync the subscription counts and lags in the client state
+    fn sync(&self, client: &Client) {
+        let (sub, psub, ssub) = (self.channels.len(), self.patterns.len(), self.shard_channels.len());
+        let invalidate_subscribed = self.channels.streams.contains_key(tracking::INVALIDATE_CHANNEL);
+        let lags = [&self.channels, &self.patterns, &self.shard_channels]
+            .iter()
+            .flat_map(|subscriptions| subscriptions.lags.values())
+            .cloned()
+            .collect();
+        client.handle.update(|state| {
+            state.pubsub = sub + psub + ssub > 0;
+            state.sub = sub;
+            state.psub = psub;
+            state.ssub = ssub;
+            state.invalidate_subscribed = invalidate_subscribed;
+            state.pubsub_lags = lags;
+        });
+    }
+}
+
+impl Drop for Subscriber {
+    /// 连接关闭时释放它订阅的频道和模式
+    /// Release the subscribed channels and patterns when the connection closes.
+    fn drop(&mut self) {
+        self.release_all();
+    }
+}
+
+impl fmt::Debug for Subscriber {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Subscriber")
+            .field("channels", &self.channels.names())
+            .field("patterns", &self.patterns.names())
+            .field("shard_channels", &self.shard_channels.names())
+            .finish()
+    }
+}
+
+/// 把订阅的接收端转换为消息流，订阅者落后、缓冲区中的消息被覆盖时按 `pubsub.lag_policy` 处理：
+/// 记录丢弃的消息数后继续接收，或者断开客户端
+/// Turn the receiver of a subscription into a message stream. When the subscriber falls behind and buffered messages
+/// are overwritten, `pubsub.lag_policy` decides whether to record the dropped messages and keep receiving, or to
+/// disconnect the client.
+///
+/// `block` 策略由发布者等待，等待超时后的丢弃与 `drop-oldest` 相同。
+/// The `block` policy is applied by the publisher waiting, messages dropped after it times out are handled like `drop-oldest`.
+pub fn receive_messages(subscription: Subscription, client: Arc<ClientHandle>) -> Messages {
+    let Subscription { mut receiver, lag } = subscription;
+    Box::pin(async_stream::stream! {
+        loop {
+            match receiver.recv().await {
+                Ok(msg) => {
+                    lag.advance(1);
+                    yield msg;  // Successfully received a message, forward it / 成功接收到消息，进行转发
+                }
+                Err(RecvError::Lagged(dropped)) => {
+                    lag.advance(dropped);
+                    client.update(|state| state.pubsub_dropped += dropped);
+                    if get_pubsub_config().lag_policy == LagPolicy::Disconnect {
+                        warn!("客户端 {} 的订阅落后 {} 条消息，断开连接", client.id, dropped);  // Subscriber fell behind, disconnecting
+                        client.kill();
+                        break;
+                    }
+                }
+                Err(RecvError::Closed) => break,  // The channel was released / 频道已被释放
+            }
+        }
+    })
+}
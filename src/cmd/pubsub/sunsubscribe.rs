@@ -0,0 +1,46 @@
+use crate::client::Client;
+use crate::cmd::pubsub::subscriber::Subscriber;
+use crate::error::Error;
+use crate::frame::Frame;
+use crate::parse::Parse;
+use crate::slot;
+
+/// `SUnsubscribe` struct represents the shard channel unsubscription operation.
+/// `SUnsubscribe` 结构体用于表示分片频道的退订操作。
+///
+/// Without any channel, the client is unsubscribed from all the shard channels it subscribed to.
+/// The given channels must hash to the same slot, like for `SSUBSCRIBE`.
+/// 没有指定频道时，退订客户端订阅的所有分片频道。与 `SSUBSCRIBE` 相同，指定的频道必须属于同一个哈希槽。
+///
+/// # Example
+/// ```text
+/// SUNSUBSCRIBE [shardchannel1 shardchannel2 ...]
+/// ```
+pub struct SUnsubscribe {
+    channels: Vec<String>,  // The list of shard channels to unsubscribe from. / 退订的分片频道列表
+}
+
+impl SUnsubscribe {
+    /// Executes the `sunsubscribe` command.
+    /// 执行 `sunsubscribe` 命令。
+    ///
+    /// A confirmation is replied for each shard channel, carrying the number of shard channels left.
+    /// 每个分片频道回复一条确认消息，其中包含剩余的分片频道数量。
+    pub fn sunsubscribe_command(parse: &mut Parse, subscriber: &mut Subscriber, client: &Client) -> crate::Result<Vec<Frame>> {
+        let u = SUnsubscribe::parse_command(parse);
+        if !slot::is_single_slot(&u.channels) {
+            return Err(Error::CrossSlot.into());
+        }
+        Ok(subscriber.sunsubscribe(u.channels, client))
+    }
+
+    /// Parse the client's `SUNSUBSCRIBE` command and return a `SUnsubscribe` instance.
+    /// 解析客户端的 `SUNSUBSCRIBE` 命令并返回一个 `SUnsubscribe` 实例
+    fn parse_command(parse: &mut Parse) -> SUnsubscribe {
+        let mut channels = Vec::new();
+        while let Ok(arg) = parse.next_string() {
+            channels.push(arg);
+        }
+        SUnsubscribe { channels }
+    }
+}
@@ -0,0 +1,42 @@
+use crate::client::Client;
+use crate::cmd::pubsub::subscriber::Subscriber;
+use crate::frame::Frame;
+use crate::parse::Parse;
+
+/// `Unsubscribe` struct represents the unsubscription operation.
+/// `Unsubscribe` 结构体用于表示退订操作。
+///
+/// Without any channel, the client is unsubscribed from all the channels it subscribed to.
+/// 没有指定频道时，退订客户端订阅的所有频道。
+///
+/// # Example
+/// ```text
+/// UNSUBSCRIBE [channel1 channel2 ...]
+/// ```
+pub struct Unsubscribe {
+    channels: Vec<String>,  // The list of channels to unsubscribe from. / 退订的频道列表
+}
+
+impl Unsubscribe {
+    /// Executes the `unsubscribe` command.
+    /// 执行 `unsubscribe` 命令。
+    ///
+    /// A confirmation is replied for each channel, carrying the number of subscriptions left.
+    /// 每个频道回复一条确认消息，其中包含剩余的订阅数量。
+    /// The connection returns to normal mode once no subscription is left.
+    /// 没有剩余订阅时连接恢复正常模式。
+    pub fn unsubscribe_command(parse: &mut Parse, subscriber: &mut Subscriber, client: &Client) -> crate::Result<Vec<Frame>> {
+        let u = Unsubscribe::parse_command(parse);
+        Ok(subscriber.unsubscribe(u.channels, client))
+    }
+
+    /// Parse the client's `UNSUBSCRIBE` command and return a `Unsubscribe` instance.
+    /// 解析客户端的 `UNSUBSCRIBE` 命令并返回一个 `Unsubscribe` 实例
+    fn parse_command(parse: &mut Parse) -> Unsubscribe {
+        let mut channels = Vec::new();
+        while let Ok(arg) = parse.next_string() {
+            channels.push(arg);
+        }
+        Unsubscribe { channels }
+    }
+}
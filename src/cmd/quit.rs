@@ -0,0 +1,18 @@
+use crate::frame::Frame;
+
+/// `QUIT` command, asks the server to close the connection after replying.
+/// `QUIT` 命令，回复之后由服务端关闭连接。
+///
+/// # Example
+/// ```text
+/// QUIT
+/// ```
+pub struct Quit;
+
+impl Quit {
+    /// Executes the `QUIT` command, the caller closes the connection once the reply is sent.
+    /// 执行 `QUIT` 命令，调用方发送回复后关闭连接。
+    pub fn quit_command() -> crate::Result<Frame> {
+        Ok(Frame::Simple("OK".to_string()))
+    }
+}
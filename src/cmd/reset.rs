@@ -0,0 +1,36 @@
+use crate::client::{Client, ReplyMode};
+use crate::frame::Frame;
+use crate::{acl, tracking};
+
+/// `RESET` command, resets the connection to the state of a new connection.
+/// `RESET` 命令，将连接重置为新连接的状态。
+///
+/// # Example
+/// ```text
+/// RESET
+/// ```
+///
+/// The subscriptions are dropped by the caller, this command resets the rest of the client state:
+/// the selected database, protocol version, name, reply mode, tracking, `CLIENT NO-EVICT` and the
+/// authenticated user.
+/// 订阅由调用方清除，该命令重置其余的客户端状态：选择的数据库、协议版本、名称、回复模式、
+/// 客户端缓存追踪、`CLIENT NO-EVICT` 和认证的用户。
+pub struct Reset;
+
+impl Reset {
+    /// Executes the `RESET` command.
+    /// 执行 `RESET` 命令。
+    pub fn reset_command(client: &mut Client) -> crate::Result<Frame> {
+        client.reply = ReplyMode::On;
+        tracking::disable(client.id());
+        client.handle.update(|state| {
+            state.db = 0;
+            state.resp = 2;
+            state.name = None;
+            state.no_evict = false;
+            state.user = acl::DEFAULT_USER.to_string();
+            state.authenticated = acl::default_user_needs_no_auth();
+        });
+        Ok(Frame::Simple("RESET".to_string()))
+    }
+}
@@ -3,8 +3,8 @@ use std::sync::Mutex;
 use crate::connection::ConnectionHandler;
 use crate::db::{Db, DbType};
 use crate::frame::Frame;
+use crate::notify;
 use crate::parse::Parse;
-use crate::persistence::aof::propagate_aof;
 
 /// `Append` command for string type.
 /// `Append` 命令用于字符串类型。
@@ -33,39 +33,32 @@ impl Append {
         db: &mut Arc<Mutex<Db>>,
         parse: &mut Parse,
     ) -> crate::Result<Frame> {
-        match Append::parse_command(parse) {
-            Ok(append) => {
-                let mut db = db.lock().unwrap();
-                // Get the current value of the key
-                // 获取键当前的值
-                let current_value = db.get(&append.key);
+        let append = Append::parse_command(parse)?;
+        let mut db = db.lock().unwrap();
+        // Get the current value of the key
+        // 获取键当前的值
+        let current_value = db.get(&append.key);
 
-                let new_value = match current_value {
-                    // If the key exists, append the new value
-                    // 如果键存在，追加新的值
-                    Some(DbType::String(existing_value)) => {
-                        format!("{}{}", existing_value, append.value)
-                    },
-                    // If the key does not exist, set the new value
-                    // 如果键不存在，设置新的值
-                    _ => append.value.clone(),
-                };
+        let new_value = match current_value {
+            // If the key exists, append the new value
+            // 如果键存在，追加新的值
+            Some(DbType::String(existing_value)) => {
+                format!("{}{}", existing_value, append.value)
+            },
+            // If the key does not exist, set the new value
+            // 如果键不存在，设置新的值
+            _ => append.value.clone(),
+        };
 
-                // Set or update the value of the key
-                // 设置或更新键的值
-                db.set(&append.key, DbType::String(new_value.clone()), None);
+        // Set or update the value of the key
+        // 设置或更新键的值
+        db.set(&append.key, DbType::String(new_value.clone()), None);
+        db.notify_keyspace_event(notify::STRING, "append", &append.key);
 
 
-                // Return the length of the new string
-                // 返回追加后的新值的长度
-                Ok(Frame::Integer(new_value.len() as i64))
-            }
-            Err(_) => {
-                // If command parsing fails, return an error message
-                // 如果命令解析失败，返回错误信息
-                Ok(Frame::Error("ERR wrong number of arguments for 'append' command".to_string()))
-            }
-        }
+        // Return the length of the new string
+        // 返回追加后的新值的长度
+        Ok(Frame::Integer(new_value.len() as i64))
     }
 
     /// Parses the command and retrieves the parameters.
@@ -2,7 +2,9 @@ use std::sync::Arc;
 use std::sync::Mutex;
 use crate::db::{Db, DbType};
 use crate::frame::Frame;
+use crate::notify;
 use crate::parse::Parse;
+use crate::error::Error;
 
 /// `Decr` command for string type.
 /// `Decr` 命令用于字符串类型。
@@ -30,41 +32,35 @@ impl Decr {
         db: &mut Arc<Mutex<Db>>,
         parse: &mut Parse
     ) -> crate::Result<Frame> {
-        match Decr::parse_command(parse) {
-            Ok(decr) => {
-                let mut db = db.lock().unwrap();
-                // Get the current value of the key
-                // 获取键的当前值
-                match db.get(&decr.key) {
-                    // If the key exists and its value is a number, decrement it
-                    // 如果键存在且值为数字，进行减少
-                    Some(DbType::String(value)) => {
-                        match value.parse::<i64>() {  // Allow negative values
-                            Ok(current_value) => {
-                                let new_value = current_value - 1; // Decrease by 1 / 减少 1
-                                db.set(&decr.key, DbType::String(new_value.to_string()), None);
-                                Ok(Frame::Integer(new_value))  // Return the new value / 返回新值
-                            }
-                            // If the value is not a number, return an error
-                            // 键的值不是数字，返回错误
-                            Err(_) => {
-                                Ok(Frame::Error("ERR value is not an integer or out of range".to_string()))
-                            }
-                        }
-                    }
-                    // If the key doesn't exist, initialize it as -1 and then decrease
-                    // 如果键不存在，初始化为 -1，然后减少
-                    _ => {
-                        let new_value = -1;  // Initialize with -1 / 初始化为 -1
+        let decr = Decr::parse_command(parse)?;
+        let mut db = db.lock().unwrap();
+        // Get the current value of the key
+        // 获取键的当前值
+        match db.get(&decr.key) {
+            // If the key exists and its value is a number, decrement it
+            // 如果键存在且值为数字，进行减少
+            Some(DbType::String(value)) => {
+                match value.parse::<i64>() {  // Allow negative values
+                    Ok(current_value) => {
+                        let new_value = current_value - 1; // Decrease by 1 / 减少 1
                         db.set(&decr.key, DbType::String(new_value.to_string()), None);
+                        db.notify_keyspace_event(notify::STRING, "decrby", &decr.key);
                         Ok(Frame::Integer(new_value))  // Return the new value / 返回新值
                     }
+                    // If the value is not a number, return an error
+                    // 键的值不是数字，返回错误
+                    Err(_) => {
+                        Ok(Error::NotInteger.into())
+                    }
                 }
             }
-            Err(_) => {
-                // If command parsing fails, return an error message
-                // 如果命令解析失败，返回错误信息
-                Ok(Frame::Error("ERR wrong number of arguments for 'decr' command".to_string()))
+            // If the key doesn't exist, initialize it as -1 and then decrease
+            // 如果键不存在，初始化为 -1，然后减少
+            _ => {
+                let new_value = -1;  // Initialize with -1 / 初始化为 -1
+                db.set(&decr.key, DbType::String(new_value.to_string()), None);
+                db.notify_keyspace_event(notify::STRING, "decrby", &decr.key);
+                Ok(Frame::Integer(new_value))  // Return the new value / 返回新值
             }
         }
     }
@@ -2,7 +2,9 @@ use std::sync::Arc;
 use std::sync::Mutex;
 use crate::db::{Db, DbType};
 use crate::frame::Frame;
+use crate::notify;
 use crate::parse::Parse;
+use crate::error::Error;
 
 /// `DecrBy` command for string type.
 /// `DecrBy` 命令用于字符串类型。
@@ -31,41 +33,35 @@ impl DecrBy {
         db: &mut Arc<Mutex<Db>>,
         parse: &mut Parse
     ) -> crate::Result<Frame> {
-        match DecrBy::parse_command(parse) {
-            Ok(decr) => {
-                let mut db = db.lock().unwrap();
-                // Get the current value of the key
-                // 获取键的当前值
-                match db.get(&decr.key) {
-                    // If the key exists and its value is a number, decrement it by the step
-                    // 如果键存在且值为数字，按步长减少
-                    Some(DbType::String(value)) => {
-                        match value.parse::<i64>() {
-                            Ok(current_value) => {
-                                let new_value = current_value - decr.step;  // Decrease by step / 按步长减少
-                                db.set(&decr.key, DbType::String(new_value.to_string()), None);
-                                Ok(Frame::Integer(new_value))  // Return the new value / 返回新值
-                            }
-                            // If the value is not a number, return an error
-                            // 键的值不是数字，返回错误
-                            Err(_) => {
-                                Ok(Frame::Error("ERR value is not an integer or out of range".to_string()))
-                            }
-                        }
-                    }
-                    // If the key doesn't exist, initialize it with -step and then decrease
-                    // 如果键不存在，初始化为 -step，然后减少
-                    _ => {
-                        let new_value = -decr.step;  // Initialize with -step / 初始化为 -step
+        let decr = DecrBy::parse_command(parse)?;
+        let mut db = db.lock().unwrap();
+        // Get the current value of the key
+        // 获取键的当前值
+        match db.get(&decr.key) {
+            // If the key exists and its value is a number, decrement it by the step
+            // 如果键存在且值为数字，按步长减少
+            Some(DbType::String(value)) => {
+                match value.parse::<i64>() {
+                    Ok(current_value) => {
+                        let new_value = current_value - decr.step;  // Decrease by step / 按步长减少
                         db.set(&decr.key, DbType::String(new_value.to_string()), None);
+                        db.notify_keyspace_event(notify::STRING, "decrby", &decr.key);
                         Ok(Frame::Integer(new_value))  // Return the new value / 返回新值
                     }
+                    // If the value is not a number, return an error
+                    // 键的值不是数字，返回错误
+                    Err(_) => {
+                        Ok(Error::NotInteger.into())
+                    }
                 }
             }
-            Err(_) => {
-                // If command parsing fails, return an error message
-                // 如果命令解析失败，返回错误信息
-                Ok(Frame::Error("ERR wrong number of arguments for 'decrby' command".to_string()))
+            // If the key doesn't exist, initialize it with -step and then decrease
+            // 如果键不存在，初始化为 -step，然后减少
+            _ => {
+                let new_value = -decr.step;  // Initialize with -step / 初始化为 -step
+                db.set(&decr.key, DbType::String(new_value.to_string()), None);
+                db.notify_keyspace_event(notify::STRING, "decrby", &decr.key);
+                Ok(Frame::Integer(new_value))  // Return the new value / 返回新值
             }
         }
     }
@@ -89,7 +85,7 @@ impl DecrBy {
         // If conversion fails, return an error / 若转化失败返回错误
         let step: i64 = match step.parse() {
             Ok(num) => num,
-            Err(_) => return Err(Box::new(std::io::Error::new(std::io::ErrorKind::Other, "ERR value is not an integer or out of range"))),
+            Err(_) => return Err(Error::NotInteger.into()),
         };
 
         Ok(DecrBy { key, step })
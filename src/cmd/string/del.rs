@@ -1,8 +1,8 @@
 use std::sync::{Arc, Mutex};
 use crate::db::Db;
 use crate::frame::Frame;
+use crate::notify;
 use crate::parse::Parse;
-use crate::persistence::aof::propagate_aof;
 
 /// `Del` command for deleting keys.
 /// `Del` 命令用于删除键。
@@ -21,31 +21,22 @@ impl Del {
     /// # Return
     /// Returns the number of keys that were deleted. / 返回成功删除的键的数量。
     pub fn del_command(db: &mut Arc<Mutex<Db>>, parse: &mut Parse) -> crate::Result<Frame> {
-        match Del::parse_command(parse) {
-            Ok(del) => {
-                let mut db = db.lock().unwrap();
-                let mut deleted_count = 0;
-
-                // Iterate through all the keys and attempt to delete them
-                // 遍历所有键，尝试删除它们
-                for key in del.keys {
-                    if db.del(&key) {
-                        // Propagate AOF for each deletion
-                        // 删除后传播到 AOF
-                        Del::propagate_aof("del", &key);
-                        deleted_count += 1;  // Increment the count of deleted keys / 增加删除的键计数
-                    }
-                }
-
-                // Return the number of deleted keys
-                // 返回删除的键的数量
-                Ok(Frame::Integer(deleted_count))
-            }
-            Err(_) => {
-                // Return error if parsing fails / 解析失败时返回错误
-                Ok(Frame::Error("ERR wrong number of arguments for 'del' command".to_string()))
+        let del = Del::parse_command(parse)?;
+        let mut db = db.lock().unwrap();
+        let mut deleted_count = 0;
+
+        // Iterate through all the keys and attempt to delete them
+        // 遍历所有键，尝试删除它们
+        for key in del.keys {
+            if db.del(&key) {
+                db.notify_keyspace_event(notify::GENERIC, "del", &key);
+                deleted_count += 1;  // Increment the count of deleted keys / 增加删除的键计数
             }
         }
+
+        // Return the number of deleted keys
+        // 返回删除的键的数量
+        Ok(Frame::Integer(deleted_count))
     }
 
     /// Parses the `DEL` command and retrieves the keys to delete.
@@ -65,21 +56,6 @@ impl Del {
             keys.push(key);
         }
 
-        // If no keys were provided, return an error
-        // 如果没有传入任何键，返回错误
-        if keys.is_empty() {
-            return Err(Box::new(std::io::Error::new(
-                std::io::ErrorKind::Other,
-                "ERR wrong number of arguments for 'del' command",
-            )));
-        }
-
         Ok(Del { keys })
     }
-
-    /// Propagates the `DEL` command to AOF.
-    /// 将 `DEL` 命令传播到 AOF。
-    fn propagate_aof(command: &str, key: &str) {
-        propagate_aof(command.to_string(), vec![key.to_string()]);
-    }
 }
@@ -3,6 +3,7 @@ use std::sync::Mutex;
 use crate::db::{Db, DbType};
 use crate::frame::Frame;
 use crate::parse::Parse;
+use crate::error::Error;
 
 /// `Get` command for string type.
 /// `Get` 命令用于字符串类型。
@@ -32,25 +33,19 @@ impl Get {
         db: &mut Arc<Mutex<Db>>,
         parse: &mut Parse,
     ) -> crate::Result<Frame> {
-        match Self::parse_command(parse) {
-            Ok(get) => match db.lock().unwrap().get(&get.key) {
-                // If the key exists and its value is a string, return the value
-                // 如果键存在且值为字符串，返回值
-                Some(DbType::String(s)) => {
-                    Ok(Frame::Bulk(s.clone().into_bytes()))  // Return the value as a Bulk Frame / 将值作为 Bulk Frame 返回
-                }
-                // If the key exists but has a wrong type, return an error
-                // 如果键存在但类型错误，返回错误
-                Some(_) => Ok(Frame::Error("WRONGTYPE Operation against a key holding the wrong kind of value".to_string())),
-                // If the key doesn't exist, return null
-                // 如果键不存在，返回 Null
-                None => Ok(Frame::Null),
-            },
-            // If command parsing fails, return an error
-            // 如果命令解析失败，返回错误
-            Err(_) => {
-                Ok(Frame::Error("ERR wrong number of arguments for 'get' command".to_string()))
+        let get = Self::parse_command(parse)?;
+        match db.lock().unwrap().lookup_read(&get.key) {
+            // If the key exists and its value is a string, return the value
+            // 如果键存在且值为字符串，返回值
+            Some(DbType::String(s)) => {
+                Ok(Frame::Bulk(s.clone().into_bytes()))  // Return the value as a Bulk Frame / 将值作为 Bulk Frame 返回
             }
+            // If the key exists but has a wrong type, return an error
+            // 如果键存在但类型错误，返回错误
+            Some(_) => Ok(Error::WrongType.into()),
+            // If the key doesn't exist, return null
+            // 如果键不存在，返回 Null
+            None => Ok(Frame::Null),
         }
     }
 
@@ -2,7 +2,9 @@ use std::sync::Arc;
 use std::sync::Mutex;
 use crate::db::{Db, DbType};
 use crate::frame::Frame;
+use crate::notify;
 use crate::parse::Parse;
+use crate::error::Error;
 
 /// `Incr` command for string type.
 /// `Incr` 命令用于字符串类型。
@@ -35,41 +37,35 @@ impl Incr {
         db: &mut Arc<Mutex<Db>>,
         parse: &mut Parse
     ) -> crate::Result<Frame> {
-        match Incr::parse_command(parse) {
-            Ok(incr) => {
-                let mut db = db.lock().unwrap();
-                // Get the current value of the key
-                // 获取键的当前值
-                match db.get(&incr.key) {
-                    // If the key exists and its value is a number, increase it by the step
-                    // 如果键存在且值为数字，按步长增加
-                    Some(DbType::String(value)) => {
-                        match value.parse::<i64>() {
-                            Ok(current_value) => {
-                                let new_value = current_value + incr.step;
-                                db.set(&incr.key, DbType::String(new_value.to_string()), None);
-                                Ok(Frame::Integer(new_value))
-                            }
-                            // If the value is not a number, return an error
-                            // 如果值不是数字，返回错误
-                            Err(_) => {
-                                Ok(Frame::Error("ERR value is not an integer or out of range".to_string()))
-                            }
-                        }
-                    }
-                    // If the key does not exist, initialize it to the step value and return the new value
-                    // 如果键不存在，将其初始化为步长的值并返回新值
-                    _ => {
-                        let new_value = incr.step;
+        let incr = Incr::parse_command(parse)?;
+        let mut db = db.lock().unwrap();
+        // Get the current value of the key
+        // 获取键的当前值
+        match db.get(&incr.key) {
+            // If the key exists and its value is a number, increase it by the step
+            // 如果键存在且值为数字，按步长增加
+            Some(DbType::String(value)) => {
+                match value.parse::<i64>() {
+                    Ok(current_value) => {
+                        let new_value = current_value + incr.step;
                         db.set(&incr.key, DbType::String(new_value.to_string()), None);
+                        db.notify_keyspace_event(notify::STRING, "incrby", &incr.key);
                         Ok(Frame::Integer(new_value))
                     }
+                    // If the value is not a number, return an error
+                    // 如果值不是数字，返回错误
+                    Err(_) => {
+                        Ok(Error::NotInteger.into())
+                    }
                 }
             }
-            // If the command has an incorrect number of arguments, return an error
-            // 如果命令参数个数不正确，返回错误
-            Err(_) => {
-                Ok(Frame::Error("ERR wrong number of arguments for 'incr' command".to_string()))
+            // If the key does not exist, initialize it to the step value and return the new value
+            // 如果键不存在，将其初始化为步长的值并返回新值
+            _ => {
+                let new_value = incr.step;
+                db.set(&incr.key, DbType::String(new_value.to_string()), None);
+                db.notify_keyspace_event(notify::STRING, "incrby", &incr.key);
+                Ok(Frame::Integer(new_value))
             }
         }
     }
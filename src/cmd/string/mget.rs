@@ -3,6 +3,7 @@ use std::sync::Mutex;
 use crate::db::{Db, DbType};
 use crate::frame::Frame;
 use crate::parse::Parse;
+use crate::error::Error;
 
 /// `Mget` command for string type.
 /// `Mget` 命令用于字符串类型。
@@ -31,43 +32,35 @@ impl Mget {
         db: &mut Arc<Mutex<Db>>,
         parse: &mut Parse
     ) -> crate::Result<Frame> {
-        match Mget::parse_command(parse) {
-            Ok(mget) => {
-                let mut db = db.lock().unwrap();
+        let mget = Mget::parse_command(parse)?;
+        let mut db = db.lock().unwrap();
 
-                let mut result = Vec::new();
-                for key in mget.keys {
-                    // Get the value for each key
-                    // 获取每个键的值
-                    match db.get(&key) {
-                        // If the key exists and its value is a string, return its value
-                        // 如果键存在且值为字符串，返回其值
-                        Some(DbType::String(value)) => {
-                            result.push(Frame::Simple(value.to_string()));
-                        }
-                        // If the key exists but its value is not a string, return an error
-                        // 如果键存在但值不是字符串，返回错误
-                        Some(_) => {
-                            result.push(Frame::Error("WRONGTYPE Operation against a key holding the wrong kind of value".to_string()))
-                        }
-                        // If the key does not exist, return null
-                        // 如果键不存在，返回 null
-                        None => {
-                            result.push(Frame::Null); // The key does not exist or is not a string
-                        }
-                    }
+        let mut result = Vec::new();
+        for key in mget.keys {
+            // Get the value for each key
+            // 获取每个键的值
+            match db.lookup_read(&key) {
+                // If the key exists and its value is a string, return its value
+                // 如果键存在且值为字符串，返回其值
+                Some(DbType::String(value)) => {
+                    result.push(Frame::Simple(value.to_string()));
+                }
+                // If the key exists but its value is not a string, return an error
+                // 如果键存在但值不是字符串，返回错误
+                Some(_) => {
+                    result.push(Error::WrongType.into())
+                }
+                // If the key does not exist, return null
+                // 如果键不存在，返回 null
+                None => {
+                    result.push(Frame::Null); // The key does not exist or is not a string
                 }
-
-                // Return the list of values
-                // 返回包含所有值的列表
-                Ok(Frame::Array(result))
-            }
-            // If the command has an incorrect number of arguments, return an error
-            // 如果命令参数个数不正确，返回错误
-            Err(_) => {
-                Ok(Frame::Error("ERR wrong number of arguments for 'mget' command".to_string()))
             }
         }
+
+        // Return the list of values
+        // 返回包含所有值的列表
+        Ok(Frame::Array(result))
     }
 
     /// Parses the command and retrieves the parameters.
@@ -89,12 +82,6 @@ impl Mget {
             keys.push(key);
         }
 
-        // If no keys are provided, return an error
-        // 如果没有提供任何键，返回错误
-        if keys.is_empty() {
-            return Err(Box::new(std::io::Error::new(std::io::ErrorKind::Other, "ERR wrong number of arguments for 'mget' command")));
-        }
-
         Ok(Mget { keys })
     }
 }
@@ -1,6 +1,7 @@
 pub mod get;
 pub mod set;
 pub mod del;
+pub mod pexpireat;
 pub mod append;
 pub mod strlen;
 pub mod incr;
@@ -1,7 +1,9 @@
 use std::sync::Arc;
 use std::sync::Mutex;
 use crate::db::{Db, DbType};
+use crate::error::Error;
 use crate::frame::Frame;
+use crate::notify;
 use crate::parse::Parse;
 
 /// `Mset` command for string type.
@@ -31,26 +33,19 @@ impl Mset {
         db: &mut Arc<Mutex<Db>>,
         parse: &mut Parse
     ) -> crate::Result<Frame> {
-        match Mset::parse_command(parse) {
-            Ok(mset) => {
-                let mut db = db.lock().unwrap();
+        let mset = Mset::parse_command(parse)?;
+        let mut db = db.lock().unwrap();
 
-                // Iterate over the key-value pairs and set each key's value
-                // 遍历键值对，设置每个键的值
-                for (key, value) in mset.keys_values {
-                    db.set(&key, DbType::String(value), None);
-                }
-
-                // Return success response
-                // 返回成功响应
-                Ok(Frame::Simple("OK".to_string()))
-            }
-            // If the command has an incorrect number of arguments, return an error
-            // 如果命令参数个数不正确，返回错误
-            Err(_) => {
-                Ok(Frame::Error("ERR wrong number of arguments for 'mset' command".to_string()))
-            }
+        // Iterate over the key-value pairs and set each key's value
+        // 遍历键值对，设置每个键的值
+        for (key, value) in mset.keys_values {
+            db.set(&key, DbType::String(value), None);
+            db.notify_keyspace_event(notify::STRING, "set", &key);
         }
+
+        // Return success response
+        // 返回成功响应
+        Ok(Frame::Simple("OK".to_string()))
     }
 
     /// Parses the command and retrieves the key-value pairs.
@@ -66,19 +61,13 @@ impl Mset {
     fn parse_command(parse: &mut Parse) -> crate::Result<Self> {
         let mut keys_values = Vec::new();
 
-        // Parse the key-value pairs from the command
-        // 解析命令中的键值对
+        // Parse the key-value pairs from the command, a key without a value is an error
+        // 解析命令中的键值对，缺少值的键是错误
         while let Ok(key) = parse.next_string() {
-            let value = parse.next_string()?;
+            let value = parse.next_string().map_err(|_| Error::WrongArity("mset".to_string()))?;
             keys_values.push((key, value));
         }
 
-        // If the number of key-value pairs is odd or empty, return an error
-        // 如果键值对的数量为奇数或为空，返回错误
-        if keys_values.is_empty() || keys_values.len() % 2 != 0 {
-            return Err(Box::new(std::io::Error::new(std::io::ErrorKind::Other, "ERR wrong number of arguments for 'mset' command")));
-        }
-
         Ok(Mset { keys_values })
     }
 }
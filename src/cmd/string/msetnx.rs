@@ -1,6 +1,8 @@
 use std::sync::{Arc, Mutex};
 use crate::db::{Db, DbType};
+use crate::error::Error;
 use crate::frame::Frame;
+use crate::notify;
 use crate::parse::Parse;
 
 /// `Msetnx` command for string type.
@@ -33,36 +35,29 @@ impl Msetnx {
         db: &mut Arc<Mutex<Db>>,
         parse: &mut Parse,
     ) -> crate::Result<Frame> {
-        match Msetnx::parse_command(parse) {
-            Ok(msetnx) => {
-                let mut db = db.lock().unwrap();
+        let msetnx = Msetnx::parse_command(parse)?;
+        let mut db = db.lock().unwrap();
 
-                // Check if any of the keys already exist
-                // 检查所有键是否存在
-                for (key, _) in &msetnx.keys_values {
-                    if db.exists(key) {
-                        // If any key exists, return 0 (no operation)
-                        // 如果有任何一个键已经存在，返回 0
-                        return Ok(Frame::Integer(0));
-                    }
-                }
-
-                // If all keys do not exist, set them
-                // 如果所有键都不存在，则设置它们
-                for (key, value) in msetnx.keys_values {
-                    db.set(&key, DbType::String(value), None);
-                }
-
-                // Return 1 to indicate success
-                // 设置成功，返回 1
-                Ok(Frame::Integer(1))
-            }
-            // If the command has incorrect arguments, return an error
-            // 如果命令参数不正确，返回错误
-            Err(_) => {
-                Ok(Frame::Error("ERR wrong number of arguments for 'msetnx' command".to_string()))
+        // Check if any of the keys already exist
+        // 检查所有键是否存在
+        for (key, _) in &msetnx.keys_values {
+            if db.exists(key) {
+                // If any key exists, return 0 (no operation)
+                // 如果有任何一个键已经存在，返回 0
+                return Ok(Frame::Integer(0));
             }
         }
+
+        // If all keys do not exist, set them
+        // 如果所有键都不存在，则设置它们
+        for (key, value) in msetnx.keys_values {
+            db.set(&key, DbType::String(value), None);
+            db.notify_keyspace_event(notify::STRING, "set", &key);
+        }
+
+        // Return 1 to indicate success
+        // 设置成功，返回 1
+        Ok(Frame::Integer(1))
     }
 
     /// Parses the command and retrieves the key-value pairs.
@@ -78,22 +73,13 @@ impl Msetnx {
     fn parse_command(parse: &mut Parse) -> crate::Result<Self> {
         let mut keys_values = Vec::new();
 
-        // Parse the key-value pairs from the command
-        // 解析命令中的键值对
+        // Parse the key-value pairs from the command, a key without a value is an error
+        // 解析命令中的键值对，缺少值的键是错误
         while let Ok(key) = parse.next_string() {
-            let value = parse.next_string()?;
+            let value = parse.next_string().map_err(|_| Error::WrongArity("msetnx".to_string()))?;
             keys_values.push((key, value));
         }
 
-        // If the number of key-value pairs is odd or empty, return an error
-        // 如果键值对的数量为奇数或为空，返回错误
-        if keys_values.is_empty() || keys_values.len() % 2 != 0 {
-            return Err(Box::new(std::io::Error::new(
-                std::io::ErrorKind::InvalidInput,
-                "ERR wrong number of arguments for 'msetnx' command",
-            )));
-        }
-
         Ok(Msetnx { keys_values })
     }
 }
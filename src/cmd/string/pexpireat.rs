@@ -0,0 +1,47 @@
+use std::sync::{Arc, Mutex};
+use crate::db::Db;
+use crate::error::Error;
+use crate::frame::Frame;
+use crate::notify;
+use crate::parse::Parse;
+
+/// `Pexpireat` command for setting the expiration of a key as a Unix time in milliseconds.
+/// `Pexpireat` 命令以 Unix 时间（毫秒）设置键的过期时间。
+///
+/// AOF 重写和传播的过期时间都使用这个命令，加载时键在原来的时间过期。
+/// Expirations written by AOF rewrites and propagation use this command, so keys expire at their original time
+/// when loaded.
+pub struct Pexpireat {
+    key: String,  // The key to expire / 要设置过期时间的键
+    at: u64,      // Expiration Unix time in milliseconds / 过期的 Unix 时间，单位：毫秒
+}
+
+impl Pexpireat {
+    /// Executes the `PEXPIREAT` command.
+    /// 执行 `PEXPIREAT` 命令。
+    ///
+    /// # Arguments
+    /// - `db`: Shared reference to the database for access. / 用于访问数据库的共享引用。
+    /// - `parse`: For parsing the command from the client. / 用于解析客户端传来的命令。
+    ///
+    /// # Return
+    /// - Returns `1` if the expiration was set. / 如果设置了过期时间，返回 1。
+    /// - Returns `0` if the key does not exist. / 如果键不存在，返回 0。
+    pub fn pexpireat_command(db: &mut Arc<Mutex<Db>>, parse: &mut Parse) -> crate::Result<Frame> {
+        let pexpireat = Pexpireat::parse_command(parse)?;
+        let mut db = db.lock().unwrap();
+        if !db.expire_at(&pexpireat.key, pexpireat.at) {
+            return Ok(Frame::Integer(0));
+        }
+        db.notify_keyspace_event(notify::GENERIC, "expire", &pexpireat.key);
+        Ok(Frame::Integer(1))
+    }
+
+    /// Parses the command and retrieves the key and the expiration.
+    /// 解析命令并获取键和过期时间。
+    fn parse_command(parse: &mut Parse) -> crate::Result<Self> {
+        let key = parse.next_string()?;
+        let at = parse.next_string()?.parse().map_err(|_| Error::NotInteger)?;
+        Ok(Pexpireat { key, at })
+    }
+}
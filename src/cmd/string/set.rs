@@ -1,22 +1,24 @@
-use std::io::Error;
+use crate::error::Error;
 use std::sync::Arc;
 use std::sync::Mutex;
-use crate::db::{Db, DbType};
+use crate::db::{now_millis, Db, DbType};
 use crate::frame::Frame;
+use crate::notify;
 use crate::parse::Parse;
-use crate::persistence::aof::propagate_aof;
 
 /// `Set` command for string type.
 /// `Set` 命令用于字符串类型。
 ///
-/// Sets the value of a key. It supports expiration time with `EX` (in seconds) or `PX` (in milliseconds).
-/// 支持设置键的值。它支持过期时间，使用 `EX`（秒）或 `PX`（毫秒）表示过期时间。
+/// Sets the value of a key. It supports expiration time with `EX` (in seconds) or `PX` (in milliseconds), or as a
+/// Unix time with `EXAT` (in seconds) or `PXAT` (in milliseconds).
+/// 支持设置键的值。它支持过期时间，使用 `EX`（秒）或 `PX`（毫秒）表示过期时间，
+/// 或者使用 `EXAT`（秒）或 `PXAT`（毫秒）表示过期的 Unix 时间。
 /// It also supports `NX` (set only if key does not exist) and `XX` (set only if key exists).
 /// 它还支持 `NX`（只有在键不存在时设置）和 `XX`（只有在键存在时设置）。
 pub struct Set {
     key: String,           // The key to set / 要设置的键
     value: String,         // The value to set / 要设置的值
-    expiration: Option<u64>, // Expiration time in milliseconds / 过期时间，单位：毫秒
+    expiration: Option<u64>, // Expiration as a Unix time in milliseconds / 过期的 Unix 时间，单位：毫秒
     nx: bool,              // Whether to set only if the key does not exist / 是否只有在键不存在时才设置
     xx: bool,              // Whether to set only if the key exists / 是否只有在键存在时才设置
 }
@@ -48,29 +50,19 @@ impl Set {
 
                 // Set the key-value pair
                 // 设置键值对
-                db.set(&set.key.clone(), DbType::String(set.value.clone()), set.expiration);
-
-                // 构建参数列表
-                let mut args = vec![set.key.clone(), set.value.clone()];
-
-                // 如果设置了过期时间，则将过期时间添加到命令参数中
-                if let Some(exp) = set.expiration {
-                    // PX（毫秒）
-                    args.push(exp.to_string());
+                db.set_at(&set.key, DbType::String(set.value.clone()), set.expiration);
+                db.notify_keyspace_event(notify::STRING, "set", &set.key);
+                if set.expiration.is_some() {
+                    db.notify_keyspace_event(notify::GENERIC, "expire", &set.key);
                 }
 
-                // 调用 propagate_aof 并传递参数
-                // propagate_aof("set".to_string(), args);
-
                 // Return success response
                 // 返回成功响应
                 Ok(Frame::Simple("OK".to_string()))
             }
-            // Return error if the command has incorrect number of arguments
-            // 如果命令的参数数量不正确，返回错误
-            Err(_) => {
-                Ok(Frame::Error("ERR wrong number of arguments for 'set' command".to_string()))
-            }
+            // Return the error of an invalid option, such as an expiration that is not an integer
+            // 返回选项无效的错误，例如过期时间不是整数
+            Err(err) => Err(err),
         }
     }
 
@@ -86,12 +78,12 @@ impl Set {
         let key = parse.next_string()?;    // The key to set / 要设置的键
         let value = parse.next_string()?;  // The value to set / 要设置的值
 
-        let mut expiration = None; // Expiration time (in milliseconds) / 过期时间（毫秒）
+        let mut expiration = None; // Expiration Unix time (in milliseconds) / 过期的 Unix 时间（毫秒）
         let mut nx = false;        // `NX` flag / `NX` 标志
         let mut xx = false;        // `XX` flag / `XX` 标志
 
-        // Parse optional parameters EX, PX, NX, XX
-        // 解析可选的 EX, PX, NX, XX 参数
+        // Parse optional parameters EX, PX, EXAT, PXAT, NX, XX
+        // 解析可选的 EX, PX, EXAT, PXAT, NX, XX 参数
         while let Ok(option) = parse.next_string() {
             match option.to_uppercase().as_str() {
                 "EX" => {
@@ -101,7 +93,7 @@ impl Set {
                     let exp_in_sec: u64 = exp.parse()?;
                     // Convert seconds to milliseconds
                     // 将秒转换为毫秒
-                    expiration = Some(exp_in_sec * 1000);
+                    expiration = Some(now_millis() + exp_in_sec * 1000);
                 }
                 "PX" => {
                     // PX should be followed by a number (in milliseconds)
@@ -110,17 +102,29 @@ impl Set {
                     let exp_in_ms: u64 = exp.parse()?;
                     // Use milliseconds directly
                     // 直接使用毫秒值
-                    expiration = Some(exp_in_ms);
+                    expiration = Some(now_millis() + exp_in_ms);
+                }
+                "EXAT" => {
+                    // EXAT should be followed by a Unix time (in seconds)
+                    // EXAT 后面应跟 Unix 时间（秒）
+                    let at: u64 = parse.next_string()?.parse()?;
+                    expiration = Some(at * 1000);
+                }
+                "PXAT" => {
+                    // PXAT should be followed by a Unix time (in milliseconds)
+                    // PXAT 后面应跟 Unix 时间（毫秒）
+                    let at: u64 = parse.next_string()?.parse()?;
+                    expiration = Some(at);
                 }
                 "NX" => {
                     if xx {
-                        return Err(Box::new(Error::new(std::io::ErrorKind::Other, "ERR syntax error")));
+                        return Err(Error::Syntax.into());
                     }
                     nx = true;
                 }
                 "XX" => {
                     if nx {
-                        return Err(Box::new(Error::new(std::io::ErrorKind::Other, "ERR syntax error")));
+                        return Err(Error::Syntax.into());
                     }
                     xx = true;
                 }
@@ -138,3 +142,4 @@ impl Set {
         })
     }
 }
+
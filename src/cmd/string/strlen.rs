@@ -28,25 +28,17 @@ impl Strlen {
         db: &mut Arc<Mutex<Db>>,
         parse: &mut Parse
     ) -> crate::Result<Frame> {
-        match Strlen::parse_command(parse) {
-            Ok(strlen) => {
-                let mut db = db.lock().unwrap();
-                // Get the current value of the key
-                // 获取键的当前值
-                match db.get(&strlen.key) {
-                    // If the key exists and is of type string, return its length
-                    // 如果键存在并且是字符串类型，返回其长度
-                    Some(DbType::String(value)) => Ok(Frame::Integer(value.len() as i64)),
-                    // If the key does not exist or is not a string, return 0
-                    // 如果键不存在或不是字符串类型，返回 0
-                    _ => Ok(Frame::Integer(0)),
-                }
-            }
-            // If there is an error in parsing, return an error frame
-            // 如果解析时发生错误，返回错误响应
-            Err(_) => {
-                Ok(Frame::Error("ERR wrong number of arguments for 'strlen' command".to_string()))
-            }
+        let strlen = Strlen::parse_command(parse)?;
+        let mut db = db.lock().unwrap();
+        // Get the current value of the key
+        // 获取键的当前值
+        match db.lookup_read(&strlen.key) {
+            // If the key exists and is of type string, return its length
+            // 如果键存在并且是字符串类型，返回其长度
+            Some(DbType::String(value)) => Ok(Frame::Integer(value.len() as i64)),
+            // If the key does not exist or is not a string, return 0
+            // 如果键不存在或不是字符串类型，返回 0
+            _ => Ok(Frame::Integer(0)),
         }
     }
 
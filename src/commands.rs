@@ -16,52 +16,548 @@ fn empty_command(_: &mut Arc<Mutex<Db>>, _: &mut Parse) -> crate::Result<Frame>
 
 /// 定义命令元数据，后续命令都可以添加到这里
 /// Define command metadata, additional commands can be added here in the future.
-pub static COMMANDS: &[(&str, &str, &str, fn(&mut Arc<Mutex<Db>>, &mut Parse) -> crate::Result<Frame>)] = &[
+///
+/// 参数个数包含命令名本身，子命令的参数个数包含命令和子命令，负数表示至少需要该数量的参数。
+/// 键的位置从 1 开始，第一个键为 0 表示没有键，最后一个键为负数表示从末尾倒数。
+/// The arity includes the command name itself, and both the command and subcommand for subcommands; a negative arity
+/// means at least that many arguments. Key positions start at 1, a first key of 0 means no keys, a negative last key
+/// counts from the end.
+pub static COMMANDS: &[(&str, &str, &str, fn(&mut Arc<Mutex<Db>>, &mut Parse) -> crate::Result<Frame>, CommandSpec)] = &[
     // ping
-    ("ping", "测试连接是否正常。", "O(1)", cmd::ping::Ping::ping_command),
+    ("ping", "测试连接是否正常。", "O(1)", cmd::ping::Ping::ping_command, CommandSpec {
+        arity: -1, flags: &["fast"], categories: &["fast", "connection"], ..NO_KEYS
+    }),
     // echo
-    ("echo", "返回指定的字符串。", "O(N)", cmd::echo::Echo::echo_command),
+    ("echo", "返回指定的字符串。", "O(N)", cmd::echo::Echo::echo_command, CommandSpec {
+        arity: 2, flags: &["fast"], categories: &["fast", "connection"], ..NO_KEYS
+    }),
+    // connection
+    ("hello", "切换协议版本并返回连接信息。", "O(1)", empty_command, CommandSpec {
+        arity: -1, flags: &["noscript", "loading", "stale", "fast"], categories: &["fast", "connection"], ..NO_KEYS
+    }),
+    ("client", "管理客户端连接，如客户端缓存的键追踪。", "O(1)", empty_command, CommandSpec {
+        arity: -2, flags: &[], categories: &["admin", "slow", "dangerous", "connection"], ..NO_KEYS
+    }),
+    ("auth", "使用用户名和密码认证当前连接。", "O(N)", empty_command, CommandSpec {
+        arity: -2, flags: &["noscript", "loading", "stale", "fast"], categories: &["fast", "connection"], ..NO_KEYS
+    }),
+    ("acl", "管理 ACL 用户及其权限。", "O(N)", empty_command, CommandSpec {
+        arity: -2, flags: &[], categories: &["admin", "slow", "dangerous"], ..NO_KEYS
+    }),
+    ("quit", "回复之后关闭连接。", "O(1)", empty_command, CommandSpec {
+        arity: -1, flags: &["noscript", "loading", "stale", "fast"], categories: &["fast", "connection"], ..NO_KEYS
+    }),
+    ("reset", "将连接重置为新连接的状态。", "O(1)", empty_command, CommandSpec {
+        arity: 1, flags: &["noscript", "loading", "stale", "fast"], categories: &["fast", "connection"], ..NO_KEYS
+    }),
+    // server
+    ("config", "在运行时读取和修改配置参数。", "O(N)", cmd::config::ConfigCommand::config_command, CommandSpec {
+        arity: -2, flags: &[], categories: &["admin", "slow", "dangerous"], ..NO_KEYS
+    }),
+    ("info", "返回服务端的运行信息和统计数据。", "O(N)", cmd::info::Info::info_command, CommandSpec {
+        arity: -1, flags: &["loading", "stale"], categories: &["slow", "dangerous"], ..NO_KEYS
+    }),
+    ("command", "返回命令的元数据，如参数个数、标志和键的位置。", "O(N)", empty_command, CommandSpec {
+        arity: -1, flags: &["loading", "stale"], categories: &["slow", "connection"], ..NO_KEYS
+    }),
+    ("save", "同步地把数据集保存到 RDB 文件。", "O(N)", cmd::persistence::save::Save::save_command, CommandSpec {
+        arity: 1, flags: &["admin", "noscript"], categories: &["admin", "slow", "dangerous"], ..NO_KEYS
+    }),
+    ("bgsave", "在后台把数据集保存到 RDB 文件。", "O(1)", cmd::persistence::bgsave::Bgsave::bgsave_command, CommandSpec {
+        arity: -1, flags: &["admin", "noscript"], categories: &["admin", "slow", "dangerous"], ..NO_KEYS
+    }),
+    ("lastsave", "返回最近一次成功保存 RDB 文件的时间。", "O(1)", cmd::persistence::lastsave::Lastsave::lastsave_command, CommandSpec {
+        arity: 1, flags: &["loading", "stale", "fast"], categories: &["admin", "fast", "dangerous"], ..NO_KEYS
+    }),
+    ("bgrewriteaof", "在后台重写 AOF 文件。", "O(1)", cmd::persistence::bgrewriteaof::Bgrewriteaof::bgrewriteaof_command, CommandSpec {
+        arity: 1, flags: &["admin", "noscript"], categories: &["admin", "slow", "dangerous"], ..NO_KEYS
+    }),
     // pubsub
-    ("publish", "向指定频道发布消息。", "O(1)", cmd::pubsub::publish::Publish::publish_command),
-    ("subscribe", "订阅指定频道，接收消息。", "O(1)", empty_command),
-    ("psubscribe", "使用模式订阅频道。", "O(1)", empty_command),
+    ("publish", "向指定频道发布消息。", "O(1)", cmd::pubsub::publish::Publish::publish_command, CommandSpec {
+        arity: 3, flags: &["pubsub", "loading", "stale", "fast"], categories: &["pubsub", "fast"], ..NO_KEYS
+    }),
+    ("subscribe", "订阅指定频道，接收消息。", "O(1)", empty_command, CommandSpec {
+        arity: -2, flags: &["pubsub", "noscript", "loading", "stale"], categories: &["pubsub", "slow"], ..NO_KEYS
+    }),
+    ("psubscribe", "使用模式订阅频道。", "O(1)", empty_command, CommandSpec {
+        arity: -2, flags: &["pubsub", "noscript", "loading", "stale"], categories: &["pubsub", "slow"], ..NO_KEYS
+    }),
+    ("unsubscribe", "退订指定频道，不指定时退订所有频道。", "O(N)", empty_command, CommandSpec {
+        arity: -1, flags: &["pubsub", "noscript", "loading", "stale"], categories: &["pubsub", "slow"], ..NO_KEYS
+    }),
+    ("punsubscribe", "退订指定模式，不指定时退订所有模式。", "O(N)", empty_command, CommandSpec {
+        arity: -1, flags: &["pubsub", "noscript", "loading", "stale"], categories: &["pubsub", "slow"], ..NO_KEYS
+    }),
+    ("spublish", "向指定分片频道发布消息。", "O(1)", cmd::pubsub::spublish::SPublish::spublish_command, CommandSpec {
+        arity: 3, flags: &["pubsub", "loading", "fast"], categories: &["pubsub", "fast"], ..NO_KEYS
+    }),
+    ("ssubscribe", "订阅指定分片频道，接收消息。", "O(1)", empty_command, CommandSpec {
+        arity: -2, flags: &["pubsub", "noscript", "loading"], categories: &["pubsub", "slow"], ..NO_KEYS
+    }),
+    ("sunsubscribe", "退订指定分片频道，不指定时退订所有分片频道。", "O(N)", empty_command, CommandSpec {
+        arity: -1, flags: &["pubsub", "noscript", "loading"], categories: &["pubsub", "slow"], ..NO_KEYS
+    }),
+    ("pubsub", "查看有订阅者的频道、频道的订阅者数量和被订阅的模式数量。", "O(N)", cmd::pubsub::introspection::PubSub::pubsub_command, CommandSpec {
+        arity: -2, flags: &[], categories: &["slow"], ..NO_KEYS
+    }),
     // string
-    ("set", "设置指定键的值。", "O(1)", cmd::string::set::Set::set_command),
-    ("get", "返回指定键的字符串值。", "O(1)", cmd::string::get::Get::get_command),
-    ("del", "删除指定的键。", "O(1)", cmd::string::del::Del::del_command),
-    ("append", "将指定的值追加到键的字符串值后面。", "O(1)", cmd::string::append::Append::append_command),
-    ("strlen", "获取指定键的字符串值的长度。", "O(1)", cmd::string::strlen::Strlen::strlen_command),
-    ("incr", "将指定键的数值增加1。", "O(1)", cmd::string::incr::Incr::incr_command),
-    ("incrby", "将指定键的数值增加指定的步长，无默认值。", "O(1)", cmd::string::incrby::IncrBy::incrby_command),
-    ("decr", "将指定键的数值减少1。", "O(1)", cmd::string::decr::Decr::decr_command),
-    ("decrby", "将指定键的数值减少指定的步长，无默认值。", "O(1)", cmd::string::decrby::DecrBy::decrby_command),
-    ("mget", "获取多个指定键的字符串值。", "O(N)", cmd::string::mget::Mget::mget_command),
-    ("mset", "设置多个键的值。", "O(N)", cmd::string::mset::Mset::mset_command),
-    ("msetnx", "只有在所有指定键都不存在的情况下，才会设置它们的值。", "O(N)", cmd::string::msetnx::Msetnx::msetnx_command),
+    ("set", "设置指定键的值。", "O(1)", cmd::string::set::Set::set_command, CommandSpec {
+        arity: -3, flags: &["write", "denyoom"], categories: &["write", "string", "slow"],
+        first_key: 1, last_key: 1, key_step: 1, key_access: "OW",
+    }),
+    ("get", "返回指定键的字符串值。", "O(1)", cmd::string::get::Get::get_command, CommandSpec {
+        arity: 2, flags: &["readonly", "fast"], categories: &["read", "string", "fast"],
+        first_key: 1, last_key: 1, key_step: 1, key_access: "RO",
+    }),
+    ("del", "删除指定的键。", "O(1)", cmd::string::del::Del::del_command, CommandSpec {
+        arity: -2, flags: &["write"], categories: &["keyspace", "write", "slow"],
+        first_key: 1, last_key: -1, key_step: 1, key_access: "RM",
+    }),
+    ("pexpireat", "以 Unix 时间（毫秒）设置键的过期时间。", "O(1)", cmd::string::pexpireat::Pexpireat::pexpireat_command, CommandSpec {
+        arity: 3, flags: &["write", "fast"], categories: &["keyspace", "write", "fast"],
+        first_key: 1, last_key: 1, key_step: 1, key_access: "RW",
+    }),
+    ("append", "将指定的值追加到键的字符串值后面。", "O(1)", cmd::string::append::Append::append_command, CommandSpec {
+        arity: 3, flags: &["write", "denyoom", "fast"], categories: &["write", "string", "fast"],
+        first_key: 1, last_key: 1, key_step: 1, key_access: "RW",
+    }),
+    ("strlen", "获取指定键的字符串值的长度。", "O(1)", cmd::string::strlen::Strlen::strlen_command, CommandSpec {
+        arity: 2, flags: &["readonly", "fast"], categories: &["read", "string", "fast"],
+        first_key: 1, last_key: 1, key_step: 1, key_access: "RO",
+    }),
+    ("incr", "将指定键的数值增加1。", "O(1)", cmd::string::incr::Incr::incr_command, CommandSpec {
+        arity: 2, flags: &["write", "denyoom", "fast"], categories: &["write", "string", "fast"],
+        first_key: 1, last_key: 1, key_step: 1, key_access: "RW",
+    }),
+    ("incrby", "将指定键的数值增加指定的步长，无默认值。", "O(1)", cmd::string::incrby::IncrBy::incrby_command, CommandSpec {
+        arity: 3, flags: &["write", "denyoom", "fast"], categories: &["write", "string", "fast"],
+        first_key: 1, last_key: 1, key_step: 1, key_access: "RW",
+    }),
+    ("decr", "将指定键的数值减少1。", "O(1)", cmd::string::decr::Decr::decr_command, CommandSpec {
+        arity: 2, flags: &["write", "denyoom", "fast"], categories: &["write", "string", "fast"],
+        first_key: 1, last_key: 1, key_step: 1, key_access: "RW",
+    }),
+    ("decrby", "将指定键的数值减少指定的步长，无默认值。", "O(1)", cmd::string::decrby::DecrBy::decrby_command, CommandSpec {
+        arity: 3, flags: &["write", "denyoom", "fast"], categories: &["write", "string", "fast"],
+        first_key: 1, last_key: 1, key_step: 1, key_access: "RW",
+    }),
+    ("mget", "获取多个指定键的字符串值。", "O(N)", cmd::string::mget::Mget::mget_command, CommandSpec {
+        arity: -2, flags: &["readonly", "fast"], categories: &["read", "string", "fast"],
+        first_key: 1, last_key: -1, key_step: 1, key_access: "RO",
+    }),
+    ("mset", "设置多个键的值。", "O(N)", cmd::string::mset::Mset::mset_command, CommandSpec {
+        arity: -3, flags: &["write", "denyoom"], categories: &["write", "string", "slow"],
+        first_key: 1, last_key: -1, key_step: 2, key_access: "OW",
+    }),
+    ("msetnx", "只有在所有指定键都不存在的情况下，才会设置它们的值。", "O(N)", cmd::string::msetnx::Msetnx::msetnx_command, CommandSpec {
+        arity: -3, flags: &["write", "denyoom"], categories: &["write", "string", "slow"],
+        first_key: 1, last_key: -1, key_step: 2, key_access: "OW",
+    }),
     // hash
-    ("hset", "设置哈希表中指定字段的值。", "O(1)", cmd::hash::hset::Hset::hset_command),
-    ("hget", "获取哈希表中指定字段的值。", "O(1)", cmd::hash::hget::Hget::hget_command),
-    ("hdel", "删除哈希表中指定字段。", "O(1)", cmd::hash::hdel::Hdel::hdel_command),
-    ("hgetall", "获取哈希表中的所有字段和值。", "O(N)", cmd::hash::hgetall::Hgetall::hgetall_command),
-    ("hmset", "设置哈希表中多个字段的值。", "O(N)", cmd::hash::hmset::Hmset::hmset_command),
-    ("hmget", "获取哈希表中多个字段的值。", "O(N)", cmd::hash::hmget::Hmget::hmget_command),
-    ("hkeys", "获取哈希表中的所有字段。", "O(N)", cmd::hash::hkeys::Hkeys::hkeys_command),
-    ("hvals", "获取哈希表中的所有值。", "O(N)", cmd::hash::hvals::Hvals::hvals_command),
-    ("hlen", "获取哈希表中的字段数量。", "O(1)", cmd::hash::hlen::Hlen::hlen_command),
-    ("hexists", "检查哈希表中指定字段是否存在。", "O(1)", cmd::hash::hexists::Hexists::hexists_command),
-    ("hsetnx", "只有在字段不存在的情况下，才会设置字段的值。", "O(1)", cmd::hash::hsetnx::Hsetnx::hsetnx_command),
+    ("hset", "设置哈希表中指定字段的值。", "O(1)", cmd::hash::hset::Hset::hset_command, CommandSpec {
+        arity: 4, flags: &["write", "denyoom", "fast"], categories: &["write", "hash", "fast"],
+        first_key: 1, last_key: 1, key_step: 1, key_access: "RW",
+    }),
+    ("hget", "获取哈希表中指定字段的值。", "O(1)", cmd::hash::hget::Hget::hget_command, CommandSpec {
+        arity: 3, flags: &["readonly", "fast"], categories: &["read", "hash", "fast"],
+        first_key: 1, last_key: 1, key_step: 1, key_access: "RO",
+    }),
+    ("hdel", "删除哈希表中指定字段。", "O(1)", cmd::hash::hdel::Hdel::hdel_command, CommandSpec {
+        arity: -3, flags: &["write", "fast"], categories: &["write", "hash", "fast"],
+        first_key: 1, last_key: 1, key_step: 1, key_access: "RW",
+    }),
+    ("hgetall", "获取哈希表中的所有字段和值。", "O(N)", cmd::hash::hgetall::Hgetall::hgetall_command, CommandSpec {
+        arity: 2, flags: &["readonly"], categories: &["read", "hash", "slow"],
+        first_key: 1, last_key: 1, key_step: 1, key_access: "RO",
+    }),
+    ("hmset", "设置哈希表中多个字段的值。", "O(N)", cmd::hash::hmset::Hmset::hmset_command, CommandSpec {
+        arity: -4, flags: &["write", "denyoom", "fast"], categories: &["write", "hash", "fast"],
+        first_key: 1, last_key: 1, key_step: 1, key_access: "RW",
+    }),
+    ("hmget", "获取哈希表中多个字段的值。", "O(N)", cmd::hash::hmget::Hmget::hmget_command, CommandSpec {
+        arity: -3, flags: &["readonly", "fast"], categories: &["read", "hash", "fast"],
+        first_key: 1, last_key: 1, key_step: 1, key_access: "RO",
+    }),
+    ("hkeys", "获取哈希表中的所有字段。", "O(N)", cmd::hash::hkeys::Hkeys::hkeys_command, CommandSpec {
+        arity: 2, flags: &["readonly"], categories: &["read", "hash", "slow"],
+        first_key: 1, last_key: 1, key_step: 1, key_access: "RO",
+    }),
+    ("hvals", "获取哈希表中的所有值。", "O(N)", cmd::hash::hvals::Hvals::hvals_command, CommandSpec {
+        arity: 2, flags: &["readonly"], categories: &["read", "hash", "slow"],
+        first_key: 1, last_key: 1, key_step: 1, key_access: "RO",
+    }),
+    ("hlen", "获取哈希表中的字段数量。", "O(1)", cmd::hash::hlen::Hlen::hlen_command, CommandSpec {
+        arity: 2, flags: &["readonly", "fast"], categories: &["read", "hash", "fast"],
+        first_key: 1, last_key: 1, key_step: 1, key_access: "RO",
+    }),
+    ("hexists", "检查哈希表中指定字段是否存在。", "O(1)", cmd::hash::hexists::Hexists::hexists_command, CommandSpec {
+        arity: 3, flags: &["readonly", "fast"], categories: &["read", "hash", "fast"],
+        first_key: 1, last_key: 1, key_step: 1, key_access: "RO",
+    }),
+    ("hsetnx", "只有在字段不存在的情况下，才会设置字段的值。", "O(1)", cmd::hash::hsetnx::Hsetnx::hsetnx_command, CommandSpec {
+        arity: 4, flags: &["write", "denyoom", "fast"], categories: &["write", "hash", "fast"],
+        first_key: 1, last_key: 1, key_step: 1, key_access: "RW",
+    }),
     // list
-    ("lpush", "将一个或多个值插入到列表的头部。", "O(1)", cmd::list::lpush::Lpush::lpush_command),
-    ("rpush", "将一个或多个值插入到列表的尾部。", "O(1)", cmd::list::rpush::Rpush::rpush_command),
-    ("lpop", "移除并返回列表的第一个元素。", "O(1)", cmd::list::lpop::Lpop::lpop_command),
-    ("rpop", "移除并返回列表的最后一个元素。", "O(1)", cmd::list::rpop::Rpop::rpop_command),
-    ("lrange", "返回列表中指定范围的元素。", "O(N)", cmd::list::lrange::Lrange::lrange_command),
-    ("lindex", "返回列表中指定索引的元素。", "O(1)", cmd::list::lindex::Lindex::lindex_command),
-    ("llen", "返回列表的长度。", "O(1)", cmd::list::llen::Llen::llen_command),
-    ("lset", "设置列表中指定索引的值。", "O(N)", cmd::list::lset::Lset::lset_command),
-    ("lrem", "移除列表中指定值的元素。", "O(N)", cmd::list::lrem::Lrem::lrem_command),
-    ("ltrim", "对列表进行修剪，保留指定范围的元素。", "O(N)", cmd::list::ltrim::Ltrim::ltrim_command),
-    ("blpop", "阻塞式从左侧弹出一个元素。", "O(1)", cmd::list::blpop::Blpop::blpop_command),
-    ("brpop", "阻塞式从右侧弹出一个元素。", "O(1)", cmd::list::brpop::Brpop::brpop_command),
+    ("lpush", "将一个或多个值插入到列表的头部。", "O(1)", cmd::list::lpush::Lpush::lpush_command, CommandSpec {
+        arity: -3, flags: &["write", "denyoom", "fast"], categories: &["write", "list", "fast"],
+        first_key: 1, last_key: 1, key_step: 1, key_access: "RW",
+    }),
+    ("rpush", "将一个或多个值插入到列表的尾部。", "O(1)", cmd::list::rpush::Rpush::rpush_command, CommandSpec {
+        arity: -3, flags: &["write", "denyoom", "fast"], categories: &["write", "list", "fast"],
+        first_key: 1, last_key: 1, key_step: 1, key_access: "RW",
+    }),
+    ("lpop", "移除并返回列表的第一个元素。", "O(1)", cmd::list::lpop::Lpop::lpop_command, CommandSpec {
+        arity: 2, flags: &["write", "fast"], categories: &["write", "list", "fast"],
+        first_key: 1, last_key: 1, key_step: 1, key_access: "RW",
+    }),
+    ("rpop", "移除并返回列表的最后一个元素。", "O(1)", cmd::list::rpop::Rpop::rpop_command, CommandSpec {
+        arity: 2, flags: &["write", "fast"], categories: &["write", "list", "fast"],
+        first_key: 1, last_key: 1, key_step: 1, key_access: "RW",
+    }),
+    ("lrange", "返回列表中指定范围的元素。", "O(N)", cmd::list::lrange::Lrange::lrange_command, CommandSpec {
+        arity: 4, flags: &["readonly"], categories: &["read", "list", "slow"],
+        first_key: 1, last_key: 1, key_step: 1, key_access: "RO",
+    }),
+    ("lindex", "返回列表中指定索引的元素。", "O(1)", cmd::list::lindex::Lindex::lindex_command, CommandSpec {
+        arity: 3, flags: &["readonly"], categories: &["read", "list", "slow"],
+        first_key: 1, last_key: 1, key_step: 1, key_access: "RO",
+    }),
+    ("llen", "返回列表的长度。", "O(1)", cmd::list::llen::Llen::llen_command, CommandSpec {
+        arity: 2, flags: &["readonly", "fast"], categories: &["read", "list", "fast"],
+        first_key: 1, last_key: 1, key_step: 1, key_access: "RO",
+    }),
+    ("lset", "设置列表中指定索引的值。", "O(N)", cmd::list::lset::Lset::lset_command, CommandSpec {
+        arity: 4, flags: &["write", "denyoom"], categories: &["write", "list", "slow"],
+        first_key: 1, last_key: 1, key_step: 1, key_access: "RW",
+    }),
+    ("lrem", "移除列表中指定值的元素。", "O(N)", cmd::list::lrem::Lrem::lrem_command, CommandSpec {
+        arity: 4, flags: &["write"], categories: &["write", "list", "slow"],
+        first_key: 1, last_key: 1, key_step: 1, key_access: "RW",
+    }),
+    ("ltrim", "对列表进行修剪，保留指定范围的元素。", "O(N)", cmd::list::ltrim::Ltrim::ltrim_command, CommandSpec {
+        arity: 4, flags: &["write"], categories: &["write", "list", "slow"],
+        first_key: 1, last_key: 1, key_step: 1, key_access: "RW",
+    }),
+    ("blpop", "阻塞式从左侧弹出一个元素。", "O(1)", cmd::list::blpop::Blpop::blpop_command, CommandSpec {
+        arity: 3, flags: &["write", "blocking"], categories: &["write", "list", "slow", "blocking"],
+        first_key: 1, last_key: -2, key_step: 1, key_access: "RW",
+    }),
+    ("brpop", "阻塞式从右侧弹出一个元素。", "O(1)", cmd::list::brpop::Brpop::brpop_command, CommandSpec {
+        arity: 3, flags: &["write", "blocking"], categories: &["write", "list", "slow", "blocking"],
+        first_key: 1, last_key: -2, key_step: 1, key_access: "RW",
+    }),
     // ("brpoplpush", "阻塞式弹出一个元素并将其推入另一个列表。", "O(1)", cmd::list::brpoplpush::Brpoplpush::brpoplpush_command)
 ];
+
+/// 子命令的元数据：命令名（`命令|子命令`）、描述、时间复杂度和规格，处理函数为所属命令的处理函数
+/// Subcommand metadata: name (`command|subcommand`), description, time complexity and specification, handled by the
+/// parent command.
+pub static SUBCOMMANDS: &[(&str, &str, &str, CommandSpec)] = &[
+    // client
+    ("client|id", "返回当前连接的 ID。", "O(1)", CommandSpec {
+        arity: 2, flags: &["noscript", "loading", "stale"], categories: &["slow", "connection"], ..NO_KEYS
+    }),
+    ("client|list", "返回所有客户端连接的信息。", "O(N)", CommandSpec {
+        arity: -2, flags: &["admin", "noscript", "loading", "stale"], categories: &["admin", "slow", "dangerous", "connection"], ..NO_KEYS
+    }),
+    ("client|info", "返回当前连接的信息。", "O(1)", CommandSpec {
+        arity: 2, flags: &["noscript", "loading", "stale"], categories: &["slow", "connection"], ..NO_KEYS
+    }),
+    ("client|setname", "设置当前连接的名称。", "O(1)", CommandSpec {
+        arity: 3, flags: &["noscript", "loading", "stale"], categories: &["slow", "connection"], ..NO_KEYS
+    }),
+    ("client|getname", "返回当前连接的名称。", "O(1)", CommandSpec {
+        arity: 2, flags: &["noscript", "loading", "stale"], categories: &["slow", "connection"], ..NO_KEYS
+    }),
+    ("client|kill", "断开指定的客户端连接。", "O(N)", CommandSpec {
+        arity: -3, flags: &["admin", "noscript", "loading", "stale"], categories: &["admin", "slow", "dangerous", "connection"], ..NO_KEYS
+    }),
+    ("client|pause", "暂停处理客户端的命令。", "O(N)", CommandSpec {
+        arity: -3, flags: &["admin", "noscript", "loading", "stale"], categories: &["admin", "slow", "dangerous", "connection"], ..NO_KEYS
+    }),
+    ("client|unpause", "恢复处理被暂停的客户端的命令。", "O(N)", CommandSpec {
+        arity: 2, flags: &["admin", "noscript", "loading", "stale"], categories: &["admin", "slow", "dangerous", "connection"], ..NO_KEYS
+    }),
+    ("client|no-evict", "设置当前连接是否不参与内存淘汰。", "O(1)", CommandSpec {
+        arity: 3, flags: &["admin", "noscript", "loading", "stale"], categories: &["admin", "slow", "dangerous", "connection"], ..NO_KEYS
+    }),
+    ("client|reply", "控制服务端是否回复当前连接的命令。", "O(1)", CommandSpec {
+        arity: 3, flags: &["noscript", "loading", "stale"], categories: &["slow", "connection"], ..NO_KEYS
+    }),
+    ("client|tracking", "开启或关闭客户端缓存的键追踪。", "O(1)", CommandSpec {
+        arity: -3, flags: &["noscript", "loading", "stale"], categories: &["slow", "connection"], ..NO_KEYS
+    }),
+    ("client|caching", "指定下一条命令读取的键是否被追踪。", "O(1)", CommandSpec {
+        arity: 3, flags: &["noscript", "loading", "stale"], categories: &["slow", "connection"], ..NO_KEYS
+    }),
+    ("client|getredir", "返回失效消息重定向到的客户端 ID。", "O(1)", CommandSpec {
+        arity: 2, flags: &["noscript", "loading", "stale"], categories: &["slow", "connection"], ..NO_KEYS
+    }),
+    // acl
+    ("acl|setuser", "创建或修改 ACL 用户。", "O(N)", CommandSpec {
+        arity: -3, flags: &["admin", "noscript", "loading", "stale"], categories: &["admin", "slow", "dangerous"], ..NO_KEYS
+    }),
+    ("acl|getuser", "返回 ACL 用户的规则。", "O(N)", CommandSpec {
+        arity: 3, flags: &["admin", "noscript", "loading", "stale"], categories: &["admin", "slow", "dangerous"], ..NO_KEYS
+    }),
+    ("acl|deluser", "删除 ACL 用户并断开其连接。", "O(1)", CommandSpec {
+        arity: -3, flags: &["admin", "noscript", "loading", "stale"], categories: &["admin", "slow", "dangerous"], ..NO_KEYS
+    }),
+    ("acl|list", "以 ACL 规则的形式返回所有用户。", "O(N)", CommandSpec {
+        arity: 2, flags: &["admin", "noscript", "loading", "stale"], categories: &["admin", "slow", "dangerous"], ..NO_KEYS
+    }),
+    ("acl|whoami", "返回当前连接认证的用户名。", "O(1)", CommandSpec {
+        arity: 2, flags: &["noscript", "loading", "stale"], categories: &["slow"], ..NO_KEYS
+    }),
+    ("acl|cat", "返回 ACL 类别，或某个类别中的命令。", "O(1)", CommandSpec {
+        arity: -2, flags: &["noscript", "loading", "stale"], categories: &["slow"], ..NO_KEYS
+    }),
+    ("acl|log", "返回或清除被拒绝的命令和认证失败的记录。", "O(N)", CommandSpec {
+        arity: -2, flags: &["admin", "noscript", "loading", "stale"], categories: &["admin", "slow", "dangerous"], ..NO_KEYS
+    }),
+    // config
+    ("config|get", "返回匹配的配置参数的值。", "O(N)", CommandSpec {
+        arity: -3, flags: &["admin", "noscript", "loading", "stale"], categories: &["admin", "slow", "dangerous"], ..NO_KEYS
+    }),
+    ("config|set", "在运行时修改配置参数。", "O(N)", CommandSpec {
+        arity: -4, flags: &["admin", "noscript", "loading", "stale"], categories: &["admin", "slow", "dangerous"], ..NO_KEYS
+    }),
+    // command
+    ("command|count", "返回命令的数量。", "O(1)", CommandSpec {
+        arity: 2, flags: &["loading", "stale"], categories: &["slow", "connection"], ..NO_KEYS
+    }),
+    ("command|info", "返回指定命令的元数据，不指定时返回所有命令。", "O(N)", CommandSpec {
+        arity: -2, flags: &["loading", "stale"], categories: &["slow", "connection"], ..NO_KEYS
+    }),
+    ("command|docs", "返回指定命令的文档，不指定时返回所有命令。", "O(N)", CommandSpec {
+        arity: -2, flags: &["loading", "stale"], categories: &["slow", "connection"], ..NO_KEYS
+    }),
+    ("command|list", "返回命令名，可以按 ACL 类别或模式过滤。", "O(N)", CommandSpec {
+        arity: -2, flags: &["loading", "stale"], categories: &["slow", "connection"], ..NO_KEYS
+    }),
+    ("command|getkeys", "返回命令参数中的键。", "O(N)", CommandSpec {
+        arity: -3, flags: &["loading", "stale"], categories: &["slow", "connection"], ..NO_KEYS
+    }),
+    // pubsub
+    ("pubsub|channels", "返回有订阅者的频道。", "O(N)", CommandSpec {
+        arity: -2, flags: &["pubsub", "loading", "stale"], categories: &["pubsub", "slow"], ..NO_KEYS
+    }),
+    ("pubsub|numsub", "返回频道的订阅者数量。", "O(N)", CommandSpec {
+        arity: -2, flags: &["pubsub", "loading", "stale"], categories: &["pubsub", "slow"], ..NO_KEYS
+    }),
+    ("pubsub|numpat", "返回被订阅的模式数量。", "O(1)", CommandSpec {
+        arity: 2, flags: &["pubsub", "loading", "stale"], categories: &["pubsub", "slow"], ..NO_KEYS
+    }),
+    ("pubsub|shardchannels", "返回有订阅者的分片频道。", "O(N)", CommandSpec {
+        arity: -2, flags: &["pubsub", "loading", "stale"], categories: &["pubsub", "slow"], ..NO_KEYS
+    }),
+    ("pubsub|shardnumsub", "返回分片频道的订阅者数量。", "O(N)", CommandSpec {
+        arity: -2, flags: &["pubsub", "loading", "stale"], categories: &["pubsub", "slow"], ..NO_KEYS
+    }),
+    ("pubsub|lag", "返回客户端订阅积压和丢弃的消息数量。", "O(N)", CommandSpec {
+        arity: -2, flags: &["pubsub", "loading", "stale"], categories: &["pubsub", "slow"], ..NO_KEYS
+    }),
+];
+
+/// 命令的标志
+/// Command flags.
+pub const FLAGS: &[&str] = &[
+    "write", "readonly", "denyoom", "admin", "pubsub", "noscript", "loading", "stale", "fast", "blocking",
+];
+
+/// 键的访问方式：`RO` 只读取值，`RW` 读取并修改值，`OW` 覆盖值而不读取，`RM` 删除键
+/// Key access flags: `RO` only reads the value, `RW` reads and modifies it, `OW` overwrites it without reading and
+/// `RM` removes the key.
+pub const KEY_ACCESS: &[&str] = &["RO", "RW", "OW", "RM"];
+
+/// 命令的规格
+/// Command specification.
+#[derive(Debug, Clone, Copy)]
+pub struct CommandSpec {
+    /// 参数个数，包含命令名本身，负数表示至少需要该数量的参数
+    /// Arity including the command name itself, a negative arity means at least that many arguments
+    pub arity: i64,
+    /// 命令的标志，见 [`FLAGS`]
+    /// Command flags, see [`FLAGS`]
+    pub flags: &'static [&'static str],
+    /// 第一个键的位置，0 表示没有键
+    /// Position of the first key, 0 means no keys
+    pub first_key: usize,
+    /// 最后一个键的位置，负数表示从末尾倒数
+    /// Position of the last key, negative counts from the end
+    pub last_key: i64,
+    /// 键之间的步长
+    /// Step between keys
+    pub key_step: usize,
+    /// 键的访问方式，见 [`KEY_ACCESS`]，没有键时为空
+    /// Key access, see [`KEY_ACCESS`], empty without keys
+    pub key_access: &'static str,
+    /// ACL 类别
+    /// ACL categories
+    pub categories: &'static [&'static str],
+}
+
+/// 没有键的命令的规格，没有键的命令只需要给出参数个数、标志和类别
+/// Specification of commands without keys, commands without keys only give their arity, flags and categories.
+pub const NO_KEYS: CommandSpec = CommandSpec {
+    arity: -1, flags: &[], categories: &[],
+    first_key: 0, last_key: 0, key_step: 0, key_access: "",
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::acl::CATEGORIES;
+    use crate::dict::Command;
+    use crate::error::Error;
+    use crate::frame::Frame;
+
+    /// 由参数生成 `Parse`，参数不包含命令名
+    /// Build a `Parse` from arguments, not including the command name
+    fn parse(args: &[&str]) -> Parse {
+        let frames = args.iter().map(|arg| Frame::Bulk(arg.as_bytes().to_vec())).collect();
+        Parse::new(Some(Frame::Array(frames))).unwrap()
+    }
+
+    /// 所有命令和子命令的名称及规格
+    /// Names and specifications of all commands and subcommands
+    fn specs() -> impl Iterator<Item = (&'static str, &'static CommandSpec)> {
+        let commands = COMMANDS.iter().map(|(name, .., spec)| (*name, spec));
+        commands.chain(SUBCOMMANDS.iter().map(|(name, .., spec)| (*name, spec)))
+    }
+
+    #[test]
+    fn every_subcommand_belongs_to_a_command() {
+        for (name, ..) in SUBCOMMANDS {
+            let (command, _) = name.split_once('|').expect("subcommands are named command|subcommand");
+            assert!(COMMANDS.iter().any(|entry| entry.0 == command), "subcommand '{}' has no command", name);
+        }
+        for (name, _) in specs() {
+            assert_eq!(specs().filter(|(other, _)| *other == name).count(), 1, "duplicate command '{}'", name);
+        }
+    }
+
+    #[test]
+    fn specs_are_consistent() {
+        for (name, &CommandSpec { arity, flags, first_key: first, last_key: last, key_step: step, key_access: access, categories }) in specs() {
+            let min_args = if name.contains('|') { 2 } else { 1 };
+            assert!(arity.abs() >= min_args, "'{}' arity {} does not cover its name", name, arity);
+            for flag in flags {
+                assert!(FLAGS.contains(flag), "'{}' has unknown flag '{}'", name, flag);
+            }
+            for category in categories {
+                assert!(CATEGORIES.contains(category), "'{}' has unknown category '{}'", name, category);
+            }
+            let has = |flag: &str| flags.contains(&flag);
+            let in_category = |category: &str| categories.contains(&category);
+            assert!(!(has("write") && has("readonly")), "'{}' is both write and readonly", name);
+            assert_eq!(has("write"), in_category("write"), "'{}' write flag and category differ", name);
+            assert_eq!(has("readonly"), in_category("read"), "'{}' readonly flag and category differ", name);
+            assert_eq!(has("fast"), in_category("fast"), "'{}' fast flag and category differ", name);
+            assert_eq!(has("blocking"), in_category("blocking"), "'{}' blocking flag and category differ", name);
+            assert!(!has("admin") || in_category("admin"), "'{}' admin flag without category", name);
+            assert!(!has("pubsub") || in_category("pubsub"), "'{}' pubsub flag without category", name);
+            assert!(!has("denyoom") || has("write"), "'{}' denyoom without write", name);
+            assert!(in_category("fast") != in_category("slow") || flags.is_empty(), "'{}' must be either fast or slow", name);
+            if first == 0 {
+                assert_eq!((last, step), (0, 0), "'{}' has key positions without a first key", name);
+                assert_eq!(access, "", "'{}' has a key access without keys", name);
+            } else {
+                assert!(KEY_ACCESS.contains(&access), "'{}' has unknown key access '{}'", name, access);
+                assert_eq!(access == "RO", has("readonly"), "'{}' key access and readonly flag differ", name);
+                assert!(step > 0, "'{}' has a zero key step", name);
+                assert!(last < 0 || last >= first as i64, "'{}' last key before first key", name);
+                assert!(arity.unsigned_abs() as usize > first, "'{}' arity does not cover its first key", name);
+                assert!(has("write") || has("readonly"), "'{}' has keys but is neither write nor readonly", name);
+            }
+        }
+    }
+
+    #[test]
+    fn arity_is_checked_from_the_table() {
+        Command::load_commands();
+        let wrong = |name: &str| Err(Error::WrongArity(name.to_string()));
+        let arity = |name: &str, mut args: Parse| Command::lookup(name).unwrap().check_arity(&mut args);
+        assert_eq!(arity("get", parse(&["key"])), Ok(()));
+        assert_eq!(arity("get", parse(&[])), wrong("get"));
+        assert_eq!(arity("get", parse(&["key", "extra"])), wrong("get"));
+        assert_eq!(arity("mset", parse(&["a", "1", "b", "2", "c", "3"])), Ok(()));
+        assert_eq!(arity("mset", parse(&["a"])), wrong("mset"));
+        assert_eq!(arity("ping", parse(&[])), Ok(()));
+        assert_eq!(arity("client", parse(&[])), wrong("client"));
+        assert_eq!(arity("client", parse(&["id"])), Ok(()));
+        assert_eq!(arity("client", parse(&["ID", "extra"])), wrong("client|id"));
+        // 不存在的子命令由命令自己回复错误
+        // Unknown subcommands are reported by the command itself
+        assert_eq!(arity("client", parse(&["nosuch"])), Ok(()));
+    }
+
+    #[test]
+    fn keys_follow_the_key_positions() {
+        Command::load_commands();
+        let args = |args: &[&str]| args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>();
+        let keys = |name: &str, args: &[String]| {
+            let command = Command::lookup(name).unwrap();
+            command.keys(args).into_iter().cloned().collect::<Vec<_>>()
+        };
+        assert_eq!(keys("mset", &args(&["a", "1", "b", "2"])), vec!["a", "b"]);
+        assert_eq!(keys("blpop", &args(&["list", "0"])), vec!["list"]);
+        assert_eq!(keys("del", &args(&["a", "b", "c"])), vec!["a", "b", "c"]);
+        assert!(keys("ping", &args(&["message"])).is_empty());
+    }
+
+    #[test]
+    fn key_permissions_follow_the_key_access() {
+        use crate::acl::{self, Denied};
+        Command::load_commands();
+        let rules = |rules: &[&str]| rules.iter().map(|rule| rule.to_string()).collect::<Vec<_>>();
+        acl::set_user("key-writer", &rules(&["on", "nopass", "+@all", "%W~*"])).unwrap();
+        acl::set_user("key-reader", &rules(&["on", "nopass", "+@all", "%R~*"])).unwrap();
+        acl::set_user("key-split", &rules(&["on", "nopass", "+@all", "%R~*", "%W~[a-z]*"])).unwrap();
+        acl::set_user("key-all", &rules(&["on", "nopass", "+@all", "~*"])).unwrap();
+        let check = |user: &str, command: &str, args: &[&str]| acl::check(user, command, &rules(args));
+        let denied = |key: &str| Err(Denied::Key(key.to_string()));
+        // 读取并修改值的命令需要读和写两种权限
+        // Commands reading and modifying the value need both read and write permissions
+        for (command, args) in [("incr", &["counter"][..]), ("append", &["s", "x"]), ("lpop", &["list"]), ("hdel", &["h", "f"])] {
+            assert_eq!(check("key-writer", command, args), denied(args[0]), "{}", command);
+            assert_eq!(check("key-reader", command, args), denied(args[0]), "{}", command);
+            // 读和写必须来自同一个模式
+            // Read and write must come from the same pattern
+            assert_eq!(check("key-split", command, args), denied(args[0]), "{}", command);
+            assert_eq!(check("key-all", command, args), Ok(()), "{}", command);
+        }
+        assert_eq!(check("key-writer", "set", &["a", "1"]), Ok(()));
+        assert_eq!(check("key-writer", "del", &["a"]), Ok(()));
+        assert_eq!(check("key-writer", "get", &["a"]), denied("a"));
+        assert_eq!(check("key-reader", "get", &["a"]), Ok(()));
+        assert_eq!(check("key-reader", "set", &["a", "1"]), denied("a"));
+    }
+
+    #[test]
+    fn dataset_memory_follows_modifications() {
+        let mut db = Arc::new(Mutex::new(Db::new()));
+        let recount = |db: &Db| db.iter().map(|(key, entry)| crate::db::entry_memory(key, entry)).sum::<usize>();
+        for command in [
+            &["set", "a", "1"][..],
+            &["append", "a", "more"],
+            &["set", "a", "x"],
+            &["rpush", "list", "1", "2", "3"],
+            &["lpop", "list"],
+            &["hset", "hash", "field", "value"],
+            &["hdel", "hash", "field"],
+            &["set", "b", "1", "px", "1"],
+            &["del", "a"],
+        ] {
+            let (_, _, _, command_fn, _) = COMMANDS.iter().find(|entry| entry.0 == command[0]).unwrap();
+            command_fn(&mut db, &mut parse(&command[1..])).unwrap();
+            let db = db.lock().unwrap();
+            assert_eq!(db.dataset_memory(), recount(&db), "after {:?}", command);
+        }
+        std::thread::sleep(std::time::Duration::from_millis(2));
+        let mut db = db.lock().unwrap();
+        assert!(!db.exists("b"));
+        assert_eq!(db.dataset_memory(), recount(&db));
+    }
+}
@@ -3,25 +3,190 @@ use std::{env, fs};
 use lazy_static::lazy_static;
 use std::sync::RwLock;
 use tokio::io;
+use crate::notify;
 
 // 配置结构体
 #[derive(Debug, Clone, Deserialize)]
 pub struct AofConfig {
     pub enabled: bool,          // 是否启用AOF
-    pub appendfsync: u64,       // AOF写入间隔时间，以秒为单位
-    pub file_path: String,      // AOF文件存储位置
+    #[serde(default)]
+    pub appendfsync: AppendFsync,       // AOF 同步到磁盘的策略：always、everysec、no
+    #[serde(default)]
+    pub no_appendfsync_on_rewrite: bool,    // 后台保存或重写期间是否不同步到磁盘
+    pub file_path: String,      // AOF文件名，AOF 目录中的文件都以它为前缀；旧版本的单个 AOF 文件在启动时移入 AOF 目录
+    #[serde(default = "default_aof_dir_name")]
+    pub dir_name: String,       // AOF 目录，保存基础文件、增量文件和清单
+    #[serde(default = "default_auto_rewrite_percentage")]
+    pub auto_rewrite_percentage: u64,   // AOF 文件相对上次重写后的大小增长该百分比时自动重写，0 表示不自动重写
+    #[serde(default = "default_auto_rewrite_min_size")]
+    pub auto_rewrite_min_size: u64,     // 自动重写要求的最小文件大小（字节）
+    #[serde(default = "default_true")]
+    pub load_truncated: bool,   // 加载时文件末尾的命令不完整时，是否截断到最后一条完整的命令并继续启动
+    #[serde(default = "default_true")]
+    pub use_rdb_preamble: bool, // 重写时是否把基础文件写为 RDB 格式，加载更快
+}
+
+// AOF 同步到磁盘的策略
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AppendFsync {
+    Always,                     // 每次写入后同步，写命令的回复在同步之后发送
+    #[default]
+    Everysec,                   // 每秒在后台同步一次，最多丢失约一秒的写入
+    No,                         // 不主动同步，由操作系统决定何时写入磁盘
+}
+
+impl AppendFsync {
+    // 解析策略名称，与配置文件中的写法相同
+    pub fn parse(name: &str) -> Option<AppendFsync> {
+        match name.to_lowercase().as_str() {
+            "always" => Some(AppendFsync::Always),
+            "everysec" => Some(AppendFsync::Everysec),
+            "no" => Some(AppendFsync::No),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            AppendFsync::Always => "always",
+            AppendFsync::Everysec => "everysec",
+            AppendFsync::No => "no",
+        }
+    }
 }
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct RdbConfig {
     pub enabled: bool,          // 是否启用RDB
-    pub save_interval: u64,     // RDB保存间隔时间，以秒为单位
+    #[serde(default = "default_save_points")]
+    pub save: Vec<(u64, u64)>,  // 保存规则，每条为 (秒数, 修改次数)：距上次保存超过该秒数且至少有该次数的修改时在后台保存，为空时不自动保存
     pub file_path: String,      // RDB文件存储位置
+    #[serde(default = "default_true")]
+    pub compression: bool,      // 是否用 LZF 压缩较长的字符串
+    #[serde(default = "default_true")]
+    pub checksum: bool,         // 是否在文件末尾写入 CRC64 校验和，并在加载时校验
+    #[serde(default = "default_true")]
+    pub stop_writes_on_bgsave_error: bool,  // 配置了保存规则且最近一次保存失败时，是否拒绝写命令
 }
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct ServerConfig {
     pub address: String,        // 服务端地址
+    #[serde(default = "default_maxclients")]
+    pub maxclients: usize,      // 最大客户端连接数
+    #[serde(default)]
+    pub maxmemory: usize,       // 内存使用上限（字节），超过时拒绝可能增加内存的命令，0 表示不限制
+    #[serde(default)]
+    pub timeout: u64,           // 客户端空闲超时时间，以秒为单位，0 表示不超时
+    #[serde(default = "default_tcp_keepalive")]
+    pub tcp_keepalive: u64,     // TCP keepalive 探测间隔，以秒为单位，0 表示关闭
+    #[serde(default = "default_tcp_backlog")]
+    pub tcp_backlog: u32,       // TCP 监听队列长度
+    #[serde(default)]
+    pub unixsocket: Option<String>,     // Unix 套接字路径，不配置时不监听
+    #[serde(default)]
+    pub unixsocketperm: Option<u32>,    // Unix 套接字文件的权限，例如 0o700
+    #[serde(default)]
+    pub client_output_buffer_limit: OutputBufferLimits,  // 各类客户端的输出缓冲区限制
+    #[serde(default)]
+    pub notify_keyspace_events: String, // 发布键空间通知的事件类别，例如 "KEA"，为空时不发布
+}
+
+// 各类客户端的输出缓冲区限制
+#[derive(Debug, Clone, Deserialize)]
+pub struct OutputBufferLimits {
+    #[serde(default = "default_normal_limit")]
+    pub normal: OutputBufferLimit,   // 普通客户端
+    #[serde(default = "default_pubsub_limit")]
+    pub pubsub: OutputBufferLimit,   // 发布/订阅客户端
+    #[serde(default = "default_replica_limit")]
+    pub replica: OutputBufferLimit,  // 从节点
+}
+
+// 输出缓冲区限制，超过硬限制或持续超过软限制时断开客户端，0 表示不限制
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct OutputBufferLimit {
+    pub hard_limit: usize,      // 硬限制，以字节为单位
+    pub soft_limit: usize,      // 软限制，以字节为单位
+    pub soft_seconds: u64,      // 允许持续超过软限制的时间，以秒为单位
+}
+
+// TLS 配置，port 为 0 时不启用
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct TlsConfig {
+    #[serde(default)]
+    pub port: u16,                      // TLS 端口，与明文端口同时监听，0 表示不启用
+    #[serde(default)]
+    pub cert_file: String,              // 服务端证书文件（PEM）
+    #[serde(default)]
+    pub key_file: String,               // 服务端私钥文件（PEM）
+    #[serde(default)]
+    pub ca_cert_file: Option<String>,   // 用于验证客户端证书的 CA 证书文件（PEM）
+    #[serde(default)]
+    pub auth_clients: TlsAuthClients,   // 是否验证客户端证书
+}
+
+// 客户端证书验证方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TlsAuthClients {
+    #[default]
+    No,                         // 不验证客户端证书
+    Optional,                   // 客户端提供证书时验证
+    Yes,                        // 客户端必须提供有效的证书
+}
+
+// 发布/订阅配置：频道缓冲区容量和订阅者跟不上发布速度时的处理方式
+#[derive(Debug, Clone, Deserialize)]
+pub struct PubSubConfig {
+    #[serde(default = "default_channel_capacity")]
+    pub channel_capacity: usize,        // 每个频道和模式缓冲的消息数，修改后只影响新建的频道
+    #[serde(default)]
+    pub lag_policy: LagPolicy,          // 订阅者的缓冲区满时的处理方式
+    #[serde(default = "default_block_timeout")]
+    pub block_timeout: u64,             // block 策略下发布者最多等待的时间，以毫秒为单位
+}
+
+// 订阅者跟不上发布速度、频道缓冲区满时的处理方式，对每个订阅的客户端分别生效
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum LagPolicy {
+    #[default]
+    DropOldest,                 // 丢弃最早的消息，并计入客户端的丢弃数量
+    Disconnect,                 // 断开落后的客户端，与 Redis 的 pubsub 输出缓冲区限制一致
+    Block,                      // 发布者最多等待 block_timeout 毫秒，超时后丢弃最早的消息
+}
+
+impl LagPolicy {
+    // 解析策略名称，与配置文件中的写法相同
+    pub fn parse(name: &str) -> Option<LagPolicy> {
+        match name.to_lowercase().as_str() {
+            "drop-oldest" => Some(LagPolicy::DropOldest),
+            "disconnect" => Some(LagPolicy::Disconnect),
+            "block" => Some(LagPolicy::Block),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            LagPolicy::DropOldest => "drop-oldest",
+            LagPolicy::Disconnect => "disconnect",
+            LagPolicy::Block => "block",
+        }
+    }
+}
+
+// 安全配置：默认用户的密码和 ACL 用户
+#[derive(Debug, Clone, Deserialize)]
+pub struct SecurityConfig {
+    #[serde(default)]
+    pub requirepass: Option<String>,    // 默认用户的密码，不配置时默认用户无需密码
+    #[serde(default)]
+    pub aclfile: Option<String>,        // ACL 用户文件，启动时加载
+    #[serde(default = "default_acllog_max_len")]
+    pub acllog_max_len: usize,          // ACL LOG 保留的最大条目数
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -29,6 +194,12 @@ pub struct Config {
     pub aof: AofConfig,         // AOF配置
     pub rdb: RdbConfig,         // RDB配置
     pub server: ServerConfig,   // 服务端配置
+    #[serde(default)]
+    pub tls: TlsConfig,         // TLS配置
+    #[serde(default)]
+    pub security: SecurityConfig,   // 安全配置
+    #[serde(default)]
+    pub pubsub: PubSubConfig,       // 发布/订阅配置
 }
 
 // 使用 lazy_static 和 RwLock 定义全局可变配置
@@ -36,20 +207,128 @@ lazy_static! {
     pub static ref CONFIG: RwLock<Config> = RwLock::new(Config {
         aof: AofConfig {
             enabled: false,
-            appendfsync: 0,
+            appendfsync: AppendFsync::default(),
+            no_appendfsync_on_rewrite: false,
             file_path: String::new(),
+            dir_name: default_aof_dir_name(),
+            auto_rewrite_percentage: default_auto_rewrite_percentage(),
+            auto_rewrite_min_size: default_auto_rewrite_min_size(),
+            load_truncated: true,
+            use_rdb_preamble: true,
         },
         rdb: RdbConfig {
             enabled: false,
-            save_interval: 0,
+            save: default_save_points(),
             file_path: String::new(),
+            compression: true,
+            checksum: true,
+            stop_writes_on_bgsave_error: true,
         },
         server: ServerConfig {
             address: String::new(),
+            maxclients: default_maxclients(),
+            maxmemory: 0,
+            timeout: 0,
+            tcp_keepalive: default_tcp_keepalive(),
+            tcp_backlog: default_tcp_backlog(),
+            unixsocket: None,
+            unixsocketperm: None,
+            client_output_buffer_limit: OutputBufferLimits::default(),
+            notify_keyspace_events: String::new(),
         },
+        tls: TlsConfig::default(),
+        security: SecurityConfig::default(),
+        pubsub: PubSubConfig::default(),
     });
 }
 
+// 默认值与 Redis 相同
+fn default_true() -> bool {
+    true
+}
+
+fn default_auto_rewrite_percentage() -> u64 {
+    100
+}
+
+fn default_auto_rewrite_min_size() -> u64 {
+    64 * 1024 * 1024
+}
+
+fn default_aof_dir_name() -> String {
+    "appendonlydir".to_string()
+}
+
+fn default_save_points() -> Vec<(u64, u64)> {
+    vec![(3600, 1), (300, 100), (60, 10000)]
+}
+
+fn default_maxclients() -> usize {
+    10000
+}
+
+fn default_tcp_keepalive() -> u64 {
+    300
+}
+
+fn default_tcp_backlog() -> u32 {
+    511
+}
+
+fn default_normal_limit() -> OutputBufferLimit {
+    OutputBufferLimit { hard_limit: 0, soft_limit: 0, soft_seconds: 0 }
+}
+
+fn default_pubsub_limit() -> OutputBufferLimit {
+    OutputBufferLimit { hard_limit: 32 * 1024 * 1024, soft_limit: 8 * 1024 * 1024, soft_seconds: 60 }
+}
+
+fn default_replica_limit() -> OutputBufferLimit {
+    OutputBufferLimit { hard_limit: 256 * 1024 * 1024, soft_limit: 64 * 1024 * 1024, soft_seconds: 60 }
+}
+
+fn default_acllog_max_len() -> usize {
+    128
+}
+
+impl Default for SecurityConfig {
+    fn default() -> Self {
+        SecurityConfig {
+            requirepass: None,
+            aclfile: None,
+            acllog_max_len: default_acllog_max_len(),
+        }
+    }
+}
+
+fn default_channel_capacity() -> usize {
+    1024
+}
+
+fn default_block_timeout() -> u64 {
+    100
+}
+
+impl Default for PubSubConfig {
+    fn default() -> Self {
+        PubSubConfig {
+            channel_capacity: default_channel_capacity(),
+            lag_policy: LagPolicy::default(),
+            block_timeout: default_block_timeout(),
+        }
+    }
+}
+
+impl Default for OutputBufferLimits {
+    fn default() -> Self {
+        OutputBufferLimits {
+            normal: default_normal_limit(),
+            pubsub: default_pubsub_limit(),
+            replica: default_replica_limit(),
+        }
+    }
+}
+
 // 解析配置文件并将结果存储到全局变量
 pub fn parse_config(file_path: &str) -> io::Result<()> {
     // 读取文件内容
@@ -84,3 +363,124 @@ pub fn get_server_config() -> ServerConfig {
     let config_lock = CONFIG.read().unwrap();
     config_lock.server.clone()
 }
+
+// 获取全局 TLS 配置
+pub fn get_tls_config() -> TlsConfig {
+    let config_lock = CONFIG.read().unwrap();
+    config_lock.tls.clone()
+}
+
+// 获取全局安全配置
+pub fn get_security_config() -> SecurityConfig {
+    let config_lock = CONFIG.read().unwrap();
+    config_lock.security.clone()
+}
+
+// 获取全局发布/订阅配置
+pub fn get_pubsub_config() -> PubSubConfig {
+    let config_lock = CONFIG.read().unwrap();
+    config_lock.pubsub.clone()
+}
+
+// 可以通过 CONFIG GET/SET 在运行时读取和修改的参数
+pub const PARAMETERS: &[&str] = &[
+    "notify-keyspace-events", "maxclients", "maxmemory", "timeout", "tcp-keepalive", "acllog-max-len",
+    "pubsub-channel-capacity", "pubsub-lag-policy", "pubsub-block-timeout", "rdbcompression", "rdbchecksum",
+    "save", "stop-writes-on-bgsave-error", "auto-aof-rewrite-percentage", "auto-aof-rewrite-min-size",
+    "appendfsync", "no-appendfsync-on-rewrite", "aof-load-truncated", "aof-use-rdb-preamble",
+];
+
+// 读取运行时参数，参数名与 Redis 相同
+pub fn get_parameter(name: &str) -> Option<String> {
+    let config = CONFIG.read().unwrap();
+    let value = match name {
+        "notify-keyspace-events" => notify::flags_to_string(notify::flags()),
+        "maxclients" => config.server.maxclients.to_string(),
+        "maxmemory" => config.server.maxmemory.to_string(),
+        "timeout" => config.server.timeout.to_string(),
+        "tcp-keepalive" => config.server.tcp_keepalive.to_string(),
+        "acllog-max-len" => config.security.acllog_max_len.to_string(),
+        "pubsub-channel-capacity" => config.pubsub.channel_capacity.to_string(),
+        "pubsub-lag-policy" => config.pubsub.lag_policy.name().to_string(),
+        "pubsub-block-timeout" => config.pubsub.block_timeout.to_string(),
+        "rdbcompression" => yes_no(config.rdb.compression),
+        "rdbchecksum" => yes_no(config.rdb.checksum),
+        "appendfsync" => config.aof.appendfsync.name().to_string(),
+        "no-appendfsync-on-rewrite" => yes_no(config.aof.no_appendfsync_on_rewrite),
+        "auto-aof-rewrite-percentage" => config.aof.auto_rewrite_percentage.to_string(),
+        "auto-aof-rewrite-min-size" => config.aof.auto_rewrite_min_size.to_string(),
+        "aof-load-truncated" => yes_no(config.aof.load_truncated),
+        "aof-use-rdb-preamble" => yes_no(config.aof.use_rdb_preamble),
+        "stop-writes-on-bgsave-error" => yes_no(config.rdb.stop_writes_on_bgsave_error),
+        "save" => config.rdb.save.iter().map(|(seconds, changes)| format!("{} {}", seconds, changes)).collect::<Vec<_>>().join(" "),
+        _ => return None,
+    };
+    Some(value)
+}
+
+// 布尔参数的写法与 Redis 相同
+fn yes_no(value: bool) -> String {
+    if value { "yes" } else { "no" }.to_string()
+}
+
+// 解析 "<秒数> <修改次数> ..." 格式的保存规则，空字符串表示不自动保存
+fn parse_save_points(value: &str) -> Option<Vec<(u64, u64)>> {
+    let numbers = value.split_whitespace().map(|number| number.parse::<u64>().ok()).collect::<Option<Vec<_>>>()?;
+    if numbers.len() % 2 != 0 {
+        return None;
+    }
+    Some(numbers.chunks(2).map(|pair| (pair[0], pair[1])).collect())
+}
+
+// 修改运行时参数，任何一个参数无效时都不修改
+pub fn set_parameters(parameters: &[(String, String)]) -> Result<(), String> {
+    let mut config = CONFIG.write().unwrap();
+    let mut updated = config.clone();
+    let mut notify_flags = None;
+    for (name, value) in parameters {
+        let invalid = |reason: &str| format!("ERR CONFIG SET failed (possibly related to argument '{}') - {}", name, reason);
+        let integer = || value.parse::<u64>().map_err(|_| invalid("argument couldn't be parsed into an integer"));
+        let boolean = || match value.to_lowercase().as_str() {
+            "yes" => Ok(true),
+            "no" => Ok(false),
+            _ => Err(invalid("argument must be 'yes' or 'no'")),
+        };
+        match name.as_str() {
+            "notify-keyspace-events" => {
+                notify_flags = Some(notify::parse_flags(value).map_err(|err| invalid(&err))?);
+                updated.server.notify_keyspace_events = value.clone();
+            }
+            "maxclients" => updated.server.maxclients = integer()? as usize,
+            "maxmemory" => updated.server.maxmemory = integer()? as usize,
+            "timeout" => updated.server.timeout = integer()?,
+            "tcp-keepalive" => updated.server.tcp_keepalive = integer()?,
+            "acllog-max-len" => updated.security.acllog_max_len = integer()? as usize,
+            "pubsub-channel-capacity" => match integer()? {
+                0 => return Err(invalid("argument must be greater than 0")),
+                capacity => updated.pubsub.channel_capacity = capacity as usize,
+            },
+            "pubsub-lag-policy" => {
+                updated.pubsub.lag_policy = LagPolicy::parse(value).ok_or_else(|| invalid("argument(s) must be one of the following: drop-oldest, disconnect, block"))?;
+            }
+            "pubsub-block-timeout" => updated.pubsub.block_timeout = integer()?,
+            "rdbcompression" => updated.rdb.compression = boolean()?,
+            "rdbchecksum" => updated.rdb.checksum = boolean()?,
+            "appendfsync" => {
+                updated.aof.appendfsync = AppendFsync::parse(value).ok_or_else(|| invalid("argument(s) must be one of the following: always, everysec, no"))?;
+            }
+            "no-appendfsync-on-rewrite" => updated.aof.no_appendfsync_on_rewrite = boolean()?,
+            "auto-aof-rewrite-percentage" => updated.aof.auto_rewrite_percentage = integer()?,
+            "auto-aof-rewrite-min-size" => updated.aof.auto_rewrite_min_size = integer()?,
+            "aof-load-truncated" => updated.aof.load_truncated = boolean()?,
+            "aof-use-rdb-preamble" => updated.aof.use_rdb_preamble = boolean()?,
+            "stop-writes-on-bgsave-error" => updated.rdb.stop_writes_on_bgsave_error = boolean()?,
+            "save" => updated.rdb.save = parse_save_points(value).ok_or_else(|| invalid("Invalid save parameters"))?,
+            _ => return Err(format!("ERR Unknown option or number of arguments for CONFIG SET - '{}'", name)),
+        }
+    }
+    *config = updated;
+    if let Some(flags) = notify_flags {
+        notify::set_flags(flags);
+    }
+    Ok(())
+}
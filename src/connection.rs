@@ -1,20 +1,27 @@
 //! 处理与客户端的连接，接收和返回消息
 /// Handle client connections, receive and send messages
 
+use std::fmt::Debug;
 use std::io::{Cursor, Error};
 use std::sync::{Arc};
-use tokio::net::TcpStream;
 use bytes::{Buf, BytesMut};
-use log::{error, info};
-use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use log::error;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
 use tokio::sync::Mutex;
-use crate::frame::Frame;
+use crate::error;
+use crate::frame::{self, Frame};
+
+/// 客户端连接使用的传输层，例如 TCP、Unix 套接字
+/// Transport used by a client connection, such as TCP or a Unix socket
+pub trait Stream: AsyncRead + AsyncWrite + Unpin + Send + Debug {}
+
+impl<T: AsyncRead + AsyncWrite + Unpin + Send + Debug> Stream for T {}
 
 #[derive(Debug, Clone)]
 pub struct ConnectionHandler {
-    /// TCP连接对象
-    /// TCP connection object
-    stream: Arc<Mutex<TcpStream>>,
+    /// 连接对象，与传输层无关
+    /// Connection object, independent of the transport
+    stream: Arc<Mutex<Box<dyn Stream>>>,
     /// 缓冲区
     /// Buffer
     buffer: BytesMut,
@@ -23,22 +30,39 @@ pub struct ConnectionHandler {
 impl ConnectionHandler {
     /// 定义一个连接，设置 1024 字节大小缓冲区，根据需要可适当扩容
     /// Define a connection with a 1024-byte buffer, which can be expanded as needed.
-    pub fn new(stream: Arc<Mutex<TcpStream>>) -> Self {
+    pub fn new(stream: impl Stream + 'static) -> Self {
         ConnectionHandler {
-            stream,
+            stream: Arc::new(Mutex::new(Box::new(stream))),
             buffer: BytesMut::with_capacity(1024),
         }
     }
 
+    /// 输入缓冲区中未处理的字节数
+    /// Unprocessed bytes in the input buffer
+    pub fn buffer_len(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// 输入缓冲区的剩余容量
+    /// Free capacity of the input buffer
+    pub fn buffer_free(&self) -> usize {
+        self.buffer.capacity() - self.buffer.len()
+    }
+
     /// 读取客户端发送的数据
     /// Read data sent by the client
     pub async fn read_data(&mut self) -> crate::Result<Option<Frame>> {
         loop {
+            // 先解析缓冲区中已有的数据，一次读取可能包含多条命令，也可能只包含一条命令的一部分
+            // Parse the data already in the buffer first, one read may hold several commands or only part of one
+            if let Some(data) = self.parse_data()? {
+                return Ok(Some(data));
+            }
             // 限制 `MutexGuard` 的作用域，避免它在调用 `parse_data` 时仍然存活
             // 在单独的块中处理锁的获取和释放，解决 MutexGuard 锁住变量导致后面不能可变借用的问题
             // Limit the scope of `MutexGuard` to avoid it remaining active when `parse_data` is called
             // Lock and release the mutex in a separate block to solve the problem where the MutexGuard locks the variable
-            let n = {
+            {
                 let mut stream = self.stream.lock().await;
                 // 从流中读取数据到缓冲区
                 // Read data from the stream into the buffer
@@ -49,7 +73,7 @@ impl ConnectionHandler {
                         self.buffer.clear();
                         return Err(Box::new(Error::new(std::io::ErrorKind::Other, "客户端断开连接"))); // Client disconnected
                     }
-                    Ok(n) => n,
+                    Ok(_) => {}
                     Err(err) => {
                         // 清理缓冲区
                         // Clear the buffer
@@ -57,20 +81,14 @@ impl ConnectionHandler {
                         return Err(Box::new(err));
                     }
                 }
-            };
-
-            if n > 0 {
-                if let Some(data) = self.parse_data(n)? {
-                    return Ok(Some(data));
-                }
             }
         }
     }
 
-    /// 解析数据
-    /// Parse the data
-    fn parse_data(&mut self, n: usize) -> crate::Result<Option<Frame>> {
-        let mut command = Cursor::new(&self.buffer[..n]);
+    /// 从缓冲区中解析一条完整的命令，数据不完整时返回 `None`，需要继续读取
+    /// Parse a complete command from the buffer, `None` is returned if the data is incomplete and more has to be read
+    fn parse_data(&mut self) -> crate::Result<Option<Frame>> {
+        let mut command = Cursor::new(&self.buffer[..]);
 
         // 检查命令是否符合 resp 协议规范
         // Check if the command follows the RESP protocol
@@ -86,15 +104,18 @@ impl ConnectionHandler {
                 command.set_position(0);
                 // 命令符合 RESP 协议规范，开始解析数据
                 // Command conforms to RESP protocol, start parsing data
-                let frame = Frame::parse(&mut command)?;
+                let frame = Frame::parse(&mut command).map_err(|err| error::Error::Protocol(err.to_string()))?;
                 // 移动游标位置，删除已经解析的数据
                 // Move the cursor position and delete the already parsed data
                 self.buffer.advance(len);
                 Ok(Some(frame))
             }
+            // 数据还没有接收完整
+            // The data has not been fully received yet
+            Err(frame::Error::NoMoreData) => Ok(None),
             Err(err) => {
                 error!("命令不符合 RESP 协议规范: {:?}", err);
-                Err(Box::new(Error::new(std::io::ErrorKind::Other, "命令不符合 RESP 协议规范"))) // Command does not conform to RESP protocol
+                Err(error::Error::Protocol("invalid RESP data".to_string()).into()) // 命令不符合 RESP 协议规范
             }
         }
     }
@@ -105,13 +126,20 @@ impl ConnectionHandler {
         // 将字符串转换为字节数组
         // Convert the string to a byte array
         if let Some(bytes) = response.to_bytes() {
-            // 将字节数组写入流中
-            // Write the byte array to the stream
-            self.stream.lock().await.write_all(&bytes).await?;
-            // 刷新流，确保数据立即发送
-            // Flush the stream to ensure data is sent immediately
-            self.stream.lock().await.flush().await?;
+            self.write_bytes(&bytes).await?;
         }
         Ok(())
     }
+
+    /// 发送已经编码的回复消息到客户端
+    /// Send an already encoded reply message to the client
+    pub async fn write_bytes(&mut self, bytes: &[u8]) -> crate::Result<()> {
+        // 将字节数组写入流中
+        // Write the byte array to the stream
+        self.stream.lock().await.write_all(bytes).await?;
+        // 刷新流，确保数据立即发送
+        // Flush the stream to ensure data is sent immediately
+        self.stream.lock().await.flush().await?;
+        Ok(())
+    }
 }
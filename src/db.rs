@@ -1,25 +1,109 @@
 use std::collections::{HashMap, HashSet, VecDeque};
-use std::fs::File;
-use std::io;
-use std::io::Write;
 use std::pin::Pin;
 use std::sync::Arc;
-use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use std::sync::Mutex;
 use tokio::sync::broadcast;
-use tokio_stream::{Stream, StreamExt};
+use tokio_stream::Stream;
 use bytes::Bytes;
-use crate::persistence::aof::propagate_aof;
+use crate::config::get_pubsub_config;
+use crate::persistence::aof;
+use crate::{glob, notify, slot, stats, tracking};
 
 /// 定义一个类型别名 Messages，表示一个动态的异步流。
-/// 这个异步流用于处理字节数据（Bytes），并且可以跨线程安全地传递。
-/// 使用 Pin<Box<dyn Stream<Item = Bytes> + Send>> 的原因如下：
-/// - dyn Stream<Item = Bytes>：允许动态地处理不同类型的异步流，
-///   只要它们产生的数据类型是 Bytes。这提供了灵活性，可以支持多种数据源。
+/// 这个异步流用于处理发布的消息（Message），并且可以跨线程安全地传递。
+/// 使用 Pin<Box<dyn Stream<Item = Message> + Send>> 的原因如下：
+/// - dyn Stream<Item = Message>：允许动态地处理不同类型的异步流，
+///   只要它们产生的数据类型是 Message。这提供了灵活性，可以支持多种数据源。
 /// - Pin<Box<...>>：确保异步流在内存中的位置不会改变。这是异步运行时（如 Tokio）
 ///   的要求，以避免悬挂指针或其他内存安全问题。
 /// - + Send：确保这个异步流可以在多个线程之间安全地传递，这是并发编程中的一个重要特性。
-pub(crate) type Messages = Pin<Box<dyn Stream<Item = Bytes> + Send>>;
+pub(crate) type Messages = Pin<Box<dyn Stream<Item = Message> + Send>>;
+
+/// 发布的消息，模式订阅需要知道消息实际发布到的频道
+/// A published message, pattern subscriptions need to know the channel it was actually published to.
+#[derive(Clone, Debug)]
+pub struct Message {
+    /// 消息发布到的频道
+    /// The channel the message was published to
+    pub channel: String,
+    /// 消息内容
+    /// The message content
+    pub payload: Bytes,
+}
+
+/// 频道或模式的广播，记录发布到其中的消息数，用于计算订阅者的积压
+/// The broadcast of a channel or pattern, counting the messages published to it to compute subscriber lag.
+#[derive(Clone, Debug)]
+struct Channel {
+    sender: broadcast::Sender<Message>,
+    /// 缓冲区容量，取自创建频道时的配置
+    /// Buffer capacity, taken from the configuration when the channel was created
+    capacity: usize,
+    /// 发布到该频道的消息数
+    /// Number of messages published to the channel
+    published: Arc<AtomicU64>,
+}
+
+impl Channel {
+    fn new() -> Channel {
+        let capacity = get_pubsub_config().channel_capacity;
+        let (sender, _) = broadcast::channel(capacity);
+        Channel { sender, capacity, published: Arc::new(AtomicU64::new(0)) }
+    }
+
+    fn subscribe(&self) -> Subscription {
+        let published = self.published.load(Ordering::Relaxed);
+        Subscription {
+            receiver: self.sender.subscribe(),
+            lag: Lag { published: self.published.clone(), processed: Arc::new(AtomicU64::new(published)) },
+        }
+    }
+
+    fn send(&self, message: Message) -> usize {
+        self.published.fetch_add(1, Ordering::Relaxed);
+        self.sender.send(message).unwrap_or(0)
+    }
+
+    /// 最慢的订阅者的缓冲区是否已满，再发布会丢弃它最早的消息
+    /// Whether the buffer of the slowest subscriber is full, publishing more would drop its oldest message
+    fn is_full(&self) -> bool {
+        self.sender.receiver_count() > 0 && self.sender.len() >= self.capacity
+    }
+}
+
+/// 一个订阅的消息接收端及其积压
+/// The message receiver of a subscription and its lag.
+pub struct Subscription {
+    pub receiver: broadcast::Receiver<Message>,
+    pub lag: Lag,
+}
+
+/// 订阅的积压：订阅之后发布的消息中，订阅者还没有处理（接收或丢弃）的数量
+/// Lag of a subscription: messages published since subscribing that the subscriber has not handled (received or dropped) yet.
+///
+/// 由发布者和订阅者各自的计数得出，其他连接无需等待订阅者即可查询。
+/// Derived from the publisher's and the subscriber's own counters, so other connections can query it without waiting on the subscriber.
+#[derive(Clone, Debug)]
+pub struct Lag {
+    published: Arc<AtomicU64>,
+    processed: Arc<AtomicU64>,
+}
+
+impl Lag {
+    /// 尚未处理的消息数
+    /// Number of messages not handled yet
+    pub fn pending(&self) -> u64 {
+        self.published.load(Ordering::Relaxed).saturating_sub(self.processed.load(Ordering::Relaxed))
+    }
+
+    /// 订阅者接收或丢弃了 `count` 条消息
+    /// The subscriber received or dropped `count` messages
+    pub fn advance(&self, count: u64) {
+        self.processed.fetch_add(count, Ordering::Relaxed);
+    }
+}
 
 #[derive(Debug)]
 pub struct DbHolder {
@@ -30,13 +114,87 @@ pub struct Db {
     storage: HashMap<String, DbEntry>,
     /// 发布/订阅模式
     /// A publish/subscribe model, where the key is the channel and the value is the broadcast sender for that channel.
-    pub_sub: HashMap<String, broadcast::Sender<Bytes>>,
-    /// 记录发布/订阅模式下，通配符的广播
-    /// Records the broadcast for the publish/subscribe pattern with wildcard.
-    psubscribes: HashMap<String, broadcast::Sender<Bytes>>,
+    pub_sub: HashMap<String, Channel>,
+    /// 记录发布/订阅模式下，通配符模式的广播，键为模式
+    /// Records the broadcast for the publish/subscribe glob patterns, keyed by the pattern.
+    psubscribes: HashMap<String, Channel>,
+    /// 分片频道，与普通频道的命名空间分开，按哈希槽分组
+    /// Shard channels, in a namespace separate from regular channels, grouped by hash slot.
+    shard_channels: HashMap<u16, HashMap<String, Channel>>,
+    /// 正在进行的后台快照，没有时为 None
+    /// The background snapshot in progress, None if there is none.
+    snapshot: Option<Snapshot>,
+    /// 估算的数据集内存，键被修改时增量维护，不包含 `touched` 中的键的变化
+    /// Estimated memory of the dataset, kept up to date as keys are modified, without the change of the key in
+    /// `touched`
+    memory: usize,
+    /// 最近一个可能改变大小的键和它当时的大小。值可能在 [`Db::get_dbtype_mut`] 返回之后被原地修改，
+    /// 所以下一次修改数据或读取内存时才重新计算它的大小
+    /// The latest key whose size may change, with its size at that time. Its value may be modified in place after
+    /// [`Db::get_dbtype_mut`] returns, so its size is only computed again on the next modification or memory read
+    touched: Option<(String, usize)>,
+    /// 设置了过期时间的键，定期删除只在其中采样
+    /// Keys with an expiration, active expiration only samples these.
+    volatile: VolatileKeys,
 }
 
-#[derive(Clone, Debug)]
+/// 后台快照的写时复制状态：快照开始时的键中还没有保存的那些，以及其中在保存之前被修改或删除的键的原值
+/// Copy-on-write state of a background snapshot: the keys present when the snapshot started that are not saved yet,
+/// and the original entries of those modified or deleted before being saved.
+#[derive(Clone, Debug, Default)]
+struct Snapshot {
+    pending: HashSet<String>,
+    originals: HashMap<String, DbEntry>,
+}
+
+/// 设置了过期时间的键的集合，可以按游标轮流取出一批键，增删都是 O(1)
+/// The set of keys with an expiration, batches of keys can be taken in turn with a cursor, adding and removing are
+/// O(1).
+#[derive(Clone, Debug, Default)]
+struct VolatileKeys {
+    keys: Vec<String>,
+    /// 键在 `keys` 中的位置
+    /// Position of a key in `keys`
+    index: HashMap<String, usize>,
+    /// 下一次采样开始的位置
+    /// Position where the next sample starts
+    cursor: usize,
+}
+
+impl VolatileKeys {
+    fn insert(&mut self, key: &str) {
+        if !self.index.contains_key(key) {
+            self.index.insert(key.to_string(), self.keys.len());
+            self.keys.push(key.to_string());
+        }
+    }
+
+    fn remove(&mut self, key: &str) {
+        if let Some(position) = self.index.remove(key) {
+            // 最后一个键移到被删除的位置
+            // The last key moves into the removed position
+            self.keys.swap_remove(position);
+            if let Some(moved) = self.keys.get(position) {
+                self.index.insert(moved.clone(), position);
+            }
+        }
+    }
+
+    /// 从游标开始取出最多 `count` 个键，到末尾后从头开始
+    /// Take up to `count` keys starting at the cursor, wrapping around at the end.
+    fn sample(&mut self, count: usize) -> Vec<String> {
+        let len = self.keys.len();
+        if len == 0 {
+            return Vec::new();
+        }
+        let count = count.min(len);
+        let start = self.cursor % len;
+        self.cursor = (start + count) % len;
+        (start..start + count).map(|i| self.keys[i % len].clone()).collect()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
 pub struct DbEntry {
     /// 基本数据结构的数据类型
     /// The data type of the basic structure.
@@ -46,7 +204,7 @@ pub struct DbEntry {
     pub(crate) expiration: Option<u64>,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum DbType {
     String(String),
     Hash(HashMap<String, String>),
@@ -58,9 +216,11 @@ pub enum DbType {
 
 impl DbHolder {
     pub fn new() -> Self {
-        Self {
-            db: Arc::new(Mutex::new(Db::new())),
-        }
+        let db = Arc::new(Mutex::new(Db::new()));
+        // 开启定时任务，定时处理过期的键值
+        // Start a periodic task to clean up expired keys.
+        tokio::spawn(periodic_cleanup(db.clone(), ACTIVE_EXPIRE_INTERVAL));
+        Self { db }
     }
 
     pub fn get_db(&self) -> Arc<Mutex<Db>> {
@@ -70,23 +230,118 @@ impl DbHolder {
 
 impl Db {
     pub fn new() -> Db {
-        let db = Db {
+        Db {
             storage: HashMap::new(),
             pub_sub: HashMap::new(),
             psubscribes: HashMap::new(),
-        };
-        // 开启定时任务，定时处理过期的键值
-        // Start a periodic task to clean up expired keys.
-        tokio::spawn(periodic_cleanup(db.clone(), Duration::from_secs(60)));
-        db
+            shard_channels: HashMap::new(),
+            snapshot: None,
+            memory: 0,
+            touched: None,
+            volatile: VolatileKeys::default(),
+        }
     }
     pub fn iter(&self) -> impl Iterator<Item = (&String, &DbEntry)> {
         self.storage.iter()
     }
 
+    /// 键的数量、设置了过期时间的键的数量，以及这些键的平均剩余生存时间（毫秒），用于 `INFO keyspace`
+    /// Number of keys, number of keys with an expiration and their average remaining time to live in milliseconds,
+    /// used by `INFO keyspace`.
+    pub fn keyspace_info(&self) -> (usize, usize, u64) {
+        let now = now_millis();
+        let ttls: Vec<u64> = self.storage.values().filter_map(|entry| entry.expiration).map(|exp| exp.saturating_sub(now)).collect();
+        let avg_ttl = if ttls.is_empty() { 0 } else { ttls.iter().sum::<u64>() / ttls.len() as u64 };
+        (self.storage.len(), ttls.len(), avg_ttl)
+    }
+
+    /// 估算数据集占用的内存，包括键、值和每个条目的固定开销，用于 `INFO memory` 和 maxmemory。
+    /// 内存在键被修改时增量维护，这里不遍历数据集
+    /// Estimate the memory used by the dataset, including keys, values and a fixed overhead per entry, used by
+    /// `INFO memory` and maxmemory. The memory is kept up to date as keys are modified, the dataset is not scanned here.
+    pub fn dataset_memory(&self) -> usize {
+        match &self.touched {
+            Some((key, old)) => self.memory - old + self.key_memory(key),
+            None => self.memory,
+        }
+    }
+
+    fn key_memory(&self, key: &str) -> usize {
+        self.storage.get_key_value(key).map_or(0, |(key, entry)| entry_memory(key, entry))
+    }
+
+    /// 键的大小即将改变：先计入上一个键的变化，再记下这个键现在的大小
+    /// The size of a key is about to change: account for the change of the previous key, then record the current
+    /// size of this one.
+    fn track_memory(&mut self, key: &str) {
+        if let Some((touched, old)) = self.touched.take() {
+            self.memory = self.memory - old + self.key_memory(&touched);
+        }
+        self.touched = Some((key.to_string(), self.key_memory(key)));
+    }
+
+    /// 开始后台快照，返回此刻的所有键。之后这些键在保存之前被修改或删除时，先保留原值
+    /// Start a background snapshot, returning every key present now. Until they are saved, the original entries of
+    /// these keys are kept when they are modified or deleted.
+    pub fn begin_snapshot(&mut self) -> Vec<String> {
+        let keys: Vec<String> = self.storage.keys().cloned().collect();
+        self.snapshot = Some(Snapshot { pending: keys.iter().cloned().collect(), originals: HashMap::new() });
+        keys
+    }
+
+    /// 按快照开始时的内容保存一批键，已经不存在的键是之后新建又删除的，直接跳过
+    /// Save a batch of keys as they were when the snapshot started, keys that no longer exist were created and
+    /// deleted since then and are skipped.
+    pub fn snapshot_entries(&mut self, keys: &[String], mut save: impl FnMut(&str, &DbEntry)) {
+        let Some(snapshot) = self.snapshot.as_mut() else {
+            return;
+        };
+        for key in keys {
+            if !snapshot.pending.remove(key) {
+                continue;
+            }
+            if let Some(entry) = snapshot.originals.remove(key) {
+                save(key, &entry);
+            } else if let Some(entry) = self.storage.get(key) {
+                save(key, entry);
+            }
+        }
+    }
+
+    /// 结束后台快照，释放保留的原值
+    /// End the background snapshot, releasing the kept original entries.
+    pub fn end_snapshot(&mut self) {
+        self.snapshot = None;
+    }
+
+    /// 键在被修改或删除之前调用，后台快照还没有保存它时保留原值
+    /// Called before a key is modified or deleted, keeping its original entry if the background snapshot has not
+    /// saved it yet.
+    fn copy_on_write(&mut self, key: &str) {
+        if let Some(snapshot) = self.snapshot.as_mut()
+            && snapshot.pending.contains(key)
+            && !snapshot.originals.contains_key(key)
+            && let Some(entry) = self.storage.get(key)
+        {
+            snapshot.originals.insert(key.to_string(), entry.clone());
+        }
+    }
+
+    /// 有订阅者的分片频道数量，用于 `INFO stats`
+    /// Number of shard channels with subscribers, used by `INFO stats`.
+    pub fn shard_channel_count(&self) -> usize {
+        self.shard_channels.values().flatten().filter(|(_, channel)| channel.sender.receiver_count() > 0).count()
+    }
+
     /// 获取DbType的可变引用
     /// Get a mutable reference to the DbType of a given key.
     pub fn get_dbtype_mut(&mut self, key: &str) -> Option<&mut DbType> {
+        tracking::remember_key(key);
+        if self.expire_if_needed(key) {
+            return None;
+        }
+        self.copy_on_write(key);
+        self.track_memory(key);
         match self.storage.get_mut(key) {
             Some(entry) => Some(&mut entry.value),
             None => None,
@@ -96,75 +351,100 @@ impl Db {
     /// 设置键值并可指定过期时间（单位：毫秒）
     /// Set the key-value pair with an optional expiration time (in milliseconds).
     pub fn set(&mut self, key: &str, value: DbType, expiration_ms: Option<u64>) {
-        let expiration_time = self.calculate_expiration(expiration_ms);
+        let expiration = self.calculate_expiration(expiration_ms);
+        self.set_at(key, value, expiration);
+    }
 
-        let entry = DbEntry {
-            value,
-            expiration: expiration_time,
-        };
+    /// 设置键值并可指定过期的 Unix 时间（单位：毫秒）
+    /// Set the key-value pair with an optional expiration as a Unix time (in milliseconds).
+    pub fn set_at(&mut self, key: &str, value: DbType, expiration: Option<u64>) {
+        // 存储数据
+        self.copy_on_write(key);
+        self.track_memory(key);
+        self.track_expiration(key, expiration);
+        self.storage.insert(key.to_string(), DbEntry { value, expiration });
+        self.signal_modified_key(key);
+    }
 
-        // 传播到 AOF
-        self.propagate_aof_if_needed(key, &entry);
+    /// 加载持久化数据时插入一个条目，过期时间是绝对时间（毫秒），不传播到 AOF
+    /// Insert an entry while loading persisted data, the expiration is an absolute time in milliseconds, nothing
+    /// is propagated to AOF.
+    pub fn load_entry(&mut self, key: String, value: DbType, expiration: Option<u64>) {
+        self.copy_on_write(&key);
+        self.track_memory(&key);
+        self.track_expiration(&key, expiration);
+        self.storage.insert(key, DbEntry { value, expiration });
+    }
 
-        // 存储数据
-        self.storage.insert(key.to_string(), entry);
+    /// 设置键的过期时间，`expiration` 是绝对时间（毫秒），键不存在时返回 false
+    /// Set the expiration of a key, `expiration` is an absolute time in milliseconds, false is returned if the key
+    /// doesn't exist.
+    pub fn expire_at(&mut self, key: &str, expiration: u64) -> bool {
+        if !self.exists(key) {
+            return false;
+        }
+        self.copy_on_write(key);
+        if let Some(entry) = self.storage.get_mut(key) {
+            entry.expiration = Some(expiration);
+        }
+        self.volatile.insert(key);
+        self.signal_modified_key(key);
+        true
     }
 
-    /// 设置键值并不传播到 AOF
-    /// Set the key-value pair without propagating to AOF.
-    pub fn set_without_aof(&mut self, key: &str, value: DbType, expiration_ms: Option<u64>) {
-        let expiration_time = self.calculate_expiration(expiration_ms);
+    /// 键被修改，通知追踪该键的客户端，并把正在执行的命令传播到 AOF。原地修改值的命令需要主动调用
+    /// Signal that a key was modified, notifying the clients tracking it and propagating the command being executed
+    /// to the AOF. Commands that modify a value in place must call it themselves.
+    pub fn signal_modified_key(&self, key: &str) {
+        tracking::invalidate_key(key);
+        stats::incr(&stats::DIRTY);
+        aof::propagate_call(self);
+    }
 
-        let entry = DbEntry {
-            value,
-            expiration: expiration_time,
-        };
+    /// 键的过期的 Unix 时间（毫秒），键不存在或没有过期时间时返回 None
+    /// The expiration of a key as a Unix time in milliseconds, None if the key doesn't exist or doesn't expire.
+    pub fn expiration(&self, key: &str) -> Option<u64> {
+        self.storage.get(key).and_then(|entry| entry.expiration)
+    }
 
-        // 存储数据
-        self.storage.insert(key.to_string(), entry);
+    /// 发布键空间通知，`class` 是事件类别，见 [`crate::notify`]，只有启用的类别会发布
+    /// Publish a keyspace notification, `class` is the event class from [`crate::notify`], only enabled classes are published.
+    pub fn notify_keyspace_event(&mut self, class: u32, event: &str, key: &str) {
+        if !notify::enabled(class) {
+            return;
+        }
+        let flags = notify::flags();
+        if flags & notify::KEYSPACE != 0 {
+            self.publish(&format!("__keyspace@0__:{}", key), event.to_string());
+        }
+        if flags & notify::KEYEVENT != 0 {
+            self.publish(&format!("__keyevent@0__:{}", event), key.to_string());
+        }
     }
 
     /// 计算过期时间戳
     /// Calculate expiration timestamp in milliseconds.
     fn calculate_expiration(&self, expiration_ms: Option<u64>) -> Option<u64> {
-        expiration_ms.map(|ms| {
-            let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
-            now.as_millis() as u64 + ms
-        })
-    }
-
-    /// 传播到 AOF，如果需要的话
-    /// Propagate to AOF if needed.
-    fn propagate_aof_if_needed(&self, key: &str, entry: &DbEntry) {
-        match &entry.value {
-            DbType::String(value) => {
-                let args = vec![key.to_string(), value.to_string()];
-                let args_with_expiration = entry.expiration.map(|exp| {
-                    let mut args = args.clone();
-                    args.push(exp.to_string());
-                    args
-                });
-
-                // 传播 AOF：有过期时间时带上过期时间
-                if let Some(args_with_exp) = args_with_expiration {
-                    propagate_aof("set".to_string(), args_with_exp);
-                } else {
-                    propagate_aof("set".to_string(), args);
-                }
-            }
-            _ => {
-                // 其他类型在各自command里面处理
-            }
-        }
+        expiration_ms.map(|ms| now_millis() + ms)
     }
 
     /// 获取键值，如果已过期则返回 None、惰性删除（Lazy Deletion）
     /// Get the value for a key. If it is expired, return None and lazily delete it.
     pub fn get(&mut self, key: &str) -> Option<&DbType> {
-        let expired = self.storage.get(key)
-            .map_or(false, |entry| self.is_expired(entry));
-        if expired {
-            self.storage.remove(key);
+        tracking::remember_key(key);
+        if self.expire_if_needed(key) {
+            return None;
+        }
+        self.storage.get(key).map(|entry| &entry.value)
+    }
+
+    /// 读命令获取键值，键不存在时发布 `keymiss` 通知
+    /// Get the value for a key on behalf of a read command, publishing a `keymiss` notification if it doesn't exist.
+    pub fn lookup_read(&mut self, key: &str) -> Option<&DbType> {
+        let hit = self.get(key).is_some();
+        stats::record_lookup(hit);
+        if !hit {
+            self.notify_keyspace_event(notify::KEY_MISS, "keymiss", key);
             return None;
         }
         self.storage.get(key).map(|entry| &entry.value)
@@ -176,118 +456,358 @@ impl Db {
         if !self.exists(key) {
             return false;
         }
-        self.storage.remove(key).is_some()
+        self.copy_on_write(key);
+        self.track_memory(key);
+        self.volatile.remove(key);
+        let removed = self.storage.remove(key).is_some();
+        if removed {
+            self.signal_modified_key(key);
+        }
+        removed
     }
 
     /// 检查键值是否存在
     /// Check if the key exists.
     pub fn exists(&mut self, key: &str) -> bool {
-        match self.storage.get(key) {
-            Some(entry) if !self.is_expired(entry) => true,
-            Some(_) => {
-                self.storage.remove(key);
-                false
+        tracking::remember_key(key);
+        !self.expire_if_needed(key) && self.storage.contains_key(key)
+    }
+
+    /// 键已过期时惰性删除（Lazy Deletion），并发布 `expired` 通知，返回是否删除
+    /// Lazily delete the key if it has expired and publish an `expired` notification, returning whether it was deleted.
+    fn expire_if_needed(&mut self, key: &str) -> bool {
+        let expired = self.storage.get(key).is_some_and(|entry| self.is_expired(entry));
+        if expired {
+            self.remove_expired(key);
+        }
+        expired
+    }
+
+    /// 删除已过期的键。过期删除不传播正在执行的命令，AOF 中的过期时间是绝对时间，加载时同样会过期
+    /// Remove an expired key. Expirations don't propagate the command being executed, expiration times in the AOF
+    /// are absolute so the key expires when loading too.
+    fn remove_expired(&mut self, key: &str) {
+        self.copy_on_write(key);
+        self.track_memory(key);
+        self.volatile.remove(key);
+        self.storage.remove(key);
+        stats::incr(&stats::EXPIRED_KEYS);
+        tracking::invalidate_key(key);
+        stats::incr(&stats::DIRTY);
+        self.notify_keyspace_event(notify::EXPIRED, "expired", key);
+    }
+
+    /// 记录键是否设置了过期时间
+    /// Record whether a key has an expiration.
+    fn track_expiration(&mut self, key: &str, expiration: Option<u64>) {
+        match expiration {
+            Some(_) => self.volatile.insert(key),
+            None => self.volatile.remove(key),
+        }
+    }
+
+    /// 在设置了过期时间的键中采样最多 `count` 个，删除其中已过期的键，返回采样的数量和删除的数量
+    /// Sample up to `count` keys with an expiration and remove the expired ones, returning the number of keys
+    /// sampled and removed.
+    pub fn expire_sample(&mut self, count: usize) -> (usize, usize) {
+        let sample = self.volatile.sample(count);
+        let now = now_millis();
+        let mut expired = 0;
+        for key in &sample {
+            if self.storage.get(key).is_some_and(|entry| entry.expiration.is_some_and(|expiration| expiration <= now)) {
+                self.remove_expired(key);
+                expired += 1;
             }
-            None => false,
         }
+        (sample.len(), expired)
     }
 
     /// 检查键值是否过期
     /// Check if the key-value entry is expired.
     fn is_expired(&self, entry: &DbEntry) -> bool {
         if let Some(expiration) = entry.expiration {
-            let now = SystemTime::now()
-                .duration_since(UNIX_EPOCH)
-                .unwrap()
-                .as_millis() as u64; // 毫秒时间戳
-            // Millisecond timestamp
-            now >= expiration
+            now_millis() >= expiration
         } else {
             false
         }
     }
 
-    /// 订阅频道
-    /// Subscribe to a channel.
-    pub fn subscribe(&mut self, channel: &str) -> &mut broadcast::Sender<Bytes> {
-        self.pub_sub.entry(channel.to_string())
-            .or_insert_with(|| {
-                let (sender, _) = broadcast::channel(1024);
-                sender
-            })
+    /// 订阅频道，频道的缓冲区容量取自 `pubsub.channel_capacity`
+    /// Subscribe to a channel, whose buffer capacity is taken from `pubsub.channel_capacity`.
+    pub fn subscribe(&mut self, channel: &str) -> Subscription {
+        self.pub_sub.entry(channel.to_string()).or_insert_with(Channel::new).subscribe()
     }
 
-    /// Subscribe to a channel with wildcard support.
-    /// This function checks if the channel name ends with a wildcard character (`*`).
-    /// If it does, the subscription is handled under the `psubscribe` pattern, allowing wildcard matching.
-    /// Otherwise, the subscription behaves like a regular `subscribe` to the specific channel.
+    /// Subscribe to a glob pattern, see [`crate::glob`] for the supported syntax.
+    /// Messages published to any channel matching the pattern are delivered to its subscribers.
     ///
-    /// 订阅频道，允许使用通配符。
-    /// 该函数会检查频道名称是否以通配符字符（`*`）结尾。如果是，它会按照 `psubscribe` 模式处理订阅，允许通配符匹配。
-    /// 否则，订阅将像普通的 `subscribe` 一样处理，针对指定的频道进行订阅。
-    pub fn psubscribe(&mut self, mut channel: &str) -> &mut broadcast::Sender<Bytes> {
-        if channel.ends_with("*") {
-            channel = &channel[..channel.len() - 1];
-            self.psubscribes.entry(channel.to_string())
-                .or_insert_with(|| {
-                    let (sender, _) = broadcast::channel(1024);
-                    sender
-                })
-        } else {
-            self.pub_sub.entry(channel.to_string())
-                .or_insert_with(|| {
-                    let (sender, _) = broadcast::channel(1024);
-                    sender
-                })
+    /// 订阅通配符模式，支持的语法见 [`crate::glob`]。
+    /// 发布到任何与该模式匹配的频道的消息都会发送给它的订阅者。
+    pub fn psubscribe(&mut self, pattern: &str) -> Subscription {
+        self.psubscribes.entry(pattern.to_string()).or_insert_with(Channel::new).subscribe()
+    }
+
+    /// 订阅者离开后释放频道，没有订阅者的频道会被删除，避免频道表无限增长
+    /// Release a channel after a subscriber left, channels without subscribers are removed so the map doesn't leak.
+    pub fn release_channel(&mut self, channel: &str) {
+        if self.pub_sub.get(channel).is_some_and(|channel| channel.sender.receiver_count() == 0) {
+            self.pub_sub.remove(channel);
+        }
+    }
+
+    /// 订阅者离开后释放模式，没有订阅者的模式会被删除
+    /// Release a pattern after a subscriber left, patterns without subscribers are removed.
+    pub fn release_pattern(&mut self, pattern: &str) {
+        if self.psubscribes.get(pattern).is_some_and(|channel| channel.sender.receiver_count() == 0) {
+            self.psubscribes.remove(pattern);
         }
     }
 
+    /// 有订阅者的频道，可以按通配符模式过滤，用于 `PUBSUB CHANNELS`
+    /// Channels with subscribers, optionally filtered by a glob pattern, used by `PUBSUB CHANNELS`.
+    pub fn channels(&self, pattern: Option<&str>) -> Vec<String> {
+        self.pub_sub
+            .iter()
+            .filter(|(name, channel)| {
+                channel.sender.receiver_count() > 0 && pattern.is_none_or(|pattern| glob::matches(pattern, name))
+            })
+            .map(|(channel, _)| channel.clone())
+            .collect()
+    }
+
+    /// 频道的订阅者数量，不包括模式订阅，用于 `PUBSUB NUMSUB`
+    /// Number of subscribers of a channel, pattern subscriptions excluded, used by `PUBSUB NUMSUB`.
+    pub fn numsub(&self, channel: &str) -> usize {
+        self.pub_sub.get(channel).map_or(0, |channel| channel.sender.receiver_count())
+    }
+
+    /// 被订阅的不同模式的数量，用于 `PUBSUB NUMPAT`
+    /// Number of distinct subscribed patterns, used by `PUBSUB NUMPAT`.
+    pub fn numpat(&self) -> usize {
+        self.psubscribes.values().filter(|channel| channel.sender.receiver_count() > 0).count()
+    }
 
     /// Publish a message to the specified channel.
-    /// Returns the total number of subscribers who received the message (from both exact and wildcard matches).
-    /// 向指定频道中发送消息。返回接收到消息的订阅者数量（包括精确匹配和通配符匹配的订阅者）。
+    /// Returns the number of clients that received the message, counting a client once for the channel
+    /// and once for every matching pattern it subscribed to, like Redis does.
+    /// 向指定频道中发送消息。返回接收到消息的客户端数量，订阅了频道的客户端计一次，
+    /// 每个匹配的模式订阅也各计一次，与 Redis 一致。
     pub fn publish(&mut self, channel: &str, message: String) -> usize {
+        let message = Message { channel: channel.to_string(), payload: Bytes::from(message) };
         let mut received_count = 0;
 
-        // Handle psubscribe with wildcard matching
+        // Handle exact channel matching in pub_sub
+        // 处理 pub_sub 中的精确频道匹配
+        if let Some(sender) = self.pub_sub.get(channel) {
+            received_count += sender.send(message.clone());
+        }
+
+        // Handle psubscribe with glob matching
         // 处理 psubscribe 的通配符匹配
-        for (pattern, sender) in self.psubscribes.iter_mut() {
-            if channel.starts_with(pattern) {  // Check if the channel starts with the pattern
-                sender.send(Bytes::from(message.clone())).unwrap_or(0);
-                received_count += 1;  // Count the subscriber
+        for (pattern, sender) in self.psubscribes.iter() {
+            if glob::matches(pattern, channel) {
+                received_count += sender.send(message.clone());
             }
         }
 
-        // Handle exact channel matching in pub_sub
-        // 处理 pub_sub 中的精确频道匹配
-        self.pub_sub.get(channel).map(|sender| {
-            sender.send(Bytes::from(message)).unwrap_or(0);
-            received_count += 1;  // Count the subscriber
-        });
-
         received_count
     }
+
+    /// 发布到该频道是否会使某个订阅者丢弃消息，即频道或匹配的模式中有缓冲区已满的订阅者
+    /// Whether publishing to the channel would make a subscriber drop messages, that is the channel or a matching
+    /// pattern has a subscriber whose buffer is full.
+    pub fn is_publish_blocked(&self, channel: &str) -> bool {
+        self.pub_sub.get(channel).is_some_and(Channel::is_full)
+            || self.psubscribes.iter().any(|(pattern, sender)| sender.is_full() && glob::matches(pattern, channel))
+    }
+
+    /// 订阅分片频道，分片频道只能由 `SPUBLISH` 发布，不匹配任何模式
+    /// Subscribe to a shard channel, which is only published to by `SPUBLISH` and never matches patterns.
+    pub fn ssubscribe(&mut self, channel: &str) -> Subscription {
+        self.shard_channels
+            .entry(slot::key_hash_slot(channel.as_bytes()))
+            .or_default()
+            .entry(channel.to_string())
+            .or_insert_with(Channel::new)
+            .subscribe()
+    }
+
+    /// 订阅者离开后释放分片频道，槽中没有频道时一并删除
+    /// Release a shard channel after a subscriber left, the slot is removed too once it has no channels.
+    pub fn release_shard_channel(&mut self, channel: &str) {
+        let slot = slot::key_hash_slot(channel.as_bytes());
+        if let Some(channels) = self.shard_channels.get_mut(&slot) {
+            if channels.get(channel).is_some_and(|channel| channel.sender.receiver_count() == 0) {
+                channels.remove(channel);
+            }
+            if channels.is_empty() {
+                self.shard_channels.remove(&slot);
+            }
+        }
+    }
+
+    /// 有订阅者的分片频道，可以按通配符模式过滤，用于 `PUBSUB SHARDCHANNELS`
+    /// Shard channels with subscribers, optionally filtered by a glob pattern, used by `PUBSUB SHARDCHANNELS`.
+    pub fn shard_channel_names(&self, pattern: Option<&str>) -> Vec<String> {
+        self.shard_channels
+            .values()
+            .flatten()
+            .filter(|(name, channel)| {
+                channel.sender.receiver_count() > 0 && pattern.is_none_or(|pattern| glob::matches(pattern, name))
+            })
+            .map(|(name, _)| name.clone())
+            .collect()
+    }
+
+    /// 分片频道的订阅者数量，用于 `PUBSUB SHARDNUMSUB`
+    /// Number of subscribers of a shard channel, used by `PUBSUB SHARDNUMSUB`.
+    pub fn shardnumsub(&self, channel: &str) -> usize {
+        self.shard_channel(channel).map_or(0, |channel| channel.sender.receiver_count())
+    }
+
+    /// 向分片频道发布消息，返回接收到消息的客户端数量
+    /// Publish a message to a shard channel, returning the number of clients that received it.
+    pub fn spublish(&mut self, channel: &str, message: String) -> usize {
+        let message = Message { channel: channel.to_string(), payload: Bytes::from(message) };
+        self.shard_channel(channel).map_or(0, |sender| sender.send(message))
+    }
+
+    /// 发布到该分片频道是否会使某个订阅者丢弃消息
+    /// Whether publishing to the shard channel would make a subscriber drop messages.
+    pub fn is_spublish_blocked(&self, channel: &str) -> bool {
+        self.shard_channel(channel).is_some_and(Channel::is_full)
+    }
+
+    fn shard_channel(&self, channel: &str) -> Option<&Channel> {
+        self.shard_channels.get(&slot::key_hash_slot(channel.as_bytes()))?.get(channel)
+    }
+}
+
+/// 当前的 Unix 时间（毫秒）
+/// The current Unix time in milliseconds.
+pub fn now_millis() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_millis() as u64
+}
+
+/// 估算一个条目占用的内存，包括键、值和固定开销
+/// Estimate the memory used by one entry, including the key, the value and a fixed overhead.
+pub(crate) fn entry_memory(key: &String, entry: &DbEntry) -> usize {
+    // 哈希表条目、`DbEntry` 和字符串头部的大致开销
+    // Rough overhead of a hash table slot, a `DbEntry` and the string headers
+    const ENTRY_OVERHEAD: usize = 64;
+    const ELEMENT_OVERHEAD: usize = 24;
+    let value = match &entry.value {
+        DbType::String(value) => value.capacity(),
+        DbType::List(list) => list.iter().map(|item| item.capacity() + ELEMENT_OVERHEAD).sum(),
+        DbType::Hash(map) => map
+            .iter()
+            .map(|(field, value)| field.capacity() + value.capacity() + 2 * ELEMENT_OVERHEAD)
+            .sum(),
+    };
+    ENTRY_OVERHEAD + key.capacity() + value
 }
 
-/// 定期删除（Active Expiration）
-/// Active expiration: a task to periodically clean up expired keys.
-async fn periodic_cleanup(mut db: Db, interval: Duration) {
+/// 定期删除的周期
+/// Interval of the active expiration cycles
+const ACTIVE_EXPIRE_INTERVAL: Duration = Duration::from_millis(100);
+/// 每次采样的设置了过期时间的键的数量
+/// Number of keys with an expiration sampled at a time
+const ACTIVE_EXPIRE_SAMPLE: usize = 20;
+/// 采样中已过期的键超过该百分比时继续采样
+/// Sampling continues while more than this percentage of the sampled keys had expired
+const ACTIVE_EXPIRE_STALE_PERCENT: usize = 25;
+/// 每个周期最多花费的时间
+/// Maximum time spent in one cycle
+const ACTIVE_EXPIRE_CYCLE_TIME: Duration = Duration::from_millis(25);
+
+/// 定期删除（Active Expiration），与 Redis 相同，每个周期在设置了过期时间的键中采样，
+/// 已过期的键比例较高时继续采样，而不是遍历整个键空间
+/// Active expiration: like Redis, every cycle samples the keys with an expiration and keeps sampling while the
+/// ratio of expired keys stays high, instead of scanning the whole keyspace.
+async fn periodic_cleanup(db: Arc<Mutex<Db>>, interval: Duration) {
     loop {
-        cleanup_expired(&mut db);
+        cleanup_expired(&db);
         tokio::time::sleep(interval).await;
     }
 }
 
-/// 清理过期的数据
-/// Cleanup expired data.
-pub fn cleanup_expired(db: &mut Db) {
-    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
-    db.storage.retain(|_, entry| {
-        match entry.expiration {
-            // Keep the non-expired entries.
-            Some(expiration) => expiration > now, // 保留未过期的条目
-            // Entries without expiration always remain.
-            None => true, // 没有过期时间的条目始终保留
-        }
-    });
+/// 执行一个定期删除的周期，每次采样之后释放锁，让其他连接的命令可以执行
+/// Run one active expiration cycle, the lock is released after every sample so commands of other connections can
+/// run.
+pub fn cleanup_expired(db: &Mutex<Db>) {
+    let start = Instant::now();
+    while start.elapsed() < ACTIVE_EXPIRE_CYCLE_TIME {
+        let (sampled, expired) = db.lock().unwrap().expire_sample(ACTIVE_EXPIRE_SAMPLE);
+        if expired * 100 <= sampled * ACTIVE_EXPIRE_STALE_PERCENT {
+            break;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn active_expiration_only_samples_keys_with_an_expiration() {
+        let db = Mutex::new(Db::new());
+        {
+            let mut db = db.lock().unwrap();
+            for i in 0..100 {
+                db.set(&format!("volatile:{}", i), DbType::String("v".to_string()), Some(1));
+                db.set(&format!("persistent:{}", i), DbType::String("v".to_string()), None);
+            }
+            // 覆盖为没有过期时间的值后不再采样
+            // Keys overwritten without an expiration are no longer sampled
+            db.set("volatile:0", DbType::String("v".to_string()), None);
+            db.del("volatile:1");
+            assert_eq!(db.volatile.keys.len(), 98);
+        }
+        std::thread::sleep(Duration::from_millis(2));
+        // 过期的键比例高时在一个周期内继续采样，直到全部删除
+        // Sampling continues within one cycle while the expired ratio is high, until all of them are removed
+        cleanup_expired(&db);
+        let mut db = db.lock().unwrap();
+        assert!(db.volatile.keys.is_empty());
+        assert_eq!(db.storage.len(), 101);
+        assert!(db.exists("volatile:0"));
+        assert_eq!(db.expire_sample(ACTIVE_EXPIRE_SAMPLE), (0, 0));
+    }
+
+    #[test]
+    fn samples_rotate_through_the_keys() {
+        let mut keys = VolatileKeys::default();
+        for key in ["a", "b", "c"] {
+            keys.insert(key);
+        }
+        assert_eq!(keys.sample(2), ["a", "b"]);
+        assert_eq!(keys.sample(2), ["c", "a"]);
+        keys.remove("a");
+        assert_eq!(keys.index["c"], 0);
+        assert_eq!(keys.sample(5), ["b", "c"]);
+    }
+
+    #[test]
+    fn channels_without_subscribers_are_released() {
+        let mut db = Db::new();
+        let first = db.subscribe("channel");
+        let second = db.subscribe("channel");
+        let pattern = db.psubscribe("chan*");
+        let shard = db.ssubscribe("shard");
+        drop(first);
+        db.release_channel("channel");
+        assert!(db.pub_sub.contains_key("channel"));
+        drop(second);
+        db.release_channel("channel");
+        assert!(db.pub_sub.is_empty());
+
+        drop(pattern);
+        db.release_pattern("chan*");
+        assert!(db.psubscribes.is_empty());
+        // 槽中没有频道时一并删除
+        // The slot is removed once it has no channels
+        drop(shard);
+        db.release_shard_channel("shard");
+        assert!(db.shard_channels.is_empty());
+    }
 }
@@ -1,32 +1,42 @@
 use lazy_static::lazy_static;
 use std::collections::HashMap;
-use std::pin::Pin;
 use std::sync::{Arc, RwLock};
+use std::sync::atomic::Ordering;
 use log::error;
 use std::sync::Mutex;
-use crate::commands::COMMANDS;
-use crate::connection::ConnectionHandler;
+use crate::commands::{COMMANDS, SUBCOMMANDS};
 use crate::db::Db;
+use crate::error::Error;
 use crate::frame::Frame;
 use crate::parse::Parse;
+use crate::stats;
 
 /// COMMAND_TABLE 存储所有命令的哈希表
 /// `COMMAND_TABLE` stores a hash map of all commands.
 lazy_static! {
-    static ref COMMAND_TABLE: Arc<RwLock<HashMap<String, Command>>> = Arc::new(RwLock::new(HashMap::new()));
+    static ref COMMAND_TABLE: Arc<RwLock<HashMap<String, Arc<Command>>>> = Arc::new(RwLock::new(HashMap::new()));
 }
 
 /// 创建命令的宏
 /// A macro to create commands.
 macro_rules! make_command {
-    ($name:expr, $description:expr, $complexity:expr, $command_fn:expr) => {
+    ($name:expr, $description:expr, $complexity:expr, $command_fn:expr, $spec:expr) => {{
+        let spec = $spec;
         Command {
             name: $name.to_string(),
             description: $description.to_string(),
             time_complexity: $complexity.to_string(),
             command_fn: Arc::new($command_fn),
+            arity: spec.arity,
+            flags: spec.flags,
+            first_key: spec.first_key,
+            last_key: spec.last_key,
+            key_step: spec.key_step,
+            key_access: spec.key_access,
+            categories: spec.categories,
+            subcommands: Vec::new(),
         }
-    };
+    }};
 }
 
 /// Command 命令细节
@@ -37,6 +47,30 @@ pub struct Command {
     pub command_fn: Arc<dyn Fn(&mut Arc<Mutex<Db>>,&mut Parse) -> crate::Result<Frame> + Send + Sync + 'static>,
     pub time_complexity: String,
     pub description: String,
+    /// 参数个数，包含命令名本身，负数表示至少需要该数量的参数
+    /// Arity including the command name itself, a negative arity means at least that many arguments
+    pub arity: i64,
+    /// 命令的标志，如 write、readonly、fast
+    /// Flags of the command, such as write, readonly and fast
+    pub flags: &'static [&'static str],
+    /// 第一个键的位置，0 表示没有键
+    /// Position of the first key, 0 means no keys
+    pub first_key: usize,
+    /// 最后一个键的位置，负数表示从末尾倒数
+    /// Position of the last key, negative counts from the end
+    pub last_key: i64,
+    /// 键之间的步长
+    /// Step between keys
+    pub key_step: usize,
+    /// 键的访问方式，如 RO、RW、OW、RM，没有键时为空
+    /// Key access such as RO, RW, OW and RM, empty without keys
+    pub key_access: &'static str,
+    /// ACL 类别
+    /// ACL categories
+    pub categories: &'static [&'static str],
+    /// 子命令，名称为 `命令|子命令`
+    /// Subcommands, named `command|subcommand`
+    pub subcommands: Vec<Command>,
 }
 
 impl Command {
@@ -54,9 +88,19 @@ impl Command {
                 return;
             }
         };
-        for &(name, description, time_complexity, command_fn) in COMMANDS.iter() {
-            let command = make_command!(name, description, time_complexity, command_fn);
-            command_map.insert(command.name.clone(), command);
+        for &(name, description, time_complexity, command_fn, spec) in COMMANDS.iter() {
+            let mut command = make_command!(name, description, time_complexity, command_fn, spec);
+            // 子命令由所属命令的处理函数分发
+            // Subcommands are dispatched by the handler of their command
+            let prefix = format!("{}|", name);
+            command.subcommands = SUBCOMMANDS
+                .iter()
+                .filter(|(subcommand, ..)| subcommand.starts_with(&prefix))
+                .map(|&(subcommand, description, time_complexity, spec)| {
+                    make_command!(subcommand, description, time_complexity, command_fn, spec)
+                })
+                .collect();
+            command_map.insert(command.name.clone(), Arc::new(command));
         }
     }
 
@@ -86,6 +130,21 @@ impl Command {
         command.map(|cmd| cmd.command_fn.clone())
     }
 
+    /// 获取命令，命令表中的命令是共享的，不需要克隆
+    /// Get a command, commands in the command table are shared so nothing is cloned.
+    pub fn lookup(name: &str) -> Option<Arc<Command>> {
+        let command_map = match COMMAND_TABLE.read() {
+            Ok(lock) => lock,
+            Err(poisoned) => {
+                // 锁被污染时的处理方式
+                // Handle the case when the lock is poisoned.
+                error!("无法获得锁: {:?}", poisoned);
+                return None;
+            }
+        };
+        command_map.get(name).cloned()
+    }
+
     /// 获取命令详情
     /// Get the details of the command.
     pub fn get_command_detail(name: &str) -> Option<Command> {
@@ -106,7 +165,7 @@ impl Command {
         let command = command_map.get(&name.to_lowercase());
         // 如果命令存在，返回命令的克隆
         // If the command exists, return a clone of the command.
-        command.map(|cmd| cmd.clone())
+        command.map(|cmd| Command::clone(cmd))
     }
 
     /// 检查命令是否存在
@@ -128,4 +187,119 @@ impl Command {
         };
         command_map.contains_key(&name.to_lowercase())
     }
+
+    /// 获取所有命令，按命令表的顺序
+    /// Get all commands, in command table order.
+    pub fn all() -> Vec<Command> {
+        let command_map = match COMMAND_TABLE.read() {
+            Ok(lock) => lock,
+            Err(poisoned) => {
+                // 锁被污染时的处理方式
+                // Handle the case when the lock is poisoned.
+                error!("无法获得锁: {:?}", poisoned);
+                return Vec::new();
+            }
+        };
+        COMMANDS.iter().filter_map(|(name, ..)| command_map.get(*name).map(|cmd| Command::clone(cmd))).collect()
+    }
+
+    /// 检查参数个数，`parse` 中是命令名之后的参数
+    /// Check the number of arguments, `parse` holds the arguments after the command name.
+    ///
+    /// 有子命令时检查子命令的参数个数，不存在的子命令由命令自己回复错误。
+    /// For commands with subcommands the subcommand's arity is checked, unknown subcommands are reported by the
+    /// command itself.
+    pub fn check_arity(&self, parse: &mut Parse) -> Result<(), Error> {
+        match self.resolve(parse) {
+            Some((command, argc)) if !command.arity_matches(argc) => Err(Error::WrongArity(command.name.to_string())),
+            _ => Ok(()),
+        }
+    }
+
+    /// 根据命令的标志检查当前能否执行：加载数据期间只能执行带有 loading 标志的命令，
+    /// 内存超过 maxmemory 时拒绝带有 denyoom 标志的命令，RDB 保存失败后按 stop-writes-on-bgsave-error 拒绝写命令，
+    /// 最近一次 AOF 写入失败时也拒绝写命令
+    /// Check whether the command can run now according to its flags: only commands with the loading flag can run
+    /// while the data is loading, commands with the denyoom flag are refused when the memory exceeds maxmemory, and
+    /// write commands are refused after a failed RDB save according to stop-writes-on-bgsave-error, and while the
+    /// latest AOF write failed.
+    pub fn check_state(&self, parse: &mut Parse) -> Result<(), Error> {
+        let Some((command, _)) = self.resolve(parse) else {
+            return Ok(());
+        };
+        if stats::LOADING.load(Ordering::Relaxed) && !command.has_flag("loading") {
+            return Err(Error::Loading);
+        }
+        if command.has_flag("denyoom") && stats::is_out_of_memory() {
+            return Err(Error::Oom);
+        }
+        if command.has_flag("write") && stats::is_rdb_write_refused() {
+            return Err(Error::Misconf);
+        }
+        if command.has_flag("write") && let Some(err) = stats::aof_status().last_error {
+            return Err(Error::AofMisconf(err));
+        }
+        Ok(())
+    }
+
+    /// 参数个数是否符合要求，`argc` 包含命令名本身
+    /// Whether the number of arguments is allowed, `argc` includes the command name itself.
+    pub fn arity_matches(&self, argc: usize) -> bool {
+        if self.arity >= 0 {
+            argc as i64 == self.arity
+        } else {
+            argc as i64 >= -self.arity
+        }
+    }
+
+    /// 命令是否有某个标志
+    /// Whether the command has a flag.
+    pub fn has_flag(&self, flag: &str) -> bool {
+        self.flags.contains(&flag)
+    }
+
+    /// 命令是否读取和修改它的键：`RW` 两者都需要，`OW` 和 `RM` 只修改，`RO` 只读取
+    /// Whether the command reads and modifies its keys: `RW` needs both, `OW` and `RM` only modify and `RO` only reads.
+    pub fn key_access(&self) -> (bool, bool) {
+        match self.key_access {
+            "RO" => (true, false),
+            "RW" => (true, true),
+            "OW" | "RM" => (false, true),
+            _ => (false, false),
+        }
+    }
+
+    /// 命令参数中的键，`args` 是命令名之后的参数
+    /// The keys in the arguments of the command, `args` are the arguments after the command name.
+    pub fn keys<'a>(&self, args: &'a [String]) -> Vec<&'a String> {
+        let (first, step) = (self.first_key, self.key_step);
+        if first == 0 || args.len() < first {
+            return Vec::new();
+        }
+        let last = if self.last_key < 0 { args.len() as i64 + self.last_key + 1 } else { self.last_key };
+        if last < first as i64 {
+            return Vec::new();
+        }
+        let last = (last as usize).min(args.len());
+        args[first - 1..last].iter().step_by(step).collect()
+    }
+
+    /// 按子命令名（不包含命令名）查找子命令，不区分大小写
+    /// Find a subcommand by its name without the command name, case-insensitively.
+    pub fn subcommand(&self, name: &str) -> Option<&Command> {
+        let name = format!("{}|{}", self.name, name.to_lowercase());
+        self.subcommands.iter().find(|subcommand| subcommand.name == name)
+    }
+
+    /// 找到要执行的命令或子命令，连同参数个数（包含命令名）一起返回，不存在的子命令返回 `None`
+    /// Find the command or subcommand to run, returned along with the argument count including the command name,
+    /// `None` is returned for unknown subcommands.
+    fn resolve(&self, parse: &mut Parse) -> Option<(&Command, usize)> {
+        let argc = parse.args_number().ok()? + 1;
+        let command = match parse.peek_string() {
+            Some(sub) if !self.subcommands.is_empty() => self.subcommand(&sub)?,
+            _ => self,
+        };
+        Some((command, argc))
+    }
 }
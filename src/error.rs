@@ -0,0 +1,187 @@
+//! 命令出错时回复给客户端的错误
+//! Errors replied to the client when a command fails.
+//!
+//! 每种错误对应一个 Redis 错误前缀，命令处理函数可以直接用 `?` 返回它们，由服务端转换为错误回复。
+//! 只有协议错误会在回复后关闭连接，其它错误只回复给客户端，连接继续处理后续的命令。
+//! Every error maps to a Redis error prefix, command handlers can return them with `?` and the server turns them
+//! into error replies. Only protocol errors close the connection after the reply, other errors are just replied to
+//! the client and the connection keeps handling the following commands.
+
+use std::fmt;
+use std::num::{ParseFloatError, ParseIntError};
+use crate::frame::{self, Frame};
+use crate::parse::ParseError;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Error {
+    /// 请求不符合 RESP 协议，回复后关闭连接
+    /// The request does not follow the RESP protocol, the connection is closed after the reply
+    Protocol(String),
+    /// 对保存了其它类型的值的键执行操作
+    /// Operation against a key holding another type of value
+    WrongType,
+    /// 语法错误
+    /// Syntax error
+    Syntax,
+    /// 值不是整数或超出范围
+    /// The value is not an integer or is out of range
+    NotInteger,
+    /// 值超出范围
+    /// The value is out of range
+    OutOfRange,
+    /// 参数个数错误，包含命令名
+    /// Wrong number of arguments, holding the command name
+    WrongArity(String),
+    /// 需要认证
+    /// Authentication required
+    NoAuth,
+    /// 没有权限，包含具体的原因
+    /// Not permitted, holding the reason
+    NoPerm(String),
+    /// 内存超过 maxmemory
+    /// The memory exceeds maxmemory
+    Oom,
+    /// RDB 保存失败，开启了 stop-writes-on-bgsave-error 时拒绝写命令
+    /// The RDB save failed, write commands are refused when stop-writes-on-bgsave-error is enabled
+    Misconf,
+    /// AOF 写入或同步到磁盘失败，包含具体的原因
+    /// Writing or syncing the AOF to disk failed, holding the reason
+    AofMisconf(String),
+    /// 服务端忙，包含具体的原因
+    /// The server is busy, holding the reason
+    Busy(String),
+    /// 正在加载数据
+    /// The dataset is loading
+    Loading,
+    /// 只读的副本不能写入
+    /// A read only replica can't be written
+    ReadOnly,
+    /// 事务因为之前的错误而被丢弃
+    /// The transaction was discarded because of previous errors
+    ExecAbort,
+    /// 槽位已经迁移到其它节点
+    /// The slot has moved to another node
+    Moved { slot: u16, addr: String },
+    /// 多个键或分片频道不属于同一个槽
+    /// The keys or shard channels don't all belong to the same slot
+    CrossSlot,
+    /// 槽位正在迁移，这次请求需要到其它节点执行
+    /// The slot is migrating, this request has to run on another node
+    Ask { slot: u16, addr: String },
+    /// 其它以 `ERR` 开头的错误，不包含前缀
+    /// Other errors prefixed with `ERR`, without the prefix
+    Err(String),
+    /// 其它已经带有前缀的错误
+    /// Other errors already carrying their prefix
+    Other(String),
+}
+
+impl Error {
+    /// 是否需要关闭连接
+    /// Whether the connection has to be closed.
+    pub fn closes_connection(&self) -> bool {
+        matches!(self, Error::Protocol(_))
+    }
+
+    /// 转换为错误回复
+    /// Convert into an error reply.
+    pub fn to_frame(&self) -> Frame {
+        Frame::Error(self.to_string())
+    }
+
+    /// 把命令处理函数返回的任意错误转换为对应的错误，已经带有大写前缀的消息保持不变
+    /// Convert any error returned by a command handler into the matching error, messages already carrying an upper
+    /// case prefix are kept as they are.
+    pub fn from_boxed(err: Box<dyn std::error::Error + Send + Sync>) -> Error {
+        let err = match err.downcast::<Error>() {
+            Ok(err) => return *err,
+            Err(err) => err,
+        };
+        if err.is::<ParseError>() {
+            return Error::Syntax;
+        }
+        if err.is::<ParseIntError>() {
+            return Error::NotInteger;
+        }
+        if err.is::<ParseFloatError>() {
+            return Error::Err("value is not a valid float".to_string());
+        }
+        if err.is::<frame::Error>() {
+            return Error::Protocol(err.to_string());
+        }
+        let message = err.to_string();
+        match message.split_once(' ') {
+            Some(("ERR", rest)) => Error::Err(rest.to_string()),
+            Some((prefix, _)) if !prefix.is_empty() && prefix.chars().all(|c| c.is_ascii_uppercase()) => {
+                Error::Other(message)
+            }
+            _ => Error::Err(message),
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Protocol(message) => write!(f, "ERR Protocol error: {}", message),
+            Error::WrongType => write!(f, "WRONGTYPE Operation against a key holding the wrong kind of value"),
+            Error::Syntax => write!(f, "ERR syntax error"),
+            Error::NotInteger => write!(f, "ERR value is not an integer or out of range"),
+            Error::OutOfRange => write!(f, "ERR value is out of range"),
+            Error::WrongArity(name) => write!(f, "ERR wrong number of arguments for '{}' command", name),
+            Error::NoAuth => write!(f, "NOAUTH Authentication required."),
+            Error::NoPerm(reason) => write!(f, "NOPERM {}", reason),
+            Error::Oom => write!(f, "OOM command not allowed when used memory > 'maxmemory'."),
+            Error::Misconf => write!(
+                f,
+                "MISCONF Errors trying to SAVE the DB to disk, commands that may modify the data set are disabled \
+                 because stop-writes-on-bgsave-error is enabled. Please check the server logs for details about the RDB error."
+            ),
+            Error::AofMisconf(reason) => write!(f, "MISCONF Errors writing to the AOF file: {}", reason),
+            Error::Busy(reason) => write!(f, "BUSY {}", reason),
+            Error::Loading => write!(f, "LOADING Redis is loading the dataset in memory"),
+            Error::ReadOnly => write!(f, "READONLY You can't write against a read only replica."),
+            Error::ExecAbort => write!(f, "EXECABORT Transaction discarded because of previous errors."),
+            Error::Moved { slot, addr } => write!(f, "MOVED {} {}", slot, addr),
+            Error::CrossSlot => write!(f, "CROSSSLOT Keys in request don't hash to the same slot"),
+            Error::Ask { slot, addr } => write!(f, "ASK {} {}", slot, addr),
+            Error::Err(message) => write!(f, "ERR {}", message),
+            Error::Other(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<Error> for Frame {
+    fn from(err: Error) -> Frame {
+        err.to_frame()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn errors_map_to_redis_prefixes() {
+        assert_eq!(Error::WrongType.to_string(), "WRONGTYPE Operation against a key holding the wrong kind of value");
+        assert_eq!(Error::NoPerm("No permissions to access a key".to_string()).to_string(), "NOPERM No permissions to access a key");
+        assert_eq!(Error::Moved { slot: 3999, addr: "127.0.0.1:6381".to_string() }.to_string(), "MOVED 3999 127.0.0.1:6381");
+        assert!(Error::Misconf.to_string().starts_with("MISCONF "));
+        assert_eq!(Error::AofMisconf("No space left on device".to_string()).to_string(), "MISCONF Errors writing to the AOF file: No space left on device");
+        assert!(Error::Protocol("invalid RESP data".to_string()).closes_connection());
+        assert!(!Error::Syntax.closes_connection());
+    }
+
+    #[test]
+    fn boxed_errors_are_converted() {
+        let boxed = |err: Box<dyn std::error::Error + Send + Sync>| Error::from_boxed(err);
+        assert_eq!(boxed(Error::Loading.into()), Error::Loading);
+        assert_eq!(boxed(ParseError::EndOfStream.into()), Error::Syntax);
+        assert_eq!(boxed("x".parse::<i64>().unwrap_err().into()), Error::NotInteger);
+        assert_eq!(boxed("ERR invalid expire time".into()), Error::Err("invalid expire time".to_string()));
+        assert_eq!(boxed("NOPERM this user has no permissions".into()), Error::Other("NOPERM this user has no permissions".to_string()));
+        assert_eq!(boxed("客户端断开连接".into()), Error::Err("客户端断开连接".to_string()));
+    }
+}
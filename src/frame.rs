@@ -28,6 +28,12 @@ pub enum Frame{
     /// 数组类型，如 *2\r\n$3\r\nfoo\r\n$3\r\nbar\r\n 表示一个包含两个元素的数组
     /// Array type, such as *2\r\n$3\r\nfoo\r\n$3\r\nbar\r\n, representing an array with two elements.
     Array(Vec<Frame>),
+    /// RESP3 映射类型，如 %1\r\n+key\r\n:1\r\n
+    /// RESP3 map type, such as %1\r\n+key\r\n:1\r\n.
+    Map(Vec<(Frame, Frame)>),
+    /// RESP3 推送类型，如 >2\r\n$10\r\ninvalidate\r\n*1\r\n$3\r\nfoo\r\n，用于服务端主动推送
+    /// RESP3 push type, such as >2\r\n$10\r\ninvalidate\r\n*1\r\n$3\r\nfoo\r\n, used for server-initiated messages.
+    Push(Vec<Frame>),
 
     /// 非 RESP协议类型
     /// Non-RESP protocol type
@@ -100,7 +106,7 @@ impl Frame {
                         .map_err(|_| Error::TypeConversionError)?;
                     // 跳过对应长度，+2是跳过\r\n
                     // Skip the corresponding length, +2 to skip "\r\n".
-                    skip_bytes(command, length + 2)
+                    skip_bulk(command, length)
                 }
             }
             b':' => {
@@ -134,7 +140,11 @@ impl Frame {
     pub fn parse(command: &mut Cursor<&[u8]>) -> Result<Frame, Error> {
         match get_bytes(command)? {
             b'*' => {
-                let number = get_number(command)? as usize;
+                // 负数长度（*-1）表示空数组
+                // A negative length (*-1) is a null array.
+                let Ok(number) = usize::try_from(get_number(command)?) else {
+                    return Ok(Frame::Null);
+                };
                 let mut frames = Vec::with_capacity(number);
                 for _ in 0..number {
                     frames.push(Frame::parse(command)?);
@@ -153,13 +163,11 @@ impl Frame {
                 } else {
                     // 读取长度信息
                     // Read the length information.
-                    let len = get_number(command)? as usize;
-                    let n = len + 2;
-                    if n > command.remaining() {
-                        return Err(Error::NoMoreData);
-                    }
-                    let data = Bytes::copy_from_slice(&command.chunk()[..len]);
-                    skip_bytes(command, n)?;
+                    let len: usize = get_number(command)?
+                        .try_into()
+                        .map_err(|_| Error::TypeConversionError)?;
+                    let data = Bytes::copy_from_slice(command.chunk().get(..len).ok_or(Error::NoMoreData)?);
+                    skip_bulk(command, len)?;
                     Ok(Frame::Bulk(data.to_vec()))
                 }
             }
@@ -265,23 +273,72 @@ impl Frame {
                 Some(bytes)
             },
 
+            // 处理 Map 类型
+            // Handle Map type
+            Frame::Map(pairs) => {
+                let mut bytes = Vec::new();
+                bytes.push(b'%'); // %符号，表示映射类型
+                bytes.extend_from_slice(pairs.len().to_string().as_bytes()); // 键值对数量
+                bytes.extend_from_slice(b"\r\n"); // 换行符
+                for (key, value) in pairs {
+                    bytes.append(&mut key.to_bytes().unwrap_or_default());
+                    bytes.append(&mut value.to_bytes().unwrap_or_default());
+                }
+                Some(bytes)
+            },
+
+            // 处理 Push 类型
+            // Handle Push type
+            Frame::Push(arr) => {
+                let mut bytes = Vec::new();
+                bytes.push(b'>'); // >符号，表示推送类型
+                bytes.extend_from_slice(arr.len().to_string().as_bytes()); // 元素数量
+                bytes.extend_from_slice(b"\r\n"); // 换行符
+                for frame in arr {
+                    bytes.append(&mut frame.to_bytes().unwrap_or_default());
+                }
+                Some(bytes)
+            },
+
             // 捕获其他未处理类型
             // Capture other unhandled types
             _ => None,
         }
     }
+
+    /// 按协议版本生成映射，RESP3 为映射类型，RESP2 为字段和值交替的扁平数组
+    /// Build a map for the protocol version, a map under RESP3 and a flat array of fields and values under RESP2.
+    pub fn map(fields: Vec<(Frame, Frame)>, resp: u8) -> Frame {
+        if resp > 2 {
+            Frame::Map(fields)
+        } else {
+            Frame::Array(fields.into_iter().flat_map(|(name, value)| [name, value]).collect())
+        }
+    }
 }
 
 /// 跳过指定数量的字节
 /// Skip the specified number of bytes.
 fn skip_bytes(command: &mut Cursor<&[u8]>, n: usize) -> Result<(), Error> {
-    if !command.has_remaining() {
+    if command.remaining() < n {
         return Err(Error::NoMoreData);
     }
     command.advance(n);
     Ok(())
 }
 
+/// 跳过批量字符串的数据和结尾的 \r\n，数据后面不是 \r\n 时不符合协议
+/// Skip the data of a bulk string and its trailing \r\n, which must follow the data.
+fn skip_bulk(command: &mut Cursor<&[u8]>, len: usize) -> Result<(), Error> {
+    match command.chunk().get(len..len + 2) {
+        Some(b"\r\n") => skip_bytes(command, len + 2),
+        // 数据还没有读完整
+        // The data is not complete yet.
+        None if command.chunk().get(len).is_none_or(|&b| b == b'\r') => Err(Error::NoMoreData),
+        _ => Err(Error::UnRESP),
+    }
+}
+
 /// 获取第一个字节，但不移动cursor位置
 /// Get the first byte without moving the cursor position.
 fn peek_bytes(command: &mut Cursor<&[u8]>) -> Result<u8, Error> {
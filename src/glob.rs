@@ -0,0 +1,144 @@
+//! Redis 风格的通配符匹配，用于 PSUBSCRIBE、ACL 的键和频道模式，以及 KEYS、SCAN 等命令
+//! Redis style glob matching, used by PSUBSCRIBE, ACL key and channel patterns, and commands such as KEYS and SCAN.
+//!
+//! 支持的语法：
+//! Supported syntax:
+//! - `*` 匹配任意数量的字符 / matches any number of characters
+//! - `?` 匹配单个字符 / matches a single character
+//! - `[abc]`、`[a-z]`、`[^a]` 匹配（或排除）字符集合中的字符 / matches (or excludes) a character from the set
+//! - `\x` 按字面匹配 `x` / matches `x` literally
+
+/// 判断字符串是否匹配模式，按字节比较
+/// Whether the text matches the pattern, compared byte by byte.
+pub fn matches(pattern: &str, text: &str) -> bool {
+    matches_bytes(pattern.as_bytes(), text.as_bytes())
+}
+
+/// 判断字节串是否匹配模式
+/// Whether the bytes match the pattern.
+pub fn matches_bytes(pattern: &[u8], text: &[u8]) -> bool {
+    let (mut p, mut t) = (0, 0);
+    // 最近一个 `*` 之后的模式位置，以及它当前吞掉的文本位置，匹配失败时从这里回溯
+    // Pattern position after the last `*` and the text position it currently swallows up to, used to backtrack on mismatch
+    let mut star: Option<(usize, usize)> = None;
+    while t < text.len() {
+        if pattern.get(p) == Some(&b'*') {
+            while pattern.get(p) == Some(&b'*') {
+                p += 1;
+            }
+            star = Some((p, t));
+            continue;
+        }
+        if p < pattern.len()
+            && let Some(next) = match_one(pattern, p, text[t])
+        {
+            p = next;
+            t += 1;
+            continue;
+        }
+        match star {
+            Some((star_p, star_t)) => {
+                p = star_p;
+                t = star_t + 1;
+                star = Some((star_p, star_t + 1));
+            }
+            None => return false,
+        }
+    }
+    pattern[p..].iter().all(|&c| c == b'*')
+}
+
+/// 匹配从 `p` 开始的单个模式元素，匹配时返回下一个元素的位置
+/// Match the single pattern element starting at `p`, returning the position of the next element if it matches.
+fn match_one(pattern: &[u8], p: usize, c: u8) -> Option<usize> {
+    match pattern[p] {
+        b'?' => Some(p + 1),
+        // 末尾的 `\` 按字面匹配
+        // A trailing `\` matches literally
+        b'\\' if p + 1 < pattern.len() => (pattern[p + 1] == c).then_some(p + 2),
+        b'[' => match_class(pattern, p + 1, c),
+        literal => (literal == c).then_some(p + 1),
+    }
+}
+
+/// 匹配 `[` 之后的字符集合，没有 `]` 时集合延续到模式末尾
+/// Match the character set after `[`, the set extends to the end of the pattern if there is no `]`.
+fn match_class(pattern: &[u8], mut p: usize, c: u8) -> Option<usize> {
+    let negate = pattern.get(p) == Some(&b'^');
+    if negate {
+        p += 1;
+    }
+    let mut matched = false;
+    while p < pattern.len() && pattern[p] != b']' {
+        if pattern[p] == b'\\' && p + 1 < pattern.len() {
+            matched |= pattern[p + 1] == c;
+            p += 2;
+        } else if p + 2 < pattern.len() && pattern[p + 1] == b'-' {
+            let (start, end) = (pattern[p].min(pattern[p + 2]), pattern[p].max(pattern[p + 2]));
+            matched |= (start..=end).contains(&c);
+            p += 3;
+        } else {
+            matched |= pattern[p] == c;
+            p += 1;
+        }
+    }
+    // 跳过结尾的 `]`
+    // Skip the closing `]`
+    let next = (p + 1).min(pattern.len());
+    (matched != negate).then_some(next)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn patterns_match_like_redis() {
+        let cases: &[(&str, &str, bool)] = &[
+            // `*`
+            ("*", "", true),
+            ("*", "anything", true),
+            ("news.*", "news.tech", true),
+            ("news.*", "sports.tech", false),
+            ("a*b*c", "aXXbYYc", true),
+            ("a*b*c", "aXXbYY", false),
+            ("a**c", "abc", true),
+            // `?`
+            ("h?llo", "hello", true),
+            ("h?llo", "hllo", false),
+            ("??", "a", false),
+            // `[abc]`
+            ("h[ae]llo", "hallo", true),
+            ("h[ae]llo", "hillo", false),
+            // `[^a]`
+            ("h[^e]llo", "hallo", true),
+            ("h[^e]llo", "hello", false),
+            // `[a-z]`
+            ("h[a-c]llo", "hbllo", true),
+            ("h[a-c]llo", "hdllo", false),
+            ("[0-9][0-9]", "42", true),
+            // 反向的范围与正向相同
+            // Reversed ranges are the same as forward ones
+            ("h[c-a]llo", "hbllo", true),
+            ("h[c-a]llo", "hdllo", false),
+            // 转义
+            // Backslash escapes
+            (r"\*", "*", true),
+            (r"\*", "a", false),
+            (r"a\?", "a?", true),
+            (r"a\?", "ab", false),
+            (r"[\]]", "]", true),
+            (r"[\-a]", "-", true),
+            (r"a\", r"a\", true),
+            // 没有 `]` 时集合延续到模式末尾
+            // Without `]` the set extends to the end of the pattern
+            ("a[bc", "ab", true),
+            ("a[bc", "ac", true),
+            ("a[bc", "ad", false),
+            ("[", "", false),
+        ];
+        for &(pattern, text, expected) in cases {
+            assert_eq!(matches(pattern, text), expected, "pattern {:?} against {:?}", pattern, text);
+        }
+    }
+}
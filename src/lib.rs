@@ -12,6 +12,15 @@ pub mod parse;
 pub mod cmd;
 pub mod persistence;
 pub mod config;
+pub mod client;
+pub mod tracking;
+pub mod tls;
+pub mod acl;
+pub mod glob;
+pub mod notify;
+pub mod slot;
+pub mod stats;
+pub mod error;
 
 /// 定义错误返回类型
 /// This defines a custom result type that can be used throughout the application.
@@ -1,10 +1,11 @@
-use tokio::net::TcpListener;
 use tokio::signal;
 use p_rudis::config::{get_aof_config, get_server_config, parse_config};
 use p_rudis::Result;
 use p_rudis::log;
 use p_rudis::dict;
 use p_rudis::server;
+use p_rudis::acl;
+use p_rudis::notify;
 
 // 目前只编写并启用服务端
 #[tokio::main]
@@ -15,9 +16,13 @@ async fn main()->Result<()> {
     log::init::setup_logger()?;
     // 从文件中加载所有指令到内存，key是命令名，value是命令细节信息
     dict::Command::load_commands();
+    // 按配置初始化 ACL 用户，依赖命令表校验规则中的命令
+    acl::load()?;
+    // 按配置启用键空间通知
+    notify::load()?;
     // 获取启动参数
     let server_config = get_server_config();
-    let listener = TcpListener::bind(server_config.address).await?;
+    let listener = server::bind(&server_config).await?;
     server::run(listener,signal::ctrl_c()).await;
     Ok(())
 }
@@ -0,0 +1,189 @@
+//! 键空间通知（keyspace notifications）
+//! Keyspace notifications.
+//!
+//! 键被修改或过期时，按 `notify-keyspace-events` 配置的事件类别发布两类消息：
+//! When a key is modified or expires, two kinds of messages are published for the event classes enabled
+//! by `notify-keyspace-events`:
+//! - `__keyspace@<db>__:<key>`，内容为事件名 / with the event name as the payload
+//! - `__keyevent@<db>__:<event>`，内容为键名 / with the key name as the payload
+
+use std::sync::atomic::{AtomicU32, Ordering};
+use crate::config::get_server_config;
+
+/// 发布 `__keyspace@<db>__` 消息
+/// Publish `__keyspace@<db>__` messages
+pub const KEYSPACE: u32 = 1 << 0;
+/// 发布 `__keyevent@<db>__` 消息
+/// Publish `__keyevent@<db>__` messages
+pub const KEYEVENT: u32 = 1 << 1;
+/// 通用命令，例如 DEL、EXPIRE
+/// Generic commands such as DEL and EXPIRE
+pub const GENERIC: u32 = 1 << 2;
+/// 字符串命令
+/// String commands
+pub const STRING: u32 = 1 << 3;
+/// 列表命令
+/// List commands
+pub const LIST: u32 = 1 << 4;
+/// 集合命令
+/// Set commands
+pub const SET: u32 = 1 << 5;
+/// 哈希命令
+/// Hash commands
+pub const HASH: u32 = 1 << 6;
+/// 有序集合命令
+/// Sorted set commands
+pub const ZSET: u32 = 1 << 7;
+/// 键过期
+/// Key expired
+pub const EXPIRED: u32 = 1 << 8;
+/// 键因内存不足被淘汰
+/// Key evicted for maxmemory
+pub const EVICTED: u32 = 1 << 9;
+/// 流命令
+/// Stream commands
+pub const STREAM: u32 = 1 << 10;
+/// 读取不存在的键
+/// Key miss on read
+pub const KEY_MISS: u32 = 1 << 11;
+/// `A` 的别名，不包括 `m`
+/// What `A` stands for, `m` excluded
+pub const ALL: u32 = GENERIC | STRING | LIST | SET | HASH | ZSET | EXPIRED | EVICTED | STREAM;
+
+/// 当前启用的事件类别
+/// The currently enabled event classes
+static FLAGS: AtomicU32 = AtomicU32::new(0);
+
+/// 事件类别字符与标志的对应关系，顺序与 `CONFIG GET` 的输出一致
+/// Event class characters and their flags, in the order used by `CONFIG GET`
+const CLASSES: [(char, u32); 9] = [
+    ('g', GENERIC),
+    ('$', STRING),
+    ('l', LIST),
+    ('s', SET),
+    ('h', HASH),
+    ('z', ZSET),
+    ('x', EXPIRED),
+    ('e', EVICTED),
+    ('t', STREAM),
+];
+
+/// 按配置初始化启用的事件类别
+/// Initialize the enabled event classes from the configuration.
+pub fn load() -> crate::Result<()> {
+    let flags = parse_flags(&get_server_config().notify_keyspace_events)?;
+    set_flags(flags);
+    Ok(())
+}
+
+/// 当前启用的事件类别
+/// The currently enabled event classes.
+pub fn flags() -> u32 {
+    FLAGS.load(Ordering::Relaxed)
+}
+
+/// 设置启用的事件类别
+/// Set the enabled event classes.
+pub fn set_flags(flags: u32) {
+    FLAGS.store(flags, Ordering::Relaxed);
+}
+
+/// 是否需要为该类别的事件发布通知，`K` 和 `E` 都未启用时不发布任何通知
+/// Whether events of this class are notified, nothing is notified unless `K` or `E` is enabled.
+pub fn enabled(class: u32) -> bool {
+    let flags = flags();
+    flags & class != 0 && flags & (KEYSPACE | KEYEVENT) != 0
+}
+
+/// 解析 `notify-keyspace-events` 的值，例如 `KEA`、`Ex`
+/// Parse a `notify-keyspace-events` value, such as `KEA` or `Ex`.
+pub fn parse_flags(value: &str) -> Result<u32, String> {
+    let mut flags = 0;
+    for c in value.chars() {
+        flags |= match c {
+            'A' => ALL,
+            'K' => KEYSPACE,
+            'E' => KEYEVENT,
+            'm' => KEY_MISS,
+            c => match CLASSES.iter().find(|(class, _)| *class == c) {
+                Some((_, flag)) => *flag,
+                None => return Err(format!("Invalid event class character '{}'. Use 'Ag$lshzxeKEtm'.", c)),
+            },
+        };
+    }
+    Ok(flags)
+}
+
+/// 把事件类别格式化为 `notify-keyspace-events` 的值
+/// Format event classes as a `notify-keyspace-events` value.
+pub fn flags_to_string(flags: u32) -> String {
+    let mut value = String::new();
+    if flags & ALL == ALL {
+        value.push('A');
+    } else {
+        value.extend(CLASSES.iter().filter(|(_, flag)| flags & flag != 0).map(|(class, _)| *class));
+    }
+    if flags & KEYSPACE != 0 {
+        value.push('K');
+    }
+    if flags & KEYEVENT != 0 {
+        value.push('E');
+    }
+    if flags & KEY_MISS != 0 {
+        value.push('m');
+    }
+    value
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+    use crate::cmd::string::{decr::Decr, incr::Incr, set::Set};
+    use crate::db::Db;
+    use crate::frame::Frame;
+    use crate::parse::Parse;
+
+    fn parse(args: &[&str]) -> Parse {
+        let frames = args.iter().map(|arg| Frame::Bulk(arg.as_bytes().to_vec())).collect();
+        Parse::new(Some(Frame::Array(frames))).unwrap()
+    }
+
+    #[test]
+    fn flags_are_parsed_and_formatted() {
+        assert_eq!(parse_flags("").unwrap(), 0);
+        assert_eq!(parse_flags("KEA").unwrap(), KEYSPACE | KEYEVENT | ALL);
+        assert_eq!(parse_flags("g$").unwrap(), GENERIC | STRING);
+        assert_eq!(parse_flags("Ex").unwrap(), KEYEVENT | EXPIRED);
+        // `A` 不包括 `m`
+        // `A` does not include `m`
+        assert_eq!(parse_flags("A").unwrap() & KEY_MISS, 0);
+        assert!(parse_flags("Kq").unwrap_err().contains("'q'"));
+        assert!(parse_flags("k").is_err());
+
+        assert_eq!(flags_to_string(0), "");
+        assert_eq!(flags_to_string(parse_flags("KEA").unwrap()), "AKE");
+        assert_eq!(flags_to_string(parse_flags("Eg$").unwrap()), "g$E");
+        // 列出了 `A` 中所有类别时合并为 `A`
+        // Listing every class of `A` collapses into `A`
+        assert_eq!(flags_to_string(parse_flags("g$lshzxetKm").unwrap()), "AKm");
+        for value in ["AKE", "g$E", "lK", "xeEm"] {
+            assert_eq!(flags_to_string(parse_flags(value).unwrap()), value);
+        }
+    }
+
+    #[test]
+    fn counters_publish_one_event_per_command() {
+        set_flags(parse_flags("KEA").unwrap());
+        let mut db = Arc::new(Mutex::new(Db::new()));
+        let mut incrby = db.lock().unwrap().subscribe("__keyevent@0__:incrby");
+        let mut decrby = db.lock().unwrap().subscribe("__keyevent@0__:decrby");
+        Set::set_command(&mut db, &mut parse(&["notify:counter", "10"])).unwrap();
+        Incr::incr_command(&mut db, &mut parse(&["notify:counter"])).unwrap();
+        Decr::decr_command(&mut db, &mut parse(&["notify:counter"])).unwrap();
+        assert!(incrby.receiver.try_recv().is_ok());
+        assert!(incrby.receiver.try_recv().is_err());
+        assert!(decrby.receiver.try_recv().is_ok());
+        assert!(decrby.receiver.try_recv().is_err());
+    }
+}
@@ -1,7 +1,7 @@
 //! 解析客户端的数据
 //! Parse the client's data.
 
-use std::io::Error;
+use crate::error;
 use std::{fmt, vec};
 use crate::frame::Frame;
 use crate::parse::ParseError::{EndOfStream, RevertFailed, WrongType};
@@ -44,11 +44,11 @@ impl Parse{
     pub fn new(data: Option<Frame>) -> crate::Result<Parse> {
         let frame = match data {
             Some(frame) => frame,
-            None => return Err(Box::new(Error::new(std::io::ErrorKind::Other, "命令为空"))),  // Command is empty
+            None => return Err(error::Error::Protocol("empty command".to_string()).into()),  // 命令为空
         };
         let parts = match frame {
             Frame::Array(parts) => parts,  // 如果是数组类型的命令
-            _ => return Err(Box::new(Error::new(std::io::ErrorKind::Other, "命令不符合 RESP 协议规范"))),  // Command does not conform to RESP protocol
+            _ => return Err(error::Error::Protocol("expected an array of bulk strings".to_string()).into()),  // 命令不符合 RESP 协议规范
         };
         Ok(Parse{
             parts: parts.into_iter(),
@@ -92,4 +92,27 @@ impl Parse{
 
         Ok(count)
     }
+
+    /// 查看下一个参数而不消耗它，无法转换为字符串的参数按 UTF-8 有损转换
+    /// Peek at the next argument without consuming it, an argument that is not valid UTF-8 is converted lossily
+    pub fn peek_string(&self) -> Option<String> {
+        match self.parts.clone().next()? {
+            Frame::Simple(data) => Some(data),
+            Frame::Bulk(data) => Some(String::from_utf8_lossy(&data).into_owned()),
+            _ => None,
+        }
+    }
+
+    /// 查看剩余的参数而不消耗它们，无法转换为字符串的参数按 UTF-8 有损转换
+    /// Peek at the remaining arguments without consuming them, arguments that are not valid UTF-8 are converted lossily
+    pub fn peek_strings(&self) -> Vec<String> {
+        self.parts
+            .clone()
+            .filter_map(|frame| match frame {
+                Frame::Simple(data) => Some(data),
+                Frame::Bulk(data) => Some(String::from_utf8_lossy(&data).into_owned()),
+                _ => None,
+            })
+            .collect()
+    }
 }
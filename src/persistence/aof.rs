@@ -1,25 +1,104 @@
-use std::collections::{HashMap, VecDeque};
+use std::cell::RefCell;
+use std::fs;
 use std::fs::{OpenOptions, File};
-use std::io::{Write, BufWriter, BufReader, BufRead};
-use std::sync::{Arc, Mutex};
-use std::thread::sleep;
-use std::time::Instant;
+use std::io;
+use std::io::{Cursor, Read, Seek, SeekFrom, Write, BufWriter};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Condvar, Mutex, OnceLock};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+use bytes::{Buf, BytesMut};
 use lazy_static::lazy_static;
-use log::{info, error};
-use crate::config::get_aof_config;
-use crate::db::{Db, DbType};
-use crate::persistence::aof_command::{handle_del_command, handle_hdel_command, handle_hset_command, handle_lpop_command, handle_lpush_command, handle_lrem_command, handle_lset_command, handle_rpop_command, handle_rpush_command, handle_set_command};
+use log::{info, error, warn};
+use tokio::sync::watch;
+use crate::config::{get_aof_config, AppendFsync};
+use crate::db::{now_millis, Db, DbEntry, DbType};
+use crate::dict::Command;
+use crate::error::Error;
+use crate::frame::{self, Frame};
+use crate::parse::Parse;
+use crate::persistence::manifest::{manifest_name, AofFile, FileType, Manifest};
+use crate::persistence::rdb::constants::RDB_MAGIC;
+use crate::persistence::rdb::{load_rdb, IncrementalDump, RdbReader};
+use crate::stats;
+
+/// 重写时一条命令最多包含的列表或哈希元素数，与 Redis 相同
+/// Maximum number of list or hash items in one command written by a rewrite, the same as Redis
+const REWRITE_ITEMS_PER_COMMAND: usize = 64;
+
+/// 重写时一次处理的键的数量，处理每批键时才持有数据库的锁
+/// Number of keys handled at a time by a rewrite, the database lock is only held while handling each batch
+const REWRITE_BATCH: usize = 1024;
+
+/// 后台重写被推迟，等正在进行的后台保存结束之后再开始
+/// A background rewrite was postponed until the background save in progress ends
+static REWRITE_SCHEDULED: AtomicBool = AtomicBool::new(false);
+
+/// 启动时或上次重写之后 AOF 文件的总大小，自动重写根据文件相对它的增长触发
+/// Total size of the AOF files at startup or after the latest rewrite, automatic rewrites trigger on the growth
+/// relative to it
+static REWRITE_BASE_SIZE: AtomicU64 = AtomicU64::new(0);
+
+/// everysec 时后台同步还没有结束，写入最多推迟的时间，与 Redis 相同
+/// Longest time a write is postponed while the background fsync of everysec has not finished, the same as Redis
+const MAX_FSYNC_DELAY: Duration = Duration::from_secs(2);
+
+/// 追加到缓冲区的字节总数，作为 AOF 写入的逻辑偏移量
+/// Total bytes appended to the buffer, used as the logical offset of AOF writes
+static APPENDED: AtomicU64 = AtomicU64::new(0);
+
+/// 是否有请求等待刷新缓冲区，以及唤醒刷新线程的条件变量
+/// Whether a flush of the buffer was requested, and the condition variable waking the flush thread up
+static FLUSH_REQUESTED: Mutex<bool> = Mutex::new(false);
+static FLUSH_WAKEUP: Condvar = Condvar::new();
+
+/// everysec 的后台同步是否在进行
+/// Whether the background fsync of everysec is in progress
+static FSYNC_IN_PROGRESS: AtomicBool = AtomicBool::new(false);
+
+/// 刷新线程的进度
+/// Progress of the flush thread
+#[derive(Debug, Clone, Copy, Default)]
+struct Flushed {
+    /// 已经写入文件的逻辑偏移量，appendfsync always 时还已经同步到磁盘
+    /// Logical offset written to the file, also synced to disk under appendfsync always
+    offset: u64,
+    /// 写入或同步失败的次数，等待的回复看到它增加时返回错误
+    /// Number of failed writes or syncs, waiting replies return an error when they see it grow
+    failures: u64,
+}
+
+tokio::task_local! {
+    /// 正在执行的写命令
+    /// The write command being executed.
+    static CURRENT_CALL: RefCell<Call>;
+}
+
+/// 一次写命令调用的传播上下文
+/// Propagation context of a single write command call.
+struct Call {
+    /// 命令名和参数，命令第一次修改键时传播，之后为 None
+    /// Command name and arguments, propagated the first time the command modifies a key and None afterwards
+    command: Option<Vec<String>>,
+    /// 接收传播的命令
+    /// Receives the propagated command
+    feed: fn(String, Vec<String>),
+}
 
 lazy_static! {
-    static ref AOF_WRITER: Arc<Mutex<AofWriter>> = {
-        let aof_config = get_aof_config();
-        Arc::new(Mutex::new(AofWriter::new(aof_config.file_path.as_str()).unwrap_or_else(|e| {
-            error!("Failed to create AOF Writer: {}", e);
-            std::process::exit(1);
-        })))
-    };
+    /// 刷新线程的进度，等待落盘的回复订阅它
+    /// Progress of the flush thread, replies waiting for the disk subscribe to it
+    static ref FLUSHED: watch::Sender<Flushed> = watch::channel(Flushed::default()).0;
+    /// AOF 目录的清单，启动时加载，重写时替换
+    /// Manifest of the AOF directory, loaded at startup and replaced by rewrites
+    static ref MANIFEST: Mutex<Manifest> = Mutex::new(Manifest::default());
 }
 
+/// AOF 写入器，加载完成之后由 [`start_writer`] 创建，在此之前清单还没有加载，不能写入或重写
+/// The AOF writer, created by [`start_writer`] once loading is done, before that the manifest is not loaded yet and
+/// nothing can be written or rewritten
+static AOF_WRITER: OnceLock<AofWriter> = OnceLock::new();
+
 #[derive(Debug, Clone)]
 pub struct AofWriter {
     file: Arc<Mutex<BufWriter<File>>>,
@@ -27,11 +106,10 @@ pub struct AofWriter {
 }
 
 impl AofWriter {
-    pub fn new(aof_file_path: &str) -> Result<AofWriter, std::io::Error> {
+    pub fn new(aof_file_path: &Path) -> Result<AofWriter, std::io::Error> {
         let file = OpenOptions::new()
             .create(true)
             .append(true)
-            .write(true)
             .open(aof_file_path)?;
 
         let file = Arc::new(Mutex::new(BufWriter::new(file)));
@@ -39,174 +117,1096 @@ impl AofWriter {
 
         let aof_writer = AofWriter { file, buffer };
 
-        // 启动一个异步任务，用于定期刷新缓冲区到磁盘
-        tokio::spawn(periodic_flush(aof_writer.clone()));
+        // 启动刷新线程，把缓冲区写入文件并按 appendfsync 同步到磁盘
+        // Start the flush thread, writing the buffer to the file and syncing it to disk according to appendfsync
+        let flusher = aof_writer.clone();
+        std::thread::spawn(move || flush_loop(flusher));
 
         Ok(aof_writer)
     }
 
     pub fn write_command(&self, command: &str, args: &[&str]) {
         let mut buf = Vec::new();
-
-        // Append the command's arguments count in the AOF format (*<arg_count>\r\n)
-        let args_count = args.len() + 1; // Including the command itself
-        buf.push(b'*');
-        buf.extend_from_slice(&args_count.to_string().into_bytes());
-        buf.push(b'\r');
-        buf.push(b'\n');
-
-        // Write the command itself, prefixed with its length
-        self.append_argument(&mut buf, command);
-
-        // Write each argument with its length
-        for arg in args {
-            self.append_argument(&mut buf, arg);
-        }
+        encode_command(&mut buf, command, args);
 
         // Append the command to the buffer
         let mut buffer = self.buffer.lock().unwrap();
-        buffer.extend(buf);
+        buffer.extend(&buf);
+        APPENDED.fetch_add(buf.len() as u64, Ordering::SeqCst);
+        request_flush();
     }
+}
 
-    fn append_argument(&self, buf: &mut Vec<u8>, arg: &str) {
-        buf.push(b'$');
-        buf.extend_from_slice(&arg.len().to_string().into_bytes());
-        buf.push(b'\r');
-        buf.push(b'\n');
-        buf.extend_from_slice(arg.as_bytes());
-        buf.push(b'\r');
-        buf.push(b'\n');
+/// 把命令编码为 RESP 数组追加到 `buf`
+/// Append the command to `buf`, encoded as a RESP array.
+fn encode_command(buf: &mut Vec<u8>, command: &str, args: &[&str]) {
+    // Append the command's arguments count in the AOF format (*<arg_count>\r\n)
+    let args_count = args.len() + 1; // Including the command itself
+    buf.push(b'*');
+    buf.extend_from_slice(&args_count.to_string().into_bytes());
+    buf.push(b'\r');
+    buf.push(b'\n');
+
+    // Write the command itself, prefixed with its length
+    append_argument(buf, command);
+
+    // Write each argument with its length
+    for arg in args {
+        append_argument(buf, arg);
     }
 }
 
+fn append_argument(buf: &mut Vec<u8>, arg: &str) {
+    buf.push(b'$');
+    buf.extend_from_slice(&arg.len().to_string().into_bytes());
+    buf.push(b'\r');
+    buf.push(b'\n');
+    buf.extend_from_slice(arg.as_bytes());
+    buf.push(b'\r');
+    buf.push(b'\n');
+}
+
 pub fn propagate_aof(command: String, args: Vec<String>) {
     let aof_config=get_aof_config();
     if !aof_config.enabled {
         return;
     }
-    let writer = AOF_WRITER.clone(); // 克隆 Arc 指针
-
-    let writer = writer.lock().unwrap(); // 获取锁
+    // 加载期间不执行写命令，写入器总是已经创建
+    // Write commands are refused while loading, so the writer always exists here
+    let Some(writer) = AOF_WRITER.get() else {
+        return;
+    };
     let args_ref: Vec<&str> = args.iter().map(|s| s.as_str()).collect();
 
     writer.write_command(&command, &args_ref);
 }
 
-/// Flush the AOF buffer to the disk (Windows version)
-pub fn flush(aof: &mut AofWriter) -> Result<(), std::io::Error> {
-    let mut buffer = aof.buffer.lock().unwrap();
-    if buffer.is_empty() {
+/// 在传播上下文中执行一条命令。写命令修改键时，在持有数据库的锁时把它的实际效果传播到 AOF，
+/// AOF 中命令的顺序因此与执行的顺序一致；没有修改任何键的命令不传播。非写命令直接执行。
+/// Execute a command in a propagation context. When a write command modifies a key, its actual effect is propagated
+/// to the AOF while the database lock is held, so commands are logged in the order they were executed; commands that
+/// modified no key are not propagated. Commands other than writes are executed directly.
+pub fn call<F, R>(command_name: &str, args: Vec<String>, f: F) -> R
+where
+    F: FnOnce() -> R,
+{
+    if !get_aof_config().enabled || !Command::lookup(command_name).is_some_and(|command| command.has_flag("write")) {
+        return f();
+    }
+    let mut command = vec![command_name.to_string()];
+    command.extend(args);
+    call_with(command, propagate_aof, f)
+}
+
+fn call_with<F, R>(command: Vec<String>, feed: fn(String, Vec<String>), f: F) -> R
+where
+    F: FnOnce() -> R,
+{
+    CURRENT_CALL.sync_scope(RefCell::new(Call { command: Some(command), feed }), f)
+}
+
+/// 当前命令修改了键，传播它的实际效果，每条命令只传播一次。由 [`Db::signal_modified_key`] 在持有数据库的锁时调用
+/// The current command modified a key, propagate its actual effect, once per command. Called by
+/// [`Db::signal_modified_key`] while the database lock is held.
+pub fn propagate_call(db: &Db) {
+    let _ = CURRENT_CALL.try_with(|call| {
+        let mut call = call.borrow_mut();
+        if let Some(command) = call.command.take() {
+            let (name, args) = normalize(db, command);
+            (call.feed)(name, args);
+        }
+    });
+}
+
+/// 命令的实际效果：带过期时间的 SET 改为过期的绝对时间 PXAT，阻塞的弹出改为普通的弹出，其他命令原样传播
+/// The actual effect of a command: SET with an expiration gets the absolute expiration time as PXAT, blocking pops
+/// become plain pops, other commands are propagated as they are.
+fn normalize(db: &Db, mut command: Vec<String>) -> (String, Vec<String>) {
+    let name = command.remove(0);
+    match name.as_str() {
+        "set" => {
+            command.truncate(2);
+            if let Some(expiration) = db.expiration(&command[0]) {
+                command.extend(["pxat".to_string(), expiration.to_string()]);
+            }
+            (name, command)
+        }
+        "blpop" | "brpop" => {
+            command.truncate(1);
+            (name[1..].to_string(), command)
+        }
+        _ => (name, command),
+    }
+}
+
+/// 尚未写入文件的 AOF 缓冲区长度，AOF 未启用或还在加载时为 0
+/// Length of the AOF buffer not yet written to the file, 0 if AOF is disabled or still loading.
+pub fn buffer_len() -> usize {
+    AOF_WRITER.get().map_or(0, |writer| writer.buffer.lock().unwrap().len())
+}
+
+/// 唤醒刷新线程
+/// Wake the flush thread up.
+fn request_flush() {
+    *FLUSH_REQUESTED.lock().unwrap() = true;
+    FLUSH_WAKEUP.notify_one();
+}
+
+/// appendfsync always 时等待到目前为止的写入同步到磁盘。并发客户端的写入由同一次同步完成（组提交）
+/// Under appendfsync always, wait until the writes made so far are synced to disk. The writes of concurrent clients
+/// are completed by the same sync (group commit). If the write or the sync fails meanwhile, the `MISCONF` error is
+/// returned instead of waiting forever.
+pub async fn wait_for_fsync() -> Result<(), Error> {
+    let config = get_aof_config();
+    if !config.enabled || config.appendfsync != AppendFsync::Always {
         return Ok(());
     }
+    wait_for_offset(APPENDED.load(Ordering::SeqCst)).await
+}
+
+/// 等待刷新线程写到 `target`，期间写入或同步失败时返回错误
+/// Wait until the flush thread wrote up to `target`, returning an error if a write or sync fails meanwhile.
+async fn wait_for_offset(target: u64) -> Result<(), Error> {
+    let mut flushed = FLUSHED.subscribe();
+    let failures = {
+        let state = flushed.borrow();
+        if state.offset >= target {
+            return Ok(());
+        }
+        state.failures
+    };
+    request_flush();
+    match flushed.wait_for(|state| state.offset >= target || state.failures > failures).await {
+        Ok(state) if state.offset >= target => Ok(()),
+        _ => Err(Error::AofMisconf(stats::aof_status().last_error.unwrap_or_else(|| "unknown error".to_string()))),
+    }
+}
 
+/// 写入的数据已经到达 `offset`
+/// The written data reached `offset`.
+fn advance_flushed(offset: u64) {
+    FLUSHED.send_if_modified(|flushed| {
+        let advanced = offset > flushed.offset;
+        if advanced {
+            flushed.offset = offset;
+        }
+        advanced
+    });
+}
+
+/// 把缓冲区写入文件，`sync` 为 true 时同步到磁盘，返回是否写入了数据
+/// Write the buffer to the file, syncing it to disk if `sync` is true, returning whether anything was written.
+fn flush(aof: &AofWriter, sync: bool) -> io::Result<bool> {
+    // 持有文件的锁直到写完，重写替换文件时不会漏掉或重复写入取出的数据
+    // The file lock is held until the data is written, so a rewrite replacing the file neither misses nor repeats it
+    let mut buffer = aof.buffer.lock().unwrap();
     let mut file = aof.file.lock().unwrap();
-    file.write_all(&buffer)?;
-    file.flush()?;
-    buffer.clear();
+    let data = std::mem::take(&mut *buffer);
+    let offset = APPENDED.load(Ordering::SeqCst);
+    drop(buffer);
+    if data.is_empty() {
+        return Ok(false);
+    }
+    if let Err(err) = file.write_all(&data).and_then(|()| file.flush()) {
+        // 放回缓冲区，下次再写
+        // Put the data back into the buffer to write it next time
+        aof.buffer.lock().unwrap().splice(0..0, data);
+        return Err(err);
+    }
+    // 同步失败时数据已经在文件中，不放回缓冲区，否则会重复写入；下次成功的同步会一起同步它们
+    // If the sync fails the data is already in the file and is not put back, which would write it twice; the next
+    // successful sync covers it
+    if sync {
+        file.get_ref().sync_data()?;
+    }
+    drop(file);
+    advance_flushed(offset);
+    Ok(true)
+}
 
-    Ok(())
+/// 在后台线程中把文件同步到磁盘，用于 everysec
+/// Sync the file to disk in a background thread, used by everysec.
+fn background_fsync(aof: &AofWriter) {
+    match aof.file.lock().unwrap().get_ref().try_clone() {
+        Ok(file) => {
+            FSYNC_IN_PROGRESS.store(true, Ordering::SeqCst);
+            std::thread::spawn(move || {
+                if let Err(err) = file.sync_data() {
+                    error!("AOF 同步到磁盘失败: {}", err);  // Failed to sync the AOF to disk
+                }
+                FSYNC_IN_PROGRESS.store(false, Ordering::SeqCst);
+            });
+        }
+        Err(err) => error!("AOF 同步到磁盘失败: {}", err),  // Failed to sync the AOF to disk
+    }
 }
 
-/// Flush the AOF buffer periodically based on time or external trigger
-pub async fn periodic_flush(mut aof: AofWriter) {
-    let aof_config=get_aof_config();
+/// 刷新线程：有写入或者每秒一次，把缓冲区写入文件，并按 appendfsync 同步到磁盘。
+/// always 每次写入后同步；everysec 每秒在后台同步一次，上次同步还没有结束时推迟写入，超过两秒仍然写入并计入
+/// `aof_delayed_fsync`；no 不主动同步。开启 no-appendfsync-on-rewrite 时，后台保存或重写期间不同步。
+/// The flush thread: on writes or once a second, write the buffer to the file and sync it to disk according to
+/// appendfsync. always syncs after every write; everysec syncs once a second in the background, postponing writes
+/// while the previous sync has not finished, and writing anyway after two seconds, counted in `aof_delayed_fsync`;
+/// no never syncs explicitly. With no-appendfsync-on-rewrite, nothing is synced during background saves or rewrites.
+fn flush_loop(aof: AofWriter) {
+    let mut last_fsync = Instant::now();
+    let mut unsynced = false;
+    let mut postponed: Option<Instant> = None;
     loop {
-        tokio::time::sleep(std::time::Duration::from_secs(aof_config.appendfsync)).await;
-        if let Err(e) = flush(&mut aof) {
+        {
+            let mut requested = FLUSH_REQUESTED.lock().unwrap();
+            if !*requested {
+                requested = FLUSH_WAKEUP.wait_timeout(requested, Duration::from_secs(1)).unwrap().0;
+            }
+            *requested = false;
+        }
+        let config = get_aof_config();
+        let no_fsync = config.no_appendfsync_on_rewrite
+            && (stats::AOF_REWRITE_IN_PROGRESS.load(Ordering::Relaxed) || stats::RDB_SAVE_IN_PROGRESS.load(Ordering::Relaxed));
+        if config.appendfsync == AppendFsync::Everysec && FSYNC_IN_PROGRESS.load(Ordering::SeqCst) {
+            if postponed.get_or_insert_with(Instant::now).elapsed() < MAX_FSYNC_DELAY {
+                continue;
+            }
+            stats::incr(&stats::AOF_DELAYED_FSYNC);
+        }
+        postponed = None;
+        let result = flush(&aof, config.appendfsync == AppendFsync::Always && !no_fsync);
+        unsynced |= matches!(result, Ok(true));
+        if config.appendfsync == AppendFsync::Everysec
+            && unsynced
+            && !no_fsync
+            && last_fsync.elapsed() >= Duration::from_secs(1)
+            && !FSYNC_IN_PROGRESS.load(Ordering::SeqCst)
+        {
+            background_fsync(&aof);
+            last_fsync = Instant::now();
+            unsynced = false;
+        }
+        let result = result.map(|_| ()).map_err(|e| e.to_string());
+        if let Err(e) = &result {
             error!("Error flushing AOF file: {}", e);
         }
+        stats::aof_written(&result);
+        if result.is_err() {
+            // 唤醒等待落盘的回复，返回错误
+            // Wake the replies waiting for the disk up to return the error
+            FLUSHED.send_modify(|flushed| flushed.failures += 1);
+        }
     }
 }
 
-pub async fn load_aof(db: &mut Arc<Mutex<Db>>, aof_file_path: &str) -> Result<(u128, ()), std::io::Error> {
+/// 加载时每次从文件读取的字节数
+/// Number of bytes read from the file at a time while loading.
+const LOAD_CHUNK_SIZE: usize = 64 * 1024;
+
+/// AOF 目录和目录中文件名的前缀
+/// The AOF directory and the prefix of the file names in it.
+fn location() -> (PathBuf, String) {
+    let config = get_aof_config();
+    let prefix = Path::new(&config.file_path)
+        .file_name()
+        .map_or_else(|| config.file_path.clone(), |name| name.to_string_lossy().into_owned());
+    (PathBuf::from(config.dir_name), prefix)
+}
+
+/// 按清单加载 AOF 目录中的文件：先加载基础文件，再按顺序加载增量文件，之后创建写入器。目录中还没有清单时，
+/// 旧版本的单个 AOF 文件被移入目录作为基础文件。
+/// Load the files of the AOF directory following the manifest: the base file first, then the incremental files in
+/// order, then create the writer. While the directory has no manifest, the single AOF file of older versions is moved
+/// into it as the base file.
+pub async fn load_aof(db: &mut Arc<Mutex<Db>>) -> Result<(u128, ()), std::io::Error> {
     // Start timing
     let start_time = Instant::now();
 
-    let file = File::open(aof_file_path)?;
-    let reader = BufReader::new(file);
+    let (dir, prefix) = location();
+    fs::create_dir_all(&dir)?;
+    upgrade_single_file(&dir, &prefix)?;
+    let manifest = load_dir(db, &dir, &prefix).await?;
+    *MANIFEST.lock().unwrap() = manifest;
+    start_writer(&dir, &prefix)?;
 
-    let mut buffer = Vec::new();
-    let mut command: Option<String> = None;
-    let mut args: Vec<String> = Vec::new();
+    // Measure the time taken
+    let duration = start_time.elapsed();
 
-    // Iterate through each line in the AOF file
-    for line in reader.lines() {
-        let line = line?;
-        if line.is_empty() {
-            continue;
+    Ok((duration.as_millis(), ())) // Return the time in milliseconds
+}
+
+/// 目录中还没有清单时，把旧版本的单个 AOF 文件移入目录作为基础文件。先写清单再移动文件，
+/// 中途失败时原来的文件还在原处。
+/// While the directory has no manifest, move the single AOF file of older versions into it as the base file. The
+/// manifest is written before the file is moved, so the original file stays in place if this fails halfway.
+fn upgrade_single_file(dir: &Path, prefix: &str) -> io::Result<()> {
+    let single = PathBuf::from(get_aof_config().file_path);
+    if dir.join(manifest_name(prefix)).exists() || !single.is_file() {
+        return Ok(());
+    }
+    let mut manifest = Manifest::default();
+    manifest.replace_base(AofFile { name: prefix.to_string(), seq: 1, file_type: FileType::Base }, 0);
+    manifest.persist(dir, prefix)?;
+    fs::rename(&single, dir.join(prefix))?;
+    info!("AOF 文件 {} 已移入目录 {}", single.display(), dir.display());  // The AOF file was moved into the directory
+    Ok(())
+}
+
+/// 加载目录中清单列出的文件并返回清单，之前的重写留下的历史文件先被删除
+/// Load the files listed by the manifest of the directory and return the manifest, history files left by a previous
+/// rewrite are deleted first.
+async fn load_dir(db: &mut Arc<Mutex<Db>>, dir: &Path, prefix: &str) -> io::Result<Manifest> {
+    let mut manifest = Manifest::load(dir, prefix)?.unwrap_or_default();
+    delete_history(&mut manifest, dir, prefix)?;
+    let files: Vec<AofFile> = manifest.files().cloned().collect();
+    for (i, file) in files.iter().enumerate() {
+        let path = dir.join(&file.name);
+        if !path.is_file() {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("AOF file {} listed in the manifest doesn't exist", path.display()),
+            ));
         }
+        if file.is_rdb() {
+            let mut rdb = RdbReader::load_file(&path.to_string_lossy()).await?;
+            load_rdb(db, &mut rdb).await?;
+        } else {
+            // 只有最后一个文件可能在写入时被截断
+            // Only the last file may have been truncated while being written
+            load_aof_file(db, &path, i + 1 == files.len()).await?;
+        }
+    }
+    Ok(manifest)
+}
 
-        let bytes = line.clone().as_bytes().to_vec();
-        buffer.push(bytes);
+/// 删除历史文件，再从清单中移除它们
+/// Delete the history files, then remove them from the manifest.
+fn delete_history(manifest: &mut Manifest, dir: &Path, prefix: &str) -> io::Result<()> {
+    if manifest.history.is_empty() {
+        return Ok(());
+    }
+    for file in &manifest.history {
+        match fs::remove_file(dir.join(&file.name)) {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => return Err(err),
+            _ => {}
+        }
+    }
+    manifest.history.clear();
+    manifest.persist(dir, prefix)
+}
 
-        // If a complete command is found, parse and apply it
-        if let Some(cmd) = parse_aof_command(&mut buffer) {
-            command = Some(cmd.0);
-            args = cmd.1;
+/// 创建写入器，写入加载的清单中最后一个增量文件，没有增量文件时新建一个
+/// Create the writer, appending to the last incremental file of the loaded manifest, a new one is created if there
+/// is none.
+fn start_writer(dir: &Path, prefix: &str) -> io::Result<()> {
+    if AOF_WRITER.get().is_some() {
+        return Ok(());
+    }
+    let mut manifest = MANIFEST.lock().unwrap();
+    let path = match manifest.incrs.last() {
+        Some(incr) => dir.join(&incr.name),
+        None => {
+            let mut updated = manifest.clone();
+            let incr = updated.new_incr(prefix);
+            // 文件在写入清单之前创建，清单不会指向不存在的文件；已经存在的文件不会被清空
+            // The file is created before the manifest is written so the manifest never lists a missing file, an
+            // existing file is not truncated
+            OpenOptions::new().create(true).append(true).open(dir.join(&incr.name))?;
+            updated.persist(dir, prefix)?;
+            *manifest = updated;
+            dir.join(incr.name)
+        }
+    };
+    let _ = AOF_WRITER.set(AofWriter::new(&path)?);
+    Ok(())
+}
 
-            if let Err(e) = apply_command_to_db(db, &command.unwrap(), &args) {
-                error!("Failed to apply command: {}", e);
+/// 用与网络请求相同的 RESP 解码器逐条加载 AOF 文件中的命令。文件可以以 RDB 格式的快照（RDB 前导）开始，
+/// 先加载快照，再加载它之后的命令。
+/// Load the commands of an AOF file one by one with the same RESP decoder as network requests. The file may start
+/// with an RDB-format snapshot (the RDB preamble), which is loaded before the commands following it.
+///
+/// 最后一个文件末尾的命令不完整时（例如写入时宕机），按 `aof-load-truncated` 截断到最后一条完整的命令后继续，
+/// 或者返回错误；其他损坏总是返回错误，错误中包含损坏位置的偏移量。
+/// When the last command of the last file is incomplete (e.g. a crash while writing), the file is truncated to the
+/// last complete command and loading goes on, or an error is returned, depending on `aof-load-truncated`. Any other
+/// corruption is an error reporting the offset of the corrupted data.
+async fn load_aof_file(db: &mut Arc<Mutex<Db>>, path: &Path, last: bool) -> io::Result<()> {
+    let mut file = File::open(path)?;
+    // 已经加载的命令在文件中的结束位置
+    // End offset in the file of the commands loaded so far
+    let mut offset = 0;
+    let mut magic = Vec::new();
+    (&mut file).take(RDB_MAGIC.len() as u64).read_to_end(&mut magic)?;
+    if magic == RDB_MAGIC {
+        let mut rdb = RdbReader::load_file(&path.to_string_lossy()).await?;
+        load_rdb(db, &mut rdb).await?;
+        offset = rdb.position() as u64;
+        info!("加载了 AOF 文件 {} 的 RDB 前导", path.display());  // Loaded the RDB preamble of the AOF file
+    }
+    file.seek(SeekFrom::Start(offset))?;
+    let mut buffer = BytesMut::with_capacity(LOAD_CHUNK_SIZE);
+    let mut chunk = vec![0; LOAD_CHUNK_SIZE];
+    let mut eof = false;
+
+    loop {
+        match decode_command(&buffer) {
+            Ok(Some((frame, len))) => {
+                if let Err(e) = replay(db, frame) {
+                    error!("Failed to apply command of {} at offset {}: {}", path.display(), offset, e);
+                }
+                buffer.advance(len);
+                offset += len as u64;
             }
+            Ok(None) if !eof => {
+                let n = file.read(&mut chunk)?;
+                eof = n == 0;
+                buffer.extend_from_slice(&chunk[..n]);
+            }
+            Ok(None) if buffer.is_empty() => return Ok(()),
+            Ok(None) if !last => {
+                return Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    format!("unexpected end of AOF file {} at offset {}", path.display(), offset),
+                ));
+            }
+            Ok(None) if !get_aof_config().load_truncated => {
+                return Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    format!(
+                        "unexpected end of AOF file {} at offset {}, set aof-load-truncated to yes to load it",
+                        path.display(),
+                        offset
+                    ),
+                ));
+            }
+            Ok(None) => {
+                warn!("AOF 文件 {} 在偏移量 {} 处被截断，丢弃最后 {} 字节不完整的命令", path.display(), offset, buffer.len());  // The AOF file is truncated at the offset, dropping the incomplete last command
+                OpenOptions::new().write(true).open(path)?.set_len(offset)?;
+                return Ok(());
+            }
+            Err(reason) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("corrupted AOF file {} at offset {}: {}", path.display(), offset, reason),
+                ));
+            }
+        }
+    }
+}
 
-            buffer.clear();
+/// 解码缓冲区开头的一条命令，返回命令和它占用的字节数，数据还不完整时返回 `None`
+/// Decode the command at the start of the buffer, returning it with the number of bytes it takes, or `None` while
+/// the data is incomplete.
+fn decode_command(buffer: &[u8]) -> Result<Option<(Frame, usize)>, String> {
+    if buffer.is_empty() {
+        return Ok(None);
+    }
+    let mut cursor = Cursor::new(buffer);
+    match Frame::check(&mut cursor) {
+        Ok(()) => {}
+        Err(frame::Error::NoMoreData) => return Ok(None),
+        Err(err) => return Err(err.to_string()),
+    }
+    let len = cursor.position() as usize;
+    cursor.set_position(0);
+    match Frame::parse(&mut cursor).map_err(|err| err.to_string())? {
+        Frame::Array(parts) if !parts.is_empty() && parts.iter().all(|part| matches!(part, Frame::Bulk(_))) => {
+            Ok(Some((Frame::Array(parts), len)))
         }
+        _ => Err("expected an array of bulk strings".to_string()),
     }
+}
 
-    // Measure the time taken
-    let duration = start_time.elapsed();
+/// 通过命令表执行 AOF 中的一条命令，与客户端发送的命令相同
+/// Execute a command of the AOF through the command table, the same as a command sent by a client.
+fn replay(db: &mut Arc<Mutex<Db>>, frame: Frame) -> Result<(), String> {
+    let mut parse = Parse::new(Some(frame)).map_err(|err| err.to_string())?;
+    let name = parse.next_string().map_err(|err| err.to_string())?.to_lowercase();
+    let command_fn = Command::get_command_fn(&name).ok_or_else(|| format!("unknown command '{}'", name))?;
+    match command_fn(db, &mut parse) {
+        Ok(Frame::Error(err)) => Err(err),
+        Ok(_) => Ok(()),
+        Err(err) => Err(err.to_string()),
+    }
+}
 
-    Ok((duration.as_millis(), ())) // Return the time in milliseconds
+/// `BGREWRITEAOF` 的结果
+/// Outcome of `BGREWRITEAOF`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Rewrite {
+    /// 开始了后台重写
+    /// A background rewrite started
+    Started,
+    /// 后台保存正在进行，结束之后再重写
+    /// A background save is in progress, the rewrite runs after it
+    Scheduled,
+    /// 已经有后台重写在进行
+    /// A background rewrite is already in progress
+    InProgress,
+    /// AOF 没有启用或者还在加载，没有可以替换的清单
+    /// AOF is disabled or still loading, there is no manifest to replace
+    NotRunning,
 }
 
-fn parse_aof_command(buffer: &mut Vec<Vec<u8>>) -> Option<(String, Vec<String>)> {
-    let arg_count = match buffer.first() {
-        Some(arg_count_str) if !arg_count_str.is_empty() && arg_count_str[0] == b'*' => {
-            String::from_utf8_lossy(&arg_count_str[1..]).parse().ok()?
+/// 开始后台重写 AOF：开启 `aof-use-rdb-preamble` 时新的基础文件是 RDB 格式的快照，否则只包含重建当前数据所需的最少命令，
+/// 过期时间写为绝对时间的 `PEXPIREAT`。
+/// 重写开始时打开新的增量文件接收之后的写入，新的基础文件写完后原子地替换清单，再删除原来的基础文件和之前的增量文件。
+/// 后台保存和后台重写共用数据库的写时复制快照，不能同时进行，后台保存正在进行时推迟重写。
+/// Start rewriting the AOF in the background: with `aof-use-rdb-preamble` the new base file is an RDB-format
+/// snapshot, otherwise it only holds the fewest commands rebuilding the current data, with expirations written as
+/// absolute `PEXPIREAT`. A new incremental file receives the writes made once the rewrite starts, and when the new
+/// base file is written the manifest is atomically replaced, then the old base file and the earlier incremental
+/// files are deleted. Background saves and rewrites share the copy-on-write snapshot of the database and can't run
+/// together, so the rewrite is postponed while a background save is in progress.
+pub fn background_rewrite(db: &Arc<Mutex<Db>>) -> Rewrite {
+    if AOF_WRITER.get().is_none() {
+        return Rewrite::NotRunning;
+    }
+    if stats::AOF_REWRITE_IN_PROGRESS.swap(true, Ordering::SeqCst) {
+        return Rewrite::InProgress;
+    }
+    if stats::RDB_SAVE_IN_PROGRESS.load(Ordering::SeqCst) {
+        stats::AOF_REWRITE_IN_PROGRESS.store(false, Ordering::SeqCst);
+        REWRITE_SCHEDULED.store(true, Ordering::Relaxed);
+        return Rewrite::Scheduled;
+    }
+    REWRITE_SCHEDULED.store(false, Ordering::Relaxed);
+    let db = db.clone();
+    std::thread::spawn(move || {
+        let started = Instant::now();
+        let result = rewrite(&db).map_err(|err| err.to_string());
+        stats::AOF_REWRITE_IN_PROGRESS.store(false, Ordering::SeqCst);
+        match &result {
+            Ok(()) => info!("AOF 重写成功，耗时 {} 毫秒", started.elapsed().as_millis()),  // AOF rewritten
+            Err(err) => error!("AOF 重写失败: {}", err),  // Failed to rewrite the AOF
         }
-        _ => return None,
-    };
+        stats::aof_rewritten(&result, started.elapsed());
+    });
+    Rewrite::Started
+}
+
+/// 后台重写是否被推迟
+/// Whether a background rewrite was postponed.
+pub fn rewrite_scheduled() -> bool {
+    REWRITE_SCHEDULED.load(Ordering::Relaxed)
+}
+
+/// 启动时或上次重写之后 AOF 文件的总大小
+/// Total size of the AOF files at startup or after the latest rewrite.
+pub fn rewrite_base_size() -> u64 {
+    REWRITE_BASE_SIZE.load(Ordering::Relaxed)
+}
+
+/// 重写开始时的快照，按基础文件的格式保存
+/// The snapshot taken when a rewrite starts, saved in the format of the base file.
+enum BaseSnapshot {
+    Rdb(IncrementalDump),
+    /// 快照中的键和开始的时间，写为重建它们的命令
+    /// Keys of the snapshot and when it started, written as the commands rebuilding them
+    Commands(Vec<String>, u64),
+}
 
-    if buffer.len() != arg_count * 2 + 1 {
-        return None;
+fn rewrite(db: &Arc<Mutex<Db>>) -> io::Result<()> {
+    let (dir, prefix) = location();
+    let temp = dir.join(format!("temp-rewriteaof-bg-{}.aof", std::process::id()));
+    let result = rewrite_into(db, &dir, &prefix, &temp);
+    if result.is_err() {
+        let _ = fs::remove_file(&temp);
     }
+    result
+}
 
-    buffer.remove(0);
-    let mut args = Vec::with_capacity(arg_count);
-    for _ in 0..arg_count {
-        buffer.remove(0);
-        if let Some(arg) = buffer.first() {
-            args.push(String::from_utf8_lossy(arg).into_owned());
+fn rewrite_into(db: &Arc<Mutex<Db>>, dir: &Path, prefix: &str, temp: &Path) -> io::Result<()> {
+    // 持有数据库的锁打开新的增量文件并开始快照，命令在持有数据库的锁时传播，之前的写入都包含在快照中，
+    // 之后的写入都在新的增量文件中
+    // Open the new incremental file and start the snapshot while holding the database lock, commands are propagated
+    // while holding it too, so earlier writes are all part of the snapshot and later ones all in the new file
+    let rdb = get_aof_config().use_rdb_preamble;
+    let used_memory = if rdb { stats::used_memory(db) } else { 0 };
+    let (first_incr, snapshot) = {
+        let mut db = db.lock().unwrap();
+        let first_incr = open_new_incr(dir, prefix)?;
+        let snapshot = if rdb {
+            BaseSnapshot::Rdb(IncrementalDump::begin_locked(&mut db, used_memory))
+        } else {
+            BaseSnapshot::Commands(db.begin_snapshot(), now_millis())
+        };
+        (first_incr, snapshot)
+    };
+    let mut file = BufWriter::new(File::create(temp)?);
+    match snapshot {
+        BaseSnapshot::Rdb(dump) => file.write_all(&dump.finish(db))?,
+        BaseSnapshot::Commands(keys, now) => {
+            let written = write_snapshot(db, &keys, now, &mut file);
+            db.lock().unwrap().end_snapshot();
+            written?;
         }
-        buffer.remove(0);
     }
+    let file = file.into_inner().map_err(|err| err.into_error())?;
+    file.sync_all()?;
 
-    if args.is_empty() {
-        None
-    } else {
-        let command = args.remove(0);
-        Some((command, args))
+    // 新的基础文件就位之后原子地替换清单，原来的基础文件和之前的增量文件成为历史文件，最后删除。
+    // 重写失败时清单不变，新的增量文件接在原来的增量文件之后加载
+    // Once the new base file is in place the manifest is atomically replaced, the old base file and the earlier
+    // incremental files become history files, deleted at the end. A failed rewrite leaves the manifest as it is,
+    // the new incremental file is loaded after the earlier ones
+    let mut manifest = MANIFEST.lock().unwrap();
+    let mut updated = manifest.clone();
+    let base = updated.next_base(prefix, rdb);
+    fs::rename(temp, dir.join(&base.name))?;
+    updated.replace_base(base, first_incr);
+    updated.persist(dir, prefix)?;
+    *manifest = updated;
+    if let Err(err) = delete_history(&mut manifest, dir, prefix) {
+        warn!("删除 AOF 历史文件失败: {}", err);  // Failed to delete the AOF history files
     }
+    REWRITE_BASE_SIZE.store(files_size(&manifest, dir), Ordering::Relaxed);
+    Ok(())
 }
 
-fn apply_command_to_db(
-    db: &mut Arc<Mutex<Db>>,
-    command: &str,
-    args: &[String],
-) -> Result<(), std::io::Error> {
-    let mut db = db.lock().unwrap();
+/// 把缓冲区写入当前的增量文件并同步到磁盘，再打开新的增量文件接收之后的写入，返回新文件的序号
+/// Write the buffer to the current incremental file and sync it to disk, then open a new incremental file receiving
+/// later writes, returning its sequence.
+fn open_new_incr(dir: &Path, prefix: &str) -> io::Result<u64> {
+    // 持有缓冲区和文件的锁，期间不会有新的写入
+    // Hold the buffer and file locks so no new writes come in meanwhile
+    let writer = AOF_WRITER.get().ok_or_else(|| io::Error::other("AOF is not running"))?;
+    let mut buffer = writer.buffer.lock().unwrap();
+    let mut file = writer.file.lock().unwrap();
+    file.write_all(&buffer)?;
+    file.flush()?;
+    file.get_ref().sync_data()?;
+    buffer.clear();
+    advance_flushed(APPENDED.load(Ordering::SeqCst));
+
+    let mut manifest = MANIFEST.lock().unwrap();
+    let mut updated = manifest.clone();
+    let incr = updated.new_incr(prefix);
+    let opened = OpenOptions::new().create(true).append(true).open(dir.join(&incr.name))?;
+    updated.persist(dir, prefix)?;
+    *manifest = updated;
+    *file = BufWriter::new(opened);
+    Ok(incr.seq)
+}
 
-    match command.to_lowercase().as_str() {
-        "set" => handle_set_command(&mut db, args)?,
-        "del" => handle_del_command(&mut db, args)?,
-        "hset" => handle_hset_command(&mut db, args)?,
-        "hdel" => handle_hdel_command(&mut db, args)?,
-        "lpush" => handle_lpush_command(&mut db, args)?,
-        "rpush" => handle_rpush_command(&mut db, args)?,
-        "lpop" => handle_lpop_command(&mut db, args)?,
-        "rpop" => handle_rpop_command(&mut db, args)?,
-        "lset" => handle_lset_command(&mut db, args)?,
-        "lrem" => handle_lrem_command(&mut db, args)?,
-        _ => info!("Unsupported command: {}", command),
+/// 把快照中的键写为重建它们的命令
+/// Write the keys of the snapshot as the commands rebuilding them.
+fn write_snapshot(db: &Arc<Mutex<Db>>, keys: &[String], now: u64, out: &mut impl Write) -> io::Result<()> {
+    for batch in keys.chunks(REWRITE_BATCH) {
+        let mut buf = Vec::new();
+        db.lock().unwrap().snapshot_entries(batch, |key, entry| rewrite_entry(&mut buf, key, entry, now));
+        out.write_all(&buf)?;
     }
     Ok(())
 }
+
+/// 重建一个键的命令，已经过期的键跳过
+/// Commands rebuilding one key, expired keys are skipped.
+fn rewrite_entry(buf: &mut Vec<u8>, key: &str, entry: &DbEntry, now: u64) {
+    if entry.expiration.is_some_and(|expiration| expiration <= now) {
+        return;
+    }
+    match &entry.value {
+        DbType::String(value) => encode_command(buf, "set", &[key, value]),
+        DbType::List(list) => {
+            let items: Vec<&str> = list.iter().map(String::as_str).collect();
+            for chunk in items.chunks(REWRITE_ITEMS_PER_COMMAND) {
+                encode_command(buf, "rpush", &[&[key], chunk].concat());
+            }
+        }
+        DbType::Hash(map) => {
+            let items: Vec<(&String, &String)> = map.iter().collect();
+            for chunk in items.chunks(REWRITE_ITEMS_PER_COMMAND) {
+                let mut args = vec![key];
+                args.extend(chunk.iter().flat_map(|(field, value)| [field.as_str(), value.as_str()]));
+                encode_command(buf, "hmset", &args);
+            }
+        }
+    }
+    if let Some(expiration) = entry.expiration {
+        encode_command(buf, "pexpireat", &[key, &expiration.to_string()]);
+    }
+}
+
+fn files_size(manifest: &Manifest, dir: &Path) -> u64 {
+    manifest.files().map(|file| fs::metadata(dir.join(&file.name)).map_or(0, |metadata| metadata.len())).sum()
+}
+
+/// 清单中基础文件和增量文件的总大小，AOF 未启用或还在加载时为 0
+/// Total size of the base and incremental files of the manifest, 0 if AOF is disabled or still loading.
+pub fn current_size() -> u64 {
+    if AOF_WRITER.get().is_none() {
+        return 0;
+    }
+    let (dir, _) = location();
+    files_size(&MANIFEST.lock().unwrap(), &dir)
+}
+
+/// 记下 AOF 文件当前的总大小作为自动重写的基准，之后每秒检查一次，文件超过 `auto_rewrite_min_size` 且相对基准增长了
+/// `auto_rewrite_percentage` 时，或者有被推迟的重写时，在后台重写
+/// Record the current total size of the AOF files as the base of automatic rewrites, then check every second,
+/// rewriting in the background when the files exceed `auto_rewrite_min_size` and grew by `auto_rewrite_percentage`
+/// relative to the base, or when a rewrite was postponed.
+pub fn start_rewriting(db: Arc<Mutex<Db>>) {
+    REWRITE_BASE_SIZE.store(current_size(), Ordering::Relaxed);
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(1));
+        loop {
+            interval.tick().await;
+            if stats::AOF_REWRITE_IN_PROGRESS.load(Ordering::Relaxed) || stats::RDB_SAVE_IN_PROGRESS.load(Ordering::Relaxed) {
+                continue;
+            }
+            if REWRITE_SCHEDULED.load(Ordering::Relaxed) || growth_exceeded() {
+                background_rewrite(&db);
+            }
+        }
+    });
+}
+
+fn growth_exceeded() -> bool {
+    let config = get_aof_config();
+    if !config.enabled || config.auto_rewrite_percentage == 0 {
+        return false;
+    }
+    let size = current_size();
+    let base = REWRITE_BASE_SIZE.load(Ordering::Relaxed).max(1);
+    size >= config.auto_rewrite_min_size && size > base && (size - base) * 100 / base >= config.auto_rewrite_percentage
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands;
+
+    thread_local! {
+        /// 测试中传播的命令，编码为 AOF 的格式
+        /// Commands propagated by the tests, encoded in the AOF format
+        static PROPAGATED: RefCell<Vec<u8>> = const { RefCell::new(Vec::new()) };
+    }
+
+    fn capture(name: String, args: Vec<String>) {
+        let args: Vec<&str> = args.iter().map(String::as_str).collect();
+        PROPAGATED.with(|propagated| encode_command(&mut propagated.borrow_mut(), &name, &args));
+    }
+
+    /// 与分发器一样，在传播上下文中执行一条命令
+    /// Execute a command in a propagation context, the same as the dispatcher
+    fn execute(db: &mut Arc<Mutex<Db>>, command: &[&str]) -> Frame {
+        let command_fn = Command::get_command_fn(command[0]).unwrap();
+        let frames = command[1..].iter().map(|arg| Frame::Bulk(arg.as_bytes().to_vec())).collect();
+        let mut parse = Parse::new(Some(Frame::Array(frames))).unwrap();
+        let command = command.iter().map(|arg| arg.to_string()).collect();
+        call_with(command, capture, || command_fn(db, &mut parse)).unwrap()
+    }
+
+    /// 加载写有给定数据的 AOF 文件，同时返回加载之后文件的内容
+    /// Load an AOF file holding the given data, also returning the content of the file after loading
+    async fn load_file(data: &[u8]) -> (io::Result<Db>, Vec<u8>) {
+        let path = std::env::temp_dir().join(format!("p-rudis-aof-{}-{:?}.aof", std::process::id(), std::thread::current().id()));
+        fs::write(&path, data).unwrap();
+        let mut loaded = Arc::new(Mutex::new(Db::new()));
+        let result = load_aof_file(&mut loaded, &path, true).await;
+        let content = fs::read(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        (result.map(|_| Arc::try_unwrap(loaded).unwrap().into_inner().unwrap()), content)
+    }
+
+    async fn load(data: &[u8]) -> Db {
+        load_file(data).await.0.unwrap()
+    }
+
+    fn entries(db: &Db) -> std::collections::HashMap<String, DbEntry> {
+        db.iter().map(|(key, entry)| (key.clone(), entry.clone())).collect()
+    }
+
+    #[tokio::test]
+    async fn replaying_propagated_commands_rebuilds_the_keyspace() {
+        Command::load_commands();
+        let later = (now_millis() + 3_600_000).to_string();
+        let script: &[&[&str]] = &[
+            &["set", "string", "value"],
+            &["set", "seconds", "value", "EX", "100"],
+            &["set", "millis", "value", "px", "100000"],
+            &["set", "unix", "value", "PXAT", &later],
+            &["set", "string", "other", "NX"],
+            &["set", "missing", "value", "XX"],
+            &["append", "string", " appended"],
+            &["append", "new", "created"],
+            &["incr", "counter"],
+            &["incrby", "counter", "10"],
+            &["decr", "counter"],
+            &["decrby", "counter", "3"],
+            &["incr", "string"],
+            &["mset", "a", "1", "b", "2"],
+            &["msetnx", "a", "3", "c", "4"],
+            &["msetnx", "c", "4", "d", "5"],
+            &["del", "b", "nosuch"],
+            &["pexpireat", "a", &later],
+            &["hset", "hash", "f1", "v1"],
+            &["hmset", "hash", "f2", "v2", "f3", "v3"],
+            &["hsetnx", "hash", "f1", "ignored"],
+            &["hsetnx", "hash", "f4", "v4"],
+            &["hdel", "hash", "f2", "nosuch"],
+            &["rpush", "list", "a", "b", "c", "b", "d"],
+            &["lpush", "list", "z", "y"],
+            &["lpop", "list"],
+            &["rpop", "list"],
+            &["lset", "list", "1", "B"],
+            &["lrem", "list", "1", "b"],
+            &["ltrim", "list", "0", "2"],
+            &["rpush", "blocking", "x", "y", "z"],
+            &["blpop", "blocking", "0"],
+            &["brpop", "blocking", "0"],
+        ];
+        for (name, .., spec) in commands::COMMANDS {
+            if spec.flags.contains(&"write") {
+                assert!(script.iter().any(|command| command[0] == *name), "'{}' is not covered", name);
+            }
+        }
+        let mut db = Arc::new(Mutex::new(Db::new()));
+        for command in script {
+            execute(&mut db, command);
+        }
+        let data = PROPAGATED.with(|propagated| propagated.take());
+        let text = String::from_utf8(data.clone()).unwrap();
+        // 过期时间写为绝对时间，阻塞的弹出写为普通的弹出，没有修改键的命令不传播
+        // Expirations are written as absolute times, blocking pops as plain pops, commands modifying no key are not
+        // propagated
+        assert_eq!(text.matches("pxat").count(), 3);
+        assert!(!text.contains("EX") && !text.contains("blpop") && !text.contains("brpop"));
+        assert!(!text.contains("missing") && !text.contains("ignored"));
+
+        let loaded = load(&data).await;
+        assert_eq!(entries(&loaded), entries(&db.lock().unwrap()));
+    }
+
+    #[tokio::test]
+    async fn rewritten_commands_rebuild_the_keyspace() {
+        Command::load_commands();
+        let db = Arc::new(Mutex::new(Db::new()));
+        let list: std::collections::VecDeque<String> = (0..150).map(|i| format!("item {}", i)).collect();
+        let map: std::collections::HashMap<String, String> = (0..70).map(|i| (format!("f{}", i), i.to_string())).collect();
+        let later = now_millis() + 3_600_000;
+        {
+            let mut db = db.lock().unwrap();
+            db.load_entry("string".to_string(), DbType::String("value".to_string()), None);
+            db.load_entry("list".to_string(), DbType::List(list.clone()), Some(later));
+            db.load_entry("hash".to_string(), DbType::Hash(map.clone()), None);
+            db.load_entry("expired".to_string(), DbType::String("gone".to_string()), Some(1));
+        }
+        let keys = db.lock().unwrap().begin_snapshot();
+        let mut data = Vec::new();
+        write_snapshot(&db, &keys, now_millis(), &mut data).unwrap();
+        db.lock().unwrap().end_snapshot();
+        let text = String::from_utf8(data.clone()).unwrap();
+        assert_eq!(text.matches("rpush").count(), 3);
+        assert_eq!(text.matches("hmset").count(), 2);
+        assert!(!text.contains("gone"));
+
+        let mut loaded = load(&data).await;
+        assert!(matches!(loaded.get("string"), Some(DbType::String(value)) if value == "value"));
+        assert!(matches!(loaded.get("list"), Some(DbType::List(items)) if *items == list));
+        assert!(matches!(loaded.get("hash"), Some(DbType::Hash(fields)) if *fields == map));
+        assert_eq!(loaded.keyspace_info().0, 3);
+        assert!(loaded.iter().any(|(key, entry)| key == "list" && entry.expiration == Some(later)));
+    }
+
+    #[tokio::test]
+    async fn values_with_line_breaks_are_loaded() {
+        Command::load_commands();
+        let mut data = Vec::new();
+        encode_command(&mut data, "set", &["lines", "a\r\nb\nc\r"]);
+        encode_command(&mut data, "rpush", &["list", "\r\n", "*2\r\n$3\r\nset"]);
+        // 加载时每次读取的数据在命令中间结束
+        // The data read at a time while loading ends in the middle of commands
+        let large = "x".repeat(LOAD_CHUNK_SIZE * 2 + 7);
+        encode_command(&mut data, "set", &["large", &large]);
+
+        let mut loaded = load(&data).await;
+        assert!(matches!(loaded.get("lines"), Some(DbType::String(value)) if value == "a\r\nb\nc\r"));
+        assert!(matches!(loaded.get("list"), Some(DbType::List(items)) if *items == ["\r\n", "*2\r\n$3\r\nset"]));
+        assert!(matches!(loaded.get("large"), Some(DbType::String(value)) if *value == large));
+    }
+
+    #[tokio::test]
+    async fn truncated_files_are_repaired_or_rejected() {
+        Command::load_commands();
+        let mut data = Vec::new();
+        encode_command(&mut data, "set", &["kept", "value"]);
+        let complete = data.len();
+        encode_command(&mut data, "set", &["lost", "value"]);
+        data.truncate(data.len() - 3);
+
+        let (loaded, content) = load_file(&data).await;
+        let mut loaded = loaded.unwrap();
+        assert!(loaded.get("kept").is_some() && loaded.get("lost").is_none());
+        assert_eq!(content, data[..complete]);
+
+        crate::config::set_parameters(&[("aof-load-truncated".to_string(), "no".to_string())]).unwrap();
+        let (loaded, content) = load_file(&data).await;
+        crate::config::set_parameters(&[("aof-load-truncated".to_string(), "yes".to_string())]).unwrap();
+        let err = loaded.err().unwrap();
+        assert!(err.to_string().contains(&format!("offset {}", complete)), "{}", err);
+        assert_eq!(content, data);
+    }
+
+    #[tokio::test]
+    async fn corrupted_files_report_the_offset() {
+        Command::load_commands();
+        let mut data = Vec::new();
+        encode_command(&mut data, "set", &["key", "value"]);
+        let offset = data.len();
+        for corrupted in [&b"*2\r\n$3\r\nset\r\n$5\r\nvalue!!\r\n"[..], b"+OK\r\n", b"set key value\r\n"] {
+            let mut file = data.clone();
+            file.extend_from_slice(corrupted);
+            encode_command(&mut file, "set", &["after", "value"]);
+            let (loaded, content) = load_file(&file).await;
+            let err = loaded.err().unwrap();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+            assert!(err.to_string().contains(&format!("offset {}", offset)), "{}", err);
+            assert_eq!(content, file);
+        }
+    }
+
+    #[tokio::test]
+    async fn directories_are_loaded_following_the_manifest() {
+        Command::load_commands();
+        let dir = std::env::temp_dir().join(format!("p-rudis-aof-dir-{}-{:?}", std::process::id(), std::thread::current().id()));
+        fs::create_dir_all(&dir).unwrap();
+        let mut manifest = Manifest::default();
+
+        // RDB 格式的基础文件
+        // An RDB-format base file
+        let snapshot = Arc::new(Mutex::new(Db::new()));
+        snapshot.lock().unwrap().set("key", DbType::String("base".to_string()), None);
+        let base = manifest.next_base("a.aof", true);
+        fs::write(dir.join(&base.name), crate::persistence::rdb::dump(&snapshot)).unwrap();
+        manifest.replace_base(base, 1);
+
+        let mut first = Vec::new();
+        encode_command(&mut first, "set", &["key", "incr"]);
+        encode_command(&mut first, "rpush", &["list", "a", "b"]);
+        fs::write(dir.join(manifest.new_incr("a.aof").name), &first).unwrap();
+
+        // 最后一个增量文件末尾的命令不完整
+        // The last command of the last incremental file is incomplete
+        let mut last = Vec::new();
+        encode_command(&mut last, "lpop", &["list"]);
+        let complete = last.len();
+        encode_command(&mut last, "set", &["lost", "value"]);
+        let last_name = manifest.new_incr("a.aof").name;
+        fs::write(dir.join(&last_name), &last[..last.len() - 2]).unwrap();
+
+        // 上次重写没有删除的历史文件
+        // A history file the previous rewrite didn't delete
+        manifest.history.push(AofFile { name: "a.aof.0.base.aof".to_string(), seq: 0, file_type: FileType::History });
+        fs::write(dir.join("a.aof.0.base.aof"), b"").unwrap();
+        manifest.persist(&dir, "a.aof").unwrap();
+
+        let mut db = Arc::new(Mutex::new(Db::new()));
+        let loaded = load_dir(&mut db, &dir, "a.aof").await.unwrap();
+        assert!(loaded.history.is_empty() && !dir.join("a.aof.0.base.aof").exists());
+        assert_eq!(Manifest::load(&dir, "a.aof").unwrap(), Some(loaded));
+        {
+            let mut db = db.lock().unwrap();
+            assert!(matches!(db.get("key"), Some(DbType::String(value)) if value == "incr"));
+            assert!(matches!(db.get("list"), Some(DbType::List(items)) if *items == ["b"]));
+            assert!(db.get("lost").is_none());
+        }
+        assert_eq!(fs::read(dir.join(&last_name)).unwrap(), last[..complete]);
+
+        // 只有最后一个文件可以被截断
+        // Only the last file may be truncated
+        fs::write(dir.join(&manifest.incrs[0].name), &first[..first.len() - 2]).unwrap();
+        let err = load_dir(&mut Arc::new(Mutex::new(Db::new())), &dir, "a.aof").await.err().unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn rdb_preambles_are_loaded_before_the_commands() {
+        Command::load_commands();
+        let snapshot = Arc::new(Mutex::new(Db::new()));
+        {
+            let mut snapshot = snapshot.lock().unwrap();
+            snapshot.set("key", DbType::String("preamble".to_string()), None);
+            snapshot.set("kept", DbType::String("preamble".to_string()), None);
+        }
+        let mut data = crate::persistence::rdb::dump(&snapshot).to_vec();
+        encode_command(&mut data, "set", &["key", "command"]);
+        encode_command(&mut data, "rpush", &["list", "a"]);
+        let complete = data.len();
+        encode_command(&mut data, "set", &["lost", "value"]);
+        data.truncate(data.len() - 1);
+
+        let (loaded, content) = load_file(&data).await;
+        let mut loaded = loaded.unwrap();
+        assert!(matches!(loaded.get("key"), Some(DbType::String(value)) if value == "command"));
+        assert!(matches!(loaded.get("kept"), Some(DbType::String(value)) if value == "preamble"));
+        assert!(matches!(loaded.get("list"), Some(DbType::List(items)) if *items == ["a"]));
+        assert!(loaded.get("lost").is_none());
+        assert_eq!(content, data[..complete]);
+    }
+
+    #[tokio::test]
+    async fn info_before_loading_keeps_the_aof_files() {
+        Command::load_commands();
+        let dir = std::env::temp_dir().join(format!("p-rudis-aof-restart-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        {
+            let mut config = crate::config::CONFIG.write().unwrap();
+            config.aof.dir_name = dir.to_string_lossy().into_owned();
+            config.aof.file_path = "restart.aof".to_string();
+        }
+        let mut manifest = Manifest::default();
+        let base = manifest.next_base("restart.aof", false);
+        let mut data = Vec::new();
+        encode_command(&mut data, "set", &["key", "base"]);
+        fs::write(dir.join(&base.name), &data).unwrap();
+        manifest.replace_base(base, 1);
+        let mut incr = Vec::new();
+        encode_command(&mut incr, "set", &["key", "incr"]);
+        fs::write(dir.join(manifest.new_incr("restart.aof").name), &incr).unwrap();
+        manifest.persist(&dir, "restart.aof").unwrap();
+        let files = |dir: &Path| {
+            let mut files: Vec<(String, Vec<u8>)> = fs::read_dir(dir).unwrap()
+                .map(|entry| entry.unwrap().path())
+                .map(|path| (path.file_name().unwrap().to_string_lossy().into_owned(), fs::read(&path).unwrap()))
+                .collect();
+            files.sort();
+            files
+        };
+        let before = files(&dir);
+
+        // 加载完成之前，INFO 和 maxmemory 的采样读取 AOF 的大小和缓冲区
+        // INFO and the maxmemory sampler read the AOF size and buffer before loading is done
+        let mut db = Arc::new(Mutex::new(Db::new()));
+        assert_eq!(current_size(), 0);
+        assert_eq!(buffer_len(), 0);
+        stats::used_memory(&db);
+        assert_eq!(background_rewrite(&db), Rewrite::NotRunning);
+        assert_eq!(files(&dir), before);
+
+        load_aof(&mut db).await.unwrap();
+        assert!(matches!(db.lock().unwrap().get("key"), Some(DbType::String(value)) if value == "incr"));
+        assert_eq!(files(&dir), before);
+        assert_eq!(current_size(), (data.len() + incr.len()) as u64);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn failed_fsyncs_wake_the_waiting_replies() {
+        let target = APPENDED.fetch_add(10, Ordering::SeqCst) + 10;
+        let waiting = tokio::spawn(wait_for_offset(target));
+        tokio::task::yield_now().await;
+        stats::aof_written(&Err("No space left on device".to_string()));
+        FLUSHED.send_modify(|flushed| flushed.failures += 1);
+        let err = waiting.await.unwrap().unwrap_err();
+        assert_eq!(err.to_string(), "MISCONF Errors writing to the AOF file: No space left on device");
+
+        let waiting = tokio::spawn(wait_for_offset(target));
+        tokio::task::yield_now().await;
+        advance_flushed(target);
+        waiting.await.unwrap().unwrap();
+        stats::aof_written(&Ok(()));
+    }
+}
@@ -0,0 +1,48 @@
+//! RDB 文件末尾的 CRC64 校验和
+//! The CRC64 checksum at the end of RDB files.
+//!
+//! 与 Redis 相同，使用 Jones 多项式的反射形式，初始值和结果异或值都为 0。
+//! Same as Redis, the reflected form of the Jones polynomial is used, with 0 as both the initial value and the
+//! final xor value.
+
+/// 反射形式的 Jones 多项式
+/// The Jones polynomial, reflected
+const POLY: u64 = 0x95ac_9329_ac4b_c9b5;
+
+/// 每个字节对应的余数，编译时计算
+/// The remainder of every byte, computed at compile time
+const TABLE: [u64; 256] = {
+    let mut table = [0u64; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u64;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 == 1 { (crc >> 1) ^ POLY } else { crc >> 1 };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+};
+
+/// 在已有的校验和上继续计算一段数据，第一段数据传入 0
+/// Continue the checksum over more data, 0 is passed for the first piece.
+pub fn update(mut crc: u64, data: &[u8]) -> u64 {
+    for &byte in data {
+        crc = TABLE[((crc ^ byte as u64) & 0xff) as usize] ^ (crc >> 8);
+    }
+    crc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_the_redis_check_value() {
+        assert_eq!(update(0, b"123456789"), 0xe9c6_d914_c4b8_d9ca);
+        assert_eq!(update(update(0, b"1234"), b"56789"), 0xe9c6_d914_c4b8_d9ca);
+    }
+}
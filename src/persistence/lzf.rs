@@ -0,0 +1,180 @@
+//! RDB 文件中压缩字符串使用的 LZF 算法
+//! The LZF algorithm used by compressed strings in RDB files.
+//!
+//! 压缩后的数据由两种块组成：控制字节小于 32 时，后面是 `控制字节 + 1` 个原样的字节；
+//! 否则高 3 位是重复长度减 2（为 7 时再读一个字节累加），低 5 位和下一个字节是到重复数据的距离减 1。
+//! Compressed data is made of two kinds of chunks: a control byte below 32 is followed by `control + 1` literal
+//! bytes; otherwise its top 3 bits are the length of the back reference minus 2 (one more byte is added when they
+//! are 7), and its low 5 bits together with the next byte are the distance to the referenced data minus 1.
+
+/// 哈希表的位数
+/// Bits of the hash table
+const HASH_LOG: usize = 14;
+
+/// 一个块最多包含的原样字节数
+/// Maximum number of literal bytes in one chunk
+const MAX_LITERAL: usize = 32;
+
+/// 最远的重复距离
+/// Maximum distance of a back reference
+const MAX_OFFSET: usize = 1 << 13;
+
+/// 最长的重复长度
+/// Maximum length of a back reference
+const MAX_REFERENCE: usize = (1 << 8) + (1 << 3);
+
+/// 压缩数据，结果不比原数据短至少 4 个字节时返回 `None`，这时应该原样保存
+/// Compress the data, `None` is returned if the result is not at least 4 bytes shorter than the input, in which
+/// case the data should be stored as it is.
+pub fn compress(input: &[u8]) -> Option<Vec<u8>> {
+    if input.len() <= 4 {
+        return None;
+    }
+    let limit = input.len() - 4;
+    let mut table = vec![0usize; 1 << HASH_LOG];
+    let mut output = Vec::with_capacity(limit);
+    // 当前原样字节块的控制字节位置和字节数
+    // Position of the control byte of the current literal chunk, and its number of bytes
+    let mut literal_at = 0;
+    let mut literals = 0;
+    output.push(0);
+    let mut ip = 0;
+    while ip < input.len() {
+        if ip + 2 < input.len() {
+            let hash = hash(&input[ip..ip + 3]);
+            // 表中保存位置加 1，0 表示空
+            // The table keeps the position plus 1, 0 means empty
+            let candidate = table[hash];
+            table[hash] = ip + 1;
+            if candidate > 0 {
+                let reference = candidate - 1;
+                let offset = ip - reference - 1;
+                if offset < MAX_OFFSET && input[reference..reference + 3] == input[ip..ip + 3] {
+                    let mut len = 3;
+                    while ip + len < input.len() && len < MAX_REFERENCE && input[reference + len] == input[ip + len] {
+                        len += 1;
+                    }
+                    // 结束当前的原样字节块
+                    // Close the current literal chunk
+                    if literals > 0 {
+                        output[literal_at] = (literals - 1) as u8;
+                    } else {
+                        output.pop();
+                    }
+                    let code = len - 2;
+                    if code < 7 {
+                        output.push(((code << 5) | (offset >> 8)) as u8);
+                    } else {
+                        output.push(((7 << 5) | (offset >> 8)) as u8);
+                        output.push((code - 7) as u8);
+                    }
+                    output.push(offset as u8);
+                    ip += len;
+                    literal_at = output.len();
+                    literals = 0;
+                    output.push(0);
+                    if output.len() > limit {
+                        return None;
+                    }
+                    continue;
+                }
+            }
+        }
+        output.push(input[ip]);
+        literals += 1;
+        ip += 1;
+        if literals == MAX_LITERAL {
+            output[literal_at] = (MAX_LITERAL - 1) as u8;
+            literal_at = output.len();
+            literals = 0;
+            output.push(0);
+        }
+        if output.len() > limit {
+            return None;
+        }
+    }
+    if literals > 0 {
+        output[literal_at] = (literals - 1) as u8;
+    } else {
+        output.pop();
+    }
+    (output.len() <= limit).then_some(output)
+}
+
+/// 压缩数据最多能展开的倍数：3 个字节的回溯引用最多展开为 264 个字节
+/// The most the compressed data can expand by: a 3-byte back reference expands to at most 264 bytes.
+pub const MAX_EXPANSION: usize = 88;
+
+/// 解压数据，`len` 是原数据的长度，数据损坏时返回 `None`
+/// Decompress the data, `len` is the length of the original data, `None` is returned if the data is corrupted.
+pub fn decompress(input: &[u8], len: usize) -> Option<Vec<u8>> {
+    if len > input.len().saturating_mul(MAX_EXPANSION) {
+        return None;
+    }
+    let mut output = Vec::with_capacity(len);
+    let mut ip = 0;
+    while ip < input.len() {
+        let control = input[ip] as usize;
+        ip += 1;
+        if control < 32 {
+            let literals = input.get(ip..ip + control + 1)?;
+            output.extend_from_slice(literals);
+            ip += control + 1;
+        } else {
+            let mut code = control >> 5;
+            if code == 7 {
+                code += *input.get(ip)? as usize;
+                ip += 1;
+            }
+            let offset = ((control & 0x1f) << 8) + *input.get(ip)? as usize + 1;
+            ip += 1;
+            let start = output.len().checked_sub(offset)?;
+            // 重复的数据可能和正在写入的数据重叠，需要逐字节复制
+            // The referenced data may overlap the data being written, so it is copied byte by byte
+            for i in 0..code + 2 {
+                output.push(output[start + i]);
+            }
+        }
+        if output.len() > len {
+            return None;
+        }
+    }
+    (output.len() == len).then_some(output)
+}
+
+fn hash(bytes: &[u8]) -> usize {
+    let value = ((bytes[0] as usize) << 16) | ((bytes[1] as usize) << 8) | bytes[2] as usize;
+    (value.wrapping_mul(2654435761) >> (32 - HASH_LOG)) & ((1 << HASH_LOG) - 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips() {
+        let inputs: Vec<Vec<u8>> = vec![
+            b"aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa".to_vec(),
+            b"hello world, hello world, hello world, hello world".repeat(40),
+            (0..5000u32).map(|i| (i % 251) as u8).collect(),
+        ];
+        for input in inputs {
+            let compressed = compress(&input).unwrap();
+            assert!(compressed.len() < input.len());
+            assert_eq!(decompress(&compressed, input.len()).unwrap(), input);
+        }
+    }
+
+    #[test]
+    fn incompressible_and_corrupted_data() {
+        let random: Vec<u8> = (0..64u64).map(|i| (i.wrapping_mul(0x9e37_79b9_7f4a_7c15) >> 56) as u8).collect();
+        assert_eq!(compress(&random), None);
+        // 引用了输出开始之前的数据
+        // A reference to data before the start of the output
+        assert_eq!(decompress(&[0x20, 0x05], 3), None);
+        assert_eq!(decompress(&[0x01, b'a', b'b'], 3), None);
+        // 损坏的长度不会被用来分配内存
+        // A corrupted length is not used to allocate memory
+        assert_eq!(decompress(&[0x01, b'a', b'b'], usize::MAX), None);
+    }
+}
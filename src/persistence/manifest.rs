@@ -0,0 +1,244 @@
+//! 多部分 AOF 的清单
+//! The manifest of the multi-part AOF.
+//!
+//! AOF 目录中有一个基础文件（RDB 或 AOF 格式）和若干个增量文件，加载时先加载基础文件，再按顺序加载增量文件。
+//! 清单每行记录一个文件的名称、序号和类型：`file <名称> seq <序号> type <b|i|h>`，`b` 是基础文件，`i` 是增量文件，
+//! `h` 是重写之后等待删除的历史文件。清单总是先写入临时文件再原子地替换。
+//! The AOF directory holds one base file (RDB or AOF format) and several incremental files, the base file is loaded
+//! first and then the incremental files in order. Each line of the manifest records the name, sequence and type of
+//! a file: `file <name> seq <seq> type <b|i|h>`, where `b` is the base file, `i` an incremental file and `h` a history
+//! file waiting to be deleted after a rewrite. The manifest is always written to a temporary file that atomically
+//! replaces it.
+
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::Path;
+
+/// 清单中文件的类型
+/// Type of a file in the manifest
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FileType {
+    Base,
+    Incr,
+    History,
+}
+
+impl FileType {
+    fn code(&self) -> &'static str {
+        match self {
+            FileType::Base => "b",
+            FileType::Incr => "i",
+            FileType::History => "h",
+        }
+    }
+}
+
+/// 清单中的一个文件
+/// A file of the manifest
+#[derive(Debug, Clone, PartialEq)]
+pub struct AofFile {
+    pub name: String,
+    pub seq: u64,
+    pub file_type: FileType,
+}
+
+impl AofFile {
+    /// 是否是 RDB 格式的基础文件
+    /// Whether this is an RDB-format base file
+    pub fn is_rdb(&self) -> bool {
+        self.file_type == FileType::Base && self.name.ends_with(".rdb")
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Manifest {
+    pub base: Option<AofFile>,
+    /// 按序号排列的增量文件
+    /// Incremental files ordered by sequence
+    pub incrs: Vec<AofFile>,
+    pub history: Vec<AofFile>,
+    /// 最近的基础文件和增量文件的序号，新文件的序号在它们之上递增
+    /// Sequences of the latest base and incremental files, new files take the next ones
+    base_seq: u64,
+    incr_seq: u64,
+}
+
+/// `prefix` 对应的清单文件名
+/// Name of the manifest file for `prefix`.
+pub fn manifest_name(prefix: &str) -> String {
+    format!("{}.manifest", prefix)
+}
+
+impl Manifest {
+    /// 解析清单的内容，空行和 `#` 开头的行被忽略
+    /// Parse the content of a manifest, ignoring empty lines and lines starting with `#`.
+    pub fn parse(text: &str) -> Result<Manifest, String> {
+        let mut manifest = Manifest::default();
+        for (number, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let invalid = |reason: &str| format!("invalid AOF manifest line {}: {}", number + 1, reason);
+            let tokens: Vec<&str> = line.split_whitespace().collect();
+            if !tokens.len().is_multiple_of(2) {
+                return Err(invalid("expected key value pairs"));
+            }
+            let (mut name, mut seq, mut file_type) = (None, None, None);
+            for pair in tokens.chunks(2) {
+                match pair[0] {
+                    "file" => name = Some(pair[1].to_string()),
+                    "seq" => seq = Some(pair[1].parse::<u64>().map_err(|_| invalid("invalid seq"))?),
+                    "type" => {
+                        file_type = Some(match pair[1] {
+                            "b" => FileType::Base,
+                            "i" => FileType::Incr,
+                            "h" => FileType::History,
+                            _ => return Err(invalid("unknown file type")),
+                        })
+                    }
+                    // 不认识的键留给以后的版本
+                    // Unknown keys are left to later versions
+                    _ => {}
+                }
+            }
+            let (Some(name), Some(seq), Some(file_type)) = (name, seq, file_type) else {
+                return Err(invalid("missing file, seq or type"));
+            };
+            let file = AofFile { name, seq, file_type };
+            match file_type {
+                FileType::Base if manifest.base.is_some() => return Err(invalid("more than one base file")),
+                FileType::Base => {
+                    manifest.base_seq = seq;
+                    manifest.base = Some(file);
+                }
+                FileType::Incr => {
+                    if seq <= manifest.incr_seq {
+                        return Err(invalid("incremental files out of order"));
+                    }
+                    manifest.incr_seq = seq;
+                    manifest.incrs.push(file);
+                }
+                FileType::History => manifest.history.push(file),
+            }
+        }
+        Ok(manifest)
+    }
+
+    /// 编码为清单文件的内容
+    /// Encode as the content of the manifest file.
+    pub fn encode(&self) -> String {
+        self.base.iter().chain(&self.history).chain(&self.incrs)
+            .map(|file| format!("file {} seq {} type {}\n", file.name, file.seq, file.file_type.code()))
+            .collect()
+    }
+
+    /// 读取目录中 `prefix` 的清单，不存在时返回 `None`
+    /// Read the manifest of `prefix` in the directory, `None` if it doesn't exist.
+    pub fn load(dir: &Path, prefix: &str) -> io::Result<Option<Manifest>> {
+        match fs::read_to_string(dir.join(manifest_name(prefix))) {
+            Ok(text) => Manifest::parse(&text).map(Some).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err)),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// 把清单写入临时文件并同步到磁盘，再原子地替换原来的清单
+    /// Write the manifest to a temporary file synced to disk, which then atomically replaces the old one.
+    pub fn persist(&self, dir: &Path, prefix: &str) -> io::Result<()> {
+        let name = manifest_name(prefix);
+        let temp = dir.join(format!("temp-{}", name));
+        let mut file = File::create(&temp)?;
+        file.write_all(self.encode().as_bytes())?;
+        file.sync_all()?;
+        fs::rename(&temp, dir.join(name))?;
+        File::open(dir)?.sync_all()
+    }
+
+    /// 加载的文件，先是基础文件，再是增量文件
+    /// Files to load, the base file first and then the incremental files.
+    pub fn files(&self) -> impl Iterator<Item = &AofFile> {
+        self.base.iter().chain(&self.incrs)
+    }
+
+    /// 添加一个新的增量文件
+    /// Add a new incremental file.
+    pub fn new_incr(&mut self, prefix: &str) -> AofFile {
+        self.incr_seq += 1;
+        let file = AofFile { name: format!("{}.{}.incr.aof", prefix, self.incr_seq), seq: self.incr_seq, file_type: FileType::Incr };
+        self.incrs.push(file.clone());
+        file
+    }
+
+    /// 新的基础文件，用 [`Manifest::replace_base`] 放入清单
+    /// A new base file, put into the manifest with [`Manifest::replace_base`].
+    pub fn next_base(&self, prefix: &str, rdb: bool) -> AofFile {
+        let extension = if rdb { "rdb" } else { "aof" };
+        AofFile { name: format!("{}.{}.base.{}", prefix, self.base_seq + 1, extension), seq: self.base_seq + 1, file_type: FileType::Base }
+    }
+
+    /// 重写结束：新的基础文件包含序号在 `first_incr` 之前的增量文件中的数据，原来的基础文件和这些增量文件成为历史文件
+    /// A rewrite ended: the new base file holds the data of the incremental files before `first_incr`, the old base
+    /// file and those incremental files become history files.
+    pub fn replace_base(&mut self, base: AofFile, first_incr: u64) {
+        self.base_seq = base.seq;
+        if let Some(old) = self.base.replace(base) {
+            self.history.push(AofFile { file_type: FileType::History, ..old });
+        }
+        let (old, kept): (Vec<AofFile>, Vec<AofFile>) = self.incrs.drain(..).partition(|file| file.seq < first_incr);
+        self.history.extend(old.into_iter().map(|file| AofFile { file_type: FileType::History, ..file }));
+        self.incrs = kept;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips() {
+        let mut manifest = Manifest::default();
+        let base = manifest.next_base("appendonly.aof", true);
+        manifest.replace_base(base, 1);
+        manifest.new_incr("appendonly.aof");
+        manifest.new_incr("appendonly.aof");
+        let text = manifest.encode();
+        assert_eq!(
+            text,
+            "file appendonly.aof.1.base.rdb seq 1 type b\n\
+             file appendonly.aof.1.incr.aof seq 1 type i\n\
+             file appendonly.aof.2.incr.aof seq 2 type i\n"
+        );
+        assert_eq!(Manifest::parse(&text).unwrap(), manifest);
+        assert!(manifest.base.as_ref().unwrap().is_rdb());
+    }
+
+    #[test]
+    fn rewrites_move_old_files_to_history() {
+        let mut manifest = Manifest::parse("file a.aof seq 1 type b\nfile a.aof.1.incr.aof seq 1 type i\n").unwrap();
+        let incr = manifest.new_incr("a.aof");
+        let base = manifest.next_base("a.aof", false);
+        assert_eq!(base.name, "a.aof.2.base.aof");
+        manifest.replace_base(base.clone(), incr.seq);
+        assert_eq!(manifest.base, Some(base));
+        assert_eq!(manifest.incrs, vec![incr]);
+        let history: Vec<&str> = manifest.history.iter().map(|file| file.name.as_str()).collect();
+        assert_eq!(history, ["a.aof", "a.aof.1.incr.aof"]);
+        assert_eq!(Manifest::parse(&manifest.encode()).unwrap(), manifest);
+    }
+
+    #[test]
+    fn invalid_manifests_are_rejected() {
+        for text in [
+            "file a seq 1",
+            "file a seq x type b",
+            "file a seq 1 type z",
+            "file a seq 1 type b\nfile b seq 2 type b",
+            "file a seq 2 type i\nfile b seq 1 type i",
+            "file a seq 1 type",
+        ] {
+            assert!(Manifest::parse(text).is_err(), "{}", text);
+        }
+        assert_eq!(Manifest::parse("# comment\n\n").unwrap(), Manifest::default());
+    }
+}
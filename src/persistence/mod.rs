@@ -1,3 +1,6 @@
 pub mod aof;
-mod aof_command;
-pub mod rdb;
\ No newline at end of file
+mod crc64;
+mod lzf;
+mod manifest;
+mod packed;
+pub mod rdb;
@@ -0,0 +1,176 @@
+//! Redis 紧凑编码的解码：ziplist、listpack 和 zipmap
+//! Decoding of the compact Redis encodings: ziplist, listpack and zipmap.
+//!
+//! RDB 文件把使用紧凑编码的列表、哈希等保存为一个字符串，这里把它们展开为元素的列表，整数元素转换为十进制字符串。
+//! 数据损坏时返回 `None`。
+//! RDB files store lists, hashes and others in a compact encoding as a single string, they are expanded here into
+//! the list of their elements, integer elements being converted to decimal strings. `None` is returned if the data
+//! is corrupted.
+
+/// 展开 ziplist
+/// Expand a ziplist.
+pub fn ziplist(data: &[u8]) -> Option<Vec<Vec<u8>>> {
+    // 头部：总字节数、最后一个元素的偏移量和元素个数
+    // Header: total bytes, offset of the last entry and number of entries
+    let mut pos = 10;
+    let mut entries = Vec::with_capacity(u16_le(data, 8)? as usize);
+    loop {
+        let first = *data.get(pos)?;
+        if first == 0xff {
+            return Some(entries);
+        }
+        // 跳过前一个元素的长度
+        // Skip the length of the previous entry
+        pos += if first < 0xfe { 1 } else { 5 };
+        let encoding = *data.get(pos)?;
+        pos += 1;
+        let entry = match encoding >> 6 {
+            0b00 => string(data, &mut pos, (encoding & 0x3f) as usize)?,
+            0b01 => {
+                let len = (((encoding & 0x3f) as usize) << 8) | *data.get(pos)? as usize;
+                pos += 1;
+                string(data, &mut pos, len)?
+            }
+            0b10 => {
+                let len = u32::from_be_bytes(data.get(pos..pos + 4)?.try_into().ok()?) as usize;
+                pos += 4;
+                string(data, &mut pos, len)?
+            }
+            _ => {
+                let value = match encoding {
+                    0xc0 => int_le(data, &mut pos, 2)?,
+                    0xd0 => int_le(data, &mut pos, 4)?,
+                    0xe0 => int_le(data, &mut pos, 8)?,
+                    0xf0 => int_le(data, &mut pos, 3)?,
+                    0xfe => int_le(data, &mut pos, 1)?,
+                    0xf1..=0xfd => (encoding & 0x0f) as i64 - 1,
+                    _ => return None,
+                };
+                value.to_string().into_bytes()
+            }
+        };
+        entries.push(entry);
+    }
+}
+
+/// 展开 listpack
+/// Expand a listpack.
+pub fn listpack(data: &[u8]) -> Option<Vec<Vec<u8>>> {
+    // 头部：总字节数和元素个数
+    // Header: total bytes and number of entries
+    let mut pos = 6;
+    let mut entries = Vec::with_capacity(u16_le(data, 4)? as usize);
+    loop {
+        let start = pos;
+        let encoding = *data.get(pos)?;
+        pos += 1;
+        let entry = if encoding == 0xff {
+            return Some(entries);
+        } else if encoding & 0x80 == 0 {
+            (encoding as i64).to_string().into_bytes()
+        } else if encoding & 0xc0 == 0x80 {
+            string(data, &mut pos, (encoding & 0x3f) as usize)?
+        } else if encoding & 0xe0 == 0xc0 {
+            let value = (((encoding & 0x1f) as i64) << 8) | *data.get(pos)? as i64;
+            pos += 1;
+            sign_extend(value, 13).to_string().into_bytes()
+        } else if encoding & 0xf0 == 0xe0 {
+            let len = (((encoding & 0x0f) as usize) << 8) | *data.get(pos)? as usize;
+            pos += 1;
+            string(data, &mut pos, len)?
+        } else {
+            match encoding {
+                0xf0 => {
+                    let len = u32::from_le_bytes(data.get(pos..pos + 4)?.try_into().ok()?) as usize;
+                    pos += 4;
+                    string(data, &mut pos, len)?
+                }
+                0xf1 => int_le(data, &mut pos, 2)?.to_string().into_bytes(),
+                0xf2 => int_le(data, &mut pos, 3)?.to_string().into_bytes(),
+                0xf3 => int_le(data, &mut pos, 4)?.to_string().into_bytes(),
+                0xf4 => int_le(data, &mut pos, 8)?.to_string().into_bytes(),
+                _ => return None,
+            }
+        };
+        // 跳过元素末尾记录的元素长度
+        // Skip the entry length stored at the end of the entry
+        pos += backlen_size(pos - start);
+        entries.push(entry);
+    }
+}
+
+/// 展开 zipmap，结果中键和值交替出现
+/// Expand a zipmap, keys and values alternate in the result.
+pub fn zipmap(data: &[u8]) -> Option<Vec<Vec<u8>>> {
+    let mut pos = 1;
+    let mut entries = Vec::new();
+    while let Some(len) = zipmap_len(data, &mut pos)? {
+        entries.push(string(data, &mut pos, len)?);
+        // 值的长度之后还有一个字节，记录值后面空闲的字节数
+        // The length of a value is followed by one byte with the number of free bytes after the value
+        let len = zipmap_len(data, &mut pos)??;
+        let free = *data.get(pos)? as usize;
+        pos += 1;
+        entries.push(string(data, &mut pos, len)?);
+        pos += free;
+    }
+    Some(entries)
+}
+
+fn u16_le(data: &[u8], pos: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(data.get(pos..pos + 2)?.try_into().ok()?))
+}
+
+/// 读取 zipmap 中的长度，遇到结束标记时返回 `Some(None)`
+/// Read a length in a zipmap, `Some(None)` is returned at the end marker.
+fn zipmap_len(data: &[u8], pos: &mut usize) -> Option<Option<usize>> {
+    match *data.get(*pos)? {
+        0xff => Some(None),
+        0xfe => {
+            let len = u32::from_le_bytes(data.get(*pos + 1..*pos + 5)?.try_into().ok()?) as usize;
+            *pos += 5;
+            Some(Some(len))
+        }
+        len => {
+            *pos += 1;
+            Some(Some(len as usize))
+        }
+    }
+}
+
+fn string(data: &[u8], pos: &mut usize, len: usize) -> Option<Vec<u8>> {
+    let value = data.get(*pos..*pos + len)?.to_vec();
+    *pos += len;
+    Some(value)
+}
+
+/// 读取 `width` 个字节的小端有符号整数
+/// Read a little endian signed integer of `width` bytes.
+fn int_le(data: &[u8], pos: &mut usize, width: usize) -> Option<i64> {
+    let bytes = data.get(*pos..*pos + width)?;
+    *pos += width;
+    let value = bytes.iter().rev().fold(0u64, |value, &byte| (value << 8) | byte as u64);
+    Some(sign_extend(value as i64, width * 8))
+}
+
+/// 把 `bits` 位的补码扩展为 64 位
+/// Extend a two's complement value of `bits` bits to 64 bits.
+fn sign_extend(value: i64, bits: usize) -> i64 {
+    if bits >= 64 {
+        return value;
+    }
+    let shift = 64 - bits;
+    (value << shift) >> shift
+}
+
+/// listpack 元素末尾记录元素长度所用的字节数
+/// Number of bytes used at the end of a listpack entry to store its length.
+fn backlen_size(len: usize) -> usize {
+    match len {
+        0..=127 => 1,
+        128..=16382 => 2,
+        16383..=2097150 => 3,
+        2097151..=268435454 => 4,
+        _ => 5,
+    }
+}
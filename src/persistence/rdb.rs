@@ -1,17 +1,51 @@
+//! RDB 快照文件，格式与 Redis 相同
+//! RDB snapshot files, in the same format as Redis.
+//!
+//! 文件以 `REDIS` 和四位数字的版本号开头，之后是辅助字段、数据库编号、数据库大小提示和键值对，
+//! 最后是 EOF 操作码和 CRC64 校验和。保存时只使用 Redis 6 及以后的版本都能读取的编码；
+//! 加载时支持 Redis 各个版本写入的编码，集合、有序集合、流和模块类型的键暂不支持，加载时跳过。
+//! A file starts with `REDIS` and a four digit version, followed by auxiliary fields, the database number, the
+//! database size hints and the key value pairs, and ends with the EOF opcode and a CRC64 checksum. Saving only uses
+//! encodings that Redis 6 and later can read; loading supports the encodings written by every Redis version, keys
+//! of the set, sorted set, stream and module types are not supported yet and are skipped while loading.
+
 use std::collections::{HashMap, VecDeque};
-use crate::db::{Db, DbEntry, DbType};
-use bytes::{Buf, BufMut, BytesMut};
-use std::fs::{File, OpenOptions};
+use std::fs;
+use std::fs::File;
 use std::io;
-use std::io::{BufWriter, Read, Seek, SeekFrom, Write};
+use std::io::Write;
+use std::path::Path;
 use std::sync::{Arc, Mutex};
-use std::time::{Instant, SystemTime, UNIX_EPOCH};
-
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use bytes::{BufMut, BytesMut};
+use log::{error, info, warn};
+use crate::config::get_rdb_config;
+use crate::db::{Db, DbEntry, DbType};
+use crate::persistence::{crc64, lzf, packed};
+use crate::stats;
 
-// Constants
-mod constants {
+pub mod constants {
     pub const RDB_MAGIC: &[u8] = b"REDIS";
-    pub const RDB_VERSION: &[u8] = b"0001";
+    /// 保存时写入的版本，与 Redis 6 相同
+    /// Version written when saving, the same as Redis 6
+    pub const RDB_VERSION: u32 = 9;
+    /// 能够加载的最高版本
+    /// Highest version that can be loaded
+    pub const RDB_MAX_VERSION: u32 = 12;
+
+    // Length encodings, in the two high bits of the first byte
+    pub const RDB_6BITLEN: u8 = 0;
+    pub const RDB_14BITLEN: u8 = 1;
+    pub const RDB_32BITLEN: u8 = 0x80;
+    pub const RDB_64BITLEN: u8 = 0x81;
+    pub const RDB_ENCVAL: u8 = 3;
+
+    // Special string encodings, following `RDB_ENCVAL`
+    pub const RDB_ENC_INT8: u8 = 0;
+    pub const RDB_ENC_INT16: u8 = 1;
+    pub const RDB_ENC_INT32: u8 = 2;
+    pub const RDB_ENC_LZF: u8 = 3;
 
     // Data type identifiers
     pub const RDB_TYPE_STRING: u8 = 0;
@@ -19,58 +53,100 @@ mod constants {
     pub const RDB_TYPE_SET: u8 = 2;
     pub const RDB_TYPE_ZSET: u8 = 3;
     pub const RDB_TYPE_HASH: u8 = 4;
+    pub const RDB_TYPE_ZSET_2: u8 = 5;
+    pub const RDB_TYPE_MODULE_2: u8 = 7;
+    pub const RDB_TYPE_HASH_ZIPMAP: u8 = 9;
+    pub const RDB_TYPE_LIST_ZIPLIST: u8 = 10;
+    pub const RDB_TYPE_SET_INTSET: u8 = 11;
+    pub const RDB_TYPE_ZSET_ZIPLIST: u8 = 12;
+    pub const RDB_TYPE_HASH_ZIPLIST: u8 = 13;
+    pub const RDB_TYPE_LIST_QUICKLIST: u8 = 14;
+    pub const RDB_TYPE_STREAM_LISTPACKS: u8 = 15;
+    pub const RDB_TYPE_HASH_LISTPACK: u8 = 16;
+    pub const RDB_TYPE_ZSET_LISTPACK: u8 = 17;
+    pub const RDB_TYPE_LIST_QUICKLIST_2: u8 = 18;
+    pub const RDB_TYPE_STREAM_LISTPACKS_2: u8 = 19;
+    pub const RDB_TYPE_SET_LISTPACK: u8 = 20;
+    pub const RDB_TYPE_STREAM_LISTPACKS_3: u8 = 21;
+
+    // Quicklist node containers
+    pub const QUICKLIST_NODE_CONTAINER_PLAIN: u64 = 1;
 
     // Opcodes
+    pub const RDB_OPCODE_SLOT_INFO: u8 = 244;
+    pub const RDB_OPCODE_FUNCTION2: u8 = 245;
+    pub const RDB_OPCODE_MODULE_AUX: u8 = 247;
+    pub const RDB_OPCODE_IDLE: u8 = 248;
+    pub const RDB_OPCODE_FREQ: u8 = 249;
     pub const RDB_OPCODE_AUX: u8 = 250;
+    pub const RDB_OPCODE_RESIZEDB: u8 = 251;
     pub const RDB_OPCODE_EXPIRETIME_MS: u8 = 252;
+    pub const RDB_OPCODE_EXPIRETIME: u8 = 253;
     pub const RDB_OPCODE_SELECTDB: u8 = 254;
     pub const RDB_OPCODE_EOF: u8 = 255;
+
+    // Opcodes inside module values
+    pub const RDB_MODULE_OPCODE_EOF: u64 = 0;
+    pub const RDB_MODULE_OPCODE_SINT: u64 = 1;
+    pub const RDB_MODULE_OPCODE_UINT: u64 = 2;
+    pub const RDB_MODULE_OPCODE_FLOAT: u64 = 3;
+    pub const RDB_MODULE_OPCODE_DOUBLE: u64 = 4;
+    pub const RDB_MODULE_OPCODE_STRING: u64 = 5;
 }
 
 use constants::*;
 
 #[derive(Debug, Clone)]
 pub struct RdbWriter {
-    file: Arc<Mutex<BufWriter<File>>>,
     buffer: BytesMut,
+    /// 是否用 LZF 压缩较长的字符串
+    /// Whether longer strings are compressed with LZF
+    compression: bool,
 }
 
 impl RdbWriter {
-    pub fn new(rdb_file_path: &str) -> Self {
-        let file = OpenOptions::new()
-            .create(true)
-            .write(true)
-            .open(rdb_file_path)
-            .expect("Failed to open RDB file");
-
+    pub fn new() -> Self {
         Self {
-            file: Arc::new(Mutex::new(BufWriter::new(file))),
             buffer: BytesMut::new(),
+            compression: get_rdb_config().compression,
         }
     }
 
-    pub async fn load_file(rdb_file_path: &str) -> io::Result<Self> {
-        let mut file = File::open(rdb_file_path)?;
-        let mut buffer = BytesMut::with_capacity(10 * 1024 * 1024);
-        let mut vec = buffer.to_vec();
-
-        file.read_to_end(&mut vec)?;
-        if vec.is_empty() {
-            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "File is empty"));
-        }
+    /// 文件头：魔数、版本号和辅助字段
+    /// Header: magic, version and auxiliary fields.
+    fn write_header(&mut self, used_memory: usize) {
+        self.buffer.put_slice(RDB_MAGIC);
+        self.buffer.put_slice(format!("{:04}", RDB_VERSION).as_bytes());
+        let ctime = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+        self.write_aux("redis-ver", env!("CARGO_PKG_VERSION"));
+        self.write_aux("redis-bits", &usize::BITS.to_string());
+        self.write_aux("ctime", &ctime.to_string());
+        self.write_aux("used-mem", &used_memory.to_string());
+        self.write_aux("aof-base", "0");
+    }
 
-        buffer.put_slice(&vec);
+    fn write_aux(&mut self, key: &str, value: &str) {
+        self.buffer.put_u8(RDB_OPCODE_AUX);
+        self.write_string(key.as_bytes());
+        self.write_string(value.as_bytes());
+    }
 
-        Ok(Self {
-            file: Arc::new(Mutex::new(BufWriter::new(file))),
-            buffer,
-        })
+    /// 选择数据库，并给出键和带过期时间的键的数量，加载时用来预先分配空间
+    /// Select the database and give the number of keys and keys with an expiration, used to preallocate when loading.
+    fn write_db_header(&mut self, index: u64, keys: u64, expires: u64) {
+        self.buffer.put_u8(RDB_OPCODE_SELECTDB);
+        self.write_length(index);
+        self.buffer.put_u8(RDB_OPCODE_RESIZEDB);
+        self.write_length(keys);
+        self.write_length(expires);
     }
 
-    // Header operations
-    fn write_header(&mut self) {
-        self.buffer.put_slice(RDB_MAGIC);
-        self.buffer.put_slice(RDB_VERSION);
+    /// 文件尾：EOF 操作码和之前所有内容的 CRC64 校验和，不校验时写入 0
+    /// Footer: the EOF opcode and the CRC64 checksum of everything before, 0 is written when checksums are disabled.
+    fn write_footer(&mut self, checksum: bool) {
+        self.buffer.put_u8(RDB_OPCODE_EOF);
+        let crc = if checksum { crc64::update(0, &self.buffer) } else { 0 };
+        self.buffer.put_u64_le(crc);
     }
 
     // Key-value operations
@@ -83,34 +159,86 @@ impl RdbWriter {
         }
 
         self.save_db_type(&value.value);
-        self.save_string(key);
+        self.write_string(key.as_bytes());
         self.save_value(&value.value);
     }
 
     fn write_expire_time(&mut self, expire_time: u64) {
         self.buffer.put_u8(RDB_OPCODE_EXPIRETIME_MS);
-        self.buffer.put_u64(expire_time);
+        self.buffer.put_u64_le(expire_time);
+    }
+
+    /// 写入长度，按大小使用 6、14、32 或 64 位
+    /// Write a length, using 6, 14, 32 or 64 bits depending on its size.
+    fn write_length(&mut self, len: u64) {
+        if len < 1 << 6 {
+            self.buffer.put_u8((RDB_6BITLEN << 6) | len as u8);
+        } else if len < 1 << 14 {
+            self.buffer.put_u8((RDB_14BITLEN << 6) | (len >> 8) as u8);
+            self.buffer.put_u8(len as u8);
+        } else if len <= u32::MAX as u64 {
+            self.buffer.put_u8(RDB_32BITLEN);
+            self.buffer.put_u32(len as u32);
+        } else {
+            self.buffer.put_u8(RDB_64BITLEN);
+            self.buffer.put_u64(len);
+        }
+    }
+
+    /// 写入字符串：能表示为 32 位整数的保存为整数，较长且能被压缩的用 LZF 压缩，其它的原样保存
+    /// Write a string: strings representing a 32 bit integer are saved as integers, longer strings that can be
+    /// compressed are compressed with LZF, the others are saved as they are.
+    fn write_string(&mut self, s: &[u8]) {
+        if s.len() <= 11 && self.write_integer_string(s) {
+            return;
+        }
+        if self.compression && s.len() > 20 && let Some(compressed) = lzf::compress(s) {
+            self.buffer.put_u8((RDB_ENCVAL << 6) | RDB_ENC_LZF);
+            self.write_length(compressed.len() as u64);
+            self.write_length(s.len() as u64);
+            self.buffer.put_slice(&compressed);
+            return;
+        }
+        self.write_length(s.len() as u64);
+        self.buffer.put_slice(s);
     }
 
-    fn save_string(&mut self, s: &str) {
-        self.buffer.put_u8(s.len() as u8);
-        self.buffer.put_slice(s.as_bytes());
+    /// 字符串是整数的规范写法且在 32 位范围内时按整数写入
+    /// Write the string as an integer if it is the canonical form of an integer in the 32 bit range.
+    fn write_integer_string(&mut self, s: &[u8]) -> bool {
+        let value = match std::str::from_utf8(s).ok().and_then(|s| s.parse::<i64>().ok()) {
+            Some(value) if value.to_string().as_bytes() == s => value,
+            _ => return false,
+        };
+        if let Ok(value) = i8::try_from(value) {
+            self.buffer.put_u8((RDB_ENCVAL << 6) | RDB_ENC_INT8);
+            self.buffer.put_i8(value);
+        } else if let Ok(value) = i16::try_from(value) {
+            self.buffer.put_u8((RDB_ENCVAL << 6) | RDB_ENC_INT16);
+            self.buffer.put_i16_le(value);
+        } else if let Ok(value) = i32::try_from(value) {
+            self.buffer.put_u8((RDB_ENCVAL << 6) | RDB_ENC_INT32);
+            self.buffer.put_i32_le(value);
+        } else {
+            return false;
+        }
+        true
     }
 
     fn save_value(&mut self, value: &DbType) {
         match value {
-            DbType::String(s) => self.save_string(s),
+            DbType::String(s) => self.write_string(s.as_bytes()),
             DbType::List(list) => {
-                self.buffer.put_u8(list.len() as u8);
+                self.write_length(list.len() as u64);
                 for item in list {
-                    self.save_string(item);
+                    self.write_string(item.as_bytes());
                 }
             }
             DbType::Hash(map) => {
-                self.buffer.put_u8(map.len() as u8);
+                self.write_length(map.len() as u64);
                 for (key, value) in map {
-                    self.save_string(key);
-                    self.save_string(value);
+                    self.write_string(key.as_bytes());
+                    self.write_string(value.as_bytes());
                 }
             }
         }
@@ -124,161 +252,914 @@ impl RdbWriter {
         };
         self.buffer.put_u8(type_code);
     }
+}
+
+impl Default for RdbWriter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// RDB 文件的解码器，记录读取的位置，数据损坏时在错误中报告偏移量
+/// Decoder of RDB files, keeping the read position, which is reported in the error when the data is corrupted.
+pub struct RdbReader {
+    data: Vec<u8>,
+    pos: usize,
+    version: u32,
+    binary: bool,
+}
+
+impl RdbReader {
+    pub fn new(data: Vec<u8>) -> Self {
+        Self { data, pos: 0, version: 0, binary: false }
+    }
+
+    pub async fn load_file(rdb_file_path: &str) -> io::Result<Self> {
+        let data = std::fs::read(rdb_file_path)?;
+        if data.is_empty() {
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "File is empty"));
+        }
+        Ok(Self::new(data))
+    }
+
+    /// 已经读取的字节数
+    /// Number of bytes read so far.
+    pub fn position(&self) -> usize {
+        self.pos
+    }
+
+    fn corrupted(&self, reason: impl std::fmt::Display) -> io::Error {
+        io::Error::new(io::ErrorKind::InvalidData, format!("corrupted RDB file at offset {}: {}", self.pos, reason))
+    }
+
+    /// 读取并检查魔数和版本号
+    /// Read and check the magic and the version.
+    fn read_header(&mut self) -> io::Result<()> {
+        let header = self.read_bytes(9)?;
+        if &header[..5] != RDB_MAGIC {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "Invalid RDB file"));
+        }
+        let version = std::str::from_utf8(&header[5..]).ok().and_then(|version| version.parse::<u32>().ok());
+        match version {
+            Some(version @ 1..=RDB_MAX_VERSION) => self.version = version,
+            _ => return Err(io::Error::new(io::ErrorKind::InvalidData, "Unsupported RDB version")),
+        }
+        Ok(())
+    }
+
+    /// 读取 EOF 之后的校验和，版本 5 之前没有校验和，值为 0 表示保存时没有计算
+    /// Read the checksum after EOF, versions before 5 have no checksum, 0 means it was not computed when saving.
+    fn read_checksum(&mut self, verify: bool) -> io::Result<()> {
+        if self.version < 5 {
+            return Ok(());
+        }
+        let crc = crc64::update(0, &self.data[..self.pos]);
+        let expected = u64::from_le_bytes(self.read_array()?);
+        if verify && expected != 0 && expected != crc {
+            return Err(self.corrupted("wrong RDB checksum"));
+        }
+        Ok(())
+    }
+
+    fn read_bytes(&mut self, n: usize) -> io::Result<&[u8]> {
+        if self.data.len() - self.pos < n {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                format!("unexpected end of RDB file at offset {}", self.data.len()),
+            ));
+        }
+        self.pos += n;
+        Ok(&self.data[self.pos - n..self.pos])
+    }
+
+    fn read_array<const N: usize>(&mut self) -> io::Result<[u8; N]> {
+        Ok(self.read_bytes(N)?.try_into().unwrap())
+    }
+
+    fn read_u8(&mut self) -> io::Result<u8> {
+        Ok(self.read_bytes(1)?[0])
+    }
+
+    /// 读取长度，第二个值表示它是否是字符串的特殊编码
+    /// Read a length, the second value tells whether it is a special encoding of a string.
+    fn read_length_with_encoding(&mut self) -> io::Result<(u64, bool)> {
+        let first = self.read_u8()?;
+        let len = match first >> 6 {
+            RDB_ENCVAL => return Ok(((first & 0x3f) as u64, true)),
+            RDB_6BITLEN => (first & 0x3f) as u64,
+            RDB_14BITLEN => (((first & 0x3f) as u64) << 8) | self.read_u8()? as u64,
+            _ => match first {
+                RDB_32BITLEN => u32::from_be_bytes(self.read_array()?) as u64,
+                RDB_64BITLEN => u64::from_be_bytes(self.read_array()?),
+                _ => return Err(self.corrupted(format!("unknown length encoding {}", first))),
+            },
+        };
+        Ok((len, false))
+    }
 
-    // Loading operations
-    fn load_string_object(&mut self) -> io::Result<BytesMut> {
-        let len = self.buffer.get_u8() as usize;
-        Ok(self.buffer.split_to(len))
+    fn read_length(&mut self) -> io::Result<u64> {
+        match self.read_length_with_encoding()? {
+            (len, false) => Ok(len),
+            (encoding, true) => Err(self.corrupted(format!("unexpected string encoding {} for a length", encoding))),
+        }
+    }
+
+    /// 读取长度作为元素个数，不超过剩余的字节数，避免损坏的数据导致分配过多的内存
+    /// Read a length as a number of elements, capped to the remaining bytes so corrupted data can't allocate too
+    /// much memory.
+    fn read_count(&mut self) -> io::Result<usize> {
+        let count = self.read_length()?;
+        if count > (self.data.len() - self.pos) as u64 {
+            return Err(self.corrupted(format!("length {} exceeds the file", count)));
+        }
+        Ok(count as usize)
     }
 
-    fn load_object(&mut self, obj_type: u8) -> io::Result<DbType> {
-        match obj_type {
-            RDB_TYPE_STRING => {
-                let bytes = self.load_string_object()?.freeze();
-                Ok(DbType::String(String::from_utf8(bytes.to_vec()).unwrap()))
+    /// 读取字符串，整数编码的转换为十进制，LZF 编码的解压
+    /// Read a string, integer encodings are converted to decimal and LZF encodings are decompressed.
+    fn read_string(&mut self) -> io::Result<Vec<u8>> {
+        let (len, encoded) = self.read_length_with_encoding()?;
+        if !encoded {
+            let len = usize::try_from(len).map_err(|_| self.corrupted("string too long"))?;
+            return Ok(self.read_bytes(len)?.to_vec());
+        }
+        let value = match len as u8 {
+            RDB_ENC_INT8 => i8::from_le_bytes(self.read_array()?) as i64,
+            RDB_ENC_INT16 => i16::from_le_bytes(self.read_array()?) as i64,
+            RDB_ENC_INT32 => i32::from_le_bytes(self.read_array()?) as i64,
+            RDB_ENC_LZF => {
+                let compressed_len = self.read_count()?;
+                let len = self.read_length()?;
+                // 解压后的长度不会超过压缩数据能展开的最大长度，避免损坏的长度导致分配过多的内存
+                // The uncompressed length can't exceed what the compressed data can expand to, so a corrupted
+                // length can't allocate too much memory
+                let len = match usize::try_from(len) {
+                    Ok(len) if len <= compressed_len.saturating_mul(lzf::MAX_EXPANSION) => len,
+                    _ => return Err(self.corrupted(format!("LZF uncompressed length {} exceeds the compressed data", len))),
+                };
+                let compressed = self.read_bytes(compressed_len)?;
+                return match lzf::decompress(compressed, len) {
+                    Some(value) => Ok(value),
+                    None => Err(self.corrupted("invalid LZF compressed string")),
+                };
             }
+            encoding => return Err(self.corrupted(format!("unknown string encoding {}", encoding))),
+        };
+        Ok(value.to_string().into_bytes())
+    }
+
+    fn read_utf8(&mut self) -> io::Result<String> {
+        let bytes = self.read_string()?;
+        Ok(self.utf8(bytes))
+    }
+
+    /// 数据库只能保存 UTF-8 字符串，二进制数据会被标记，读完当前的键后由 `take_binary` 取出，以便跳过该键
+    /// The database only holds UTF-8 strings, binary data is flagged so the current key can be skipped once it
+    /// has been read, see `take_binary`.
+    fn utf8(&mut self, bytes: Vec<u8>) -> String {
+        String::from_utf8(bytes).unwrap_or_else(|err| {
+            self.binary = true;
+            String::from_utf8_lossy(err.as_bytes()).into_owned()
+        })
+    }
+
+    /// 上次调用之后是否读到了二进制数据
+    /// Whether binary data has been read since the last call.
+    fn take_binary(&mut self) -> bool {
+        std::mem::take(&mut self.binary)
+    }
+
+    /// 读取使用紧凑编码保存的字符串并展开
+    /// Read a string saved in a compact encoding and expand it.
+    fn read_packed(&mut self, expand: fn(&[u8]) -> Option<Vec<Vec<u8>>>) -> io::Result<Vec<String>> {
+        let data = self.read_string()?;
+        let entries = expand(&data).ok_or_else(|| self.corrupted("invalid compact encoding"))?;
+        Ok(entries.into_iter().map(|entry| self.utf8(entry)).collect())
+    }
+
+    /// 读取值，类型暂不支持时跳过并返回 `None`
+    /// Read a value, it is skipped and `None` is returned if its type is not supported yet.
+    fn read_object(&mut self, object_type: u8) -> io::Result<Option<DbType>> {
+        let value = match object_type {
+            RDB_TYPE_STRING => DbType::String(self.read_utf8()?),
             RDB_TYPE_LIST => {
-                let len = self.buffer.get_u8() as usize;
+                let len = self.read_count()?;
                 let mut list = VecDeque::with_capacity(len);
-
                 for _ in 0..len {
-                    let bytes = self.load_string_object()?.freeze();
-                    list.push_back(String::from_utf8(bytes.to_vec()).unwrap());
+                    list.push_back(self.read_utf8()?);
                 }
-
-                Ok(DbType::List(list))
+                DbType::List(list)
+            }
+            RDB_TYPE_LIST_ZIPLIST => DbType::List(self.read_packed(packed::ziplist)?.into()),
+            RDB_TYPE_LIST_QUICKLIST | RDB_TYPE_LIST_QUICKLIST_2 => {
+                let nodes = self.read_count()?;
+                let mut list = VecDeque::new();
+                for _ in 0..nodes {
+                    if object_type == RDB_TYPE_LIST_QUICKLIST {
+                        list.extend(self.read_packed(packed::ziplist)?);
+                    } else if self.read_length()? == QUICKLIST_NODE_CONTAINER_PLAIN {
+                        list.push_back(self.read_utf8()?);
+                    } else {
+                        list.extend(self.read_packed(packed::listpack)?);
+                    }
+                }
+                DbType::List(list)
             }
             RDB_TYPE_HASH => {
-                let len = self.buffer.get_u8() as usize;
+                let len = self.read_count()?;
                 let mut map = HashMap::with_capacity(len);
-
                 for _ in 0..len {
-                    let key = self.load_string_object()?.freeze();
-                    let value = self.load_string_object()?.freeze();
-                    map.insert(
-                        String::from_utf8(key.to_vec()).unwrap(),
-                        String::from_utf8(value.to_vec()).unwrap(),
-                    );
+                    let field = self.read_utf8()?;
+                    map.insert(field, self.read_utf8()?);
+                }
+                DbType::Hash(map)
+            }
+            RDB_TYPE_HASH_ZIPMAP => self.read_packed_hash(packed::zipmap)?,
+            RDB_TYPE_HASH_ZIPLIST => self.read_packed_hash(packed::ziplist)?,
+            RDB_TYPE_HASH_LISTPACK => self.read_packed_hash(packed::listpack)?,
+            RDB_TYPE_SET => {
+                for _ in 0..self.read_count()? {
+                    self.read_string()?;
+                }
+                return Ok(None);
+            }
+            RDB_TYPE_ZSET | RDB_TYPE_ZSET_2 => {
+                for _ in 0..self.read_count()? {
+                    self.read_string()?;
+                    self.skip_score(object_type)?;
                 }
+                return Ok(None);
+            }
+            RDB_TYPE_SET_INTSET | RDB_TYPE_SET_LISTPACK | RDB_TYPE_ZSET_ZIPLIST | RDB_TYPE_ZSET_LISTPACK => {
+                self.read_string()?;
+                return Ok(None);
+            }
+            RDB_TYPE_STREAM_LISTPACKS | RDB_TYPE_STREAM_LISTPACKS_2 | RDB_TYPE_STREAM_LISTPACKS_3 => {
+                self.skip_stream(object_type)?;
+                return Ok(None);
+            }
+            RDB_TYPE_MODULE_2 => {
+                self.read_length()?;
+                self.skip_module_value()?;
+                return Ok(None);
+            }
+            _ => return Err(self.corrupted(format!("unsupported value type {}", object_type))),
+        };
+        Ok(Some(value))
+    }
 
-                Ok(DbType::Hash(map))
+    /// 读取使用紧凑编码保存的哈希，字段和值交替出现
+    /// Read a hash saved in a compact encoding, fields and values alternate.
+    fn read_packed_hash(&mut self, expand: fn(&[u8]) -> Option<Vec<Vec<u8>>>) -> io::Result<DbType> {
+        let entries = self.read_packed(expand)?;
+        if entries.len() % 2 != 0 {
+            return Err(self.corrupted("hash with a field without value"));
+        }
+        let mut entries = entries.into_iter();
+        let mut map = HashMap::with_capacity(entries.len() / 2);
+        while let (Some(field), Some(value)) = (entries.next(), entries.next()) {
+            map.insert(field, value);
+        }
+        Ok(DbType::Hash(map))
+    }
+
+    /// 跳过有序集合成员的分数，旧格式是带长度的字符串，253、254、255 分别表示 NaN、正无穷和负无穷
+    /// Skip the score of a sorted set member, the old format is a string with its length, where 253, 254 and 255
+    /// mean NaN, positive and negative infinity.
+    fn skip_score(&mut self, object_type: u8) -> io::Result<()> {
+        if object_type == RDB_TYPE_ZSET_2 {
+            self.read_bytes(8)?;
+        } else if let len @ 0..=252 = self.read_u8()? {
+            self.read_bytes(len as usize)?;
+        }
+        Ok(())
+    }
+
+    /// 跳过流，包括它的消费者组和待确认的消息
+    /// Skip a stream, including its consumer groups and pending messages.
+    fn skip_stream(&mut self, object_type: u8) -> io::Result<()> {
+        for _ in 0..self.read_count()? {
+            self.read_string()?;
+            self.read_string()?;
+        }
+        // 长度和最后的 ID，新版本还有第一个 ID、最大的已删除 ID 和添加过的条目数
+        // Length and last ID, newer versions also have the first ID, the max deleted ID and the entries added
+        let fields = if object_type == RDB_TYPE_STREAM_LISTPACKS { 3 } else { 8 };
+        for _ in 0..fields {
+            self.read_length()?;
+        }
+        for _ in 0..self.read_count()? {
+            self.read_string()?;
+            self.read_length()?;
+            self.read_length()?;
+            if object_type != RDB_TYPE_STREAM_LISTPACKS {
+                self.read_length()?;
+            }
+            // 消费者组的待确认消息：ID、投递时间和投递次数
+            // Pending messages of the group: ID, delivery time and delivery count
+            for _ in 0..self.read_count()? {
+                self.read_bytes(16 + 8)?;
+                self.read_length()?;
+            }
+            for _ in 0..self.read_count()? {
+                self.read_string()?;
+                self.read_bytes(if object_type == RDB_TYPE_STREAM_LISTPACKS_3 { 16 } else { 8 })?;
+                for _ in 0..self.read_count()? {
+                    self.read_bytes(16)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// 跳过模块保存的值，它由带操作码的字段组成，直到 EOF 操作码
+    /// Skip a value saved by a module, made of fields with opcodes up to the EOF opcode.
+    fn skip_module_value(&mut self) -> io::Result<()> {
+        loop {
+            match self.read_length()? {
+                RDB_MODULE_OPCODE_EOF => return Ok(()),
+                RDB_MODULE_OPCODE_SINT | RDB_MODULE_OPCODE_UINT => {
+                    self.read_length()?;
+                }
+                RDB_MODULE_OPCODE_FLOAT => {
+                    self.read_bytes(4)?;
+                }
+                RDB_MODULE_OPCODE_DOUBLE => {
+                    self.read_bytes(8)?;
+                }
+                RDB_MODULE_OPCODE_STRING => {
+                    self.read_string()?;
+                }
+                opcode => return Err(self.corrupted(format!("unknown module opcode {}", opcode))),
             }
-            _ => panic!("Unsupported RDB type"),
         }
     }
 }
 
-// Public interface functions
-pub async fn dump(db: &Arc<Mutex<Db>>, rdb_file_path: &str) -> RdbWriter {
+/// 一次后台保存的键的数量，保存每批键时才持有数据库的锁
+/// Number of keys saved at a time by a background save, the database lock is only held while saving each batch
+const BGSAVE_BATCH: usize = 1024;
+
+/// 后台保存失败之后，按保存规则再次尝试之前等待的秒数
+/// Seconds to wait after a failed background save before trying again for the save rules
+const BGSAVE_RETRY_DELAY: u64 = 5;
+
+/// 写 RDB 文件时持有，前台和后台的保存不会同时写同一个文件
+/// Held while writing the RDB file, so foreground and background saves never write the file at the same time
+static WRITE_LOCK: Mutex<()> = Mutex::new(());
+
+/// `BGSAVE SCHEDULE` 请求在当前的后台保存结束之后再保存一次
+/// `BGSAVE SCHEDULE` asked for another save once the current background save ends
+static BGSAVE_SCHEDULED: AtomicBool = AtomicBool::new(false);
+
+/// 最近一次开始后台保存的时间，UNIX 时间戳，以秒为单位
+/// Time the latest background save started, as a UNIX timestamp in seconds
+static LAST_BGSAVE_TRY: AtomicU64 = AtomicU64::new(0);
+
+/// `BGSAVE` 的结果
+/// Outcome of `BGSAVE`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Bgsave {
+    /// 开始了后台保存
+    /// A background save started
+    Started,
+    /// 已经有后台保存在进行，结束之后再保存一次
+    /// A background save is already in progress, another one runs after it
+    Scheduled,
+    /// 已经有后台保存在进行
+    /// A background save is already in progress
+    InProgress,
+    /// AOF 重写正在进行，它与后台保存共用数据库的快照
+    /// An AOF rewrite is in progress, it shares the database snapshot with background saves
+    RewriteInProgress,
+}
+
+/// 持有数据库的锁序列化整个数据库，用于 `SAVE`
+/// Serialize the whole database while holding its lock, used by `SAVE`.
+pub fn dump(db: &Arc<Mutex<Db>>) -> BytesMut {
     let now = SystemTime::now()
         .duration_since(UNIX_EPOCH)
         .unwrap()
         .as_millis() as u64;
 
-    let mut rdb = RdbWriter::new(rdb_file_path);
-    rdb.write_header();
+    let mut rdb = RdbWriter::new();
+    rdb.write_header(stats::used_memory(db));
 
-    // Select database 0
-    rdb.buffer.put_u8(RDB_OPCODE_SELECTDB);
-    rdb.buffer.put_u32(0);
+    let db = db.lock().unwrap();
+    let (keys, expires) = live_counts(&db, now);
+    rdb.write_db_header(0, keys, expires);
 
     // Save all key-value pairs
-    let db = db.lock().unwrap();
     for (key, value) in db.iter() {
         rdb.save_key_value_pair(key, value, now);
     }
 
-    // Write EOF marker
-    rdb.buffer.put_u8(RDB_OPCODE_EOF);
-    rdb
+    rdb.write_footer(get_rdb_config().checksum);
+    rdb.buffer
 }
 
-pub fn save(db: Arc<Mutex<Db>>, rdb_file_path: String,duration_secs: u64) -> io::Result<()> {
-    tokio::spawn(async move {
-        loop {
-            tokio::time::sleep(std::time::Duration::from_secs(duration_secs)).await;
+/// 未过期的键的数量和其中带过期时间的键的数量
+/// Number of keys not expired yet, and how many of them have an expiration.
+fn live_counts(db: &Db, now: u64) -> (u64, u64) {
+    let live = |entry: &&DbEntry| entry.expiration.is_none_or(|expiration| expiration >= now);
+    let keys = db.iter().map(|(_, entry)| entry).filter(live).count();
+    let expires = db.iter().map(|(_, entry)| entry).filter(live).filter(|entry| entry.expiration.is_some()).count();
+    (keys as u64, expires as u64)
+}
+
+/// 增量的快照：开始时记下所有的键，之后分批保存，每批只短暂持有数据库的锁。
+/// 期间被修改或删除的键由数据库保留原值（写时复制），保存的内容与快照开始时一致。
+/// An incremental snapshot: every key is recorded when it starts and then saved in batches, each batch holding the
+/// database lock only briefly. Keys modified or deleted meanwhile have their original entry kept by the database
+/// (copy-on-write), so what is saved matches the moment the snapshot started.
+pub struct IncrementalDump {
+    rdb: RdbWriter,
+    keys: Vec<String>,
+    next: usize,
+    now: u64,
+}
+
+impl IncrementalDump {
+    fn begin(db: &Arc<Mutex<Db>>) -> Self {
+        let used_memory = stats::used_memory(db);
+        Self::begin_locked(&mut db.lock().unwrap(), used_memory)
+    }
+
+    /// 在已经持有数据库的锁时开始快照，AOF 重写用它生成 RDB 格式的基础文件
+    /// Start the snapshot while the database lock is already held, used by AOF rewrites for RDB-format base files.
+    pub fn begin_locked(db: &mut Db, used_memory: usize) -> Self {
+        let mut rdb = RdbWriter::new();
+        rdb.write_header(used_memory);
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_millis() as u64;
+        let (keys, expires) = live_counts(db, now);
+        rdb.write_db_header(0, keys, expires);
+        Self { rdb, keys: db.begin_snapshot(), next: 0, now }
+    }
+
+    /// 保存下一批键，全部保存之后返回 false
+    /// Save the next batch of keys, false is returned once every key is saved.
+    fn step(&mut self, db: &Arc<Mutex<Db>>) -> bool {
+        let end = (self.next + BGSAVE_BATCH).min(self.keys.len());
+        let (rdb, now) = (&mut self.rdb, self.now);
+        db.lock().unwrap().snapshot_entries(&self.keys[self.next..end], |key, entry| rdb.save_key_value_pair(key, entry, now));
+        self.next = end;
+        end < self.keys.len()
+    }
+
+    /// 保存剩下的键并结束快照
+    /// Save the remaining keys and end the snapshot.
+    pub fn finish(mut self, db: &Arc<Mutex<Db>>) -> BytesMut {
+        while self.step(db) {}
+        db.lock().unwrap().end_snapshot();
+        self.rdb.write_footer(get_rdb_config().checksum);
+        self.rdb.buffer
+    }
+}
+
+/// 把序列化的数据写入 RDB 文件：先写入同一目录下的临时文件并同步到磁盘，再原子地重命名覆盖原文件，最后同步目录。
+/// 任何一步失败都不会破坏原来的快照，临时文件会被删除。
+/// Write the serialized data to the RDB file: it is first written to a temporary file in the same directory and
+/// synced to disk, then atomically renamed over the target, and finally the directory is synced. A failure at any
+/// step leaves the previous snapshot intact, and the temporary file is removed.
+fn write_file(rdb_file_path: &str, data: &[u8]) -> io::Result<()> {
+    let _guard = WRITE_LOCK.lock().unwrap();
+    let path = Path::new(rdb_file_path);
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    let temp = dir.join(format!("temp-{}.rdb", std::process::id()));
+    let result = write_synced(&temp, data)
+        .and_then(|()| fs::rename(&temp, path))
+        .and_then(|()| File::open(dir)?.sync_all());
+    if result.is_err() {
+        let _ = fs::remove_file(&temp);
+    }
+    result
+}
+
+fn write_synced(path: &Path, data: &[u8]) -> io::Result<()> {
+    let mut file = File::create(path)?;
+    file.write_all(data)?;
+    file.sync_all()
+}
+
+/// 在前台保存，`SAVE` 使用，期间持有数据库的锁。后台保存正在进行时返回错误
+/// Save in the foreground, used by `SAVE`, holding the database lock meanwhile. An error is returned while a
+/// background save is in progress.
+pub fn save(db: &Arc<Mutex<Db>>) -> Result<(), String> {
+    if stats::RDB_SAVE_IN_PROGRESS.load(Ordering::Relaxed) {
+        return Err("Background save already in progress".to_string());
+    }
+    let started = Instant::now();
+    let dirty = stats::get(&stats::DIRTY);
+    let data = dump(db);
+    let result = write_file(&get_rdb_config().file_path, &data).map_err(|err| err.to_string());
+    saved(&result, started, dirty);
+    result
+}
 
-            let mut rdb = dump(&db, &rdb_file_path).await;
-            let mut file = rdb.file.lock().unwrap();
+/// 开始后台保存，`schedule` 为 true 时如果已经有后台保存在进行，在它结束之后再保存一次
+/// Start a background save, if one is already in progress and `schedule` is true, another save runs after it ends.
+pub fn background_save(db: &Arc<Mutex<Db>>, schedule: bool) -> Bgsave {
+    if stats::RDB_SAVE_IN_PROGRESS.swap(true, Ordering::SeqCst) {
+        if schedule {
+            BGSAVE_SCHEDULED.store(true, Ordering::Relaxed);
+            return Bgsave::Scheduled;
+        }
+        return Bgsave::InProgress;
+    }
+    if stats::AOF_REWRITE_IN_PROGRESS.load(Ordering::SeqCst) {
+        stats::RDB_SAVE_IN_PROGRESS.store(false, Ordering::SeqCst);
+        if schedule {
+            BGSAVE_SCHEDULED.store(true, Ordering::Relaxed);
+            return Bgsave::Scheduled;
+        }
+        return Bgsave::RewriteInProgress;
+    }
+    BGSAVE_SCHEDULED.store(false, Ordering::Relaxed);
+    LAST_BGSAVE_TRY.store(stats::unix_time(), Ordering::Relaxed);
+    let db = db.clone();
+    std::thread::spawn(move || {
+        let started = Instant::now();
+        let dirty = stats::get(&stats::DIRTY);
+        let data = IncrementalDump::begin(&db).finish(&db);
+        let result = write_file(&get_rdb_config().file_path, &data).map_err(|err| err.to_string());
+        stats::RDB_SAVE_IN_PROGRESS.store(false, Ordering::SeqCst);
+        saved(&result, started, dirty);
+    });
+    Bgsave::Started
+}
 
-            file.get_ref().set_len(0).unwrap();
-            file.write_all(&rdb.buffer).unwrap();
-            file.flush().unwrap();
+fn saved(result: &Result<(), String>, started: Instant, dirty: u64) {
+    match result {
+        Ok(()) => info!("RDB 保存成功，耗时 {} 毫秒", started.elapsed().as_millis()),  // RDB saved
+        Err(err) => error!("保存 RDB 失败: {}", err),  // Failed to save the RDB file
+    }
+    stats::rdb_saved(result, started.elapsed(), dirty);
+}
 
-            rdb.buffer.clear();
+/// 每秒检查一次，满足某条保存规则（`seconds` 秒内至少 `changes` 次修改）或者有 `BGSAVE SCHEDULE` 的请求时在后台保存。
+/// 上次保存失败时，间隔几秒再按规则重试。
+/// Check every second, saving in the background when a save rule matches (at least `changes` changes within
+/// `seconds` seconds) or `BGSAVE SCHEDULE` asked for a save. After a failed save, the rules only try again after a
+/// few seconds.
+pub fn start_saving(db: Arc<Mutex<Db>>) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(1));
+        loop {
+            interval.tick().await;
+            if stats::RDB_SAVE_IN_PROGRESS.load(Ordering::Relaxed) || stats::AOF_REWRITE_IN_PROGRESS.load(Ordering::Relaxed) {
+                continue;
+            }
+            if BGSAVE_SCHEDULED.load(Ordering::Relaxed) || save_rule_matches() {
+                background_save(&db, false);
+            }
         }
     });
+}
 
-    Ok(())
+fn save_rule_matches() -> bool {
+    let config = get_rdb_config();
+    if !config.enabled {
+        return false;
+    }
+    let status = stats::rdb_status();
+    let now = stats::unix_time();
+    let dirty = stats::get(&stats::DIRTY);
+    let due = config.save.iter().any(|&(seconds, changes)| dirty >= changes && now.saturating_sub(status.last_success) >= seconds);
+    let retry = status.last_error.is_none() || now.saturating_sub(LAST_BGSAVE_TRY.load(Ordering::Relaxed)) >= BGSAVE_RETRY_DELAY;
+    due && retry
 }
 
-pub async fn load_rdb(db: &Arc<Mutex<Db>>, rdb: &mut RdbWriter) -> Result<(u128, ()), std::io::Error> {
+/// 加载 RDB 文件到数据库，已经过期的键和其它数据库的键不加载，暂不支持的类型的键被跳过。
+/// 键先解码到临时的列表中，校验和通过之后才放入数据库，损坏的文件不会加载一部分数据
+/// Load an RDB file into the database, expired keys and keys of other databases are not loaded, keys of types not
+/// supported yet are skipped. Keys are decoded into a scratch list first and only put into the database once the
+/// checksum matches, so a corrupted file loads no data at all.
+pub async fn load_rdb(db: &Arc<Mutex<Db>>, rdb: &mut RdbReader) -> Result<(u128, ()), std::io::Error> {
     let start_time = Instant::now();
     let now = SystemTime::now()
         .duration_since(UNIX_EPOCH)
         .unwrap()
         .as_millis() as u64;
 
-    // Verify magic number and version
-    let magic = [RDB_MAGIC, RDB_VERSION].concat();
-    if rdb.buffer.len() < magic.len() || &rdb.buffer[..magic.len()] != magic {
-        return Err(io::Error::new(io::ErrorKind::InvalidData, "Invalid RDB file"));
-    }
-    rdb.buffer.advance(magic.len());
-
-    // Process RDB contents
+    rdb.read_header()?;
+    let mut selected_db = 0;
+    let mut expiration = None;
+    let (mut unsupported, mut other_db, mut binary) = (0, 0, 0);
+    let mut entries = Vec::new();
     loop {
-        let mut expiration = None;
-        let mut opcode = rdb.buffer.get_u8();
-
-        match opcode {
-            RDB_OPCODE_EXPIRETIME_MS => {
-                expiration = Some(rdb.buffer.get_u64());
-                opcode = rdb.buffer.get_u8();
+        match rdb.read_u8()? {
+            RDB_OPCODE_EOF => break,
+            RDB_OPCODE_SELECTDB => selected_db = rdb.read_length()?,
+            RDB_OPCODE_RESIZEDB => {
+                rdb.read_length()?;
+                rdb.read_length()?;
             }
-            RDB_OPCODE_SELECTDB => {
-                let _db_index = rdb.buffer.get_u32();
-                continue;
+            RDB_OPCODE_EXPIRETIME_MS => expiration = Some(u64::from_le_bytes(rdb.read_array()?)),
+            RDB_OPCODE_EXPIRETIME => expiration = Some(u32::from_le_bytes(rdb.read_array()?) as u64 * 1000),
+            RDB_OPCODE_AUX => {
+                let key = rdb.read_string()?;
+                let value = rdb.read_string()?;
+                if key == b"redis-ver" {
+                    info!("RDB 文件由 Redis {} 保存", String::from_utf8_lossy(&value));  // The RDB file was saved by Redis
+                }
             }
-            RDB_OPCODE_EOF => break,
-            _ => (),
-        }
-
-        let key = rdb.load_string_object()?.freeze();
-        let value = rdb.load_object(opcode)?;
-
-        if let Some(exp) = expiration {
-            if exp < now {
-                continue;
+            // 淘汰策略使用的访问信息和集群的槽位信息，不需要
+            // Access information for eviction and cluster slot information, not needed
+            RDB_OPCODE_FREQ => {
+                rdb.read_u8()?;
             }
-        }
-
-        let mut db = db.lock().unwrap();
-        let key_str = String::from_utf8(key.to_vec()).unwrap();
-
-        match value {
-            DbType::String(s) => {
-                db.set(&key_str, DbType::String(s), None);
+            RDB_OPCODE_IDLE => {
+                rdb.read_length()?;
             }
-            DbType::List(list) => {
-                if let Some(DbType::List(existing)) = db.get_dbtype_mut(&key_str) {
-                    existing.extend(list);
-                } else {
-                    db.set(&key_str, DbType::List(list), None);
+            RDB_OPCODE_SLOT_INFO => {
+                for _ in 0..3 {
+                    rdb.read_length()?;
                 }
             }
-            DbType::Hash(map) => {
-                if let Some(DbType::Hash(existing)) = db.get_dbtype_mut(&key_str) {
-                    existing.extend(map);
-                } else {
-                    db.set(&key_str, DbType::Hash(map), None);
+            RDB_OPCODE_FUNCTION2 => {
+                rdb.read_string()?;
+                warn!("跳过 RDB 文件中的函数库");  // Skipping a function library in the RDB file
+            }
+            RDB_OPCODE_MODULE_AUX => {
+                for _ in 0..3 {
+                    rdb.read_length()?;
+                }
+                rdb.skip_module_value()?;
+            }
+            object_type => {
+                rdb.take_binary();
+                let key = rdb.read_utf8()?;
+                let value = rdb.read_object(object_type)?;
+                let expiration = expiration.take();
+                match value {
+                    None => unsupported += 1,
+                    Some(_) if rdb.take_binary() => {
+                        warn!("跳过了包含二进制数据的键 {:?}", key);  // Skipped a key holding binary data
+                        binary += 1;
+                    }
+                    Some(_) if selected_db != 0 => other_db += 1,
+                    Some(_) if expiration.is_some_and(|expiration| expiration < now) => {}
+                    Some(value) => entries.push((key, value, expiration)),
                 }
             }
         }
     }
+    rdb.read_checksum(get_rdb_config().checksum)?;
+    {
+        let mut db = db.lock().unwrap();
+        for (key, value, expiration) in entries {
+            db.load_entry(key, value, expiration);
+        }
+    }
+    if unsupported > 0 {
+        warn!("跳过了 {} 个类型暂不支持的键", unsupported);  // Skipped keys of types not supported yet
+    }
+    if binary > 0 {
+        warn!("跳过了 {} 个包含二进制数据的键", binary);  // Skipped keys holding binary data
+    }
+    if other_db > 0 {
+        warn!("跳过了其它数据库中的 {} 个键", other_db);  // Skipped keys in other databases
+    }
 
     // Measure the time taken
     let duration = start_time.elapsed();
 
     Ok((duration.as_millis(), ())) // Return the time in milliseconds
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn string(db: &mut Db, key: &str) -> Option<String> {
+        match db.get(key) {
+            Some(DbType::String(value)) => Some(value.clone()),
+            _ => None,
+        }
+    }
+
+    fn list(db: &mut Db, key: &str) -> Vec<String> {
+        match db.get(key) {
+            Some(DbType::List(list)) => list.iter().cloned().collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    fn hash(db: &mut Db, key: &str) -> HashMap<String, String> {
+        match db.get(key) {
+            Some(DbType::Hash(map)) => map.clone(),
+            _ => HashMap::new(),
+        }
+    }
+
+    async fn load(data: Vec<u8>) -> io::Result<Db> {
+        let db = Arc::new(Mutex::new(Db::new()));
+        load_rdb(&db, &mut RdbReader::new(data)).await?;
+        Ok(Arc::try_unwrap(db).unwrap().into_inner().unwrap())
+    }
+
+    /// 带 6 位长度的字符串
+    /// A string with a 6 bit length
+    fn short(s: &[u8]) -> Vec<u8> {
+        [&[s.len() as u8][..], s].concat()
+    }
+
+    #[tokio::test]
+    async fn dump_and_load_round_trip() {
+        let long = "compressible ".repeat(100);
+        let large_list: VecDeque<String> = (0..20000).map(|i| format!("item-{}", i)).collect();
+        let db = Arc::new(Mutex::new(Db::new()));
+        {
+            let mut db = db.lock().unwrap();
+            db.load_entry("small".to_string(), DbType::String("v".to_string()), None);
+            db.load_entry("int".to_string(), DbType::String("-70000".to_string()), None);
+            db.load_entry("not-canonical".to_string(), DbType::String("007".to_string()), None);
+            db.load_entry("long".to_string(), DbType::String(long.clone()), None);
+            db.load_entry("list".to_string(), DbType::List(large_list.clone()), None);
+            let map = HashMap::from([("f".to_string(), "1".to_string()), ("g".to_string(), "x".repeat(300))]);
+            db.load_entry("hash".to_string(), DbType::Hash(map.clone()), None);
+            db.load_entry("ttl".to_string(), DbType::String("t".to_string()), Some(u64::MAX / 2));
+            db.load_entry("expired".to_string(), DbType::String("e".to_string()), Some(1));
+        }
+        let data = dump(&db).to_vec();
+        assert!(data.starts_with(b"REDIS0009"));
+        // 较长的字符串被压缩保存
+        // Longer strings are saved compressed
+        assert!(!data.windows(long.len()).any(|window| window == long.as_bytes()));
+
+        let mut loaded = load(data).await.unwrap();
+        assert_eq!(string(&mut loaded, "small").as_deref(), Some("v"));
+        assert_eq!(string(&mut loaded, "int").as_deref(), Some("-70000"));
+        assert_eq!(string(&mut loaded, "not-canonical").as_deref(), Some("007"));
+        assert_eq!(string(&mut loaded, "long"), Some(long));
+        assert_eq!(list(&mut loaded, "list"), Vec::from(large_list));
+        assert_eq!(hash(&mut loaded, "hash").get("g").map(String::len), Some(300));
+        assert_eq!(string(&mut loaded, "ttl").as_deref(), Some("t"));
+        assert_eq!(string(&mut loaded, "expired"), None);
+        assert_eq!(loaded.keyspace_info().1, 1);
+    }
+
+    #[tokio::test]
+    async fn background_snapshot_keeps_the_data_of_its_start() {
+        let db = Arc::new(Mutex::new(Db::new()));
+        let count = BGSAVE_BATCH * 2 + 10;
+        for i in 0..count {
+            db.lock().unwrap().load_entry(format!("key-{}", i), DbType::String(i.to_string()), None);
+        }
+        let mut snapshot = IncrementalDump::begin(&db);
+        assert!(snapshot.step(&db));
+        {
+            // 修改已经保存的键、还没有保存的键，删除键并新建键
+            // Modify keys already saved and keys not saved yet, delete keys and create new ones
+            let mut db = db.lock().unwrap();
+            for i in 0..count {
+                db.set(&format!("key-{}", i), DbType::String("changed".to_string()), None);
+            }
+            if let Some(DbType::String(value)) = db.get_dbtype_mut("key-5") {
+                value.push('!');
+            }
+            db.del("key-1");
+            db.del(&format!("key-{}", count - 1));
+            db.set("new", DbType::String("n".to_string()), None);
+        }
+        let data = snapshot.finish(&db).to_vec();
+
+        let mut loaded = load(data).await.unwrap();
+        assert_eq!(loaded.keyspace_info().0, count);
+        for i in 0..count {
+            assert_eq!(string(&mut loaded, &format!("key-{}", i)), Some(i.to_string()));
+        }
+        assert_eq!(string(&mut loaded, "new"), None);
+        // 快照结束之后不再保留原值
+        // Original entries are no longer kept once the snapshot ended
+        db.lock().unwrap().del("key-2");
+        db.lock().unwrap().snapshot_entries(&["key-2".to_string()], |_, _| panic!("snapshot ended"));
+    }
+
+    #[test]
+    fn snapshots_replace_the_file_atomically() {
+        let dir = std::env::temp_dir().join(format!("p-rudis-rdb-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("dump.rdb");
+        let path = path.to_str().unwrap();
+        write_file(path, b"first").unwrap();
+        write_file(path, b"second").unwrap();
+        assert_eq!(fs::read(path).unwrap(), b"second");
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+
+        // 写入失败时原来的快照保持不变
+        // The previous snapshot is kept when writing fails
+        let missing = dir.join("missing").join("dump.rdb");
+        assert!(write_file(missing.to_str().unwrap(), b"third").is_err());
+        assert!(write_file(dir.to_str().unwrap(), b"third").is_err());
+        assert_eq!(fs::read(path).unwrap(), b"second");
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn loads_compact_encodings_written_by_redis() {
+        let mut data = b"REDIS0011".to_vec();
+        data.extend([RDB_OPCODE_AUX].iter().chain(&short(b"redis-ver")).chain(&short(b"7.2.4")));
+        data.extend([RDB_OPCODE_SELECTDB, 0, RDB_OPCODE_RESIZEDB, 6, 1]);
+        // 整数编码的字符串，带过期时间
+        // An integer encoded string with an expiration
+        data.push(RDB_OPCODE_EXPIRETIME_MS);
+        data.extend((u64::MAX / 2).to_le_bytes());
+        data.extend([RDB_TYPE_STRING].iter().chain(&short(b"i")).chain(&[0xc1, 0xe8, 0x03]));
+        // listpack 编码的哈希：f1 => v1, n => 5, neg => -100
+        // A listpack encoded hash: f1 => v1, n => 5, neg => -100
+        let listpack = [
+            &[28, 0, 0, 0, 6, 0][..],
+            &[0x82, b'f', b'1', 3, 0x82, b'v', b'1', 3],
+            &[0x81, b'n', 2, 0x05, 1],
+            &[0x83, b'n', b'e', b'g', 4, 0xdf, 0x9c, 2],
+            &[0xff],
+        ]
+        .concat();
+        data.extend([RDB_TYPE_HASH_LISTPACK].iter().chain(&short(b"h")).chain(&short(&listpack)));
+        // quicklist 编码的列表，一个 listpack 节点和一个普通节点
+        // A quicklist encoded list, with a listpack node and a plain node
+        let listpack = [&[12, 0, 0, 0, 2, 0][..], &[0x81, b'a', 2, 0x07, 1, 0xff]].concat();
+        data.extend([RDB_TYPE_LIST_QUICKLIST_2].iter().chain(&short(b"q")).chain(&[2, 2]).chain(&short(&listpack)));
+        data.extend([1].iter().chain(&short(b"plain")));
+        // ziplist 编码的列表：x, 300
+        // A ziplist encoded list: x, 300
+        let ziplist = [&[18, 0, 0, 0, 13, 0, 0, 0, 2, 0][..], &[0, 0x01, b'x', 3, 0xc0, 0x2c, 0x01, 0xff]].concat();
+        data.extend([RDB_TYPE_LIST_ZIPLIST].iter().chain(&short(b"z")).chain(&short(&ziplist)));
+        // 暂不支持的集合和已经过期的键
+        // A set, not supported yet, and an expired key
+        data.extend([RDB_TYPE_SET].iter().chain(&short(b"s")).chain(&[1]).chain(&short(b"m")));
+        data.push(RDB_OPCODE_EXPIRETIME_MS);
+        data.extend(1000u64.to_le_bytes());
+        data.extend([RDB_TYPE_STRING].iter().chain(&short(b"old")).chain(&short(b"x")));
+        data.push(RDB_OPCODE_EOF);
+        data.extend(crc64::update(0, &data).to_le_bytes());
+
+        let mut db = load(data).await.unwrap();
+        assert_eq!(string(&mut db, "i").as_deref(), Some("1000"));
+        let expected = [("f1", "v1"), ("n", "5"), ("neg", "-100")];
+        assert_eq!(hash(&mut db, "h"), expected.iter().map(|(f, v)| (f.to_string(), v.to_string())).collect());
+        assert_eq!(list(&mut db, "q"), ["a", "7", "plain"]);
+        assert_eq!(list(&mut db, "z"), ["x", "300"]);
+        assert!(db.get("s").is_none());
+        assert!(db.get("old").is_none());
+        let (keys, expires, _) = db.keyspace_info();
+        assert_eq!((keys, expires), (4, 1));
+    }
+
+    #[tokio::test]
+    async fn corrupted_files_are_reported() {
+        let mut data = b"REDIS0009".to_vec();
+        data.extend([RDB_TYPE_STRING].iter().chain(&short(b"k")).chain(&short(b"value")));
+        data.push(RDB_OPCODE_EOF);
+        data.extend(crc64::update(0, &data).to_le_bytes());
+        assert!(load(data.clone()).await.is_ok());
+
+        let mut flipped = data.clone();
+        flipped[13] = b'V';
+        // 校验和错误时不加载任何键
+        // No key is loaded when the checksum is wrong
+        let db = Arc::new(Mutex::new(Db::new()));
+        let err = load_rdb(&db, &mut RdbReader::new(flipped)).await.unwrap_err();
+        assert!(err.to_string().contains("wrong RDB checksum"), "{}", err);
+        assert_eq!(db.lock().unwrap().keyspace_info().0, 0);
+
+        let truncated = data[..14].to_vec();
+        assert_eq!(load(truncated).await.unwrap_err().kind(), io::ErrorKind::UnexpectedEof);
+
+        let mut unknown = data[..9].to_vec();
+        unknown.extend([100, 1, b'k']);
+        let err = load(unknown).await.unwrap_err();
+        assert!(err.to_string().contains("offset 12"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn corrupted_lzf_lengths_are_reported() {
+        // 3 个字节的压缩数据声称能解压出 4GB
+        // 3 bytes of compressed data claiming to expand to 4GB
+        let mut data = b"REDIS0009".to_vec();
+        data.extend([RDB_TYPE_STRING].iter().chain(&short(b"k")));
+        data.extend([(RDB_ENCVAL << 6) | RDB_ENC_LZF, 3, RDB_32BITLEN, 0xff, 0xff, 0xff, 0xff, 0x01, b'a', b'b']);
+        data.push(RDB_OPCODE_EOF);
+        let err = load(data).await.unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert!(err.to_string().contains("LZF uncompressed length 4294967295"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn keys_holding_binary_data_are_skipped() {
+        let mut data = b"REDIS0009".to_vec();
+        data.extend([RDB_TYPE_STRING].iter().chain(&short(b"binary")).chain(&short(&[0xff, 0xfe])));
+        data.extend([RDB_TYPE_LIST].iter().chain(&short(b"list")).chain(&[2]).chain(&short(b"a")).chain(&short(&[0xc0])));
+        data.extend([RDB_TYPE_STRING].iter().chain(&short(b"text")).chain(&short(b"value")));
+        data.push(RDB_OPCODE_EOF);
+        data.extend(crc64::update(0, &data).to_le_bytes());
+        let mut db = load(data).await.unwrap();
+        assert_eq!(db.keyspace_info().0, 1);
+        assert_eq!(string(&mut db, "text").as_deref(), Some("value"));
+    }
+}
@@ -1,27 +1,41 @@
 use std::io::Error;
-use std::ops::Deref;
 use std::process::id;
 use std::sync::{Arc};
+use std::sync::atomic::Ordering;
 use log::{error, info};
 use tokio::net::{TcpListener, TcpStream};
 use std::sync::Mutex;
 use tokio::select;
 use tokio::sync::broadcast;
-use crate::{cmd, dict, frame, parse};
-use crate::config::{get_aof_config, get_rdb_config};
-use crate::connection::ConnectionHandler;
+use std::os::unix::fs::{FileTypeExt, PermissionsExt};
+use std::time::{Duration, Instant};
+use socket2::{SockRef, TcpKeepalive};
+use tokio::io::AsyncWriteExt;
+use tokio::net::{lookup_host, TcpSocket, UnixListener};
+use tokio_rustls::TlsAcceptor;
+use crate::{acl, client, cmd, error, parse, stats, tls, tracking};
+use crate::client::Client;
+use crate::cmd::pubsub::subscriber::Subscriber;
+use crate::config::{get_aof_config, get_rdb_config, get_server_config, get_tls_config, ServerConfig, TlsConfig};
+use crate::connection::{ConnectionHandler, Stream};
 use crate::db::{ Db, DbHolder};
 use crate::shutdown::Shutdown;
 use crate::dict::Command;
 use crate::frame::Frame;
-use crate::persistence::aof::load_aof;
-use crate::persistence::rdb::{dump, load_rdb, save, RdbWriter};
+use crate::persistence::aof::{self, load_aof, start_rewriting};
+use crate::persistence::rdb::{load_rdb, start_saving, RdbReader};
 
 #[derive(Debug)]
 pub struct Listener {
     /// 监听客户端的连接
     /// Listen to client connections
     listener: TcpListener,
+    /// 监听 Unix 套接字上的客户端连接，未配置时为 None
+    /// Listen to client connections on a Unix socket, None if not configured
+    unix_listener: Option<UnixListener>,
+    /// 监听 TLS 端口上的客户端连接及其 TLS 配置，未配置时为 None
+    /// Listen to client connections on the TLS port with its TLS configuration, None if not configured
+    tls_listener: Option<(TcpListener, Arc<rustls::ServerConfig>)>,
     /// 管理数据库
     /// Manage the database
     db_holder: DbHolder,
@@ -41,6 +55,12 @@ pub struct Handler {
     /// 关闭信号
     /// Shutdown signal
     shutdown: Shutdown,
+    /// 客户端状态，在注册表中可见
+    /// Client state, visible in the client registry
+    client: Client,
+    /// 订阅的频道和模式，有订阅时连接处于订阅模式
+    /// Subscribed channels and patterns, the connection is in subscribed mode while there are any
+    subscriber: Subscriber,
 }
 
 // TODO port硬编码待修改
@@ -60,6 +80,62 @@ fn go() {
     println!("{}", welcome);
 }
 
+/// 按配置绑定监听地址，使用配置的 TCP 监听队列长度
+/// Bind the listening address from the configuration, with the configured TCP backlog
+pub async fn bind(config: &ServerConfig) -> Result<TcpListener, Error> {
+    let mut last_err = None;
+    for addr in lookup_host(config.address.as_str()).await? {
+        let socket = if addr.is_ipv4() { TcpSocket::new_v4()? } else { TcpSocket::new_v6()? };
+        socket.set_reuseaddr(true)?;
+        match socket.bind(addr).and_then(|_| socket.listen(config.tcp_backlog)) {
+            Ok(listener) => return Ok(listener),
+            Err(err) => last_err = Some(err),
+        }
+    }
+    Err(last_err.unwrap_or_else(|| Error::new(std::io::ErrorKind::InvalidInput, "无法解析服务端地址")))  // Could not resolve the server address
+}
+
+/// 按配置绑定 Unix 套接字，启动时先删除遗留的套接字文件
+/// Bind the Unix socket from the configuration, removing a stale socket file left behind first
+pub fn bind_unix(config: &ServerConfig) -> Result<Option<UnixListener>, Error> {
+    let Some(path) = &config.unixsocket else {
+        return Ok(None);
+    };
+    remove_unix_socket(path);
+    let listener = UnixListener::bind(path)?;
+    if let Some(perm) = config.unixsocketperm {
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(perm))?;
+    }
+    Ok(Some(listener))
+}
+
+/// 按配置绑定 TLS 端口，与明文端口使用相同的主机地址
+/// Bind the TLS port from the configuration, on the same host as the plaintext port
+pub async fn bind_tls(server_config: &ServerConfig, tls_config: &TlsConfig) -> crate::Result<Option<(TcpListener, Arc<rustls::ServerConfig>)>> {
+    if tls_config.port == 0 {
+        return Ok(None);
+    }
+    let tls_server_config = tls::server_config(tls_config)?;
+    let host = match server_config.address.rsplit_once(':') {
+        Some((host, _)) => host,
+        None => server_config.address.as_str(),
+    };
+    let mut config = server_config.clone();
+    config.address = format!("{}:{}", host, tls_config.port);
+    Ok(Some((bind(&config).await?, tls_server_config)))
+}
+
+/// 删除 Unix 套接字文件，只删除套接字类型的文件
+/// Remove a Unix socket file, only files of the socket type are removed
+fn remove_unix_socket(path: &str) {
+    if let Ok(metadata) = std::fs::symlink_metadata(path)
+        && metadata.file_type().is_socket()
+        && let Err(err) = std::fs::remove_file(path)
+    {
+        error!("删除 Unix 套接字文件失败: {}", err);  // Failed to remove the Unix socket file
+    }
+}
+
 /// 启动 p-rudis 服务端
 /// Start the p-rudis server
 pub async fn run(listener: TcpListener, shutdown: impl Future) {
@@ -67,11 +143,31 @@ pub async fn run(listener: TcpListener, shutdown: impl Future) {
     // Start the interface
     go();
 
+    let server_config = get_server_config();
+    let unix_listener = match bind_unix(&server_config) {
+        Ok(unix_listener) => unix_listener,
+        Err(err) => {
+            error!("监听 Unix 套接字失败: {}", err);  // Failed to listen on the Unix socket
+            return;
+        }
+    };
+    let tls_listener = match bind_tls(&server_config, &get_tls_config()).await {
+        Ok(tls_listener) => tls_listener,
+        Err(err) => {
+            error!("监听 TLS 端口失败: {}", err);  // Failed to listen on the TLS port
+            return;
+        }
+    };
     let mut listener = Listener {
         listener,
+        unix_listener,
+        tls_listener,
         db_holder: DbHolder::new(),
         notify_shutdown: broadcast::channel(1).0,
     };
+    // 开始记录运行时间、每秒操作数和内存使用
+    // Start recording the uptime, operations per second and memory usage
+    stats::start(listener.db_holder.get_db());
     select! {
         res = listener.run() => {
             if let Err(err) = res {
@@ -82,58 +178,181 @@ pub async fn run(listener: TcpListener, shutdown: impl Future) {
             info!("接收到关闭信号，服务端已优雅关闭")  // Shutdown signal received, gracefully closing the server
         }
     }
+    // 关闭时删除 Unix 套接字文件
+    // Remove the Unix socket file on shutdown
+    if let Some(path) = &server_config.unixsocket {
+        remove_unix_socket(path);
+    }
 }
 
 impl Listener {
     /// 启动监听
     /// Start listening
     async fn run(&mut self) -> Result<(), Error> {
-        let aof_config = get_aof_config();
-        if aof_config.enabled {
-            if let Ok((time, _)) = load_aof(&mut self.db_holder.get_db(), aof_config.file_path.as_str()).await {
-                // 成功加载 AOF 数据后处理
-                info!("加载 AOF 数据花费时间: {} 毫秒", time);
-            } else {
-                error!("加载 AOF 数据失败");
-            }
-        }
-        let rdb_config= get_rdb_config();
-        if rdb_config.enabled {
-            match RdbWriter::load_file(rdb_config.file_path.as_str()).await{
-                Ok(mut rdb)=>{
-                    if let Ok((time, _))= load_rdb(&mut self.db_holder.get_db(),&mut rdb).await{
-                        info!("加载 rdb 数据花费时间: {} 毫秒", time);
-                    } else {
-                        error!("加载 rdb 数据失败");
-                    }
+        // 在后台加载数据，加载期间连接只能执行带有 loading 标志的命令
+        // Load the data in the background, connections can only run commands with the loading flag meanwhile
+        stats::LOADING.store(true, Ordering::Relaxed);
+        // 加载时读取文件会阻塞，放到阻塞线程上执行，避免占用处理连接的工作线程
+        // Loading blocks on file reads, so it runs on a blocking thread instead of a worker serving connections
+        let db = self.db_holder.get_db();
+        let runtime = tokio::runtime::Handle::current();
+        tokio::task::spawn_blocking(move || runtime.block_on(load_data(db)));
+        let mut backoff = ACCEPT_BACKOFF_MIN;
+        loop {
+            // 接收 TCP 或 Unix 套接字上的连接
+            // Accept a connection on TCP or the Unix socket
+            select! {
+                res = self.listener.accept() => {
+                    let Some((socket, addr)) = accepted(res, &mut backoff).await else { continue };
+                    set_keepalive(&socket);
+                    let laddr = socket.local_addr().map(|addr| addr.to_string()).unwrap_or_default();
+                    tokio::spawn(serve(socket, addr.to_string(), laddr, self.db_holder.get_db(), self.notify_shutdown.subscribe()));
+                }
+                res = async { self.unix_listener.as_ref().unwrap().accept().await }, if self.unix_listener.is_some() => {
+                    let Some((socket, _)) = accepted(res, &mut backoff).await else { continue };
+                    // Unix 套接字的客户端没有地址，与 Redis 相同使用 `路径:0`
+                    // Unix socket clients have no address, `path:0` is used like Redis
+                    let addr = format!("{}:0", get_server_config().unixsocket.unwrap_or_default());
+                    tokio::spawn(serve(socket, addr.clone(), addr, self.db_holder.get_db(), self.notify_shutdown.subscribe()));
                 }
-                Err(_)=>{
-                    error!("rdb文件为空");
+                res = async { self.tls_listener.as_ref().unwrap().0.accept().await }, if self.tls_listener.is_some() => {
+                    let Some((socket, addr)) = accepted(res, &mut backoff).await else { continue };
+                    set_keepalive(&socket);
+                    let laddr = socket.local_addr().map(|addr| addr.to_string()).unwrap_or_default();
+                    let acceptor = TlsAcceptor::from(self.tls_listener.as_ref().unwrap().1.clone());
+                    let db = self.db_holder.get_db();
+                    let notify_shutdown = self.notify_shutdown.subscribe();
+                    // 在单独的任务中握手，避免慢客户端阻塞接收新连接
+                    // Handshake in a separate task, so slow clients do not block accepting new connections
+                    tokio::spawn(async move {
+                        match acceptor.accept(socket).await {
+                            Ok(stream) => serve(stream, addr.to_string(), laddr, db, notify_shutdown).await,
+                            Err(err) => error!("TLS 握手失败: {}: {}", addr, err),  // TLS handshake failed
+                        }
+                    });
                 }
             }
-            // 保存的定时任务
-            save(self.db_holder.get_db(), rdb_config.file_path, rdb_config.save_interval)?;
         }
-        loop {
-            // 接收连接
-            // Accept a connection
-            let (socket, addr) = self.listener.accept().await?;
-            info!("接收客户端连接: {}", addr);  // Accepting client connection
-            // 处理连接
-            // Handle the connection
-            let mut handler = Handler {
-                db: self.db_holder.get_db(),
-                connection: ConnectionHandler::new(Arc::new(tokio::sync::Mutex::new(socket))),
-                shutdown: Shutdown::new(self.notify_shutdown.subscribe()),
-            };
+    }
+}
+
+/// 接收连接连续失败时的最短和最长等待时间
+/// Shortest and longest wait after accepting connections keeps failing
+const ACCEPT_BACKOFF_MIN: Duration = Duration::from_millis(10);
+const ACCEPT_BACKOFF_MAX: Duration = Duration::from_secs(1);
 
-           tokio::spawn(async move {
-                if let Err(err) = handler.run().await {
-                    error!("处理连接: {}", err)  // Error handling connection
+/// 接收连接的结果。出错时（例如文件描述符用尽）记录错误并等待一段时间后返回 None，继续接收之后的连接，
+/// 连续出错时等待时间加倍
+/// Result of accepting a connection. On errors, such as running out of file descriptors, the error is logged and
+/// None is returned after a wait so later connections are still accepted, the wait doubles while errors keep coming.
+async fn accepted<T>(res: std::io::Result<T>, backoff: &mut Duration) -> Option<T> {
+    match res {
+        Ok(conn) => {
+            *backoff = ACCEPT_BACKOFF_MIN;
+            Some(conn)
+        }
+        Err(err) => {
+            error!("接收连接失败: {}", err);  // Failed to accept a connection
+            tokio::time::sleep(*backoff).await;
+            *backoff = (*backoff * 2).min(ACCEPT_BACKOFF_MAX);
+            None
+        }
+    }
+}
+
+/// 开启 TCP keepalive，检测已经断开的连接，TLS 连接在握手之前设置
+/// Enable TCP keepalive to detect dead connections, TLS connections set it before the handshake.
+fn set_keepalive(socket: &TcpStream) {
+    let tcp_keepalive = get_server_config().tcp_keepalive;
+    if tcp_keepalive > 0 {
+        let keepalive = TcpKeepalive::new().with_time(Duration::from_secs(tcp_keepalive));
+        if let Err(err) = SockRef::from(socket).set_tcp_keepalive(&keepalive) {
+            error!("设置 TCP keepalive 失败: {}", err);  // Failed to set TCP keepalive
+        }
+    }
+}
+
+/// 加载启动时的 AOF 和 RDB 数据，之后开始定时保存 RDB
+/// Load the AOF and RDB data at startup, then start saving RDB periodically.
+async fn load_data(mut db: Arc<Mutex<Db>>) {
+    let aof_config = get_aof_config();
+    if aof_config.enabled {
+        match load_aof(&mut db).await {
+            // 成功加载 AOF 数据后处理
+            Ok((time, _)) => info!("加载 AOF 数据花费时间: {} 毫秒", time),
+            // 与 Redis 相同，AOF 损坏时拒绝启动，避免之后的写入覆盖还能修复的数据
+            // Like Redis, refuse to start with a corrupted AOF so later writes don't bury data that can still be fixed
+            Err(err) => {
+                error!("加载 AOF 数据失败: {}", err);
+                std::process::exit(1);
+            }
+        }
+    }
+    let rdb_config= get_rdb_config();
+    if rdb_config.enabled {
+        match RdbReader::load_file(rdb_config.file_path.as_str()).await{
+            Ok(mut rdb)=>{
+                match load_rdb(&db, &mut rdb).await {
+                    Ok((time, _)) => info!("加载 rdb 数据花费时间: {} 毫秒", time),
+                    // 与 AOF 相同，RDB 损坏时拒绝启动，避免之后的保存覆盖原来的文件
+                    // Like the AOF, refuse to start with a corrupted RDB so a later save doesn't overwrite the file
+                    Err(err) => {
+                        error!("加载 rdb 数据失败: {}", err);  // Failed to load the RDB data
+                        std::process::exit(1);
+                    }
                 }
-            });
+            }
+            Err(_)=>{
+                error!("rdb文件为空");
+            }
         }
     }
+    // 按保存规则和 `BGSAVE SCHEDULE` 的请求在后台保存
+    // Save in the background for the save rules and `BGSAVE SCHEDULE`
+    start_saving(db.clone());
+    // 按 AOF 文件的增长和被推迟的请求在后台重写
+    // Rewrite the AOF in the background for its growth and postponed requests
+    start_rewriting(db.clone());
+    // 加载的数据不计入上次保存之后的修改
+    // Loaded data does not count as changes since the last save
+    stats::DIRTY.store(0, Ordering::Relaxed);
+    stats::LOADING.store(false, Ordering::Relaxed);
+}
+
+/// 为新的连接注册客户端并处理它的请求，与传输层无关
+/// Register a client for a new connection and handle its requests, independent of the transport
+pub async fn serve(
+    mut socket: impl Stream + 'static,
+    addr: String,
+    laddr: String,
+    db: Arc<Mutex<Db>>,
+    notify_shutdown: broadcast::Receiver<()>,
+) {
+    info!("接收客户端连接: {}", addr);  // Accepting client connection
+    stats::incr(&stats::TOTAL_CONNECTIONS_RECEIVED);
+    // 超过最大连接数时拒绝连接
+    // Reject the connection when the maximum number of clients is reached
+    if client::count() >= get_server_config().maxclients {
+        stats::incr(&stats::REJECTED_CONNECTIONS);
+        error!("超过最大连接数，拒绝客户端连接: {}", addr);  // Max number of clients reached, rejecting the client
+        let _ = socket.write_all(b"-ERR max number of clients reached\r\n").await;
+        return;
+    }
+    // 注册客户端，使其可以被 CLIENT 命令查看和断开
+    // Register the client, so it can be inspected and killed by the CLIENT command
+    let client = Client::register(addr, laddr);
+    // 处理连接
+    // Handle the connection
+    let mut handler = Handler {
+        subscriber: Subscriber::new(db.clone()),
+        db,
+        connection: ConnectionHandler::new(socket),
+        shutdown: Shutdown::new(notify_shutdown, client.handle.kill_signal()),
+        client,
+    };
+    if let Err(err) = handler.run().await {
+        error!("处理连接: {}", err)  // Error handling connection
+    }
 }
 
 impl Handler {
@@ -141,64 +360,586 @@ impl Handler {
         while !self.shutdown.is_shutdown() {
             // TODO 处理关闭后的逻辑，保存数据等
             // TODO: Handle post-shutdown logic, such as saving data
-            if let Err(err) = self.process_data().await {
-                Err(err)?;  // Error processing data
-                continue;
-            }
+            // 等待客户端的请求，同时转发推送给该客户端的消息
+            // Wait for the client's request, while forwarding messages pushed to this client
+            // 订阅模式下的客户端不会因为空闲而超时
+            // Clients in subscribed mode never time out for being idle
+            let timeout = if self.subscriber.is_active() { 0 } else { get_server_config().timeout };
+            let state = self.client.handle.state();
+            let idle_deadline = state.last_interaction + Duration::from_secs(timeout);
+            let data = select! {
+                res = self.connection.read_data() => match res {
+                    Ok(data) => data,
+                    // 请求不符合协议时回复错误后关闭连接
+                    // Reply the error and close the connection if the request breaks the protocol
+                    Err(err) => {
+                        if let Some(err) = err.downcast_ref::<error::Error>() {
+                            let _ = self.connection.write_data(err.to_frame()).await;
+                        }
+                        return Err(err);
+                    }
+                },
+                // 客户端空闲超时，关闭连接
+                // The client has been idle for too long, close the connection
+                _ = tokio::time::sleep_until(idle_deadline.into()), if timeout > 0 => {
+                    info!("客户端空闲超时: {}", self.client.handle.addr);  // Client idle timeout
+                    return Ok(());
+                }
+                Some(frame) = self.client.pushes.recv() => {
+                    self.deliver(frame, true).await?;
+                    continue;
+                }
+                // 转发订阅的频道和模式收到的消息
+                // Forward the messages received by the subscribed channels and patterns
+                Some(frame) = self.subscriber.next_message(state.resp) => {
+                    self.deliver(frame, false).await?;
+                    continue;
+                }
+                _ = self.shutdown.recv() => return Ok(()),
+            };
+            // 命令的错误已经回复给客户端，这里只返回需要关闭连接的错误
+            // Command errors have been replied to the client, only errors closing the connection are returned here
+            self.process_data(data).await?;
         }
         Ok(())
     }
 
-    /// 读取和解析数据
-    /// Read and parse data
-    async fn process_data(&mut self) -> crate::Result<()> {
-        // 读取数据并处理错误
-        // Read data and handle errors
-        let data = self.connection.read_data().await?;
+    /// 解析并处理数据，命令出错时回复错误，只有协议错误和连接错误会返回
+    /// Parse and process data, command errors are replied, only protocol and connection errors are returned
+    async fn process_data(&mut self, data: Option<Frame>) -> crate::Result<()> {
         // 解析数据并处理错误
         // Parse data and handle errors
-        let mut parts = parse::Parse::new(data)?;
+        let mut parts = match parse::Parse::new(data) {
+            Ok(parts) => parts,
+            Err(err) => return self.reply_error(err).await,
+        };
         // 获取命令名称并转换为小写
         // Get the command name and convert it to lowercase
-        let command_name = parts.next_string()?.to_lowercase();
+        let command_name = match parts.next_string() {
+            Ok(name) => name.to_lowercase(),
+            Err(_) => {
+                let err = error::Error::Protocol("invalid command name".to_string());
+                return self.reply_error(err.into()).await;
+            }
+        };
+        // 记录客户端最后执行的命令和缓冲区大小
+        // Record the client's last command and buffer sizes
+        let (qbuf, qbuf_free) = (self.connection.buffer_len(), self.connection.buffer_free());
+        self.client.handle.update(|state| {
+            state.cmd = command_name.clone();
+            state.last_interaction = Instant::now();
+            state.qbuf = qbuf;
+            state.qbuf_free = qbuf_free;
+        });
+        self.client.begin_command();
         // 查看命令是否存在于命令表中
         // Check if the command exists in the command table
-        if !Command::exists(&command_name) {
-            self.connection
-                .write_data(Frame::Error(format!("ERR unknown command '{}'", command_name)))
-                .await?;  // Write error if command is unknown
+        let Some(command) = Command::lookup(&command_name) else {
+            let err = error::Error::Err(format!("unknown command '{}'", command_name));
+            return self.reply(err.to_frame()).await;  // Write error if command is unknown
+        };
+        if let Err(err) = command.check_arity(&mut parts) {
+            // 参数个数由命令表统一检查
+            // The number of arguments is checked uniformly from the command table
+            stats::record_rejected(&command_name);
+            self.reply(err.to_frame()).await?;
+        } else if self.subscriber.is_active()
+            && self.client.handle.state().resp == 2
+            && !Subscriber::allows(&command_name)
+        {
+            // 订阅模式下的 RESP2 连接只能执行订阅相关的命令
+            // A RESP2 connection in subscribed mode can only run subscription related commands
+            stats::record_rejected(&command_name);
+            self.reply(Subscriber::rejected(&command_name).to_frame()).await?;
+        } else if let Some(err) = self.check_access(&command_name, &parts) {
+            // 未认证或没有权限时拒绝执行
+            // Refuse to execute if not authenticated or not permitted
+            stats::record_rejected(&command_name);
+            self.reply(err.to_frame()).await?;
+        } else if let Err(err) = command.check_state(&mut parts) {
+            // 加载数据期间或内存超过上限时，根据命令的标志拒绝执行
+            // Refuse to execute according to the command's flags while loading or when the memory exceeds the limit
+            stats::record_rejected(&command_name);
+            self.reply(err.to_frame()).await?;
         } else {
-            // 命令存在，获取并调用对应处理函数
-            // If command exists, get and call the corresponding handler function
-            if let Some(command_fn) = Command::get_command_fn(&command_name) {
-                // TODO 对于需要阻塞返回的函数暂时单独处理，后续可以封装一个阻塞处理的命令表
-                // TODO: Temporarily handle blocking return functions, later can encapsulate a blocking command table
-                match command_name.as_str() {
-                    "subscribe"=>{
-                        cmd::pubsub::subscribe::Subscribe::subscribe_command(&mut self.db, &mut parts, &mut self.connection, &mut self.shutdown)
-                            .await?;  // Handle subscribe command
-                        return Ok(());
-                    }
-                   "psubscribe"=>{
-                    cmd::pubsub::psubscribe::PSubscribe::psubscribe_command(&mut self.db, &mut parts, &mut self.connection, &mut self.shutdown)
-                        .await?;
-                    return Ok(());
-                    }
-                    _=>{
-                        // 传数据库，Parse命令内容,返回错误信息
-                        // Pass the database, parse the command content, return error information
-                        let res = command_fn(&mut self.db, &mut parts)?;
-                        self.connection.write_data(res).await?;  // Write result to connection
-                    }
+            // 客户端被 CLIENT PAUSE 暂停时等待
+            // Wait while clients are paused by CLIENT PAUSE
+            client::wait_if_paused(&command).await;
+            // 命令存在，调用对应处理函数
+            // The command exists, call the corresponding handler function
+            let command_fn = &command.command_fn;
+            // 记录命令的调用次数和耗时，阻塞命令执行期间计入阻塞的客户端
+            // Record the command's calls and time spent, clients running a blocking command are counted as blocked
+            let started = Instant::now();
+            let blocked = command.has_flag("blocking").then(stats::BlockedClient::enter);
+            // TODO 对于需要阻塞返回的函数暂时单独处理，后续可以封装一个阻塞处理的命令表
+            // TODO: Temporarily handle blocking return functions, later can encapsulate a blocking command table
+            let res = match command_name.as_str() {
+                // 订阅相关的命令改变连接的订阅状态，每个频道或模式回复一条消息
+                // Subscription related commands change the connection's subscriptions, replying once per channel or pattern
+                "subscribe"=>{
+                    cmd::pubsub::subscribe::Subscribe::subscribe_command(&mut parts, &mut self.subscriber, &self.client)
+                }
+                "psubscribe"=>{
+                    cmd::pubsub::psubscribe::PSubscribe::psubscribe_command(&mut parts, &mut self.subscriber, &self.client)
+                }
+                "unsubscribe"=>{
+                    cmd::pubsub::unsubscribe::Unsubscribe::unsubscribe_command(&mut parts, &mut self.subscriber, &self.client)
+                }
+                "punsubscribe"=>{
+                    cmd::pubsub::punsubscribe::PUnsubscribe::punsubscribe_command(&mut parts, &mut self.subscriber, &self.client)
+                }
+                // block 策略下发布者可能需要等待订阅者赶上
+                // Under the block policy the publisher may wait for subscribers to catch up
+                "publish"=>{
+                    cmd::pubsub::publish::Publish::publish_blocking_command(&mut self.db, &mut parts).await.map(|res| vec![res])
+                }
+                "ssubscribe"=>{
+                    cmd::pubsub::ssubscribe::SSubscribe::ssubscribe_command(&mut parts, &mut self.subscriber, &self.client)
+                }
+                "sunsubscribe"=>{
+                    cmd::pubsub::sunsubscribe::SUnsubscribe::sunsubscribe_command(&mut parts, &mut self.subscriber, &self.client)
+                }
+                "spublish"=>{
+                    cmd::pubsub::spublish::SPublish::spublish_blocking_command(&mut self.db, &mut parts).await.map(|res| vec![res])
+                }
+                "ping" if self.subscriber.is_active() && self.client.handle.state().resp == 2 => {
+                    cmd::ping::Ping::subscribed_ping_command(&mut parts).map(|res| vec![res])
+                }
+                "quit"=>{
+                    self.shutdown.trigger();
+                    cmd::quit::Quit::quit_command().map(|res| vec![res])
                 }
+                "reset"=>{
+                    self.subscriber.clear(&self.client);
+                    cmd::reset::Reset::reset_command(&mut self.client).map(|res| vec![res])
+                }
+                // 连接相关的命令需要访问客户端状态
+                // Connection related commands need access to the client state
+                "hello"=>{
+                    cmd::hello::Hello::hello_command(&mut self.client, &mut parts).map(|res| vec![res])
+                }
+                "client"=>{
+                    cmd::client::ClientCommand::client_command(&mut self.client, &mut parts).map(|res| vec![res])
+                }
+                "auth"=>{
+                    cmd::auth::Auth::auth_command(&mut self.client, &mut parts).map(|res| vec![res])
+                }
+                "acl"=>{
+                    cmd::acl::AclCommand::acl_command(&mut self.client, &mut parts).map(|res| vec![res])
+                }
+                "command"=>{
+                    cmd::command::CommandCommand::command_command(&mut self.client, &mut parts).map(|res| vec![res])
+                }
+                _=>{
+                    // 传数据库，Parse命令内容,返回错误信息
+                    // 在追踪上下文中执行，以便记录客户端缓存读取的键；写命令还在传播上下文中执行，实际效果传播到 AOF
+                    // Pass the database, parse the command content, return error information
+                    // Executed in a tracking context so that keys read for client-side caching are remembered, and
+                    // write commands also in a propagation context, so that their actual effect is propagated to the AOF
+                    let client_id = self.client.id();
+                    let db = &mut self.db;
+                    aof::call(&command_name, parts.peek_strings(), || {
+                        tracking::call(client_id, || command_fn(db, &mut parts))
+                    }).map(|res| vec![res])
+                }
+            };
+            // appendfsync always 时，写命令的回复等到写入同步到磁盘之后再发送
+            // Under appendfsync always, replies to write commands are only sent once the writes are synced to disk
+            // 写入或同步失败时回复 MISCONF 错误
+            // A failed write or sync replies the MISCONF error
+            let res = if command.has_flag("write") {
+                aof::wait_for_fsync().await.map_err(Into::into).and(res)
             } else {
-                // 处理错误
-                // Handle error
-                self.connection
-                    .write_data(Frame::Error(format!("ERR unknown command '{}'", command_name)))
-                    .await?;  // Write error if command is unknown
+                res
+            };
+            // 命令出错时回复对应的错误，连接继续处理后续的命令
+            // A failed command replies the matching error, the connection keeps handling the following commands
+            match res {
+                Ok(replies) => self.reply_all(replies).await?,
+                Err(err) => self.reply_error(err).await?,
             }
+            drop(blocked);
+            stats::record_command(&command_name, started.elapsed());
+        }
+        Ok(())
+    }
+
+    /// 检查客户端是否已认证、是否有权限执行该命令，被拒绝时返回错误回复并记录到 ACL LOG
+    /// Check whether the client is authenticated and permitted to run the command, returning the error reply and
+    /// recording it in ACL LOG if denied
+    fn check_access(&self, command_name: &str, parts: &parse::Parse) -> Option<error::Error> {
+        let state = self.client.handle.state();
+        // 未认证时只能执行认证相关的命令
+        // Only authentication related commands can be run before authenticating
+        if !state.authenticated {
+            if matches!(command_name, "auth" | "hello" | "quit" | "reset") {
+                return None;
+            }
+            return Some(error::Error::NoAuth);
+        }
+        let (reason, object, message) = match acl::check(&state.user, command_name, &parts.peek_strings()) {
+            Ok(()) => return None,
+            Err(acl::Denied::Command(name)) => {
+                let message = format!("User {} has no permissions to run the '{}' command", state.user, name);
+                ("command", name, message)
+            }
+            Err(acl::Denied::Key(key)) => ("key", key, "No permissions to access a key".to_string()),
+            Err(acl::Denied::Channel(channel)) => {
+                ("channel", channel, "No permissions to access a channel".to_string())
+            }
+        };
+        acl::log(reason, &object, &state.user, self.client.handle.info());
+        Some(error::Error::NoPerm(message))
+    }
+
+    /// 回复命令的错误，协议错误在回复后返回，以便关闭连接
+    /// Reply a command's error, protocol errors are returned after the reply so that the connection is closed
+    async fn reply_error(&mut self, err: Box<dyn std::error::Error + Send + Sync>) -> crate::Result<()> {
+        let err = error::Error::from_boxed(err);
+        self.reply(err.to_frame()).await?;
+        if err.closes_connection() {
+            return Err(err.into());
         }
         Ok(())
     }
+
+    /// 依次回复多条消息，例如订阅每个频道的确认
+    /// Reply several messages in order, such as the confirmation for every subscribed channel
+    async fn reply_all(&mut self, frames: Vec<Frame>) -> crate::Result<()> {
+        for frame in frames {
+            self.reply(frame).await?;
+        }
+        Ok(())
+    }
+
+    /// 回复客户端，`CLIENT REPLY OFF|SKIP` 时不回复
+    /// Reply to the client, nothing is sent under `CLIENT REPLY OFF|SKIP`
+    ///
+    /// 回复超过输出缓冲区限制时断开连接
+    /// The connection is closed if the reply exceeds the output buffer limit
+    async fn reply(&mut self, frame: Frame) -> crate::Result<()> {
+        if !self.client.should_reply() {
+            return Ok(());
+        }
+        if let Some(bytes) = frame.to_bytes() {
+            if self.client.handle.exceeds_output_limit(bytes.len()) {
+                return Err("客户端超过输出缓冲区限制".into());  // Client output buffer limit reached
+            }
+            self.connection.write_bytes(&bytes).await?;
+        }
+        Ok(())
+    }
+
+    /// 发送推送或订阅收到的消息，不受 `CLIENT REPLY` 影响
+    /// Send a pushed message or a message received by a subscription, regardless of `CLIENT REPLY`
+    ///
+    /// 消息在写出前计入输出缓冲区，写出后扣除，超过输出缓冲区限制时断开连接。
+    /// `queued` 表示消息推送时已经计入。
+    /// The message is accounted in the output buffer until it has been written, and the connection is
+    /// closed if the output buffer limit is exceeded. `queued` tells whether it was accounted when pushed.
+    async fn deliver(&mut self, frame: Frame, queued: bool) -> crate::Result<()> {
+        let Some(bytes) = frame.to_bytes() else {
+            return Ok(());
+        };
+        if !queued {
+            self.client.handle.queued(bytes.len());
+        }
+        let written = if self.client.handle.exceeds_output_limit(0) {
+            Err("客户端超过输出缓冲区限制".into())  // Client output buffer limit reached
+        } else {
+            self.connection.write_bytes(&bytes).await
+        };
+        self.client.handle.sent(bytes.len());
+        written
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::CONFIG;
+    use tokio::io::{AsyncRead, AsyncReadExt, DuplexStream};
+    use tokio::net::UnixStream;
+
+    /// 在内存管道上启动一个连接，返回客户端一端
+    /// Serve a connection over an in-memory pipe, returning the client end
+    fn connect(db: &Arc<Mutex<Db>>) -> (DuplexStream, broadcast::Sender<()>) {
+        Command::load_commands();
+        let (client, server) = tokio::io::duplex(64 * 1024);
+        let (notify_shutdown, _) = broadcast::channel(1);
+        let shutdown = notify_shutdown.subscribe();
+        tokio::spawn(serve(server, "127.0.0.1:50000".to_string(), "127.0.0.1:6379".to_string(), db.clone(), shutdown));
+        (client, notify_shutdown)
+    }
+
+    /// 以 RESP 数组发送命令
+    /// Send a command as a RESP array
+    async fn send(stream: &mut (impl AsyncWriteExt + Unpin), args: &[&str]) {
+        let mut request = format!("*{}\r\n", args.len());
+        for arg in args {
+            request.push_str(&format!("${}\r\n{}\r\n", arg.len(), arg));
+        }
+        stream.write_all(request.as_bytes()).await.unwrap();
+    }
+
+    /// 读取服务端已经写出的数据，连接关闭时返回 None
+    /// Read what the server has written, None once the connection is closed
+    async fn receive(stream: &mut (impl AsyncRead + Unpin)) -> Option<String> {
+        let mut buffer = vec![0; 64 * 1024];
+        match tokio::time::timeout(Duration::from_secs(1), stream.read(&mut buffer)).await {
+            Ok(Ok(0)) | Ok(Err(_)) => None,
+            Ok(Ok(n)) => Some(String::from_utf8_lossy(&buffer[..n]).into_owned()),
+            Err(_) => Some(String::new()),
+        }
+    }
+
+    /// 读取直到收到与 `expected` 一样长的回复，再比较内容
+    /// Read until a reply as long as `expected` has arrived, then compare the contents
+    async fn expect(stream: &mut (impl AsyncRead + Unpin), expected: &str) {
+        let mut reply = String::new();
+        while reply.len() < expected.len() {
+            match receive(stream).await {
+                Some(data) if !data.is_empty() => reply.push_str(&data),
+                _ => break,
+            }
+        }
+        assert_eq!(reply, expected);
+    }
+
+    #[tokio::test]
+    async fn split_and_pipelined_commands_are_parsed() {
+        let db = Arc::new(Mutex::new(Db::new()));
+        let (mut stream, _shutdown) = connect(&db);
+        // 一条命令分多次到达
+        // One command arriving in several pieces
+        stream.write_all(b"*1\r\n$4\r\nPI").await.unwrap();
+        assert_eq!(receive(&mut stream).await.unwrap(), "");
+        stream.write_all(b"NG\r\n").await.unwrap();
+        assert_eq!(receive(&mut stream).await.unwrap(), "+PONG\r\n");
+        // 多条命令一次到达
+        // Several commands arriving at once
+        stream.write_all(b"*2\r\n$4\r\nECHO\r\n$1\r\na\r\n*2\r\n$4\r\nECHO\r\n$1\r\nb\r\n").await.unwrap();
+        let mut replies = receive(&mut stream).await.unwrap();
+        if replies.len() < 14 {
+            replies.push_str(&receive(&mut stream).await.unwrap());
+        }
+        assert_eq!(replies, "$1\r\na\r\n$1\r\nb\r\n");
+    }
+
+    #[tokio::test]
+    async fn pubsub_clients_over_the_hard_limit_are_closed() {
+        let previous = CONFIG.read().unwrap().server.client_output_buffer_limit.pubsub;
+        CONFIG.write().unwrap().server.client_output_buffer_limit.pubsub.hard_limit = 1024;
+        let db = Arc::new(Mutex::new(Db::new()));
+        let (mut stream, _shutdown) = connect(&db);
+        send(&mut stream, &["SUBSCRIBE", "server:limit"]).await;
+        assert_eq!(receive(&mut stream).await.unwrap(), "*3\r\n$9\r\nsubscribe\r\n$12\r\nserver:limit\r\n:1\r\n");
+
+        db.lock().unwrap().publish("server:limit", "small".to_string());
+        assert!(receive(&mut stream).await.unwrap().ends_with("$5\r\nsmall\r\n"));
+        db.lock().unwrap().publish("server:limit", "x".repeat(2048));
+        let closed = receive(&mut stream).await;
+        CONFIG.write().unwrap().server.client_output_buffer_limit.pubsub = previous;
+        assert_eq!(closed, None);
+    }
+
+    #[tokio::test]
+    async fn clients_are_served_on_the_unix_socket() {
+        let path = std::env::temp_dir().join(format!("p-rudis-{}.sock", id()));
+        let path_str = path.to_str().unwrap().to_string();
+        // 遗留的套接字文件在绑定前被删除
+        // A stale socket file left behind is removed before binding
+        drop(std::os::unix::net::UnixListener::bind(&path).unwrap());
+        let mut config = get_server_config();
+        config.unixsocket = Some(path_str.clone());
+        config.unixsocketperm = Some(0o700);
+        let listener = bind_unix(&config).unwrap().unwrap();
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o700);
+
+        Command::load_commands();
+        let db = Arc::new(Mutex::new(Db::new()));
+        let (notify_shutdown, _) = broadcast::channel(1);
+        let shutdown = notify_shutdown.subscribe();
+        let mut stream = UnixStream::connect(&path).await.unwrap();
+        let (socket, _) = listener.accept().await.unwrap();
+        let addr = format!("{}:0", path_str);
+        tokio::spawn(serve(socket, addr.clone(), addr, db, shutdown));
+        send(&mut stream, &["PING"]).await;
+        assert_eq!(receive(&mut stream).await.unwrap(), "+PONG\r\n");
+        send(&mut stream, &["CLIENT", "INFO"]).await;
+        assert!(receive(&mut stream).await.unwrap().contains(&format!("addr={}:0 ", path_str)));
+
+        // 只删除套接字类型的文件
+        // Only files of the socket type are removed
+        remove_unix_socket(&path_str);
+        assert!(!path.exists());
+        std::fs::write(&path, "not a socket").unwrap();
+        remove_unix_socket(&path_str);
+        assert!(path.exists());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn resp2_subscribed_mode_only_allows_subscription_commands() {
+        let db = Arc::new(Mutex::new(Db::new()));
+        let (mut stream, _shutdown) = connect(&db);
+        let rejected = |command: &str| format!("-{}\r\n", Subscriber::rejected(command));
+        send(&mut stream, &["SUBSCRIBE", "a"]).await;
+        expect(&mut stream, "*3\r\n$9\r\nsubscribe\r\n$1\r\na\r\n:1\r\n").await;
+        send(&mut stream, &["GET", "x"]).await;
+        expect(&mut stream, &rejected("get")).await;
+        // PING 的回复与订阅消息的格式相同
+        // PING replies are shaped like subscribed messages
+        send(&mut stream, &["PING"]).await;
+        expect(&mut stream, "*2\r\n$4\r\npong\r\n$0\r\n\r\n").await;
+        send(&mut stream, &["PING", "hi"]).await;
+        expect(&mut stream, "*2\r\n$4\r\npong\r\n$2\r\nhi\r\n").await;
+
+        // 还有模式订阅时仍处于订阅模式
+        // The connection stays in subscribed mode while a pattern is still subscribed
+        send(&mut stream, &["PSUBSCRIBE", "p*"]).await;
+        expect(&mut stream, "*3\r\n$10\r\npsubscribe\r\n$2\r\np*\r\n:2\r\n").await;
+        send(&mut stream, &["UNSUBSCRIBE"]).await;
+        expect(&mut stream, "*3\r\n$11\r\nunsubscribe\r\n$1\r\na\r\n:1\r\n").await;
+        send(&mut stream, &["SET", "x", "1"]).await;
+        expect(&mut stream, &rejected("set")).await;
+
+        // 订阅数量回到 0 后恢复正常模式
+        // Normal mode is back once the count drops to 0
+        send(&mut stream, &["PUNSUBSCRIBE", "p*"]).await;
+        expect(&mut stream, "*3\r\n$12\r\npunsubscribe\r\n$2\r\np*\r\n:0\r\n").await;
+        send(&mut stream, &["PING"]).await;
+        expect(&mut stream, "+PONG\r\n").await;
+        send(&mut stream, &["GET", "x"]).await;
+        expect(&mut stream, "$-1\r\n").await;
+
+        // RESET 退订所有频道并回到正常模式
+        // RESET unsubscribes from everything and returns to normal mode
+        send(&mut stream, &["SSUBSCRIBE", "s"]).await;
+        expect(&mut stream, "*3\r\n$10\r\nssubscribe\r\n$1\r\ns\r\n:1\r\n").await;
+        send(&mut stream, &["RESET"]).await;
+        expect(&mut stream, "+RESET\r\n").await;
+        send(&mut stream, &["GET", "x"]).await;
+        expect(&mut stream, "$-1\r\n").await;
+        assert_eq!(db.lock().unwrap().shard_channel_count(), 0);
+    }
+
+    #[tokio::test]
+    async fn resp3_subscribed_connections_run_any_command() {
+        let db = Arc::new(Mutex::new(Db::new()));
+        let (mut stream, _shutdown) = connect(&db);
+        send(&mut stream, &["HELLO", "3"]).await;
+        assert!(receive(&mut stream).await.unwrap().starts_with('%'));
+        send(&mut stream, &["SUBSCRIBE", "a"]).await;
+        expect(&mut stream, ">3\r\n$9\r\nsubscribe\r\n$1\r\na\r\n:1\r\n").await;
+        send(&mut stream, &["SET", "x", "1"]).await;
+        expect(&mut stream, "+OK\r\n").await;
+        send(&mut stream, &["PING"]).await;
+        expect(&mut stream, "+PONG\r\n").await;
+    }
+
+    #[tokio::test]
+    async fn pubsub_introspection_reports_subscribed_channels() {
+        let db = Arc::new(Mutex::new(Db::new()));
+        let (mut first, _first_shutdown) = connect(&db);
+        let (mut second, _second_shutdown) = connect(&db);
+        let (mut stream, _shutdown) = connect(&db);
+        send(&mut first, &["SUBSCRIBE", "news.a", "news.b"]).await;
+        send(&mut first, &["PSUBSCRIBE", "news.*"]).await;
+        send(&mut first, &["SSUBSCRIBE", "shard"]).await;
+        send(&mut second, &["SUBSCRIBE", "news.a"]).await;
+        expect(&mut first, concat!(
+            "*3\r\n$9\r\nsubscribe\r\n$6\r\nnews.a\r\n:1\r\n",
+            "*3\r\n$9\r\nsubscribe\r\n$6\r\nnews.b\r\n:2\r\n",
+            "*3\r\n$10\r\npsubscribe\r\n$6\r\nnews.*\r\n:3\r\n",
+            "*3\r\n$10\r\nssubscribe\r\n$5\r\nshard\r\n:1\r\n",
+        )).await;
+        expect(&mut second, "*3\r\n$9\r\nsubscribe\r\n$6\r\nnews.a\r\n:1\r\n").await;
+
+        send(&mut stream, &["PUBSUB", "CHANNELS", "*.b"]).await;
+        expect(&mut stream, "*1\r\n$6\r\nnews.b\r\n").await;
+        let mut channels = db.lock().unwrap().channels(None);
+        channels.sort();
+        assert_eq!(channels, ["news.a", "news.b"]);
+        // 模式订阅不计入 NUMSUB
+        // Pattern subscriptions are not counted by NUMSUB
+        send(&mut stream, &["PUBSUB", "NUMSUB", "news.a", "news.b", "news.c"]).await;
+        expect(&mut stream, "*6\r\n$6\r\nnews.a\r\n:2\r\n$6\r\nnews.b\r\n:1\r\n$6\r\nnews.c\r\n:0\r\n").await;
+        send(&mut stream, &["PUBSUB", "NUMPAT"]).await;
+        expect(&mut stream, ":1\r\n").await;
+        // 分片频道与普通频道分开
+        // Shard channels are kept apart from regular channels
+        send(&mut stream, &["PUBSUB", "SHARDCHANNELS"]).await;
+        expect(&mut stream, "*1\r\n$5\r\nshard\r\n").await;
+        send(&mut stream, &["PUBSUB", "SHARDNUMSUB", "shard", "news.a"]).await;
+        expect(&mut stream, "*4\r\n$5\r\nshard\r\n:1\r\n$6\r\nnews.a\r\n:0\r\n").await;
+
+        // 连接关闭后它的订阅被释放
+        // Subscriptions are released once the connection closes
+        drop(first);
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        send(&mut stream, &["PUBSUB", "NUMSUB", "news.a", "news.b"]).await;
+        expect(&mut stream, "*4\r\n$6\r\nnews.a\r\n:1\r\n$6\r\nnews.b\r\n:0\r\n").await;
+        send(&mut stream, &["PUBSUB", "NUMPAT"]).await;
+        expect(&mut stream, ":0\r\n").await;
+        send(&mut stream, &["PUBSUB", "SHARDCHANNELS"]).await;
+        expect(&mut stream, "*0\r\n").await;
+    }
+
+    #[tokio::test]
+    async fn lagging_subscribers_follow_the_lag_policy() {
+        use crate::config::LagPolicy;
+        let previous = CONFIG.read().unwrap().pubsub.clone();
+        let set_policy = |lag_policy: LagPolicy| {
+            let mut config = CONFIG.write().unwrap();
+            config.pubsub.channel_capacity = 2;
+            config.pubsub.lag_policy = lag_policy;
+            config.pubsub.block_timeout = 50;
+        };
+        let db = Arc::new(Mutex::new(Db::new()));
+        let (mut stream, _shutdown) = connect(&db);
+        let publish = |channel: &str, count: usize| {
+            let mut db = db.lock().unwrap();
+            (1..=count).for_each(|i| { db.publish(channel, i.to_string()); });
+        };
+
+        // drop-oldest 丢弃最早的消息并计入丢弃数量
+        // drop-oldest drops the oldest messages and counts them
+        set_policy(LagPolicy::DropOldest);
+        let (mut subscriber, _subscriber_shutdown) = connect(&db);
+        send(&mut subscriber, &["CLIENT", "ID"]).await;
+        let id = receive(&mut subscriber).await.unwrap();
+        send(&mut subscriber, &["SUBSCRIBE", "lag:drop"]).await;
+        expect(&mut subscriber, "*3\r\n$9\r\nsubscribe\r\n$8\r\nlag:drop\r\n:1\r\n").await;
+        publish("lag:drop", 5);
+        let message = |payload: &str| format!("*3\r\n$7\r\nmessage\r\n$8\r\nlag:drop\r\n$1\r\n{}\r\n", payload);
+        expect(&mut subscriber, &(message("4") + &message("5"))).await;
+        send(&mut stream, &["PUBSUB", "LAG", id.trim_start_matches(':').trim_end()]).await;
+        let lag = format!("*1\r\n*6\r\n$2\r\nid\r\n{}$3\r\nlag\r\n:0\r\n$7\r\ndropped\r\n:3\r\n", id);
+        expect(&mut stream, &lag).await;
+
+        // disconnect 断开落后的订阅者
+        // disconnect closes lagging subscribers
+        set_policy(LagPolicy::Disconnect);
+        let (mut subscriber, _subscriber_shutdown) = connect(&db);
+        send(&mut subscriber, &["SUBSCRIBE", "lag:disconnect"]).await;
+        assert!(receive(&mut subscriber).await.unwrap().ends_with(":1\r\n"));
+        publish("lag:disconnect", 5);
+        assert_eq!(receive(&mut subscriber).await, None);
+
+        // block 时发布者等待缓冲区已满的订阅者，超时后再发布
+        // block makes publishers wait for subscribers with a full buffer, publishing after the timeout
+        set_policy(LagPolicy::Block);
+        let stalled = db.lock().unwrap().subscribe("lag:block");
+        publish("lag:block", 2);
+        let start = Instant::now();
+        send(&mut stream, &["PUBLISH", "lag:block", "3"]).await;
+        expect(&mut stream, ":1\r\n").await;
+        let waited = start.elapsed();
+        CONFIG.write().unwrap().pubsub = previous;
+        assert!(waited >= Duration::from_millis(50), "waited {:?}", waited);
+        drop(stalled);
+    }
 }
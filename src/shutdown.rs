@@ -1,4 +1,5 @@
-use tokio::sync::broadcast;
+use std::sync::Arc;
+use tokio::sync::{broadcast, Notify};
 
 #[derive(Debug)]
 pub struct Shutdown {
@@ -8,15 +9,19 @@ pub struct Shutdown {
     /// 接收发布订阅模式的关闭信号
     /// Receiver for the shutdown signal in the publish-subscribe model
     notify: broadcast::Receiver<()>,
+    /// 只关闭当前连接的信号，例如 `CLIENT KILL`
+    /// Signal to close only the current connection, such as `CLIENT KILL`
+    kill: Arc<Notify>,
 }
 
 impl Shutdown {
     /// TODO 待实现
     /// TODO: To be implemented
-    pub fn new(notify: broadcast::Receiver<()>) -> Self {
+    pub fn new(notify: broadcast::Receiver<()>, kill: Arc<Notify>) -> Self {
         Shutdown {
             is_shutdown: false,
             notify,
+            kill,
         }
     }
 
@@ -34,7 +39,10 @@ impl Shutdown {
         if self.is_shutdown {
             return;
         }
-        let _ = self.notify.recv().await;
+        tokio::select! {
+            _ = self.notify.recv() => {}
+            _ = self.kill.notified() => {}
+        }
         self.is_shutdown = true;  // Mark as shutdown after receiving the signal
     }
 
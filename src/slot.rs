@@ -0,0 +1,98 @@
+//! 键和分片频道的哈希槽，算法与 Redis Cluster 相同
+//! Hash slots of keys and shard channels, computed the same way as Redis Cluster.
+//!
+//! 槽号为 `CRC16(key) mod 16384`。键中包含非空的 `{...}` 时只对第一对花括号之间的内容计算，
+//! 使相关的键可以落在同一个槽中。
+//! The slot is `CRC16(key) mod 16384`. If the key contains a non-empty `{...}`, only the content between the
+//! first pair of braces is hashed, so related keys can be placed in the same slot.
+
+/// 哈希槽的数量
+/// Number of hash slots
+pub const SLOTS: u16 = 16384;
+
+/// CRC16 的查找表，多项式为 0x1021（XMODEM）
+/// Lookup table for CRC16 with the 0x1021 polynomial (XMODEM)
+const CRC16_TABLE: [u16; 256] = crc16_table();
+
+const fn crc16_table() -> [u16; 256] {
+    let mut table = [0u16; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = (i as u16) << 8;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 0x8000 != 0 { (crc << 1) ^ 0x1021 } else { crc << 1 };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+}
+
+/// 计算 CRC16（XMODEM）
+/// Compute CRC16 (XMODEM).
+pub fn crc16(bytes: &[u8]) -> u16 {
+    bytes.iter().fold(0, |crc, &byte| (crc << 8) ^ CRC16_TABLE[((crc >> 8) as u8 ^ byte) as usize])
+}
+
+/// 键的哈希槽，支持 `{...}` 哈希标签
+/// The hash slot of a key, honoring `{...}` hash tags.
+pub fn key_hash_slot(key: &[u8]) -> u16 {
+    let hashed = match key.iter().position(|&c| c == b'{') {
+        Some(start) => match key[start + 1..].iter().position(|&c| c == b'}') {
+            // `{}` 为空时对整个键计算
+            // The whole key is hashed if `{}` is empty
+            Some(len) if len > 0 => &key[start + 1..start + 1 + len],
+            _ => key,
+        },
+        None => key,
+    };
+    crc16(hashed) & (SLOTS - 1)
+}
+
+/// 所有键是否属于同一个槽，没有键时也视为属于同一个槽
+/// Whether all keys belong to the same slot, which also holds when there are no keys.
+pub fn is_single_slot<K: AsRef<[u8]>>(keys: &[K]) -> bool {
+    let mut slots = keys.iter().map(|key| key_hash_slot(key.as_ref()));
+    match slots.next() {
+        Some(first) => slots.all(|slot| slot == first),
+        None => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn crc16_matches_the_xmodem_check_value() {
+        assert_eq!(crc16(b"123456789"), 0x31C3);
+        assert_eq!(crc16(b""), 0);
+    }
+
+    #[test]
+    fn keys_map_to_redis_cluster_slots() {
+        assert_eq!(key_hash_slot(b"foo"), 12182);
+        assert_eq!(key_hash_slot(b"{user1000}.following"), key_hash_slot(b"{user1000}.followers"));
+        assert_eq!(key_hash_slot(b"{user1000}.following"), key_hash_slot(b"user1000"));
+        // 只使用第一对花括号
+        // Only the first pair of braces is used
+        assert_eq!(key_hash_slot(b"foo{bar}{zap}"), key_hash_slot(b"bar"));
+    }
+
+    #[test]
+    fn empty_or_unterminated_tags_hash_the_whole_key() {
+        for key in [&b"{}"[..], b"foo{}{bar}", b"foo{bar", b"foo}bar{"] {
+            assert_eq!(key_hash_slot(key), crc16(key) & (SLOTS - 1), "{}", String::from_utf8_lossy(key));
+        }
+        assert_ne!(key_hash_slot(b"foo{}{bar}"), key_hash_slot(b"bar"));
+    }
+
+    #[test]
+    fn single_slot_checks_every_key() {
+        assert!(is_single_slot::<&str>(&[]));
+        assert!(is_single_slot(&["{tag}a", "{tag}b"]));
+        assert!(!is_single_slot(&["a", "b"]));
+    }
+}
@@ -0,0 +1,340 @@
+//! 服务端运行时的统计数据，供 `INFO` 使用
+//! Runtime statistics of the server, reported by `INFO`.
+//!
+//! 命令分发路径上的计数只使用原子操作：每个命令的统计在启动时按命令表建好，之后只读查找，不需要加锁。
+//! Counters on the command dispatch path only use atomics: the per-command statistics are built from the command
+//! table at startup and only looked up afterwards, without taking any lock.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use lazy_static::lazy_static;
+use sha2::{Digest, Sha256};
+use crate::client;
+use crate::commands::COMMANDS;
+use crate::config::{get_rdb_config, get_server_config};
+use crate::db::Db;
+use crate::persistence::aof;
+
+/// 计算每秒操作数时保留的采样数量
+/// Number of samples kept to compute operations per second
+const OPS_SAMPLES: usize = 16;
+
+/// 采样间隔
+/// Sampling interval
+const OPS_SAMPLE_INTERVAL: Duration = Duration::from_millis(100);
+
+/// 一个命令的调用次数和耗时
+/// Calls and time spent of a command.
+#[derive(Debug, Default)]
+pub struct CommandStat {
+    /// 调用次数
+    /// Number of calls
+    pub calls: AtomicU64,
+    /// 执行耗时总和，以微秒为单位
+    /// Total execution time, in microseconds
+    pub usec: AtomicU64,
+    /// 执行前被拒绝的次数，例如没有权限
+    /// Number of calls rejected before execution, such as for missing permissions
+    pub rejected_calls: AtomicU64,
+}
+
+/// 最近一次 RDB 或 AOF 写入的结果
+/// Outcome of the latest RDB or AOF write.
+#[derive(Debug, Clone, Default)]
+pub struct WriteStatus {
+    /// 最近一次写入的错误，成功时为 None
+    /// Error of the latest write, None if it succeeded
+    pub last_error: Option<String>,
+    /// 最近一次成功写入的时间，UNIX 时间戳，以秒为单位
+    /// Time of the latest successful write, as a UNIX timestamp in seconds
+    pub last_success: u64,
+    /// 最近一次写入的耗时，以秒为单位，没有写入过时为 -1
+    /// Duration of the latest write in seconds, -1 if nothing was written yet
+    pub last_duration: i64,
+}
+
+lazy_static! {
+    /// 服务端启动的时间
+    /// Time the server started
+    static ref STARTED: Instant = Instant::now();
+    /// 本次运行的随机标识
+    /// Random identifier of this run
+    static ref RUN_ID: String = {
+        let seed = format!("{}:{:?}:{:?}", std::process::id(), SystemTime::now(), Instant::now());
+        Sha256::digest(seed.as_bytes()).iter().take(20).map(|byte| format!("{:02x}", byte)).collect()
+    };
+    /// 每个命令的统计，键为命令名
+    /// Statistics of every command, keyed by command name
+    static ref COMMAND_STATS: HashMap<&'static str, CommandStat> =
+        COMMANDS.iter().map(|(name, ..)| (*name, CommandStat::default())).collect();
+    /// 每秒操作数的采样
+    /// Samples of operations per second
+    static ref OPS: Mutex<OpsSamples> = Mutex::new(OpsSamples::default());
+    /// RDB 保存的状态
+    /// Status of RDB saves
+    static ref RDB_STATUS: Mutex<WriteStatus> = Mutex::new(WriteStatus { last_success: unix_time(), last_duration: -1, ..Default::default() });
+    /// AOF 写入的状态
+    /// Status of AOF writes
+    static ref AOF_STATUS: Mutex<WriteStatus> = Mutex::new(WriteStatus { last_duration: -1, ..Default::default() });
+    /// AOF 重写的状态
+    /// Status of AOF rewrites
+    static ref AOF_REWRITE_STATUS: Mutex<WriteStatus> = Mutex::new(WriteStatus { last_duration: -1, ..Default::default() });
+}
+
+pub static TOTAL_CONNECTIONS_RECEIVED: AtomicU64 = AtomicU64::new(0);
+pub static REJECTED_CONNECTIONS: AtomicU64 = AtomicU64::new(0);
+pub static TOTAL_COMMANDS_PROCESSED: AtomicU64 = AtomicU64::new(0);
+pub static KEYSPACE_HITS: AtomicU64 = AtomicU64::new(0);
+pub static KEYSPACE_MISSES: AtomicU64 = AtomicU64::new(0);
+pub static EXPIRED_KEYS: AtomicU64 = AtomicU64::new(0);
+pub static EVICTED_KEYS: AtomicU64 = AtomicU64::new(0);
+/// 上次 RDB 保存之后修改的键的次数
+/// Number of key modifications since the last RDB save
+pub static DIRTY: AtomicU64 = AtomicU64::new(0);
+/// 正在执行阻塞命令的客户端数量
+/// Number of clients running a blocking command
+pub static BLOCKED_CLIENTS: AtomicUsize = AtomicUsize::new(0);
+pub static RDB_SAVE_IN_PROGRESS: AtomicBool = AtomicBool::new(false);
+/// 最近一次 RDB 保存是否失败，命令分发时据此判断是否拒绝写命令，不需要加锁
+/// Whether the latest RDB save failed, checked without a lock when dispatching commands to refuse writes
+pub static RDB_LAST_SAVE_FAILED: AtomicBool = AtomicBool::new(false);
+/// everysec 时因为后台同步太慢，没有等它结束就写入 AOF 的次数
+/// Number of AOF writes made without waiting for a background fsync of everysec that was too slow
+pub static AOF_DELAYED_FSYNC: AtomicU64 = AtomicU64::new(0);
+/// 是否正在后台重写 AOF
+/// Whether the AOF is being rewritten in the background
+pub static AOF_REWRITE_IN_PROGRESS: AtomicBool = AtomicBool::new(false);
+/// 是否正在加载启动时的 AOF 或 RDB 数据
+/// Whether the AOF or RDB data is being loaded at startup
+pub static LOADING: AtomicBool = AtomicBool::new(false);
+/// 最近一次采样的内存使用估算，设置了 maxmemory 时才定期采样
+/// Latest sample of the memory usage estimate, only sampled periodically when maxmemory is set
+pub static USED_MEMORY: AtomicUsize = AtomicUsize::new(0);
+/// 内存使用估算的峰值
+/// Peak of the memory usage estimate
+pub static USED_MEMORY_PEAK: AtomicUsize = AtomicUsize::new(0);
+
+/// 每秒操作数的采样：最近一次采样的时间和命令总数，以及最近几次采样算出的速率
+/// Samples of operations per second: time and command total of the latest sample, and the rates of the latest samples
+#[derive(Debug, Default)]
+struct OpsSamples {
+    last: Option<(Instant, u64)>,
+    rates: [u64; OPS_SAMPLES],
+    index: usize,
+}
+
+/// 记录启动时间并开始采样每秒操作数，设置了 maxmemory 时同时采样内存使用
+/// Record the start time and start sampling operations per second, along with the memory usage when maxmemory is set.
+pub fn start(db: Arc<Mutex<Db>>) {
+    lazy_static::initialize(&STARTED);
+    lazy_static::initialize(&COMMAND_STATS);
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(OPS_SAMPLE_INTERVAL);
+        loop {
+            interval.tick().await;
+            sample_ops();
+            if get_server_config().maxmemory > 0 {
+                used_memory(&db);
+            }
+        }
+    });
+}
+
+/// 运行时间
+/// Uptime
+pub fn uptime() -> Duration {
+    STARTED.elapsed()
+}
+
+/// 本次运行的随机标识，40 个十六进制字符
+/// Random identifier of this run, 40 hex characters
+pub fn run_id() -> &'static str {
+    &RUN_ID
+}
+
+/// 记录一次命令调用及其耗时
+/// Record a command call and the time it took.
+pub fn record_command(name: &str, elapsed: Duration) {
+    TOTAL_COMMANDS_PROCESSED.fetch_add(1, Ordering::Relaxed);
+    if let Some(stat) = COMMAND_STATS.get(name) {
+        stat.calls.fetch_add(1, Ordering::Relaxed);
+        stat.usec.fetch_add(elapsed.as_micros() as u64, Ordering::Relaxed);
+    }
+}
+
+/// 记录一次执行前被拒绝的命令调用
+/// Record a command call rejected before execution.
+pub fn record_rejected(name: &str) {
+    if let Some(stat) = COMMAND_STATS.get(name) {
+        stat.rejected_calls.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// 调用过的命令的统计，按命令表的顺序
+/// Statistics of the commands that were called, in command table order
+pub fn command_stats() -> Vec<(&'static str, &'static CommandStat)> {
+    COMMANDS
+        .iter()
+        .filter_map(|(name, ..)| COMMAND_STATS.get_key_value(name))
+        .filter(|(_, stat)| stat.calls.load(Ordering::Relaxed) + stat.rejected_calls.load(Ordering::Relaxed) > 0)
+        .map(|(name, stat)| (*name, stat))
+        .collect()
+}
+
+/// 记录一次读命令的键查找是否命中
+/// Record whether a key lookup of a read command was a hit.
+pub fn record_lookup(hit: bool) {
+    let counter = if hit { &KEYSPACE_HITS } else { &KEYSPACE_MISSES };
+    counter.fetch_add(1, Ordering::Relaxed);
+}
+
+/// 增加计数
+/// Increment a counter.
+pub fn incr(counter: &AtomicU64) {
+    counter.fetch_add(1, Ordering::Relaxed);
+}
+
+/// 读取计数
+/// Read a counter.
+pub fn get(counter: &AtomicU64) -> u64 {
+    counter.load(Ordering::Relaxed)
+}
+
+/// 最近一段时间每秒执行的命令数
+/// Commands executed per second over the recent samples
+pub fn instantaneous_ops_per_sec() -> u64 {
+    let ops = OPS.lock().unwrap();
+    ops.rates.iter().sum::<u64>() / OPS_SAMPLES as u64
+}
+
+fn sample_ops() {
+    let now = Instant::now();
+    let total = get(&TOTAL_COMMANDS_PROCESSED);
+    let mut ops = OPS.lock().unwrap();
+    if let Some((time, last_total)) = ops.last {
+        let elapsed = now.duration_since(time).as_millis().max(1) as u64;
+        let index = ops.index;
+        ops.rates[index] = (total - last_total) * 1000 / elapsed;
+        ops.index = (index + 1) % OPS_SAMPLES;
+    }
+    ops.last = Some((now, total));
+}
+
+/// 估算使用的内存：数据集、客户端的输入输出缓冲区和 AOF 缓冲区，同时更新采样和峰值
+/// Estimate the memory in use: the dataset, the client input and output buffers and the AOF buffer, updating the
+/// sample and the peak as well.
+pub fn used_memory(db: &Arc<Mutex<Db>>) -> usize {
+    let dataset = db.lock().unwrap().dataset_memory();
+    let buffers: usize = client::all().iter().map(|client| {
+        let state = client.state();
+        state.qbuf + state.omem
+    }).sum();
+    let used = dataset + buffers + aof::buffer_len();
+    USED_MEMORY.store(used, Ordering::Relaxed);
+    USED_MEMORY_PEAK.fetch_max(used, Ordering::Relaxed);
+    used
+}
+
+/// 内存使用是否超过 maxmemory，没有设置 maxmemory 时总是 false
+/// Whether the memory usage exceeds maxmemory, always false if maxmemory is not set.
+pub fn is_out_of_memory() -> bool {
+    let maxmemory = get_server_config().maxmemory;
+    maxmemory > 0 && USED_MEMORY.load(Ordering::Relaxed) > maxmemory
+}
+
+/// RDB 保存失败之后是否拒绝写命令：需要配置了保存规则并开启 stop-writes-on-bgsave-error
+/// Whether write commands are refused after a failed RDB save: save rules must be configured and
+/// stop-writes-on-bgsave-error enabled.
+pub fn is_rdb_write_refused() -> bool {
+    if !RDB_LAST_SAVE_FAILED.load(Ordering::Relaxed) {
+        return false;
+    }
+    let config = get_rdb_config();
+    config.stop_writes_on_bgsave_error && !config.save.is_empty()
+}
+
+/// 执行阻塞命令期间计入阻塞的客户端，离开作用域时移除
+/// Counts the client as blocked while a blocking command runs, removed when dropped.
+pub struct BlockedClient;
+
+impl BlockedClient {
+    pub fn enter() -> BlockedClient {
+        BLOCKED_CLIENTS.fetch_add(1, Ordering::Relaxed);
+        BlockedClient
+    }
+}
+
+impl Drop for BlockedClient {
+    fn drop(&mut self) {
+        BLOCKED_CLIENTS.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// 记录一次 RDB 保存的结果，成功时清零修改计数
+/// Record the outcome of an RDB save, resetting the modification counter on success.
+pub fn rdb_saved(result: &Result<(), String>, duration: Duration, dirty_before: u64) {
+    let mut status = RDB_STATUS.lock().unwrap();
+    status.last_duration = duration.as_secs() as i64;
+    RDB_LAST_SAVE_FAILED.store(result.is_err(), Ordering::Relaxed);
+    match result {
+        Ok(()) => {
+            status.last_error = None;
+            status.last_success = unix_time();
+            DIRTY.fetch_sub(dirty_before.min(get(&DIRTY)), Ordering::Relaxed);
+        }
+        Err(err) => status.last_error = Some(err.clone()),
+    }
+}
+
+/// 记录一次 AOF 写入的结果
+/// Record the outcome of an AOF write.
+pub fn aof_written(result: &Result<(), String>) {
+    let mut status = AOF_STATUS.lock().unwrap();
+    match result {
+        Ok(()) => {
+            status.last_error = None;
+            status.last_success = unix_time();
+        }
+        Err(err) => status.last_error = Some(err.clone()),
+    }
+}
+
+/// 记录一次 AOF 重写的结果
+/// Record the outcome of an AOF rewrite.
+pub fn aof_rewritten(result: &Result<(), String>, duration: Duration) {
+    let mut status = AOF_REWRITE_STATUS.lock().unwrap();
+    status.last_duration = duration.as_secs() as i64;
+    match result {
+        Ok(()) => {
+            status.last_error = None;
+            status.last_success = unix_time();
+        }
+        Err(err) => status.last_error = Some(err.clone()),
+    }
+}
+
+/// RDB 保存的状态
+/// Status of RDB saves
+pub fn rdb_status() -> WriteStatus {
+    RDB_STATUS.lock().unwrap().clone()
+}
+
+/// AOF 写入的状态
+/// Status of AOF writes
+pub fn aof_status() -> WriteStatus {
+    AOF_STATUS.lock().unwrap().clone()
+}
+
+/// AOF 重写的状态
+/// Status of AOF rewrites
+pub fn aof_rewrite_status() -> WriteStatus {
+    AOF_REWRITE_STATUS.lock().unwrap().clone()
+}
+
+/// 当前的 UNIX 时间戳，以秒为单位
+/// Current UNIX timestamp, in seconds
+pub fn unix_time() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |now| now.as_secs())
+}
@@ -0,0 +1,247 @@
+//! 客户端连接的 TLS 支持，基于 rustls
+//! TLS support for client connections, based on rustls.
+//!
+//! TLS 连接握手完成后与 TCP、Unix 套接字连接走相同的处理流程。
+//! Once the handshake is done, TLS connections are handled the same way as TCP and Unix socket connections.
+
+use std::fs::File;
+use std::io::BufReader;
+use std::sync::Arc;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer};
+use rustls::server::WebPkiClientVerifier;
+use rustls::{RootCertStore, ServerConfig};
+use crate::config::{TlsAuthClients, TlsConfig};
+
+/// 根据配置创建 TLS 服务端配置，每个连接用它创建握手器
+/// Create the TLS server configuration from the configuration, an acceptor is created from it for every connection.
+///
+/// 配置了客户端证书验证时必须提供 CA 证书文件。
+/// A CA certificate file is required when client certificates are verified.
+pub fn server_config(config: &TlsConfig) -> crate::Result<Arc<ServerConfig>> {
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+    let builder = ServerConfig::builder_with_provider(provider.clone())
+        .with_safe_default_protocol_versions()?;
+    let builder = match config.auth_clients {
+        TlsAuthClients::No => builder.with_no_client_auth(),
+        auth_clients => {
+            let Some(ca_cert_file) = &config.ca_cert_file else {
+                return Err("验证客户端证书需要配置 ca_cert_file".into());  // ca_cert_file is required to verify client certificates
+            };
+            let mut roots = RootCertStore::empty();
+            for cert in load_certs(ca_cert_file)? {
+                roots.add(cert)?;
+            }
+            let mut verifier = WebPkiClientVerifier::builder_with_provider(Arc::new(roots), provider);
+            // 可选验证时允许不提供证书的客户端
+            // Clients without a certificate are allowed when verification is optional
+            if auth_clients == TlsAuthClients::Optional {
+                verifier = verifier.allow_unauthenticated();
+            }
+            builder.with_client_cert_verifier(verifier.build()?)
+        }
+    };
+    let config = builder.with_single_cert(load_certs(&config.cert_file)?, load_key(&config.key_file)?)?;
+    Ok(Arc::new(config))
+}
+
+/// 读取 PEM 文件中的所有证书
+/// Read all the certificates in a PEM file.
+fn load_certs(path: &str) -> crate::Result<Vec<CertificateDer<'static>>> {
+    let mut reader = BufReader::new(File::open(path)?);
+    let certs = rustls_pemfile::certs(&mut reader).collect::<Result<Vec<_>, _>>()?;
+    if certs.is_empty() {
+        return Err(format!("证书文件中没有证书: {}", path).into());  // No certificate in the file
+    }
+    Ok(certs)
+}
+
+/// 读取 PEM 文件中的私钥
+/// Read the private key in a PEM file.
+fn load_key(path: &str) -> crate::Result<PrivateKeyDer<'static>> {
+    let mut reader = BufReader::new(File::open(path)?);
+    match rustls_pemfile::private_key(&mut reader)? {
+        Some(key) => Ok(key),
+        None => Err(format!("私钥文件中没有私钥: {}", path).into()),  // No private key in the file
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+    use rcgen::{BasicConstraints, CertificateParams, CertifiedKey, ExtendedKeyUsagePurpose, IsCa, KeyPair};
+    use rustls::pki_types::ServerName;
+    use rustls::ClientConfig;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::{TcpListener, TcpStream};
+    use tokio::sync::broadcast;
+    use tokio_rustls::client::TlsStream;
+    use tokio_rustls::{TlsAcceptor, TlsConnector};
+    use crate::db::DbHolder;
+    use crate::dict::Command;
+    use crate::server;
+
+    /// 测试生成的自签名 CA、服务端证书和客户端证书
+    /// Self-signed CA, server and client certificates generated for a test
+    struct Certs {
+        dir: PathBuf,
+        ca: CertifiedKey,
+        client: CertifiedKey,
+    }
+
+    impl Certs {
+        /// 生成证书并把 CA 证书、服务端证书和私钥写入临时目录
+        /// Generate the certificates and write the CA certificate, server certificate and key to a temporary directory
+        fn generate(name: &str) -> Certs {
+            let dir = std::env::temp_dir().join(format!("p-rudis-tls-{}-{}", std::process::id(), name));
+            std::fs::create_dir_all(&dir).unwrap();
+
+            let mut ca_params = CertificateParams::new(Vec::<String>::new()).unwrap();
+            ca_params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+            let ca_key = KeyPair::generate().unwrap();
+            let ca = CertifiedKey { cert: ca_params.self_signed(&ca_key).unwrap(), key_pair: ca_key };
+
+            let server = Self::signed(&ca, vec!["localhost".to_string()], ExtendedKeyUsagePurpose::ServerAuth);
+            let client = Self::signed(&ca, vec!["client".to_string()], ExtendedKeyUsagePurpose::ClientAuth);
+
+            std::fs::write(dir.join("ca.crt"), ca.cert.pem()).unwrap();
+            std::fs::write(dir.join("server.crt"), server.cert.pem()).unwrap();
+            std::fs::write(dir.join("server.key"), server.key_pair.serialize_pem()).unwrap();
+            Certs { dir, ca, client }
+        }
+
+        /// 生成由 CA 签发的证书
+        /// Generate a certificate signed by the CA
+        fn signed(ca: &CertifiedKey, names: Vec<String>, usage: ExtendedKeyUsagePurpose) -> CertifiedKey {
+            let mut params = CertificateParams::new(names).unwrap();
+            params.extended_key_usages = vec![usage];
+            let key_pair = KeyPair::generate().unwrap();
+            let cert = params.signed_by(&key_pair, &ca.cert, &ca.key_pair).unwrap();
+            CertifiedKey { cert, key_pair }
+        }
+
+        /// 使用这些证书的 TLS 配置
+        /// The TLS configuration using these certificates
+        fn config(&self, auth_clients: TlsAuthClients) -> TlsConfig {
+            let path = |file: &str| self.dir.join(file).to_string_lossy().to_string();
+            TlsConfig {
+                port: 0,
+                cert_file: path("server.crt"),
+                key_file: path("server.key"),
+                ca_cert_file: Some(path("ca.crt")),
+                auth_clients,
+            }
+        }
+
+        /// 信任该 CA 的客户端配置，可选地提供客户端证书
+        /// Client configuration trusting the CA, optionally presenting the client certificate
+        fn client_config(&self, with_cert: bool) -> ClientConfig {
+            let mut roots = RootCertStore::empty();
+            roots.add(self.ca.cert.der().clone()).unwrap();
+            let provider = Arc::new(rustls::crypto::ring::default_provider());
+            let builder = ClientConfig::builder_with_provider(provider)
+                .with_safe_default_protocol_versions()
+                .unwrap()
+                .with_root_certificates(roots);
+            if with_cert {
+                let key = PrivateKeyDer::try_from(self.client.key_pair.serialize_der()).unwrap();
+                builder.with_client_auth_cert(vec![self.client.cert.der().clone()], key).unwrap()
+            } else {
+                builder.with_no_client_auth()
+            }
+        }
+    }
+
+    impl Drop for Certs {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.dir);
+        }
+    }
+
+    /// 启动一个 TLS 端口，连接经过 TLS 握手后交给与 TCP 相同的处理流程
+    /// Start a TLS port whose connections are handed to the same handler path as TCP after the handshake
+    async fn start_server(config: &TlsConfig) -> std::net::SocketAddr {
+        Command::load_commands();
+        let acceptor = TlsAcceptor::from(server_config(config).unwrap());
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let db_holder = DbHolder::new();
+        let notify_shutdown = broadcast::channel(1).0;
+        tokio::spawn(async move {
+            loop {
+                let (socket, addr) = listener.accept().await.unwrap();
+                let acceptor = acceptor.clone();
+                let db = db_holder.get_db();
+                let notify_shutdown = notify_shutdown.subscribe();
+                tokio::spawn(async move {
+                    if let Ok(stream) = acceptor.accept(socket).await {
+                        server::serve(stream, addr.to_string(), String::new(), db, notify_shutdown).await;
+                    }
+                });
+            }
+        });
+        addr
+    }
+
+    /// 连接 TLS 端口
+    /// Connect to the TLS port
+    async fn connect(addr: std::net::SocketAddr, client_config: ClientConfig) -> std::io::Result<TlsStream<TcpStream>> {
+        let connector = TlsConnector::from(Arc::new(client_config));
+        let socket = TcpStream::connect(addr).await?;
+        connector.connect(ServerName::try_from("localhost").unwrap(), socket).await
+    }
+
+    /// 发送 PING 并读取回复，连接被拒绝时返回错误
+    /// Send PING and read the reply, an error is returned if the connection is rejected
+    async fn ping(stream: &mut TlsStream<TcpStream>) -> std::io::Result<Vec<u8>> {
+        stream.write_all(b"*1\r\n$4\r\nPING\r\n").await?;
+        let mut reply = vec![0; 7];
+        stream.read_exact(&mut reply).await?;
+        Ok(reply)
+    }
+
+    #[tokio::test]
+    async fn serves_commands_over_tls() {
+        let certs = Certs::generate("serve");
+        let addr = start_server(&certs.config(TlsAuthClients::No)).await;
+        let mut stream = connect(addr, certs.client_config(false)).await.unwrap();
+        assert_eq!(ping(&mut stream).await.unwrap(), b"+PONG\r\n");
+    }
+
+    #[tokio::test]
+    async fn required_client_certificate() {
+        let certs = Certs::generate("required");
+        let addr = start_server(&certs.config(TlsAuthClients::Yes)).await;
+
+        let mut stream = connect(addr, certs.client_config(true)).await.unwrap();
+        assert_eq!(ping(&mut stream).await.unwrap(), b"+PONG\r\n");
+
+        // TLS 1.3 中服务端在握手之后才拒绝客户端，所以错误可能在第一次读写时出现
+        // With TLS 1.3 the server rejects the client after the handshake, so the error may appear on the first read or write
+        let rejected = match connect(addr, certs.client_config(false)).await {
+            Ok(mut stream) => ping(&mut stream).await.is_err(),
+            Err(_) => true,
+        };
+        assert!(rejected);
+    }
+
+    #[tokio::test]
+    async fn optional_client_certificate() {
+        let certs = Certs::generate("optional");
+        let addr = start_server(&certs.config(TlsAuthClients::Optional)).await;
+        for with_cert in [true, false] {
+            let mut stream = connect(addr, certs.client_config(with_cert)).await.unwrap();
+            assert_eq!(ping(&mut stream).await.unwrap(), b"+PONG\r\n");
+        }
+    }
+
+    #[test]
+    fn client_verification_requires_ca() {
+        let certs = Certs::generate("no-ca");
+        let mut config = certs.config(TlsAuthClients::Yes);
+        config.ca_cert_file = None;
+        assert!(server_config(&config).is_err());
+        config.auth_clients = TlsAuthClients::No;
+        assert!(server_config(&config).is_ok());
+    }
+}
@@ -0,0 +1,417 @@
+//! 客户端缓存（client-side caching）的键追踪与失效消息
+//! Key tracking and invalidation messages for client-side caching.
+//!
+//! 默认模式下记录每个客户端读取过的键，键被修改时只通知读取过它的客户端；
+//! BCAST 模式下按前缀广播，不记录读取的键。
+//! In default mode the keys read by every client are remembered, and only those clients are
+//! notified when a key is modified; in BCAST mode invalidations are broadcast by prefix and no
+//! keys are remembered.
+
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+use lazy_static::lazy_static;
+use crate::client;
+use crate::error::Error;
+use crate::frame::Frame;
+
+/// RESP2 客户端通过重定向接收失效消息时使用的频道
+/// Channel used when a RESP2 client receives invalidation messages through redirection.
+pub const INVALIDATE_CHANNEL: &str = "__redis__:invalidate";
+
+lazy_static! {
+    static ref TRACKING_TABLE: Mutex<TrackingTable> = Mutex::new(TrackingTable::default());
+}
+
+tokio::task_local! {
+    /// 当前正在执行的命令调用
+    /// The command call currently being executed.
+    static CURRENT_CALL: RefCell<CallContext>;
+}
+
+/// 客户端的追踪选项，对应 `CLIENT TRACKING ON` 的参数
+/// Tracking options of a client, matching the arguments of `CLIENT TRACKING ON`.
+#[derive(Debug, Clone, Default)]
+pub struct TrackingOptions {
+    /// 失效消息重定向到的客户端 ID
+    /// Client ID that invalidation messages are redirected to
+    pub redirect: Option<u64>,
+    /// 广播模式
+    /// Broadcasting mode
+    pub bcast: bool,
+    /// 广播模式下关注的前缀，为空表示所有键
+    /// Prefixes of interest in broadcasting mode, empty means every key
+    pub prefixes: Vec<String>,
+    /// 只追踪 `CLIENT CACHING yes` 之后的读取
+    /// Only track reads following `CLIENT CACHING yes`
+    pub optin: bool,
+    /// 不追踪 `CLIENT CACHING no` 之后的读取
+    /// Do not track reads following `CLIENT CACHING no`
+    pub optout: bool,
+    /// 不通知客户端自身修改的键
+    /// Do not notify the client about keys modified by itself
+    pub noloop: bool,
+    /// `CLIENT CACHING` 设置的值，只对下一条命令有效
+    /// Value set by `CLIENT CACHING`, only valid for the next command
+    caching: Option<bool>,
+}
+
+/// 追踪表
+/// The tracking table.
+#[derive(Debug, Default)]
+struct TrackingTable {
+    /// 开启追踪的客户端
+    /// Clients with tracking enabled
+    clients: HashMap<u64, TrackingOptions>,
+    /// 默认模式下，键和读取过它的客户端
+    /// In default mode, keys and the clients that read them
+    keys: HashMap<String, HashSet<u64>>,
+    /// 广播模式下，前缀和关注它的客户端
+    /// In broadcasting mode, prefixes and the clients interested in them
+    prefixes: HashMap<String, HashSet<u64>>,
+}
+
+/// 一次命令调用的上下文
+/// Context of a single command call.
+#[derive(Debug)]
+struct CallContext {
+    /// 执行命令的客户端
+    /// Client executing the command
+    client_id: u64,
+    /// 是否需要记录读取的键
+    /// Whether the keys read should be remembered
+    remember: bool,
+    /// 命令读取的键
+    /// Keys read by the command
+    read_keys: Vec<String>,
+    /// 命令是否修改了键
+    /// Whether the command modified any key
+    dirty: bool,
+}
+
+/// 开启追踪，已开启时更新选项
+/// Enable tracking, or update the options if it is already enabled.
+pub fn enable(client_id: u64, options: TrackingOptions) -> Result<(), Error> {
+    let mut table = TRACKING_TABLE.lock().unwrap();
+    if let Some(current) = table.clients.get(&client_id)
+        && current.bcast != options.bcast
+    {
+        return Err(Error::Err("You can't switch BCAST mode on/off before disabling tracking for this client, and then re-enabling it with a different mode.".to_string()));
+    }
+    let mut options = options;
+    if options.bcast {
+        if options.prefixes.is_empty() {
+            options.prefixes.push(String::new());
+        }
+        // 已开启时保留之前注册的前缀
+        // Keep the prefixes registered before if tracking is already enabled
+        if let Some(current) = table.clients.get(&client_id) {
+            for prefix in &current.prefixes {
+                if !options.prefixes.contains(prefix) {
+                    options.prefixes.push(prefix.clone());
+                }
+            }
+        }
+        for prefix in &options.prefixes {
+            table.prefixes.entry(prefix.clone()).or_default().insert(client_id);
+        }
+    }
+    table.clients.insert(client_id, options);
+    Ok(())
+}
+
+/// 关闭追踪
+/// Disable tracking.
+pub fn disable(client_id: u64) {
+    let mut table = TRACKING_TABLE.lock().unwrap();
+    if let Some(options) = table.clients.remove(&client_id) {
+        for prefix in &options.prefixes {
+            if let Some(ids) = table.prefixes.get_mut(prefix) {
+                ids.remove(&client_id);
+                if ids.is_empty() {
+                    table.prefixes.remove(prefix);
+                }
+            }
+        }
+    }
+}
+
+/// 获取客户端的追踪选项，未开启时返回 None
+/// Get the tracking options of a client, None if tracking is disabled.
+pub fn options(client_id: u64) -> Option<TrackingOptions> {
+    TRACKING_TABLE.lock().unwrap().clients.get(&client_id).cloned()
+}
+
+/// 设置下一条命令是否追踪，对应 `CLIENT CACHING yes|no`
+/// Set whether the next command is tracked, matching `CLIENT CACHING yes|no`.
+pub fn set_caching(client_id: u64, yes: bool) {
+    if let Some(options) = TRACKING_TABLE.lock().unwrap().clients.get_mut(&client_id) {
+        options.caching = Some(yes);
+    }
+}
+
+/// 在追踪上下文中执行一条命令，命令结束后记录它读取的键
+/// Execute a command in a tracking context, remembering the keys it read once it finishes.
+///
+/// 修改过键的命令不会记录读取的键。
+/// Commands that modified keys do not remember the keys they read.
+pub fn call<F, R>(client_id: u64, f: F) -> R
+where
+    F: FnOnce() -> R,
+{
+    let remember = {
+        let mut table = TRACKING_TABLE.lock().unwrap();
+        match table.clients.get_mut(&client_id) {
+            Some(options) if !options.bcast => {
+                let caching = options.caching.take();
+                if options.optin {
+                    caching == Some(true)
+                } else if options.optout {
+                    caching != Some(false)
+                } else {
+                    true
+                }
+            }
+            _ => false,
+        }
+    };
+    let context = RefCell::new(CallContext {
+        client_id,
+        remember,
+        read_keys: Vec::new(),
+        dirty: false,
+    });
+    let (result, context) = CURRENT_CALL.sync_scope(context, || {
+        let result = f();
+        let context = CURRENT_CALL.with(|c| {
+            let mut c = c.borrow_mut();
+            (std::mem::take(&mut c.read_keys), c.dirty)
+        });
+        (result, context)
+    });
+    let (read_keys, dirty) = context;
+    if remember && !dirty && !read_keys.is_empty() {
+        let mut table = TRACKING_TABLE.lock().unwrap();
+        for key in read_keys {
+            table.keys.entry(key).or_default().insert(client_id);
+        }
+    }
+    result
+}
+
+/// 记录当前命令读取的键
+/// Remember a key read by the current command.
+pub fn remember_key(key: &str) {
+    let _ = CURRENT_CALL.try_with(|c| {
+        let mut c = c.borrow_mut();
+        if c.remember {
+            c.read_keys.push(key.to_string());
+        }
+    });
+}
+
+/// 键被修改，通知追踪它的客户端
+/// A key was modified, notify the clients tracking it.
+pub fn invalidate_key(key: &str) {
+    let writer = CURRENT_CALL
+        .try_with(|c| {
+            let mut c = c.borrow_mut();
+            c.dirty = true;
+            c.client_id
+        })
+        .ok();
+
+    let mut table = TRACKING_TABLE.lock().unwrap();
+    if table.clients.is_empty() {
+        return;
+    }
+    let mut targets: HashSet<u64> = table.keys.remove(key).unwrap_or_default();
+    for (prefix, ids) in table.prefixes.iter() {
+        if key.starts_with(prefix.as_str()) {
+            targets.extend(ids.iter().copied());
+        }
+    }
+    for id in targets {
+        let Some(options) = table.clients.get(&id) else {
+            continue;
+        };
+        if options.noloop && Some(id) == writer {
+            continue;
+        }
+        send_invalidation(id, options, key);
+    }
+}
+
+/// 发送失效消息，RESP3 使用推送消息，RESP2 只能通过重定向到订阅了失效消息频道的连接发送
+/// Send an invalidation message, as a push message for RESP3, or through redirection to a
+/// connection subscribed to the invalidation channel for RESP2.
+fn send_invalidation(client_id: u64, options: &TrackingOptions, key: &str) {
+    let keys = Frame::Array(vec![Frame::Bulk(key.as_bytes().to_vec())]);
+    match options.redirect {
+        Some(redirect) => {
+            let Some(target) = client::lookup(redirect) else {
+                // 重定向的客户端已断开，告知原客户端
+                // The redirected client has gone, tell the original client
+                if let Some(origin) = client::lookup(client_id)
+                    && origin.state().resp > 2
+                {
+                    origin.push(Frame::Push(vec![
+                        Frame::Bulk(b"tracking-redir-broken".to_vec()),
+                        Frame::Integer(redirect as i64),
+                    ]));
+                }
+                return;
+            };
+            let state = target.state();
+            if state.resp > 2 {
+                target.push(Frame::Push(vec![Frame::Bulk(b"invalidate".to_vec()), keys]));
+            } else if state.invalidate_subscribed {
+                target.push(Frame::Array(vec![
+                    Frame::Bulk(b"message".to_vec()),
+                    Frame::Bulk(INVALIDATE_CHANNEL.as_bytes().to_vec()),
+                    keys,
+                ]));
+            }
+        }
+        None => {
+            // RESP2 不支持在同一连接上推送消息
+            // RESP2 does not support pushing messages on the same connection
+            if let Some(origin) = client::lookup(client_id)
+                && origin.state().resp > 2
+            {
+                origin.push(Frame::Push(vec![Frame::Bulk(b"invalidate".to_vec()), keys]));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::Client;
+
+    fn client(resp: u8) -> Client {
+        let client = Client::register("127.0.0.1:50000".to_string(), "127.0.0.1:6379".to_string());
+        client.handle.update(|state| state.resp = resp);
+        client
+    }
+
+    fn read(client: &Client, key: &str) {
+        call(client.id(), || remember_key(key));
+    }
+
+    fn write(client: &Client, key: &str) {
+        call(client.id(), || invalidate_key(key));
+    }
+
+    /// 客户端收到的消息，编码为 RESP
+    /// Messages received by the client, encoded as RESP
+    fn received(client: &mut Client) -> Vec<String> {
+        let mut messages = Vec::new();
+        while let Ok(frame) = client.pushes.try_recv() {
+            messages.push(String::from_utf8(frame.to_bytes().unwrap().to_vec()).unwrap());
+        }
+        messages
+    }
+
+    fn invalidate(key: &str) -> String {
+        format!(">2\r\n$10\r\ninvalidate\r\n*1\r\n${}\r\n{}\r\n", key.len(), key)
+    }
+
+    #[test]
+    fn resp3_clients_get_push_messages_for_keys_they_read() {
+        let (mut reader, writer) = (client(3), client(3));
+        enable(reader.id(), TrackingOptions::default()).unwrap();
+        read(&reader, "tracking:resp3");
+        write(&writer, "tracking:resp3:other");
+        assert!(received(&mut reader).is_empty());
+        write(&writer, "tracking:resp3");
+        assert_eq!(received(&mut reader), [invalidate("tracking:resp3")]);
+        // 失效之后需要再次读取才会追踪
+        // The key has to be read again to be tracked after an invalidation
+        write(&writer, "tracking:resp3");
+        assert!(received(&mut reader).is_empty());
+    }
+
+    #[test]
+    fn resp2_redirection_needs_the_invalidation_channel() {
+        let (reader, mut target, writer) = (client(2), client(2), client(2));
+        let redirect = TrackingOptions { redirect: Some(target.id()), ..Default::default() };
+        enable(reader.id(), redirect).unwrap();
+        // 订阅了其它频道的连接收不到失效消息
+        // A connection subscribed to another channel gets no invalidations
+        target.handle.update(|state| state.pubsub = true);
+        read(&reader, "tracking:redirect");
+        write(&writer, "tracking:redirect");
+        assert!(received(&mut target).is_empty());
+
+        target.handle.update(|state| state.invalidate_subscribed = true);
+        read(&reader, "tracking:redirect");
+        write(&writer, "tracking:redirect");
+        let message = "*3\r\n$7\r\nmessage\r\n$20\r\n__redis__:invalidate\r\n*1\r\n$17\r\ntracking:redirect\r\n";
+        assert_eq!(received(&mut target), [message]);
+    }
+
+    #[test]
+    fn broken_redirections_are_reported() {
+        let (mut reader, target, writer) = (client(3), client(2), client(3));
+        let target_id = target.id();
+        enable(reader.id(), TrackingOptions { redirect: Some(target_id), ..Default::default() }).unwrap();
+        drop(target);
+        read(&reader, "tracking:broken");
+        write(&writer, "tracking:broken");
+        let broken = format!(">2\r\n$21\r\ntracking-redir-broken\r\n:{}\r\n", target_id);
+        assert_eq!(received(&mut reader), [broken]);
+    }
+
+    #[test]
+    fn bcast_clients_get_keys_matching_their_prefixes() {
+        let (mut reader, writer) = (client(3), client(3));
+        let options = TrackingOptions { bcast: true, prefixes: vec!["tracking:bcast:a".to_string()], ..Default::default() };
+        enable(reader.id(), options).unwrap();
+        write(&writer, "tracking:bcast:b1");
+        write(&writer, "tracking:bcast:a1");
+        // 广播模式不需要读取键
+        // Keys don't have to be read in broadcasting mode
+        write(&writer, "tracking:bcast:a1");
+        assert_eq!(received(&mut reader), [invalidate("tracking:bcast:a1"), invalidate("tracking:bcast:a1")]);
+        assert!(enable(reader.id(), TrackingOptions::default()).is_err());
+    }
+
+    #[test]
+    fn noloop_skips_keys_modified_by_the_client_itself() {
+        let (mut reader, writer) = (client(3), client(3));
+        enable(reader.id(), TrackingOptions { noloop: true, ..Default::default() }).unwrap();
+        read(&reader, "tracking:noloop");
+        write(&reader, "tracking:noloop");
+        assert!(received(&mut reader).is_empty());
+        read(&reader, "tracking:noloop");
+        write(&writer, "tracking:noloop");
+        assert_eq!(received(&mut reader), [invalidate("tracking:noloop")]);
+    }
+
+    #[test]
+    fn optin_and_optout_follow_client_caching() {
+        let (mut optin, mut optout, writer) = (client(3), client(3), client(3));
+        enable(optin.id(), TrackingOptions { optin: true, ..Default::default() }).unwrap();
+        enable(optout.id(), TrackingOptions { optout: true, ..Default::default() }).unwrap();
+
+        read(&optin, "tracking:optin");
+        set_caching(optout.id(), false);
+        read(&optout, "tracking:optout");
+        write(&writer, "tracking:optin");
+        write(&writer, "tracking:optout");
+        assert!(received(&mut optin).is_empty());
+        assert!(received(&mut optout).is_empty());
+
+        // `CLIENT CACHING` 只对下一条命令有效
+        // `CLIENT CACHING` only applies to the next command
+        set_caching(optin.id(), true);
+        read(&optin, "tracking:optin");
+        read(&optout, "tracking:optout");
+        write(&writer, "tracking:optin");
+        write(&writer, "tracking:optout");
+        assert_eq!(received(&mut optin), [invalidate("tracking:optin")]);
+        assert_eq!(received(&mut optout), [invalidate("tracking:optout")]);
+    }
+}